@@ -0,0 +1,737 @@
+//! Verbindungsdiagnose: prüft Schritt für Schritt, was der Server kann (DAV-Klassen,
+//! PROPFIND, MKCOL, PUT/GET/DELETE, LOCK, sync-collection, Quota, Uhrzeit), und liefert
+//! einen strukturierten Bericht, den der Einstellungsdialog anzeigt und kopieren lässt.
+//!
+//! Seiteneffekte beschränken sich auf eine Probe-Datei und ein Probe-Verzeichnis im
+//! Sync-Ordner, die sofort wieder gelöscht werden.
+
+use std::time::Instant;
+
+use chrono::{DateTime, Utc};
+use reqwest::{Method, StatusCode};
+use serde::Serialize;
+
+use crate::error::AppError;
+use crate::multistatus;
+use crate::tls::TlsPolicy;
+use crate::webdav::{parse_lock_token, WebDavClient, LOCK, MKCOL, PROPFIND, UNLOCK};
+
+/// Ab dieser Abweichung der Server-Uhr wird gewarnt — Konflikte werden per Zeitstempel entschieden
+const CLOCK_SKEW_WARN_SECS: i64 = 60;
+
+/// Prüfungen, die einen erreichbaren Server voraussetzen
+const SERVER_CHECKS: &[&str] = &[
+    "dav_compliance",
+    "propfind_depth0",
+    "propfind_depth1",
+    "mkcol",
+    "put",
+    "get",
+    "lock",
+    "delete",
+    "sync_collection",
+    "quota",
+    "clock",
+];
+
+/// Prüfungen, die den Sync-Ordner voraussetzen
+const FOLDER_CHECKS: &[&str] = &[
+    "propfind_depth1",
+    "mkcol",
+    "put",
+    "get",
+    "lock",
+    "delete",
+    "sync_collection",
+];
+
+/// Prüfungen, die eine hochgeladene Probe-Datei voraussetzen
+const PROBE_FILE_CHECKS: &[&str] = &["get", "lock", "delete"];
+
+const PROPFIND_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:">
+  <d:prop>
+    <d:resourcetype/>
+    <d:getetag/>
+  </d:prop>
+</d:propfind>"#;
+
+const QUOTA_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:">
+  <d:prop>
+    <d:quota-used-bytes/>
+    <d:quota-available-bytes/>
+  </d:prop>
+</d:propfind>"#;
+
+const LOCK_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:lockinfo xmlns:d="DAV:">
+  <d:lockscope><d:exclusive/></d:lockscope>
+  <d:locktype><d:write/></d:locktype>
+  <d:owner>Simple Notes Desktop (diagnostics)</d:owner>
+</d:lockinfo>"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Ok,
+    /// Funktioniert, aber mit Einschränkung (z.B. kein LOCK → Fallback)
+    Warning,
+    Failed,
+    /// Nicht ausgeführt, weil eine Voraussetzung fehlte
+    Skipped,
+}
+
+/// Ergebnis einer einzelnen Prüfung.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DiagnosticCheck {
+    /// Stabile Kennung (`options`, `propfind_depth0`, …) für Frontend und Tests
+    pub id: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+    /// Dauer der Requests dieser Prüfung (0 bei abgeleiteten oder übersprungenen Prüfungen)
+    pub duration_ms: u64,
+}
+
+/// Diagnosebericht für den Einstellungsdialog.
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticReport {
+    /// Zeitpunkt der Diagnose (RFC 3339, UTC)
+    pub generated_at: String,
+    pub app_version: String,
+    pub server_url: String,
+    pub sync_folder: String,
+    /// Zertifikatsprüfung gemäß Settings
+    pub tls: String,
+    /// `Server`-Header der OPTIONS-Antwort
+    pub server_software: Option<String>,
+    /// Compliance-Klassen aus dem `DAV`-Header (`1`, `2`, `3`, …)
+    pub dav_classes: Vec<String>,
+    /// Methoden aus dem `Allow`-Header
+    pub allowed_methods: Vec<String>,
+    /// Server-Uhr minus lokale Uhr in Sekunden (aus dem `Date`-Header)
+    pub clock_offset_secs: Option<i64>,
+    pub quota_used_bytes: Option<i64>,
+    pub quota_available_bytes: Option<i64>,
+    pub checks: Vec<DiagnosticCheck>,
+}
+
+impl DiagnosticReport {
+    fn record(&mut self, id: &'static str, status: CheckStatus, detail: String, started: Instant) {
+        self.checks.push(DiagnosticCheck {
+            id,
+            status,
+            detail,
+            duration_ms: started.elapsed().as_millis() as u64,
+        });
+    }
+
+    /// Abgeleitete Prüfung ohne eigenen Request
+    fn derive(&mut self, id: &'static str, status: CheckStatus, detail: String) {
+        self.checks.push(DiagnosticCheck {
+            id,
+            status,
+            detail,
+            duration_ms: 0,
+        });
+    }
+
+    fn skip(&mut self, ids: &[&'static str], reason: &str) {
+        for id in ids {
+            if !self.checks.iter().any(|c| c.id == *id) {
+                self.derive(id, CheckStatus::Skipped, reason.to_string());
+            }
+        }
+    }
+}
+
+/// Führt alle Prüfungen nacheinander aus. Fehler landen im Bericht, nie als `Err` —
+/// gerade ein fehlschlagender Server soll einen vollständigen Bericht liefern.
+pub async fn run(client: &WebDavClient, tls: &TlsPolicy) -> DiagnosticReport {
+    let now = Utc::now();
+    let mut report = DiagnosticReport {
+        generated_at: now.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        server_url: client.base_url().to_string(),
+        sync_folder: client.sync_folder().to_string(),
+        tls: describe_tls(tls),
+        server_software: None,
+        dav_classes: Vec::new(),
+        allowed_methods: Vec::new(),
+        clock_offset_secs: None,
+        quota_used_bytes: None,
+        quota_available_bytes: None,
+        checks: Vec::new(),
+    };
+
+    if !check_options(client, &mut report).await {
+        report.skip(SERVER_CHECKS, "server not reachable");
+        return report;
+    }
+    check_dav_compliance(&mut report);
+
+    let root = client.root_url();
+    if check_propfind_depth0(client, &mut report, &root).await {
+        check_propfind_depth1(client, &mut report, &root).await;
+        check_mkcol(client, &mut report, &root).await;
+        let probe_url = format!("{}.diagnose-{}.txt", root, uuid::Uuid::new_v4().simple());
+        let content = format!("Simple Notes Desktop diagnostics {}", report.generated_at);
+        if check_put(client, &mut report, &probe_url, &content).await {
+            check_get(client, &mut report, &probe_url, &content).await;
+            check_lock(client, &mut report, &probe_url).await;
+            check_delete(client, &mut report, &probe_url).await;
+        } else {
+            report.skip(PROBE_FILE_CHECKS, "probe file could not be uploaded");
+        }
+        check_sync_collection(client, &mut report, &root).await;
+    } else {
+        report.skip(FOLDER_CHECKS, "sync folder not available");
+    }
+    check_quota(client, &mut report).await;
+    check_clock(&mut report);
+    report
+}
+
+/// OPTIONS auf die Server-URL: Erreichbarkeit, DAV-Klassen, Allow, Server-Software, Uhrzeit.
+/// `false` wenn der Server gar nicht antwortet (Netzwerk/TLS) — dann ist jede weitere Prüfung sinnlos.
+async fn check_options(client: &WebDavClient, report: &mut DiagnosticReport) -> bool {
+    let url = format!("{}/", client.base_url());
+    let started = Instant::now();
+    let sent_at = Utc::now();
+    let response = match client.probe(Method::OPTIONS, &url, &[], None).await {
+        Ok(r) => r,
+        Err(e) => {
+            report.record("options", CheckStatus::Failed, e.to_string(), started);
+            return false;
+        }
+    };
+    let received_at = Utc::now();
+
+    report.server_software = response.header("Server");
+    report.dav_classes = parse_header_list(response.header("DAV").as_deref());
+    report.allowed_methods = parse_header_list(response.header("Allow").as_deref())
+        .into_iter()
+        .map(|m| m.to_ascii_uppercase())
+        .collect();
+    report.clock_offset_secs = response
+        .header("Date")
+        .and_then(|date| clock_offset(&date, sent_at, received_at));
+
+    let (status, detail) = match response.status {
+        s if s.is_success() => (CheckStatus::Ok, format!("reachable ({})", s)),
+        s => (CheckStatus::Warning, describe_status(s)),
+    };
+    report.record("options", status, detail, started);
+    true
+}
+
+fn check_dav_compliance(report: &mut DiagnosticReport) {
+    let classes = report.dav_classes.join(", ");
+    let has = |class: &str| report.dav_classes.iter().any(|c| c == class);
+    let (status, detail) = if report.dav_classes.is_empty() {
+        (
+            CheckStatus::Failed,
+            "no DAV header — the URL is probably not a WebDAV endpoint".to_string(),
+        )
+    } else if !has("1") {
+        (
+            CheckStatus::Failed,
+            format!("DAV: {} — class 1 missing", classes),
+        )
+    } else if !has("2") {
+        (
+            CheckStatus::Warning,
+            format!(
+                "DAV: {} — no class 2 (LOCK); shared files fall back to conditional PUTs",
+                classes
+            ),
+        )
+    } else {
+        (CheckStatus::Ok, format!("DAV: {}", classes))
+    };
+    report.derive("dav_compliance", status, detail);
+}
+
+/// PROPFIND Depth 0 auf den Sync-Ordner; `true` wenn er existiert.
+async fn check_propfind_depth0(
+    client: &WebDavClient,
+    report: &mut DiagnosticReport,
+    root: &str,
+) -> bool {
+    let started = Instant::now();
+    let (status, detail, exists) = match propfind(client, root, "0", PROPFIND_BODY).await {
+        Ok((StatusCode::MULTI_STATUS, _)) => (
+            CheckStatus::Ok,
+            format!("sync folder /{}/ found", client.sync_folder()),
+            true,
+        ),
+        Ok((StatusCode::NOT_FOUND, _)) => (
+            CheckStatus::Warning,
+            format!(
+                "sync folder /{}/ does not exist yet — it is created on connect",
+                client.sync_folder()
+            ),
+            false,
+        ),
+        Ok((s, _)) => (CheckStatus::Failed, describe_status(s), false),
+        Err(e) => (CheckStatus::Failed, e.to_string(), false),
+    };
+    report.record("propfind_depth0", status, detail, started);
+    exists
+}
+
+async fn check_propfind_depth1(client: &WebDavClient, report: &mut DiagnosticReport, root: &str) {
+    let started = Instant::now();
+    let (status, detail) = match propfind(client, root, "1", PROPFIND_BODY).await {
+        Ok((StatusCode::MULTI_STATUS, body)) => match multistatus::parse(&body) {
+            Ok(ms) => (
+                CheckStatus::Ok,
+                format!("{} entries", ms.responses.len().saturating_sub(1)),
+            ),
+            Err(e) => (CheckStatus::Failed, e.to_string()),
+        },
+        Ok((s, _)) => (CheckStatus::Failed, describe_status(s)),
+        Err(e) => (CheckStatus::Failed, e.to_string()),
+    };
+    report.record("propfind_depth1", status, detail, started);
+}
+
+/// Legt ein Probe-Verzeichnis an und entfernt es wieder (Ordner-Sync braucht MKCOL).
+async fn check_mkcol(client: &WebDavClient, report: &mut DiagnosticReport, root: &str) {
+    let url = format!("{}.diagnose-{}/", root, uuid::Uuid::new_v4().simple());
+    let started = Instant::now();
+    let (status, detail) = match client.probe(MKCOL.clone(), &url, &[], None).await {
+        Ok(r) if r.status == StatusCode::CREATED => {
+            match client.probe(Method::DELETE, &url, &[], None).await {
+                Ok(d) if d.status.is_success() => (
+                    CheckStatus::Ok,
+                    "collection created and removed".to_string(),
+                ),
+                Ok(d) => (
+                    CheckStatus::Warning,
+                    format!("created, but could not remove {}: {}", url, d.status),
+                ),
+                Err(e) => (
+                    CheckStatus::Warning,
+                    format!("created, but could not remove {}: {}", url, e),
+                ),
+            }
+        }
+        Ok(r) => (CheckStatus::Failed, describe_status(r.status)),
+        Err(e) => (CheckStatus::Failed, e.to_string()),
+    };
+    report.record("mkcol", status, detail, started);
+}
+
+/// `true` wenn die Probe-Datei angelegt wurde.
+async fn check_put(
+    client: &WebDavClient,
+    report: &mut DiagnosticReport,
+    url: &str,
+    content: &str,
+) -> bool {
+    let started = Instant::now();
+    let headers = [("Content-Type", "text/plain; charset=utf-8")];
+    let (status, detail, stored) = match client
+        .probe(Method::PUT, url, &headers, Some(content.to_string()))
+        .await
+    {
+        Ok(r) if r.status.is_success() => match r.header("ETag") {
+            Some(etag) => (
+                CheckStatus::Ok,
+                format!("stored ({}, ETag {})", r.status, etag),
+                true,
+            ),
+            None => (
+                CheckStatus::Warning,
+                "stored, but the server sent no ETag — concurrent edits are only detected by timestamp"
+                    .to_string(),
+                true,
+            ),
+        },
+        Ok(r) => (CheckStatus::Failed, describe_status(r.status), false),
+        Err(e) => (CheckStatus::Failed, e.to_string(), false),
+    };
+    report.record("put", status, detail, started);
+    stored
+}
+
+async fn check_get(client: &WebDavClient, report: &mut DiagnosticReport, url: &str, content: &str) {
+    let started = Instant::now();
+    let (status, detail) = match client.probe(Method::GET, url, &[], None).await {
+        Ok(r) if r.status.is_success() && r.body == content => {
+            (CheckStatus::Ok, "content matches".to_string())
+        }
+        Ok(r) if r.status.is_success() => (
+            CheckStatus::Failed,
+            format!(
+                "content differs from the upload ({} bytes instead of {})",
+                r.body.len(),
+                content.len()
+            ),
+        ),
+        Ok(r) => (CheckStatus::Failed, describe_status(r.status)),
+        Err(e) => (CheckStatus::Failed, e.to_string()),
+    };
+    report.record("get", status, detail, started);
+}
+
+/// LOCK + UNLOCK auf die Probe-Datei. Fehlender LOCK-Support ist nur eine Warnung —
+/// `folders.json`/`deletions.json` werden dann per bedingtem PUT geschrieben.
+async fn check_lock(client: &WebDavClient, report: &mut DiagnosticReport, url: &str) {
+    let started = Instant::now();
+    let headers = [
+        ("Depth", "0"),
+        ("Timeout", "Second-60"),
+        ("Content-Type", "application/xml"),
+    ];
+    let response = client
+        .probe(LOCK.clone(), url, &headers, Some(LOCK_BODY.to_string()))
+        .await;
+    let (status, detail) = match response {
+        Ok(r) if matches!(r.status, StatusCode::OK | StatusCode::CREATED) => {
+            match parse_lock_token(r.header("Lock-Token").as_deref(), &r.body) {
+                Some(token) => {
+                    let lock_token = format!("<{}>", token);
+                    match client
+                        .probe(UNLOCK.clone(), url, &[("Lock-Token", &lock_token)], None)
+                        .await
+                    {
+                        Ok(u) if u.status.is_success() => {
+                            (CheckStatus::Ok, "LOCK and UNLOCK supported".to_string())
+                        }
+                        Ok(u) => (
+                            CheckStatus::Warning,
+                            format!(
+                                "locked, but UNLOCK returned {} — the lock expires after 60 s",
+                                u.status
+                            ),
+                        ),
+                        Err(e) => (CheckStatus::Warning, format!("UNLOCK failed: {}", e)),
+                    }
+                }
+                None => (
+                    CheckStatus::Warning,
+                    "LOCK response without lock token — shared files fall back to conditional PUTs"
+                        .to_string(),
+                ),
+            }
+        }
+        Ok(r)
+            if matches!(
+                r.status,
+                StatusCode::METHOD_NOT_ALLOWED | StatusCode::NOT_IMPLEMENTED
+            ) =>
+        {
+            (
+                CheckStatus::Warning,
+                format!(
+                    "not supported ({}) — shared files fall back to conditional PUTs",
+                    r.status
+                ),
+            )
+        }
+        Ok(r) => (CheckStatus::Warning, describe_status(r.status)),
+        Err(e) => (CheckStatus::Warning, e.to_string()),
+    };
+    report.record("lock", status, detail, started);
+}
+
+async fn check_delete(client: &WebDavClient, report: &mut DiagnosticReport, url: &str) {
+    let started = Instant::now();
+    let (status, detail) = match client.probe(Method::DELETE, url, &[], None).await {
+        Ok(r) if r.status.is_success() => (CheckStatus::Ok, "probe file removed".to_string()),
+        Ok(r) => (
+            CheckStatus::Failed,
+            format!("could not remove {}: {}", url, describe_status(r.status)),
+        ),
+        Err(e) => (CheckStatus::Failed, e.to_string()),
+    };
+    report.record("delete", status, detail, started);
+}
+
+/// Initialer sync-collection REPORT (RFC 6578) — ohne ihn listet jeder Sync den ganzen Ordner.
+async fn check_sync_collection(client: &WebDavClient, report: &mut DiagnosticReport, root: &str) {
+    let started = Instant::now();
+    let (status, detail) = match client.sync_collection_text(root, None).await {
+        Ok(text) => match multistatus::parse(&text) {
+            Ok(ms) if ms.sync_token.is_some() => (
+                CheckStatus::Ok,
+                "supported — syncs only fetch changes".to_string(),
+            ),
+            Ok(_) => (
+                CheckStatus::Warning,
+                "response without sync-token — full PROPFIND listing is used instead".to_string(),
+            ),
+            Err(e) => (CheckStatus::Warning, e.to_string()),
+        },
+        Err(AppError::Unsupported(reason)) => (
+            CheckStatus::Warning,
+            format!(
+                "not supported ({}) — full PROPFIND listing is used instead",
+                reason
+            ),
+        ),
+        Err(e) => (CheckStatus::Warning, e.to_string()),
+    };
+    report.record("sync_collection", status, detail, started);
+}
+
+/// Quota-Props (RFC 4331) auf der Server-URL — gelten für das ganze Konto.
+async fn check_quota(client: &WebDavClient, report: &mut DiagnosticReport) {
+    let url = format!("{}/", client.base_url());
+    let started = Instant::now();
+    let entry = match propfind(client, &url, "0", QUOTA_BODY).await {
+        Ok((StatusCode::MULTI_STATUS, body)) => multistatus::parse(&body)
+            .ok()
+            .and_then(|ms| ms.responses.into_iter().next()),
+        _ => None,
+    };
+    report.quota_used_bytes = entry.as_ref().and_then(|e| e.quota_used());
+    report.quota_available_bytes = entry.as_ref().and_then(|e| e.quota_available());
+
+    let (status, detail) = match (report.quota_used_bytes, report.quota_available_bytes) {
+        (None, None) => (
+            CheckStatus::Warning,
+            "server reports no quota properties".to_string(),
+        ),
+        (used, Some(available)) if available >= 0 => (
+            CheckStatus::Ok,
+            format!(
+                "{} used, {} available",
+                used.map(format_bytes).unwrap_or_else(|| "?".to_string()),
+                format_bytes(available)
+            ),
+        ),
+        (used, _) => (
+            CheckStatus::Ok,
+            format!(
+                "{} used, no limit reported",
+                used.map(format_bytes).unwrap_or_else(|| "?".to_string())
+            ),
+        ),
+    };
+    report.record("quota", status, detail, started);
+}
+
+fn check_clock(report: &mut DiagnosticReport) {
+    let (status, detail) = match report.clock_offset_secs {
+        None => (
+            CheckStatus::Skipped,
+            "server sent no Date header".to_string(),
+        ),
+        Some(offset) if offset.abs() >= CLOCK_SKEW_WARN_SECS => (
+            CheckStatus::Warning,
+            format!(
+                "server clock differs by {} s — conflicts between devices may resolve to the older version",
+                offset
+            ),
+        ),
+        Some(offset) => (CheckStatus::Ok, format!("offset {} s", offset)),
+    };
+    report.derive("clock", status, detail);
+}
+
+async fn propfind(
+    client: &WebDavClient,
+    url: &str,
+    depth: &str,
+    body: &str,
+) -> crate::error::Result<(StatusCode, String)> {
+    let headers = [("Depth", depth), ("Content-Type", "application/xml")];
+    client
+        .probe(PROPFIND.clone(), url, &headers, Some(body.to_string()))
+        .await
+        .map(|r| (r.status, r.body))
+}
+
+/// Kommagetrennte Header-Liste (`DAV: 1, 2, 3, access-control`) → Einträge
+fn parse_header_list(value: Option<&str>) -> Vec<String> {
+    value
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Abweichung der Server-Uhr in Sekunden: `Date`-Header gegen die Mitte des Request-Zeitraums.
+fn clock_offset(date: &str, sent_at: DateTime<Utc>, received_at: DateTime<Utc>) -> Option<i64> {
+    let server = DateTime::parse_from_rfc2822(date.trim()).ok()?;
+    let local = sent_at + (received_at - sent_at) / 2;
+    Some((server.with_timezone(&Utc) - local).num_seconds())
+}
+
+fn describe_status(status: StatusCode) -> String {
+    match status {
+        StatusCode::UNAUTHORIZED => "credentials rejected (401 Unauthorized)".to_string(),
+        StatusCode::FORBIDDEN => "access denied (403 Forbidden)".to_string(),
+        StatusCode::NOT_FOUND => "not found (404)".to_string(),
+        StatusCode::METHOD_NOT_ALLOWED => "method not allowed (405)".to_string(),
+        StatusCode::CONFLICT => "parent collection missing (409 Conflict)".to_string(),
+        StatusCode::INSUFFICIENT_STORAGE => "quota exceeded (507 Insufficient Storage)".to_string(),
+        s => format!("unexpected response: {}", s),
+    }
+}
+
+fn describe_tls(tls: &TlsPolicy) -> String {
+    match tls {
+        TlsPolicy::System => "system trust store".to_string(),
+        TlsPolicy::CustomCa(path) => format!("custom CA ({})", path),
+        TlsPolicy::Pinned(Some(fingerprint)) => format!("pinned ({})", fingerprint),
+        TlsPolicy::Pinned(None) => "pinned (not captured yet)".to_string(),
+        TlsPolicy::Insecure => "disabled (insecure)".to_string(),
+    }
+}
+
+/// Bytes in lesbarer Form (1024er-Einheiten, eine Nachkommastelle)
+pub fn format_bytes(bytes: i64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value.abs() >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{AuthScheme, Credentials};
+    use crate::test_server::TestServer;
+
+    fn client_for(url: &str) -> WebDavClient {
+        let credentials = Credentials {
+            url: url.to_string(),
+            username: "user".to_string(),
+            password: "secret".to_string(),
+            auth_scheme: AuthScheme::Basic,
+        };
+        WebDavClient::new(
+            &credentials,
+            "notes",
+            &TlsPolicy::System,
+            &crate::proxy::ProxyPolicy::None,
+        )
+        .unwrap()
+    }
+
+    fn status(report: &DiagnosticReport, id: &str) -> CheckStatus {
+        report
+            .checks
+            .iter()
+            .find(|c| c.id == id)
+            .unwrap_or_else(|| panic!("check {} missing", id))
+            .status
+    }
+
+    #[test]
+    fn test_parse_header_list() {
+        assert_eq!(
+            parse_header_list(Some("1, 2,3 , access-control,")),
+            vec!["1", "2", "3", "access-control"]
+        );
+        assert!(parse_header_list(None).is_empty());
+    }
+
+    #[test]
+    fn test_clock_offset_uses_request_midpoint() {
+        let sent = DateTime::parse_from_rfc3339("2025-07-01T10:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let received = sent + chrono::Duration::seconds(2);
+        assert_eq!(
+            clock_offset("Tue, 01 Jul 2025 10:02:01 GMT", sent, received),
+            Some(120)
+        );
+        assert_eq!(
+            clock_offset("Tue, 01 Jul 2025 09:59:01 GMT", sent, received),
+            Some(-60)
+        );
+        assert_eq!(clock_offset("gestern", sent, received), None);
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KB");
+        assert_eq!(format_bytes(5 * 1024 * 1024 * 1024), "5.0 GB");
+    }
+
+    #[tokio::test]
+    async fn test_full_report_against_lock_capable_server() {
+        let server = TestServer::start(true).await;
+        server.mkdir("/notes/");
+        let report = run(&client_for(&server.url), &TlsPolicy::System).await;
+
+        for id in [
+            "options",
+            "dav_compliance",
+            "propfind_depth0",
+            "propfind_depth1",
+        ] {
+            assert_eq!(status(&report, id), CheckStatus::Ok, "{}", id);
+        }
+        for id in ["mkcol", "put", "get", "lock", "delete", "clock"] {
+            assert_eq!(status(&report, id), CheckStatus::Ok, "{}", id);
+        }
+        // Test-Server kennt weder REPORT noch Quota-Props
+        assert_eq!(status(&report, "sync_collection"), CheckStatus::Warning);
+        assert_eq!(status(&report, "quota"), CheckStatus::Warning);
+        assert_eq!(report.dav_classes, vec!["1", "2"]);
+        assert!(report.allowed_methods.contains(&"PROPFIND".to_string()));
+        assert!(report.clock_offset_secs.is_some_and(|o| o.abs() < 5));
+        // Probe-Datei und -Verzeichnis sind wieder weg
+        assert_eq!(server.count("DELETE"), 2);
+        assert!(server.file_names().is_empty());
+        assert_eq!(server.dir_names(), vec!["/notes/"]);
+    }
+
+    #[tokio::test]
+    async fn test_report_without_lock_support() {
+        let server = TestServer::start(false).await;
+        server.mkdir("/notes/");
+        let report = run(&client_for(&server.url), &TlsPolicy::System).await;
+
+        assert_eq!(status(&report, "dav_compliance"), CheckStatus::Warning);
+        assert_eq!(status(&report, "lock"), CheckStatus::Warning);
+        assert_eq!(status(&report, "delete"), CheckStatus::Ok);
+    }
+
+    #[tokio::test]
+    async fn test_missing_sync_folder_skips_folder_checks() {
+        let server = TestServer::start(true).await;
+        let report = run(&client_for(&server.url), &TlsPolicy::System).await;
+
+        assert_eq!(status(&report, "propfind_depth0"), CheckStatus::Warning);
+        for id in FOLDER_CHECKS {
+            assert_eq!(status(&report, id), CheckStatus::Skipped, "{}", id);
+        }
+        assert_eq!(server.count("PUT"), 0);
+        assert_eq!(status(&report, "clock"), CheckStatus::Ok);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_server_fails_options_and_skips_rest() {
+        // Port eines sofort wieder geschlossenen Listeners → Verbindung abgelehnt
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+
+        let report = run(&client_for(&url), &TlsPolicy::System).await;
+        assert_eq!(status(&report, "options"), CheckStatus::Failed);
+        assert_eq!(report.checks.len(), 1 + SERVER_CHECKS.len());
+        assert!(report
+            .checks
+            .iter()
+            .skip(1)
+            .all(|c| c.status == CheckStatus::Skipped));
+    }
+}
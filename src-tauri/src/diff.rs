@@ -0,0 +1,544 @@
+//! Strukturierter Vergleich zweier Notiz-Fassungen — Grundlage für Konfliktauflösung,
+//! Versionsverlauf und Sync-Vorschau.
+//!
+//! `content` wird zeilenweise verglichen (gleiche LCS wie beim Merge), geänderte Blöcke
+//! zusätzlich wortweise. Titel, Farbe, Labels, Pin, Ordner und Typ erscheinen als Feld-
+//! Änderungen, Checklisten je Item (über die stabile Item-ID): hinzugefügt, entfernt,
+//! bearbeitet, verschoben.
+
+use std::collections::{BTreeSet, HashMap};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::merge::{lines, matching, ordered};
+use crate::models::{ChecklistItem, Note, NoteType};
+
+/// Art eines Wort-Abschnitts innerhalb eines geänderten Blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpanKind {
+    Equal,
+    Insert,
+    Delete,
+}
+
+/// Zusammenhängender Text gleicher Art; Leerraum bleibt erhalten, die Spans ergeben
+/// aneinandergereiht wieder alten (ohne `insert`) bzw. neuen Text (ohne `delete`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WordSpan {
+    pub kind: SpanKind,
+    pub text: String,
+}
+
+/// Geänderter Zeilenblock im Inhalt (ohne Kontextzeilen).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TextHunk {
+    /// Erste betroffene Zeile der alten Fassung (0-basiert)
+    pub old_start: usize,
+    pub old_count: usize,
+    /// Erste betroffene Zeile der neuen Fassung (0-basiert)
+    pub new_start: usize,
+    pub new_count: usize,
+    /// Entfernte bzw. eingefügte Zeilen inkl. Zeilenenden
+    pub old_text: String,
+    pub new_text: String,
+    /// Wortweiser Vergleich, nur wenn beide Seiten Text haben (sonst leer)
+    pub words: Vec<WordSpan>,
+}
+
+/// Geändertes Notiz-Feld; Werte in der JSON-Form des Frontends (`null` = nicht gesetzt).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldChange {
+    /// Stabile Kennung: `title`, `note_type`, `color`, `labels`, `pinned`, `folder`
+    pub field: &'static str,
+    pub old: Value,
+    pub new: Value,
+}
+
+/// Änderung an einem Checklist-Item. Positionen beziehen sich auf die nach `order`
+/// sortierte Liste der jeweiligen Fassung.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum ItemChange {
+    Added {
+        item: ChecklistItem,
+        position: usize,
+    },
+    Removed {
+        item: ChecklistItem,
+        position: usize,
+    },
+    /// `fields`: geänderte JSON-Schlüssel des Items — `text`, `isChecked` und unbekannte
+    /// Felder (z.B. `indentationLevel`)
+    Edited {
+        old: ChecklistItem,
+        new: ChecklistItem,
+        fields: Vec<String>,
+    },
+    /// Item hat seinen Platz relativ zu den übrigen gemeinsamen Items verlassen
+    Moved { id: String, from: usize, to: usize },
+}
+
+/// Ergebnis von [`diff_notes`]; alle Listen leer = inhaltlich gleich.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct NoteDiff {
+    pub fields: Vec<FieldChange>,
+    pub content: Vec<TextHunk>,
+    pub checklist: Vec<ItemChange>,
+}
+
+impl NoteDiff {
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty() && self.content.is_empty() && self.checklist.is_empty()
+    }
+}
+
+/// Quelle einer Fassung für das `diff_notes`-Command: direkt übergeben (z.B. aus dem
+/// Verlauf) oder per ID aus lokalem Speicher, Server, Merge-Basis bzw. lokaler
+/// Konflikt-Fassung.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "source", rename_all = "snake_case")]
+pub enum NoteVersion {
+    Note { note: Box<Note> },
+    Local { id: String },
+    Server { id: String },
+    Base { id: String },
+    ConflictLocal { id: String },
+}
+
+/// Abwechselnd Wörter und Leerraum — verlustfrei wieder zusammensetzbar.
+fn words(text: &str) -> Vec<&str> {
+    let mut out = Vec::new();
+    let mut start = 0;
+    let mut prev = None;
+    for (idx, c) in text.char_indices() {
+        let ws = c.is_whitespace();
+        if prev.is_some_and(|p| p != ws) {
+            out.push(&text[start..idx]);
+            start = idx;
+        }
+        prev = Some(ws);
+    }
+    if start < text.len() {
+        out.push(&text[start..]);
+    }
+    out
+}
+
+fn push_span(spans: &mut Vec<WordSpan>, kind: SpanKind, text: &str) {
+    match spans.last_mut() {
+        Some(last) if last.kind == kind => last.text.push_str(text),
+        _ => spans.push(WordSpan {
+            kind,
+            text: text.to_string(),
+        }),
+    }
+}
+
+/// Wortweiser Vergleich; je Lücke erst die entfernten, dann die eingefügten Wörter.
+pub fn diff_words(old: &str, new: &str) -> Vec<WordSpan> {
+    let (a, b) = (words(old), words(new));
+    let in_new = matching(&a, &b);
+    let mut spans = Vec::new();
+    let mut j = 0;
+    for (i, token) in a.iter().enumerate() {
+        match in_new[i] {
+            Some(k) => {
+                for inserted in &b[j..k] {
+                    push_span(&mut spans, SpanKind::Insert, inserted);
+                }
+                push_span(&mut spans, SpanKind::Equal, token);
+                j = k + 1;
+            }
+            None => push_span(&mut spans, SpanKind::Delete, token),
+        }
+    }
+    for inserted in &b[j..] {
+        push_span(&mut spans, SpanKind::Insert, inserted);
+    }
+    spans
+}
+
+/// Zeilenweiser Vergleich: ein Hunk je zusammenhängendem geänderten Block.
+pub fn diff_text(old: &str, new: &str) -> Vec<TextHunk> {
+    if old == new {
+        return Vec::new();
+    }
+    let (a, b) = (lines(old), lines(new));
+    let in_new = matching(&a, &b);
+    let mut hunks = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    loop {
+        // Nächste unveränderte Zeile (oder das Ende beider Texte)
+        let (k, kb) = (i..a.len())
+            .find_map(|k| in_new[k].map(|kb| (k, kb)))
+            .unwrap_or((a.len(), b.len()));
+        if k > i || kb > j {
+            let old_text = a[i..k].concat();
+            let new_text = b[j..kb].concat();
+            let words = if old_text.is_empty() || new_text.is_empty() {
+                Vec::new()
+            } else {
+                diff_words(&old_text, &new_text)
+            };
+            hunks.push(TextHunk {
+                old_start: i,
+                old_count: k - i,
+                new_start: j,
+                new_count: kb - j,
+                old_text,
+                new_text,
+                words,
+            });
+        }
+        if k == a.len() {
+            break;
+        }
+        (i, j) = (k + 1, kb + 1);
+    }
+    hunks
+}
+
+/// Labels als Menge — Reihenfolge und `None` vs. leere Liste sind keine Änderung.
+fn label_set(note: &Note) -> BTreeSet<&str> {
+    note.labels.iter().flatten().map(String::as_str).collect()
+}
+
+fn diff_fields(old: &Note, new: &Note) -> Vec<FieldChange> {
+    let pairs = [
+        ("title", json!(old.title), json!(new.title)),
+        ("note_type", json!(old.note_type), json!(new.note_type)),
+        ("color", json!(old.color), json!(new.color)),
+        ("labels", json!(label_set(old)), json!(label_set(new))),
+        (
+            "pinned",
+            json!(old.is_pinned.unwrap_or(false)),
+            json!(new.is_pinned.unwrap_or(false)),
+        ),
+        ("folder", json!(old.folder_name), json!(new.folder_name)),
+    ];
+    pairs
+        .into_iter()
+        .filter(|(_, old, new)| old != new)
+        .map(|(field, old, new)| FieldChange { field, old, new })
+        .collect()
+}
+
+/// Geänderte Felder eines Items; unbekannte Felder einzeln nach Schlüssel.
+fn item_fields(old: &ChecklistItem, new: &ChecklistItem) -> Vec<String> {
+    let mut fields = Vec::new();
+    if old.text != new.text {
+        fields.push("text".to_string());
+    }
+    if old.is_checked != new.is_checked {
+        fields.push("isChecked".to_string());
+    }
+    let keys: BTreeSet<&String> = old.extra.keys().chain(new.extra.keys()).collect();
+    fields.extend(
+        keys.into_iter()
+            .filter(|key| old.extra.get(*key) != new.extra.get(*key))
+            .cloned(),
+    );
+    fields
+}
+
+/// Item-Vergleich über die IDs. Verschoben sind die gemeinsamen Items, die nicht in der
+/// längsten gemeinsamen Reihenfolge liegen — ein einzelnes umgehängtes Item markiert
+/// also nicht alle dazwischenliegenden mit.
+pub fn diff_checklist(old: &[ChecklistItem], new: &[ChecklistItem]) -> Vec<ItemChange> {
+    let (old, new) = (ordered(old), ordered(new));
+    let old_pos: HashMap<&str, usize> = old
+        .iter()
+        .enumerate()
+        .map(|(p, item)| (item.id.as_str(), p))
+        .collect();
+    let new_pos: HashMap<&str, usize> = new
+        .iter()
+        .enumerate()
+        .map(|(p, item)| (item.id.as_str(), p))
+        .collect();
+
+    let mut changes = Vec::new();
+    for (position, item) in old.iter().enumerate() {
+        if !new_pos.contains_key(item.id.as_str()) {
+            changes.push(ItemChange::Removed {
+                item: (*item).clone(),
+                position,
+            });
+        }
+    }
+    for (position, item) in new.iter().enumerate() {
+        match old_pos.get(item.id.as_str()) {
+            None => changes.push(ItemChange::Added {
+                item: (*item).clone(),
+                position,
+            }),
+            Some(&p) => {
+                let fields = item_fields(old[p], item);
+                if !fields.is_empty() {
+                    changes.push(ItemChange::Edited {
+                        old: old[p].clone(),
+                        new: (*item).clone(),
+                        fields,
+                    });
+                }
+            }
+        }
+    }
+
+    let old_common: Vec<&str> = old
+        .iter()
+        .map(|item| item.id.as_str())
+        .filter(|id| new_pos.contains_key(id))
+        .collect();
+    let new_common: Vec<&str> = new
+        .iter()
+        .map(|item| item.id.as_str())
+        .filter(|id| old_pos.contains_key(id))
+        .collect();
+    let kept = matching(&old_common, &new_common);
+    for (k, id) in old_common.iter().enumerate() {
+        if kept[k].is_none() {
+            changes.push(ItemChange::Moved {
+                id: id.to_string(),
+                from: old_pos[id],
+                to: new_pos[id],
+            });
+        }
+    }
+    changes
+}
+
+/// Vergleicht zwei Fassungen derselben (oder einer kopierten) Notiz. Bei zwei Checklisten
+/// ist `content` nur der generierte Fallback und wird nicht verglichen — die Items sind
+/// maßgebend.
+pub fn diff_notes(old: &Note, new: &Note) -> NoteDiff {
+    let both_checklists =
+        old.note_type == NoteType::Checklist && new.note_type == NoteType::Checklist;
+    NoteDiff {
+        fields: diff_fields(old, new),
+        content: if both_checklists {
+            Vec::new()
+        } else {
+            diff_text(&old.content, &new.content)
+        },
+        checklist: diff_checklist(
+            old.checklist_items.as_deref().unwrap_or_default(),
+            new.checklist_items.as_deref().unwrap_or_default(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(content: &str) -> Note {
+        let mut n = Note::new("Notiz".to_string(), "dev".to_string());
+        n.content = content.to_string();
+        n
+    }
+
+    fn item(id: &str, text: &str, order: i32) -> ChecklistItem {
+        ChecklistItem {
+            id: id.to_string(),
+            text: text.to_string(),
+            order,
+            ..Default::default()
+        }
+    }
+
+    fn checklist(items: Vec<ChecklistItem>) -> Note {
+        let mut n = note("");
+        n.note_type = NoteType::Checklist;
+        n.checklist_items = Some(items);
+        n
+    }
+
+    #[test]
+    fn test_identical_notes_have_empty_diff() {
+        let n = note("a\nb\n");
+        assert!(diff_notes(&n, &n.clone()).is_empty());
+    }
+
+    #[test]
+    fn test_diff_text_hunks() {
+        let hunks = diff_text("a\nb\nc\nd\n", "a\nB\nc\nd\ne\n");
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(
+            (
+                hunks[0].old_start,
+                hunks[0].old_count,
+                hunks[0].new_start,
+                hunks[0].new_count
+            ),
+            (1, 1, 1, 1)
+        );
+        assert_eq!(hunks[0].old_text, "b\n");
+        assert_eq!(hunks[0].new_text, "B\n");
+        // Reine Einfügung am Ende: keine Wort-Spans
+        assert_eq!(
+            (
+                hunks[1].old_start,
+                hunks[1].old_count,
+                hunks[1].new_start,
+                hunks[1].new_count
+            ),
+            (4, 0, 4, 1)
+        );
+        assert_eq!(hunks[1].new_text, "e\n");
+        assert!(hunks[1].words.is_empty());
+    }
+
+    #[test]
+    fn test_diff_text_deletion_at_start() {
+        let hunks = diff_text("x\ny\nz", "y\nz");
+        assert_eq!(hunks.len(), 1);
+        assert_eq!(
+            (hunks[0].old_start, hunks[0].old_count, hunks[0].new_count),
+            (0, 1, 0)
+        );
+        assert_eq!(hunks[0].old_text, "x\n");
+    }
+
+    #[test]
+    fn test_diff_words_within_changed_line() {
+        let spans = diff_words("Milch und Brot kaufen\n", "Hafermilch und Brot holen\n");
+        let kinds: Vec<(SpanKind, &str)> =
+            spans.iter().map(|s| (s.kind, s.text.as_str())).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (SpanKind::Delete, "Milch"),
+                (SpanKind::Insert, "Hafermilch"),
+                (SpanKind::Equal, " und Brot "),
+                (SpanKind::Delete, "kaufen"),
+                (SpanKind::Insert, "holen"),
+                (SpanKind::Equal, "\n"),
+            ]
+        );
+        // Spans ergeben verlustfrei beide Fassungen
+        let old: String = spans
+            .iter()
+            .filter(|s| s.kind != SpanKind::Insert)
+            .map(|s| s.text.as_str())
+            .collect();
+        assert_eq!(old, "Milch und Brot kaufen\n");
+    }
+
+    #[test]
+    fn test_diff_fields() {
+        let old = note("x");
+        let mut new = old.clone();
+        new.title = "Neu".to_string();
+        new.color = Some("#FF0000".to_string());
+        new.labels = Some(vec!["b".to_string(), "a".to_string()]);
+        new.is_pinned = Some(true);
+        new.folder_name = Some("Arbeit".to_string());
+        let diff = diff_notes(&old, &new);
+        let fields: Vec<&str> = diff.fields.iter().map(|f| f.field).collect();
+        assert_eq!(fields, vec!["title", "color", "labels", "pinned", "folder"]);
+        assert_eq!(diff.fields[2].new, json!(["a", "b"]));
+        assert_eq!(diff.fields[4].old, Value::Null);
+        assert!(diff.content.is_empty());
+    }
+
+    #[test]
+    fn test_unset_and_default_fields_are_equal() {
+        let old = note("x");
+        let mut new = old.clone();
+        new.labels = Some(Vec::new());
+        new.is_pinned = Some(false);
+        assert!(diff_notes(&old, &new).is_empty());
+
+        new.labels = Some(vec!["a".to_string(), "b".to_string()]);
+        let mut reordered = new.clone();
+        reordered.labels = Some(vec!["b".to_string(), "a".to_string()]);
+        assert!(diff_notes(&new, &reordered).is_empty());
+    }
+
+    #[test]
+    fn test_diff_checklist_items() {
+        let old = checklist(vec![
+            item("1", "Milch", 0),
+            item("2", "Brot", 1),
+            item("3", "Eier", 2),
+        ]);
+        let mut done = item("2", "Brot", 0);
+        done.is_checked = true;
+        done.extra.insert("indentationLevel".to_string(), json!(1));
+        let mut new = checklist(vec![done, item("3", "Eier", 1), item("4", "Käse", 2)]);
+        // Checklist-Fallback im content wird ignoriert
+        new.content = "anders".to_string();
+
+        let diff = diff_notes(&old, &new);
+        assert!(diff.content.is_empty());
+        assert_eq!(diff.checklist.len(), 3);
+        assert!(
+            matches!(&diff.checklist[0], ItemChange::Removed { item, position: 0 } if item.id == "1")
+        );
+        assert!(
+            matches!(&diff.checklist[1], ItemChange::Edited { new, fields, .. }
+                if new.id == "2" && fields == &["isChecked", "indentationLevel"])
+        );
+        assert!(
+            matches!(&diff.checklist[2], ItemChange::Added { item, position: 2 } if item.id == "4")
+        );
+    }
+
+    #[test]
+    fn test_diff_checklist_reorder_marks_only_moved_item() {
+        let old = vec![
+            item("a", "A", 0),
+            item("b", "B", 1),
+            item("c", "C", 2),
+            item("d", "D", 3),
+        ];
+        let new = vec![
+            item("d", "D", 0),
+            item("a", "A", 1),
+            item("b", "B", 2),
+            item("c", "C", 3),
+        ];
+        assert_eq!(
+            diff_checklist(&old, &new),
+            vec![ItemChange::Moved {
+                id: "d".to_string(),
+                from: 3,
+                to: 0
+            }]
+        );
+        // Nur neu nummeriert, Reihenfolge gleich → keine Änderung
+        let renumbered: Vec<ChecklistItem> = old
+            .iter()
+            .map(|i| ChecklistItem {
+                order: i.order * 10,
+                ..i.clone()
+            })
+            .collect();
+        assert!(diff_checklist(&old, &renumbered).is_empty());
+    }
+
+    #[test]
+    fn test_type_change_diffs_content() {
+        let old = note("Milch\n");
+        let mut new = checklist(vec![item("1", "Milch", 0)]);
+        new.content = "☐ Milch\n".to_string();
+        let diff = diff_notes(&old, &new);
+        assert_eq!(diff.fields[0].field, "note_type");
+        assert_eq!(diff.content.len(), 1);
+        assert!(matches!(&diff.checklist[0], ItemChange::Added { .. }));
+    }
+
+    #[test]
+    fn test_diff_serializes_tagged() {
+        let diff = diff_checklist(&[], &[item("1", "Milch", 0)]);
+        let value = serde_json::to_value(&diff).unwrap();
+        assert_eq!(value[0]["change"], "added");
+        assert_eq!(value[0]["item"]["isChecked"], false);
+        let version: NoteVersion =
+            serde_json::from_value(json!({"source": "conflict_local", "id": "x"})).unwrap();
+        assert!(matches!(version, NoteVersion::ConflictLocal { id } if id == "x"));
+    }
+}
@@ -31,12 +31,61 @@ pub enum AppError {
     #[error("Network error: {0}")]
     NetworkError(String),
 
+    /// Server-Datei hat sich seit dem letzten Lesen geändert (412 Precondition Failed)
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    /// Server hat den Sync-Token eines sync-collection REPORTs verworfen (RFC 6578)
+    #[error("Sync token rejected by server")]
+    InvalidSyncToken,
+
+    /// Server unterstützt die angeforderte WebDAV-Funktion nicht
+    #[error("Not supported by server: {0}")]
+    Unsupported(String),
+
+    /// TLS-Einstellungen unbrauchbar (CA-Datei fehlt, Pin fehlt, …)
+    #[error("TLS configuration error: {0}")]
+    TlsConfig(String),
+
+    /// Proxy-Einstellungen unbrauchbar (Adresse fehlt, Schema falsch, …)
+    #[error("Proxy configuration error: {0}")]
+    ProxyConfig(String),
+
+    /// Server-Zertifikat entspricht nicht dem gepinnten Fingerprint (möglicher MITM)
+    #[error("TLS certificate mismatch: server presented {0}, which does not match the pinned fingerprint")]
+    CertificateMismatch(String),
+
+    /// Server vorübergehend überlastet/nicht verfügbar (429/502/503/504), auch nach Wiederholungen
+    #[error("Server temporarily unavailable: {0}")]
+    ServerBusy(String),
+
+    /// Speicherplatz/Quota am Server erschöpft (507 Insufficient Storage, RFC 4918 §11.5)
+    #[error("Insufficient storage on server: {0}")]
+    InsufficientStorage(String),
+
+    /// Profil unbekannt, Name ungültig/doppelt oder Profil nicht löschbar
+    #[error("Profile error: {0}")]
+    Profile(String),
+
+    /// Umzug auf ein neues Speicherziel unvollständig (Settings bleiben unverändert)
+    #[error("Migration failed: {0}")]
+    Migration(String),
+
     /// Ungültiges Timestamp-Format
     #[allow(dead_code)]
     #[error("Invalid timestamp: {0}")]
     InvalidTimestamp(String),
 }
 
+impl AppError {
+    /// Vorübergehender Fehler, bei dem ein späterer Versuch Erfolg haben kann
+    /// (Verbindungsabbruch, Timeout, Rate-Limit). Zertifikats-, Auth- und
+    /// Inhaltsfehler sind dauerhaft und werden nicht wiederholt.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, AppError::NetworkError(_) | AppError::ServerBusy(_))
+    }
+}
+
 /// Serialisierung für Tauri (Fehler als String)
 impl serde::Serialize for AppError {
     fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
@@ -66,6 +115,57 @@ mod tests {
         assert!(err.to_string().contains("404"));
     }
 
+    #[test]
+    fn test_error_conflict() {
+        let err = AppError::Conflict("abc123".to_string());
+        assert!(err.to_string().starts_with("Conflict"));
+        assert!(err.to_string().contains("abc123"));
+    }
+
+    #[test]
+    fn test_error_unsupported() {
+        let err = AppError::Unsupported("REPORT".to_string());
+        assert_eq!(err.to_string(), "Not supported by server: REPORT");
+    }
+
+    #[test]
+    fn test_error_certificate_mismatch() {
+        let err = AppError::CertificateMismatch("AB:CD".to_string());
+        assert!(err.to_string().contains("AB:CD"));
+        assert!(err.to_string().contains("pinned"));
+    }
+
+    #[test]
+    fn test_error_insufficient_storage() {
+        let err = AppError::InsufficientStorage("PUT /notes/a.json".to_string());
+        assert!(err.to_string().starts_with("Insufficient storage"));
+        assert!(!err.is_retryable());
+    }
+
+    #[test]
+    fn test_error_profile() {
+        let err = AppError::Profile("Unbekanntes Profil: p-1".to_string());
+        assert_eq!(err.to_string(), "Profile error: Unbekanntes Profil: p-1");
+        assert!(!err.is_retryable());
+    }
+
+    #[test]
+    fn test_error_migration() {
+        let err = AppError::Migration("2 von 5 Notizen fehlen am neuen Speicherort".to_string());
+        assert!(err.to_string().starts_with("Migration failed: 2 von 5"));
+        assert!(!err.is_retryable());
+    }
+
+    #[test]
+    fn test_error_retryable_classification() {
+        assert!(AppError::NetworkError("connection reset".to_string()).is_retryable());
+        assert!(AppError::ServerBusy("503".to_string()).is_retryable());
+        assert!(!AppError::InvalidCredentials.is_retryable());
+        assert!(!AppError::Conflict("x".to_string()).is_retryable());
+        assert!(!AppError::CertificateMismatch("AB".to_string()).is_retryable());
+        assert!(!AppError::WebDav("404".to_string()).is_retryable());
+    }
+
     #[test]
     fn test_error_serialize() {
         let err = AppError::NoteNotFound("abc123".to_string());
@@ -19,6 +19,9 @@ pub struct FolderMeta {
     /// Nur lokal, nie zum Server synchronisiert (Desktop-intern, Android ignoriert es).
     #[serde(default, skip_serializing_if = "is_false")]
     pub local_only: bool,
+    /// Weitere Felder (u.a. der HLC-Stempel `hlc`), beim Round-Trip 1:1 erhalten
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 /// UI-facing Ordner-Typ (an das Frontend zurückgegeben)
@@ -93,10 +96,7 @@ pub fn parse_folders_json(text: &str) -> Vec<FolderMeta> {
             if !trimmed.is_empty() {
                 result.push(FolderMeta {
                     name: trimmed,
-                    color: None,
-                    updated_at: 0,
-                    deleted: false,
-                    local_only: false,
+                    ..FolderMeta::default()
                 });
             }
         } else if item.is_object() {
@@ -115,10 +115,10 @@ pub fn parse_folders_json(text: &str) -> Vec<FolderMeta> {
 }
 
 /// LWW-Merge zweier `FolderMeta`-Listen (case-insensitiver Schlüssel).
-/// Höhere `updatedAt` gewinnt; bei Gleichstand gewinnt Tombstone über Lebender.
+/// Neuerer Stand gewinnt (HLC-Stempel, ohne Stempel `updatedAt`); bei Gleichstand gewinnt
+/// Tombstone über Lebender.
 /// Insertion-Order der local-Liste bleibt erhalten (neue remote-Einträge hinten).
-/// Wird im Server-Format-Test und für zukünftige Multi-Device-Sync-Fälle gebraucht.
-#[allow(dead_code)]
+/// Auch für Merge-Konflikte auf `folders.json` im Git-Backend.
 pub fn merge_by_name(local: Vec<FolderMeta>, remote: Vec<FolderMeta>) -> Vec<FolderMeta> {
     let mut result: Vec<FolderMeta> = Vec::new();
     let mut index: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
@@ -127,8 +127,11 @@ pub fn merge_by_name(local: Vec<FolderMeta>, remote: Vec<FolderMeta>) -> Vec<Fol
         let key = meta.name.to_lowercase();
         if let Some(&pos) = index.get(&key) {
             let existing = &result[pos];
-            let replace = meta.updated_at > existing.updated_at
-                || (meta.updated_at == existing.updated_at && meta.deleted && !existing.deleted);
+            let replace = match crate::hlc::compare(&meta, existing) {
+                std::cmp::Ordering::Greater => true,
+                std::cmp::Ordering::Equal => meta.deleted && !existing.deleted,
+                std::cmp::Ordering::Less => false,
+            };
             if replace {
                 result[pos] = meta;
             }
@@ -284,6 +287,7 @@ mod tests {
             updated_at,
             deleted,
             local_only: false,
+            ..FolderMeta::default()
         }
     }
 
@@ -0,0 +1,746 @@
+//! Sync in ein Git-Arbeitsverzeichnis — Verlauf und `git blame` für jede Notiz.
+//!
+//! Die Dateien liegen wie beim lokalen Sync-Verzeichnis ([`LocalDirBackend`]) im
+//! Server-Layout; der Sync-Lauf selbst arbeitet nur auf dem Arbeitsverzeichnis. Drumherum:
+//!
+//! - vor dem Lauf: lokale Änderungen committen, optional `fetch` + `merge` vom Remote
+//! - nach dem Lauf: alle Änderungen als ein Commit (geänderte Notiz-Titel + Geräte-ID),
+//!   optional `push`
+//! - Merge-Konflikte auf einer Notiz-JSON: eigene Fassung bleibt, die fremde wird als
+//!   Konfliktkopie `{id}.sync-conflict-git-{commit}.json` abgelegt → `SyncStatus::Conflict`
+//!   wie bei Syncthing. Konfliktkopien stehen in `.git/info/exclude`.
+//!
+//! Git wird als Kommandozeilenprogramm aufgerufen; Zugangsdaten für den Remote kommen aus
+//! der Git-Konfiguration des Nutzers (SSH-Agent, Credential-Helper).
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Output;
+
+use tokio::process::Command;
+
+use crate::error::{AppError, Result};
+use crate::folders::{merge_by_name, parse_folders_json};
+use crate::hlc;
+use crate::local_dir::LocalDirBackend;
+use crate::models::{DeletionLedger, Note};
+use crate::webdav::{sync_folder_name, UUID_PATTERN};
+
+/// Name des konfigurierten Remotes im Arbeitsverzeichnis
+const REMOTE: &str = "origin";
+/// Branch neuer Repositories
+const DEFAULT_BRANCH: &str = "main";
+/// Fallback-Identität für Commits, wenn Git keine `user.email` kennt
+const FALLBACK_NAME: &str = "Simple Notes";
+const FALLBACK_EMAIL: &str = "simple-notes@localhost";
+/// Konfliktkopien und temporäre Dateien nie committen
+const EXCLUDES: &str = "*.sync-conflict-*\n.syncthing.*\n";
+
+/// Git-Arbeitsverzeichnis als Speicherziel.
+#[derive(Debug, Clone)]
+pub struct GitBackend {
+    dir: LocalDirBackend,
+    /// Wurzel des Arbeitsverzeichnisses (= vom Nutzer gewähltes Verzeichnis)
+    repo: PathBuf,
+    sync_folder: String,
+    /// Remote-URL; `None` = nur lokale Commits
+    remote: Option<String>,
+}
+
+/// Änderung einer Notiz im Commit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Change {
+    Added,
+    Modified,
+    Deleted,
+    Moved,
+}
+
+impl Change {
+    fn label(self) -> &'static str {
+        match self {
+            Change::Added => "added",
+            Change::Modified => "changed",
+            Change::Deleted => "deleted",
+            Change::Moved => "moved",
+        }
+    }
+}
+
+/// Commit-Nachricht eines Sync-Laufs: Geräte-ID im Betreff, darunter die Notiz-Titel.
+fn commit_message(device_id: &str, changes: &[(String, Change)]) -> String {
+    let subject = match changes.len() {
+        0 => format!("Sync from device {}: folders and metadata", device_id),
+        1 => format!("Sync from device {}: 1 note changed", device_id),
+        n => format!("Sync from device {}: {} notes changed", device_id, n),
+    };
+    let mut message = subject;
+    if !changes.is_empty() {
+        message.push('\n');
+    }
+    for (title, change) in changes {
+        message.push_str(&format!("\n- {} ({})", title, change.label()));
+    }
+    message
+}
+
+/// Vereinigung zweier Lösch-Ledger; pro ID gewinnt die spätere Löschung (HLC, sonst Wanduhr).
+fn merge_ledgers(ours: DeletionLedger, theirs: DeletionLedger) -> DeletionLedger {
+    let mut merged = ours;
+    merged.version = merged.version.max(theirs.version);
+    for record in theirs.deleted_notes {
+        match merged.deleted_notes.iter_mut().find(|r| r.id == record.id) {
+            Some(existing) if hlc::compare(existing, &record) != Ordering::Less => {}
+            Some(existing) => *existing = record,
+            None => merged.deleted_notes.push(record),
+        }
+    }
+    merged
+}
+
+fn write_file(path: &Path, content: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| AppError::StorageError(e.to_string()))?;
+    }
+    fs::write(path, content)
+        .map_err(|e| AppError::StorageError(format!("{}: {}", path.display(), e)))
+}
+
+fn git_error(args: &[&str], output: &Output) -> AppError {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let detail = if stderr.trim().is_empty() {
+        stdout.trim().to_string()
+    } else {
+        stderr.trim().to_string()
+    };
+    // Unterbefehl für die Meldung: `-c name=wert`-Paare überspringen
+    let mut rest = args.iter();
+    let mut command = "";
+    while let Some(arg) = rest.next() {
+        if *arg == "-c" {
+            rest.next();
+        } else if !arg.starts_with('-') {
+            command = arg;
+            break;
+        }
+    }
+    AppError::StorageError(format!("git {}: {}", command, detail))
+}
+
+impl GitBackend {
+    /// `dir` muss existieren (siehe [`LocalDirBackend::new`]); ein Repository wird beim
+    /// ersten Lauf angelegt. `remote` leer = ohne Push/Pull.
+    pub fn new(dir: &str, sync_folder: &str, remote: &str) -> Result<Self> {
+        let local = LocalDirBackend::new(dir, sync_folder)?;
+        let remote = remote.trim();
+        // Würde von git als Option gelesen (z.B. `--upload-pack=…`)
+        if remote.starts_with('-') {
+            return Err(AppError::StorageError(format!(
+                "Ungültige Git-Remote-URL: {}",
+                remote
+            )));
+        }
+        Ok(Self {
+            dir: local,
+            repo: PathBuf::from(dir.trim()),
+            sync_folder: sync_folder_name(sync_folder),
+            remote: (!remote.is_empty()).then(|| remote.to_string()),
+        })
+    }
+
+    /// Das Arbeitsverzeichnis als Sync-Backend.
+    pub fn dir(&self) -> &LocalDirBackend {
+        &self.dir
+    }
+
+    // ── Git-Aufrufe ─────────────────────────────────────────────────────────────
+
+    /// Führt `git -C {repo} {args}` aus, ohne den Exit-Status zu prüfen.
+    async fn output(&self, args: &[&str]) -> Result<Output> {
+        Command::new("git")
+            .arg("-C")
+            .arg(&self.repo)
+            .args(args)
+            // Nie interaktiv nach Zugangsdaten fragen — der Sync läuft im Hintergrund
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .env("LC_ALL", "C")
+            .kill_on_drop(true)
+            .output()
+            .await
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => {
+                    AppError::StorageError("git ist nicht installiert".to_string())
+                }
+                _ => AppError::StorageError(format!("git: {}", e)),
+            })
+    }
+
+    /// Führt `git` aus und liefert stdout; Exit-Status ≠ 0 → `AppError::StorageError`.
+    async fn git(&self, args: &[&str]) -> Result<String> {
+        let output = self.output(args).await?;
+        if !output.status.success() {
+            return Err(git_error(args, &output));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// `-c user.name=… -c user.email=…`, falls Git keine Identität kennt — Commits schlügen
+    /// sonst fehl. Sonst committet die Identität des Nutzers.
+    async fn identity_args(&self) -> Vec<String> {
+        if self.git(&["config", "user.email"]).await.is_ok() {
+            return Vec::new();
+        }
+        vec![
+            "-c".to_string(),
+            format!("user.name={}", FALLBACK_NAME),
+            "-c".to_string(),
+            format!("user.email={}", FALLBACK_EMAIL),
+        ]
+    }
+
+    async fn commit(&self, message: &str) -> Result<()> {
+        let identity = self.identity_args().await;
+        let mut args: Vec<&str> = identity.iter().map(String::as_str).collect();
+        args.extend(["commit", "--quiet", "--no-verify", "-m", message]);
+        self.git(&args).await.map(|_| ())
+    }
+
+    /// Legt das Repository an, falls das Verzeichnis noch keins ist, und hält die
+    /// Ausschlüsse für Konfliktkopien aktuell.
+    async fn ensure_repo(&self) -> Result<()> {
+        if !self.repo.join(".git").exists() {
+            self.git(&["init", "--quiet"]).await?;
+            self.git(&[
+                "symbolic-ref",
+                "HEAD",
+                &format!("refs/heads/{}", DEFAULT_BRANCH),
+            ])
+            .await?;
+        }
+        let exclude = self.repo.join(".git").join("info").join("exclude");
+        let current = fs::read_to_string(&exclude).unwrap_or_default();
+        if !current.contains(EXCLUDES) {
+            write_file(&exclude, &format!("{}{}", current, EXCLUDES))?;
+        }
+        Ok(())
+    }
+
+    async fn branch(&self) -> Result<String> {
+        Ok(self
+            .git(&["symbolic-ref", "--short", "HEAD"])
+            .await?
+            .trim()
+            .to_string())
+    }
+
+    async fn has_commits(&self) -> bool {
+        self.git(&["rev-parse", "--verify", "--quiet", "HEAD"])
+            .await
+            .is_ok()
+    }
+
+    /// Remote `origin` auf die eingestellte URL setzen.
+    async fn ensure_remote(&self, url: &str) -> Result<()> {
+        match self.git(&["remote", "get-url", REMOTE]).await {
+            Ok(current) if current.trim() == url => Ok(()),
+            Ok(_) => self
+                .git(&["remote", "set-url", "--", REMOTE, url])
+                .await
+                .map(|_| ()),
+            Err(_) => self
+                .git(&["remote", "add", "--", REMOTE, url])
+                .await
+                .map(|_| ()),
+        }
+    }
+
+    // ── Commit ──────────────────────────────────────────────────────────────────
+
+    /// ID einer Notiz-JSON im Sync-Ordner (`{sync_folder}/{ordner/}{id}.json`), sonst `None`.
+    fn note_id(&self, path: &str) -> Option<String> {
+        let rest = path.strip_prefix(&format!("{}/", self.sync_folder))?;
+        if rest.matches('/').count() > 1 {
+            return None;
+        }
+        let file = rest.rsplit('/').next()?;
+        UUID_PATTERN.captures(file).map(|c| c[1].to_lowercase())
+    }
+
+    /// Titel der Notiz unter `path` im Arbeitsverzeichnis bzw. (gelöscht) in `HEAD`.
+    async fn note_title(&self, path: &str, deleted: bool) -> Option<String> {
+        let text = if deleted {
+            self.git(&["show", &format!("HEAD:{}", path)]).await.ok()?
+        } else {
+            fs::read_to_string(self.repo.join(path)).ok()?
+        };
+        serde_json::from_str::<Note>(&text).ok().map(|n| n.title)
+    }
+
+    /// Geänderte Notizen im Index, nach ID zusammengefasst (Löschen + Anlegen = Verschieben).
+    async fn staged_changes(&self) -> Result<Option<Vec<(String, Change)>>> {
+        let status = self
+            .git(&["diff", "--cached", "--name-status", "--no-renames", "-z"])
+            .await?;
+        let fields: Vec<&str> = status.split('\0').filter(|f| !f.is_empty()).collect();
+        if fields.is_empty() {
+            return Ok(None);
+        }
+        let mut notes: BTreeMap<String, (String, Change)> = BTreeMap::new();
+        for pair in fields.chunks(2) {
+            let [kind, path] = pair else { continue };
+            let Some(id) = self.note_id(path) else {
+                continue;
+            };
+            let change = match *kind {
+                "A" => Change::Added,
+                "D" => Change::Deleted,
+                _ => Change::Modified,
+            };
+            let title = self
+                .note_title(path, change == Change::Deleted)
+                .await
+                .unwrap_or_else(|| id.clone());
+            notes
+                .entry(id)
+                .and_modify(|(existing_title, existing)| {
+                    if *existing != change {
+                        *existing = Change::Moved;
+                    }
+                    if change != Change::Deleted {
+                        *existing_title = title.clone();
+                    }
+                })
+                .or_insert((title, change));
+        }
+        let mut changes: Vec<(String, Change)> = notes.into_values().collect();
+        changes.sort_by_key(|(title, _)| title.to_lowercase());
+        Ok(Some(changes))
+    }
+
+    /// Committet alle Änderungen im Arbeitsverzeichnis. `Ok(false)` = nichts zu tun.
+    async fn commit_pending(&self, device_id: &str) -> Result<bool> {
+        self.git(&["add", "--all"]).await?;
+        let Some(changes) = self.staged_changes().await? else {
+            return Ok(false);
+        };
+        self.commit(&commit_message(device_id, &changes)).await?;
+        Ok(true)
+    }
+
+    // ── Pull ────────────────────────────────────────────────────────────────────
+
+    /// Löst einen Merge-Konflikt auf einem Pfad auf und merkt ihn vor.
+    /// Notiz-JSON: eigene Fassung bleibt, fremde wird Konfliktkopie. `folders.json` und
+    /// `deletions.json` werden inhaltlich zusammengeführt, alles andere (Markdown-Spiegel)
+    /// übernimmt die fremde Fassung — er wird beim nächsten Upload ohnehin neu geschrieben.
+    async fn resolve_conflict(&self, path: &str, theirs_rev: &str) -> Result<()> {
+        let ours = self.git(&["show", &format!(":2:{}", path)]).await.ok();
+        let theirs = self.git(&["show", &format!(":3:{}", path)]).await.ok();
+        let full = self.repo.join(path);
+        let folders_file = format!("{}/folders.json", self.sync_folder);
+        let deletions_file = format!("{}/deletions.json", self.sync_folder);
+
+        let resolved = if let Some(id) = self.note_id(path) {
+            if let (Some(_), Some(theirs)) = (&ours, &theirs) {
+                let copy =
+                    full.with_file_name(format!("{}.sync-conflict-git-{}.json", id, theirs_rev));
+                write_file(&copy, theirs)?;
+            }
+            // Auf einer Seite gelöscht, auf der anderen geändert → die Änderung gewinnt
+            ours.or(theirs)
+        } else if path == folders_file {
+            let merged = merge_by_name(
+                parse_folders_json(ours.as_deref().unwrap_or_default()),
+                parse_folders_json(theirs.as_deref().unwrap_or_default()),
+            );
+            serde_json::to_string_pretty(&merged).ok()
+        } else if path == deletions_file {
+            let parse = |text: Option<&String>| -> DeletionLedger {
+                text.and_then(|t| serde_json::from_str(t).ok())
+                    .unwrap_or_default()
+            };
+            let merged = merge_ledgers(parse(ours.as_ref()), parse(theirs.as_ref()));
+            serde_json::to_string_pretty(&merged).ok()
+        } else {
+            theirs.or(ours)
+        };
+
+        match resolved {
+            Some(content) => write_file(&full, &content)?,
+            None => {
+                let _ = fs::remove_file(&full);
+            }
+        }
+        self.git(&["add", "--all", "--", path]).await.map(|_| ())
+    }
+
+    /// Holt den Stand des Remotes und führt ihn mit dem lokalen zusammen. Lokale Änderungen
+    /// werden vorher committet. Liefert die Zahl der aufgelösten Konflikt-Pfade.
+    pub async fn pull(&self, device_id: &str) -> Result<usize> {
+        self.ensure_repo().await?;
+        let Some(url) = &self.remote else {
+            return Ok(0);
+        };
+        self.ensure_remote(url).await?;
+        self.commit_pending(device_id).await?;
+        let branch = self.branch().await?;
+        self.git(&["fetch", "--quiet", REMOTE]).await?;
+        let upstream = format!("{}/{}", REMOTE, branch);
+        let theirs_rev = match self
+            .git(&["rev-parse", "--verify", "--quiet", "--short", &upstream])
+            .await
+        {
+            Ok(rev) => rev.trim().to_string(),
+            // Leerer Remote → nichts zu holen, der Push legt den Branch an
+            Err(_) => return Ok(0),
+        };
+
+        let identity = self.identity_args().await;
+        let mut merge_args: Vec<&str> = identity.iter().map(String::as_str).collect();
+        merge_args.extend([
+            "merge",
+            "--no-edit",
+            "--no-verify",
+            "--allow-unrelated-histories",
+            &upstream,
+        ]);
+        let output = self.output(&merge_args).await?;
+        if output.status.success() {
+            return Ok(0);
+        }
+
+        let unmerged = self
+            .git(&["diff", "--name-only", "--diff-filter=U", "-z"])
+            .await?;
+        let paths: Vec<&str> = unmerged.split('\0').filter(|p| !p.is_empty()).collect();
+        if paths.is_empty() {
+            // Kein inhaltlicher Konflikt (z.B. unversionierte Dateien im Weg) → abbrechen
+            let _ = self.git(&["merge", "--abort"]).await;
+            return Err(git_error(&merge_args, &output));
+        }
+        for path in &paths {
+            self.resolve_conflict(path, &theirs_rev).await?;
+        }
+        self.commit(&format!(
+            "Merge {} from device {}: {} conflict(s) resolved",
+            upstream,
+            device_id,
+            paths.len()
+        ))
+        .await?;
+        Ok(paths.len())
+    }
+
+    // ── Commit + Push ───────────────────────────────────────────────────────────
+
+    /// Committet die Änderungen des Sync-Laufs und schiebt sie zum Remote. Ein abgelehnter
+    /// Push (Remote inzwischen weiter) wird beim nächsten Lauf per Pull + Merge nachgeholt.
+    pub async fn commit_and_push(&self, device_id: &str) -> Result<bool> {
+        self.ensure_repo().await?;
+        let committed = self.commit_pending(device_id).await?;
+        if self.remote.is_some() && self.has_commits().await {
+            let branch = self.branch().await?;
+            self.git(&[
+                "push",
+                "--quiet",
+                REMOTE,
+                &format!("HEAD:refs/heads/{}", branch),
+            ])
+            .await?;
+        }
+        Ok(committed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::local_store;
+    use crate::memory_backend::TestApp;
+    use crate::models::{DeletionRecord, SyncStatus};
+    use crate::sync_backend::{SyncBackend, SyncTarget};
+    use crate::webdav::WriteCondition;
+
+    /// Temporäres Verzeichnis, wird beim Drop entfernt.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new() -> Self {
+            let dir =
+                std::env::temp_dir().join(format!("simple-notes-git-{}", uuid::Uuid::new_v4()));
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+
+        fn path(&self) -> &str {
+            self.0.to_str().unwrap()
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// Leeres Bare-Repository als Remote.
+    fn bare_remote() -> TempDir {
+        let remote = TempDir::new();
+        git_sync(remote.path(), &["init", "--quiet", "--bare"]);
+        remote
+    }
+
+    fn checkout(remote: Option<&TempDir>) -> (TempDir, GitBackend) {
+        let dir = TempDir::new();
+        let backend =
+            GitBackend::new(dir.path(), "notes", remote.map_or("", |r| r.path())).unwrap();
+        (dir, backend)
+    }
+
+    fn git_sync(dir: &str, args: &[&str]) -> String {
+        let output = std::process::Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?}: {:?}", args, output);
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    }
+
+    fn last_message(dir: &str) -> String {
+        git_sync(dir, &["log", "-1", "--format=%B"])
+    }
+
+    fn note(title: &str, folder: Option<&str>) -> Note {
+        let mut note = Note::new(title.to_string(), "tauri-test".to_string());
+        note.content = format!("{} Inhalt", title);
+        note.folder_name = folder.map(str::to_owned);
+        note
+    }
+
+    #[test]
+    fn test_rejects_remote_url_starting_with_dash() {
+        let dir = TempDir::new();
+        assert!(GitBackend::new(dir.path(), "notes", "--upload-pack=touch /tmp/x").is_err());
+        assert!(GitBackend::new(dir.path(), "notes", " -c core.sshCommand=x").is_err());
+    }
+
+    #[test]
+    fn test_commit_message_lists_titles_and_device() {
+        let message = commit_message(
+            "device-a",
+            &[
+                ("Einkauf".to_string(), Change::Added),
+                ("Reise".to_string(), Change::Moved),
+            ],
+        );
+        assert_eq!(
+            message,
+            "Sync from device device-a: 2 notes changed\n\n- Einkauf (added)\n- Reise (moved)"
+        );
+        assert_eq!(
+            commit_message("device-a", &[]),
+            "Sync from device device-a: folders and metadata"
+        );
+    }
+
+    #[test]
+    fn test_merge_ledgers_keeps_later_deletion() {
+        let record = |id: &str, at: i64, device: &str| DeletionRecord {
+            id: id.to_string(),
+            deleted_at: at,
+            device_id: device.to_string(),
+            ..Default::default()
+        };
+        let ours = DeletionLedger {
+            version: 1,
+            deleted_notes: vec![record("a", 5, "ours"), record("b", 1, "ours")],
+        };
+        let theirs = DeletionLedger {
+            version: 1,
+            deleted_notes: vec![record("b", 7, "theirs"), record("c", 3, "theirs")],
+        };
+        let merged = merge_ledgers(ours, theirs);
+        let summary: Vec<(&str, i64)> = merged
+            .deleted_notes
+            .iter()
+            .map(|r| (r.id.as_str(), r.deleted_at))
+            .collect();
+        assert_eq!(summary, vec![("a", 5), ("b", 7), ("c", 3)]);
+    }
+
+    #[tokio::test]
+    async fn test_commit_per_run_lists_changed_notes() {
+        let (dir, repo) = checkout(None);
+        let mut n = note("Erste Notiz", None);
+        repo.dir()
+            .put_note(&n, WriteCondition::IfNoneMatch)
+            .await
+            .unwrap();
+        assert!(repo.commit_and_push("device-a").await.unwrap());
+        let message = last_message(dir.path());
+        assert!(message.starts_with("Sync from device device-a: 1 note changed"));
+        assert!(message.contains("- Erste Notiz (added)"));
+        let files = git_sync(dir.path(), &["ls-files"]);
+        assert!(files.contains(&format!("notes/{}.json", n.id)));
+        assert!(files.contains("notes-md/Erste Notiz.md"));
+
+        // Nichts geändert → kein leerer Commit
+        assert!(!repo.commit_and_push("device-a").await.unwrap());
+
+        n.title = "Umbenannt".to_string();
+        repo.dir()
+            .put_note(&n, WriteCondition::Unconditional)
+            .await
+            .unwrap();
+        repo.commit_and_push("device-a").await.unwrap();
+        assert!(last_message(dir.path()).contains("- Umbenannt (changed)"));
+
+        repo.dir()
+            .move_note(&n.id, None, Some("Archiv"))
+            .await
+            .unwrap();
+        repo.commit_and_push("device-a").await.unwrap();
+        assert!(last_message(dir.path()).contains("- Umbenannt (moved)"));
+
+        repo.dir().delete_note(&n.id, Some("Archiv")).await.unwrap();
+        repo.commit_and_push("device-b").await.unwrap();
+        let message = last_message(dir.path());
+        assert!(message.starts_with("Sync from device device-b"));
+        assert!(message.contains("- Umbenannt (deleted)"));
+    }
+
+    #[tokio::test]
+    async fn test_push_and_pull_through_bare_remote() {
+        let remote = bare_remote();
+        let (_a_dir, a) = checkout(Some(&remote));
+        let (_b_dir, b) = checkout(Some(&remote));
+
+        // Leerer Remote: Pull ist ein No-op
+        assert_eq!(b.pull("device-b").await.unwrap(), 0);
+
+        let n = note("Geteilt", Some("Team"));
+        a.pull("device-a").await.unwrap();
+        a.dir()
+            .put_note(&n, WriteCondition::IfNoneMatch)
+            .await
+            .unwrap();
+        a.commit_and_push("device-a").await.unwrap();
+        assert!(git_sync(remote.path(), &["log", "--format=%s", "main"]).contains("device-a"));
+
+        b.pull("device-b").await.unwrap();
+        let (pulled, _) = b.dir().get_note(&n.id, Some("Team")).await.unwrap();
+        assert_eq!(pulled.title, "Geteilt");
+    }
+
+    #[tokio::test]
+    async fn test_merge_conflict_becomes_conflict_copy() {
+        let remote = bare_remote();
+        let (a_dir, a) = checkout(Some(&remote));
+        let (b_dir, b) = checkout(Some(&remote));
+        let mut n = note("Streit", None);
+        a.pull("device-a").await.unwrap();
+        a.dir()
+            .put_note(&n, WriteCondition::IfNoneMatch)
+            .await
+            .unwrap();
+        a.dir()
+            .append_deletions(&["from-a".to_string()], "device-a", 10, None, i64::MAX)
+            .await;
+        a.commit_and_push("device-a").await.unwrap();
+        b.pull("device-b").await.unwrap();
+
+        n.content = "Fassung A".to_string();
+        n.updated_at += 1;
+        a.dir()
+            .put_note(&n, WriteCondition::Unconditional)
+            .await
+            .unwrap();
+        a.commit_and_push("device-a").await.unwrap();
+
+        n.content = "Fassung B".to_string();
+        n.updated_at += 1;
+        b.dir()
+            .put_note(&n, WriteCondition::Unconditional)
+            .await
+            .unwrap();
+        b.dir()
+            .append_deletions(&["from-b".to_string()], "device-b", 10, None, i64::MAX)
+            .await;
+
+        assert!(b.pull("device-b").await.unwrap() >= 2);
+        assert!(git_sync(b_dir.path(), &["diff", "--name-only", "--diff-filter=U"]).is_empty());
+        assert_eq!(b.dir().conflicted_notes().await, vec![n.id.clone()]);
+        let (kept, _) = b.dir().get_note(&n.id, None).await.unwrap();
+        assert_eq!(kept.content, "Fassung B");
+        let mut ledger: Vec<String> = b
+            .dir()
+            .read_deletions()
+            .await
+            .deleted_notes
+            .into_iter()
+            .map(|r| r.id)
+            .collect();
+        ledger.sort();
+        assert_eq!(ledger, vec!["from-a", "from-b"]);
+        // Konfliktkopien bleiben lokal
+        assert!(!git_sync(b_dir.path(), &["status", "--porcelain"]).contains("sync-conflict"));
+
+        b.commit_and_push("device-b").await.unwrap();
+        a.pull("device-a").await.unwrap();
+        let (merged, _) = a.dir().get_note(&n.id, None).await.unwrap();
+        assert_eq!(merged.content, "Fassung B");
+        assert!(a.dir().conflicted_notes().await.is_empty());
+        assert!(git_sync(a_dir.path(), &["log", "--format=%s"]).contains("conflict(s) resolved"));
+    }
+
+    #[tokio::test]
+    async fn test_sync_runs_mark_diverged_notes_as_conflict() {
+        let remote = bare_remote();
+        let (_a_dir, a) = checkout(Some(&remote));
+        let (_b_dir, b) = checkout(Some(&remote));
+        let (app_a, app_b) = (TestApp::new(), TestApp::new());
+        let (target_a, target_b) = (SyncTarget::Git(a), SyncTarget::Git(b));
+        let run = |target: &SyncTarget, app: &TestApp, device: &'static str| {
+            let (target, handle) = (target.clone(), app.handle().clone());
+            async move {
+                target
+                    .run_sync(&handle, device, crate::TRASH_RETENTION_MS)
+                    .await
+            }
+        };
+
+        let mut n = note("Projektplan", None);
+        n.sync_status = SyncStatus::Pending;
+        local_store::put_note(app_a.handle(), &n);
+        assert_eq!(run(&target_a, &app_a, "device-a").await.notes_uploaded, 1);
+        assert_eq!(run(&target_b, &app_b, "device-b").await.notes_downloaded, 1);
+
+        let edit = |app: &TestApp, content: &str| {
+            let mut local = local_store::get_note(app.handle(), &n.id).unwrap();
+            local.content = content.to_string();
+            local.updated_at += 1;
+            local_store::mark_dirty(app.handle(), &mut local);
+            local_store::put_note(app.handle(), &local);
+        };
+        // B synct ohne erreichbaren Remote: nur lokaler Commit, Push schlägt fehl
+        let offline = remote.0.with_extension("offline");
+        fs::rename(&remote.0, &offline).unwrap();
+        edit(&app_b, "Plan B");
+        run(&target_b, &app_b, "device-b").await;
+        fs::rename(&offline, &remote.0).unwrap();
+        edit(&app_a, "Plan A");
+        run(&target_a, &app_a, "device-a").await;
+
+        // Pull von B läuft in einen Merge-Konflikt auf der Notiz-JSON
+        let summary = run(&target_b, &app_b, "device-b").await;
+
+        assert_eq!(summary.conflicts_detected, 1);
+        let stored = local_store::get_note(app_b.handle(), &n.id).unwrap();
+        assert_eq!(stored.sync_status, SyncStatus::Conflict);
+    }
+}
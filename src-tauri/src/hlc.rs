@@ -0,0 +1,232 @@
+//! Hybrid Logical Clock (HLC) für Sync-Entscheidungen.
+//!
+//! Reiner Wanduhr-Vergleich (`updated_at`) lässt ein Gerät mit vorgehender Uhr jedes Rennen
+//! gewinnen. Ein HLC-Stempel besteht aus physischer Zeit (ms), logischem Zähler und Knoten-ID.
+//! Jedes Gerät übernimmt beim Sync den höchsten gesehenen Stempel, eine danach gemachte
+//! Änderung stempelt also immer höher als alles, was das Gerät vorher gesehen hat — auch wenn
+//! seine Uhr nachgeht. Der Stempel steht im round-getrippten `extra`-Feld (`hlc`), Android
+//! erhält ihn unverändert zurück. Er gilt nur zusammen mit dem Wanduhr-Zeitpunkt, unter dem er
+//! vergeben wurde: bearbeitet ein Client ohne HLC (Android, ältere Desktop-Versionen) die
+//! Notiz, bleibt der alte Stempel stehen, zählt aber nicht mehr. Fehlt ein gültiger Stempel auf
+//! einer Seite, wird wie bisher nach Wanduhr verglichen.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use serde_json::{json, Map, Value};
+
+use crate::folders::FolderMeta;
+use crate::models::{DeletionRecord, Note};
+
+/// Schlüssel im `extra`-Feld von Notiz, Ordner-Metadaten und Lösch-Eintrag; Wert
+/// `{"ts": "<physical>:<logical>:<node>", "at": <Wanduhr beim Stempeln>}`
+pub const HLC_KEY: &str = "hlc";
+
+/// Ein HLC-Stempel; Ordnung: physische Zeit, dann Zähler, dann Knoten (nur Gleichstand).
+/// Serialisiert als `"<physical>:<logical>:<node>"`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Hlc {
+    pub physical: i64,
+    pub logical: u32,
+    pub node: String,
+}
+
+impl fmt::Display for Hlc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.physical, self.logical, self.node)
+    }
+}
+
+impl Hlc {
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut parts = s.splitn(3, ':');
+        let physical = parts.next()?.parse().ok()?;
+        let logical = parts.next()?.parse().ok()?;
+        let node = parts.next()?;
+        (!node.is_empty()).then(|| Self {
+            physical,
+            logical,
+            node: node.to_string(),
+        })
+    }
+
+    /// Stempel für eine lokale Änderung: größer als `last`, nie hinter der Wanduhr `now`.
+    pub fn tick(last: Option<&Hlc>, now: i64, node: &str) -> Hlc {
+        match last {
+            Some(last) if last.physical >= now => Hlc {
+                physical: last.physical,
+                logical: last.logical.saturating_add(1),
+                node: node.to_string(),
+            },
+            _ => Hlc {
+                physical: now,
+                logical: 0,
+                node: node.to_string(),
+            },
+        }
+    }
+
+    /// Uhrstand nach dem Empfang eines fremden Stempels: der höhere von beiden (eigener Knoten).
+    pub fn observe(last: Option<&Hlc>, remote: &Hlc, node: &str) -> Hlc {
+        match last {
+            Some(last) if (last.physical, last.logical) >= (remote.physical, remote.logical) => {
+                last.clone()
+            }
+            _ => Hlc {
+                physical: remote.physical,
+                logical: remote.logical,
+                node: node.to_string(),
+            },
+        }
+    }
+}
+
+/// Versionierte Sync-Objekte mit HLC-Stempel im `extra`-Feld und Wanduhr-Fallback.
+pub trait Stamped {
+    fn extra(&self) -> &Map<String, Value>;
+    fn extra_mut(&mut self) -> &mut Map<String, Value>;
+    /// Wanduhr-Zeitpunkt (Unix ms) für den Vergleich ohne Stempel
+    fn wall_clock(&self) -> i64;
+
+    /// Gültiger Stempel: vorhanden und für den aktuellen Wanduhr-Zeitpunkt vergeben.
+    fn hlc(&self) -> Option<Hlc> {
+        let value = self.extra().get(HLC_KEY)?;
+        if value.get("at").and_then(Value::as_i64) != Some(self.wall_clock()) {
+            return None;
+        }
+        value.get("ts").and_then(Value::as_str).and_then(Hlc::parse)
+    }
+
+    /// Stempeln — erst nachdem der Wanduhr-Zeitpunkt gesetzt ist.
+    fn set_hlc(&mut self, hlc: &Hlc) {
+        let value = json!({ "ts": hlc.to_string(), "at": self.wall_clock() });
+        self.extra_mut().insert(HLC_KEY.to_string(), value);
+    }
+}
+
+impl Stamped for Note {
+    fn extra(&self) -> &Map<String, Value> {
+        &self.extra
+    }
+    fn extra_mut(&mut self) -> &mut Map<String, Value> {
+        &mut self.extra
+    }
+    fn wall_clock(&self) -> i64 {
+        self.updated_at
+    }
+}
+
+impl Stamped for FolderMeta {
+    fn extra(&self) -> &Map<String, Value> {
+        &self.extra
+    }
+    fn extra_mut(&mut self) -> &mut Map<String, Value> {
+        &mut self.extra
+    }
+    fn wall_clock(&self) -> i64 {
+        self.updated_at
+    }
+}
+
+impl Stamped for DeletionRecord {
+    fn extra(&self) -> &Map<String, Value> {
+        &self.extra
+    }
+    fn extra_mut(&mut self) -> &mut Map<String, Value> {
+        &mut self.extra
+    }
+    fn wall_clock(&self) -> i64 {
+        self.deleted_at
+    }
+}
+
+/// Zeitliche Ordnung zweier Fassungen: per HLC, wenn beide einen Stempel tragen, sonst per
+/// Wanduhr.
+pub fn compare(a: &impl Stamped, b: &impl Stamped) -> Ordering {
+    match (a.hlc(), b.hlc()) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        _ => a.wall_clock().cmp(&b.wall_clock()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hlc(physical: i64, logical: u32, node: &str) -> Hlc {
+        Hlc {
+            physical,
+            logical,
+            node: node.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_hlc_roundtrip() {
+        let stamp = hlc(1_700_000_000_000, 3, "tauri-ab:cd");
+        assert_eq!(stamp.to_string(), "1700000000000:3:tauri-ab:cd");
+        assert_eq!(Hlc::parse(&stamp.to_string()), Some(stamp));
+        assert_eq!(Hlc::parse("1700000000000:3"), None);
+        assert_eq!(Hlc::parse("abc:0:node"), None);
+        assert_eq!(Hlc::parse("1:0:"), None);
+    }
+
+    #[test]
+    fn test_tick_is_monotonic_when_wall_clock_goes_back() {
+        let first = Hlc::tick(None, 1_000, "a");
+        assert_eq!(first, hlc(1_000, 0, "a"));
+        let second = Hlc::tick(Some(&first), 900, "a");
+        assert_eq!(second, hlc(1_000, 1, "a"));
+        assert!(second > first);
+        assert_eq!(Hlc::tick(Some(&second), 2_000, "a"), hlc(2_000, 0, "a"));
+    }
+
+    #[test]
+    fn test_tick_after_observe_beats_fast_remote_clock() {
+        // Gerät b geht 10 Minuten vor; a übernimmt den Stempel beim Sync und
+        // stempelt die nächste eigene Änderung trotzdem höher
+        let remote = hlc(1_000 + 600_000, 0, "b");
+        let clock = Hlc::observe(Some(&hlc(1_000, 5, "a")), &remote, "a");
+        let next = Hlc::tick(Some(&clock), 1_060, "a");
+        assert!(next > remote);
+        assert_eq!(next.node, "a");
+        // Älterer fremder Stempel ändert die Uhr nicht
+        assert_eq!(Hlc::observe(Some(&next), &hlc(5, 0, "b"), "a"), next);
+    }
+
+    #[test]
+    fn test_compare_prefers_hlc_and_falls_back_to_wall_clock() {
+        let mut fast = Note::new("a".to_string(), "b".to_string());
+        fast.updated_at = 2_000_000;
+        fast.set_hlc(&hlc(2_000_000, 0, "b"));
+        let mut later = fast.clone();
+        later.updated_at = 1_500_000;
+        later.set_hlc(&hlc(2_000_000, 1, "a"));
+        assert_eq!(compare(&later, &fast), Ordering::Greater);
+
+        // Eine Seite ohne Stempel (z.B. Android) → Wanduhr
+        later.extra.remove(HLC_KEY);
+        assert_eq!(compare(&later, &fast), Ordering::Less);
+    }
+
+    #[test]
+    fn test_stamp_survives_note_roundtrip() {
+        let mut note = Note::new("a".to_string(), "d".to_string());
+        note.set_hlc(&hlc(42, 1, "d"));
+        let json = serde_json::to_value(&note).unwrap();
+        assert_eq!(json["hlc"]["ts"], "42:1:d");
+        let back: Note = serde_json::from_value(json).unwrap();
+        assert_eq!(back.hlc(), Some(hlc(42, 1, "d")));
+    }
+
+    #[test]
+    fn test_stamp_is_stale_after_edit_without_hlc() {
+        // Android bearbeitet die Notiz: `extra` bleibt erhalten, `updated_at` ändert sich
+        let mut note = Note::new("a".to_string(), "d".to_string());
+        note.set_hlc(&hlc(note.updated_at + 600_000, 0, "d"));
+        let mut edited = note.clone();
+        edited.updated_at += 1;
+        assert_eq!(edited.hlc(), None);
+        assert_eq!(compare(&edited, &note), Ordering::Greater);
+    }
+}
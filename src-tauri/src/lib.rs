@@ -1,26 +1,51 @@
+mod diagnostics;
+mod diff;
 mod error;
 mod folders;
+mod git_backend;
+mod hlc;
+mod local_dir;
 mod local_store;
 mod markdown;
+#[cfg(test)]
+mod memory_backend;
+mod merge;
+mod migration;
 mod models;
+mod multistatus;
+mod profiles;
+mod proxy;
+mod s3;
+#[cfg(test)]
+mod s3_test_server;
 mod scheduler;
 mod storage;
+mod sync_backend;
 mod sync_engine;
 mod sync_queue;
+#[cfg(test)]
+mod test_server;
+mod tls;
 mod webdav;
 
 use error::{AppError, Result};
 use folders::{validate_folder_name, Folder};
+use git_backend::GitBackend;
+use local_dir::LocalDirBackend;
 use models::{Note, NoteMetadata, SyncStatus};
+use proxy::ProxyPolicy;
+use s3::S3Client;
 use std::sync::{Arc, Mutex};
-use storage::{Credentials, Settings};
+use storage::{AuthScheme, Credentials, Settings};
+use sync_backend::{SyncTarget, SYNC_BACKEND_DIRECTORY, SYNC_BACKEND_GIT, SYNC_BACKEND_S3};
 use tauri::{
     menu::{MenuBuilder, MenuItemBuilder},
     tray::TrayIconBuilder,
-    AppHandle, Emitter, Manager, State,
+    AppHandle, Emitter, Manager, Runtime, State,
 };
 use tauri_plugin_autostart::{MacosLauncher, ManagerExt};
 use tauri_plugin_store::StoreExt;
+use tls::TlsPolicy;
 use uuid::Uuid;
 use webdav::WebDavClient;
 
@@ -29,6 +54,9 @@ pub(crate) const TRASH_RETENTION_MS: i64 = 30 * 24 * 60 * 60 * 1000;
 /// Global WebDAV Client State
 pub(crate) struct WebDavState(Mutex<Option<WebDavClient>>);
 
+/// Global S3 Client State (nur `sync_backend` "s3"; dann ist `WebDavState` leer)
+pub(crate) struct S3State(Mutex<Option<S3Client>>);
+
 /// Global Device ID
 pub(crate) struct DeviceIdState(pub(crate) Mutex<Option<String>>);
 
@@ -76,25 +104,83 @@ pub(crate) fn get_or_create_device_id(
 
 // ============ TAURI COMMANDS ============
 
+/// TLS-Richtlinie aus den Settings. Im Pin-Modus ohne gespeicherten Fingerprint wird das
+/// Server-Zertifikat per Trust-on-first-use abgefragt; der neue Pin wird zurückgegeben und
+/// erst nach erfolgreichem Verbindungstest gespeichert.
+async fn resolve_tls_policy(
+    app: &AppHandle,
+    url: &str,
+    proxy: &ProxyPolicy,
+) -> Result<(TlsPolicy, Option<String>)> {
+    let policy = TlsPolicy::from_settings(&load_settings(app)?);
+    if policy != TlsPolicy::Pinned(None) {
+        return Ok((policy, None));
+    }
+    let fingerprint = tls::probe_certificate(url, proxy).await?;
+    eprintln!(
+        "[TLS] Trust-on-first-use: Server-Zertifikat {}",
+        fingerprint
+    );
+    Ok((
+        TlsPolicy::Pinned(Some(fingerprint.clone())),
+        Some(fingerprint),
+    ))
+}
+
+/// Proxy-Richtlinie aus den Settings (Standard: System-Proxy).
+fn resolve_proxy_policy(app: &AppHandle) -> Result<ProxyPolicy> {
+    Ok(ProxyPolicy::from_settings(&load_settings(app)?))
+}
+
+fn save_pinned_fingerprint(app: &AppHandle, fingerprint: &str) -> Result<()> {
+    let store = app
+        .store("settings.json")
+        .map_err(|e| AppError::StorageError(e.to_string()))?;
+    store.set("tls_pinned_fingerprint", serde_json::json!(fingerprint));
+    store
+        .save()
+        .map_err(|e| AppError::StorageError(e.to_string()))
+}
+
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 async fn connect(
     url: String,
     username: String,
     password: String,
     sync_folder: Option<String>,
+    auth_scheme: Option<AuthScheme>,
     app: AppHandle,
     device_id_state: State<'_, DeviceIdState>,
     state: State<'_, WebDavState>,
+    s3_state: State<'_, S3State>,
 ) -> Result<bool> {
     let folder = sync_folder.unwrap_or_else(|| "notes".to_string());
-    let client = WebDavClient::new(&url, &username, &password, &folder)?;
-    let success = client.test_connection().await?;
+    let settings = load_settings(&app)?;
+    let proxy = resolve_proxy_policy(&app)?;
+    let (tls, new_pin) = resolve_tls_policy(&app, &url, &proxy).await?;
+    let credentials = Credentials {
+        url,
+        username,
+        password,
+        auth_scheme: auth_scheme.unwrap_or_default(),
+    };
+    // Je nach Speicherziel genau einen Client halten — `sync_target` wählt anhand der Settings
+    let (success, webdav_client, s3_client) = if settings.sync_backend == SYNC_BACKEND_S3 {
+        let client = S3Client::new(&credentials, &folder, &settings.s3_region, &tls, &proxy)?;
+        (client.test_connection().await?, None, Some(client))
+    } else {
+        let client = WebDavClient::new(&credentials, &folder, &tls, &proxy)?;
+        (client.test_connection().await?, Some(client), None)
+    };
 
     if success {
+        if let Some(fingerprint) = new_pin {
+            save_pinned_fingerprint(&app, &fingerprint)?;
+        }
         let _ = get_or_create_device_id(&app, &device_id_state)?;
-        let mut client_lock = lock_recover(&state.0);
-        *client_lock = Some(client);
-        drop(client_lock);
+        *lock_recover(&state.0) = webdav_client;
+        *lock_recover(&s3_state.0) = s3_client;
         // Sofort synchronisieren (nicht erst nach Debounce), damit die Liste gleich aktuell ist.
         let app2 = app.clone();
         tauri::async_runtime::spawn(async move {
@@ -167,30 +253,96 @@ async fn save_note(mut note: Note, app: AppHandle) -> Result<Note> {
 }
 
 #[tauri::command]
-async fn disconnect(state: State<'_, WebDavState>) -> Result<()> {
-    let mut lock = lock_recover(&state.0);
-    *lock = None;
+async fn disconnect(state: State<'_, WebDavState>, s3_state: State<'_, S3State>) -> Result<()> {
+    *lock_recover(&state.0) = None;
+    *lock_recover(&s3_state.0) = None;
     Ok(())
 }
 
 #[tauri::command]
-async fn is_connected(state: State<'_, WebDavState>) -> Result<bool> {
-    Ok(lock_recover(&state.0).is_some())
+async fn is_connected(state: State<'_, WebDavState>, s3_state: State<'_, S3State>) -> Result<bool> {
+    Ok(lock_recover(&state.0).is_some() || lock_recover(&s3_state.0).is_some())
 }
 
-/// Reiner Verbindungstest — KEINE Seiteneffekte.
+/// Reiner Verbindungstest — KEINE Seiteneffekte. `sync_backend`/`s3_region` kommen aus dem
+/// (noch nicht gespeicherten) Settings-Dialog.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 async fn test_connection(
     url: String,
     username: String,
     password: String,
     sync_folder: Option<String>,
+    auth_scheme: Option<AuthScheme>,
+    sync_backend: Option<String>,
+    s3_region: Option<String>,
+    app: AppHandle,
 ) -> Result<bool> {
     let folder = sync_folder.unwrap_or_else(|| "notes".to_string());
-    let client = WebDavClient::new(&url, &username, &password, &folder)?;
+    // TOFU-Pin wird hier nur für den Test verwendet, nicht gespeichert
+    let proxy = resolve_proxy_policy(&app)?;
+    let (tls, _) = resolve_tls_policy(&app, &url, &proxy).await?;
+    let credentials = Credentials {
+        url,
+        username,
+        password,
+        auth_scheme: auth_scheme.unwrap_or_default(),
+    };
+    if sync_backend.as_deref() == Some(SYNC_BACKEND_S3) {
+        let region = s3_region.unwrap_or_default();
+        let client = S3Client::new(&credentials, &folder, &region, &tls, &proxy)?;
+        return client.test_connection().await;
+    }
+    let client = WebDavClient::new(&credentials, &folder, &tls, &proxy)?;
     client.test_connection().await
 }
 
+/// Verbindungsdiagnose (OPTIONS, DAV-Klassen, PROPFIND, MKCOL, PUT/GET/DELETE, LOCK,
+/// sync-collection, Quota, Uhrzeit) mit den eingegebenen Zugangsdaten. Wie `test_connection`
+/// ohne Seiteneffekte — Probe-Datei und -Verzeichnis werden wieder gelöscht.
+#[tauri::command]
+async fn diagnose_server(
+    url: String,
+    username: String,
+    password: String,
+    sync_folder: Option<String>,
+    auth_scheme: Option<AuthScheme>,
+    app: AppHandle,
+) -> Result<diagnostics::DiagnosticReport> {
+    let folder = sync_folder.unwrap_or_else(|| "notes".to_string());
+    let proxy = resolve_proxy_policy(&app)?;
+    let (tls, _) = resolve_tls_policy(&app, &url, &proxy).await?;
+    let credentials = Credentials {
+        url,
+        username,
+        password,
+        auth_scheme: auth_scheme.unwrap_or_default(),
+    };
+    let client = WebDavClient::new(&credentials, &folder, &tls, &proxy)?;
+    Ok(diagnostics::run(&client, &tls).await)
+}
+
+/// Startet den Nextcloud Login Flow v2; das Frontend öffnet `login_url` im Browser.
+#[tauri::command]
+async fn start_nextcloud_login(url: String, app: AppHandle) -> Result<webdav::LoginFlow> {
+    let proxy = resolve_proxy_policy(&app)?;
+    let (tls, _) = resolve_tls_policy(&app, &url, &proxy).await?;
+    webdav::start_login_flow(&url, &tls, &proxy).await
+}
+
+/// Ein Poll-Versuch des Login Flow v2. `None` solange der Nutzer noch nicht bestätigt hat;
+/// danach Zugangsdaten mit App-Passwort (werden vom Frontend wie gewohnt gespeichert).
+#[tauri::command]
+async fn poll_nextcloud_login(
+    url: String,
+    flow: webdav::LoginFlow,
+    app: AppHandle,
+) -> Result<Option<Credentials>> {
+    let proxy = resolve_proxy_policy(&app)?;
+    let (tls, _) = resolve_tls_policy(&app, &url, &proxy).await?;
+    webdav::poll_login_flow(&url, &flow, &tls, &proxy).await
+}
+
 #[tauri::command]
 async fn create_note(
     title: String,
@@ -310,62 +462,172 @@ async fn empty_trash(app: AppHandle) -> Result<()> {
 
 #[tauri::command]
 async fn get_credentials(app: AppHandle) -> Result<Option<Credentials>> {
-    let store = app
-        .store("settings.json")
-        .map_err(|e| AppError::StorageError(e.to_string()))?;
-
-    let url = store
-        .get("server_url")
-        .and_then(|v| v.as_str().map(String::from));
-    let username = store
-        .get("username")
-        .and_then(|v| v.as_str().map(String::from));
-    let password = store
-        .get("password")
-        .and_then(|v| v.as_str().map(String::from));
-
-    match (url, username, password) {
-        (Some(url), Some(username), Some(password)) => Ok(Some(Credentials {
-            url,
-            username,
-            password,
-        })),
-        _ => Ok(None),
-    }
+    profiles::stored_credentials(&app)
 }
 
 #[tauri::command]
 async fn save_credentials(credentials: Credentials, app: AppHandle) -> Result<()> {
-    let store = app
-        .store("settings.json")
-        .map_err(|e| AppError::StorageError(e.to_string()))?;
+    profiles::store_credentials(&app, Some(&credentials))
+}
 
-    store.set("server_url", serde_json::json!(credentials.url));
-    store.set("username", serde_json::json!(credentials.username));
-    store.set("password", serde_json::json!(credentials.password));
+#[tauri::command]
+async fn clear_credentials(app: AppHandle) -> Result<()> {
+    profiles::store_credentials(&app, None)
+}
 
-    store
-        .save()
-        .map_err(|e| AppError::StorageError(e.to_string()))?;
+#[tauri::command]
+async fn list_profiles(app: AppHandle) -> Result<Vec<profiles::ProfileInfo>> {
+    profiles::list(&app)
+}
 
-    Ok(())
+#[tauri::command]
+async fn create_profile(name: String, app: AppHandle) -> Result<Vec<profiles::ProfileInfo>> {
+    profiles::create(&app, &name)?;
+    profiles::list(&app)
 }
 
 #[tauri::command]
-async fn clear_credentials(app: AppHandle) -> Result<()> {
+async fn rename_profile(
+    id: String,
+    name: String,
+    app: AppHandle,
+) -> Result<Vec<profiles::ProfileInfo>> {
+    profiles::rename(&app, &id, &name)?;
+    profiles::list(&app)
+}
+
+#[tauri::command]
+async fn set_profile_enabled(
+    id: String,
+    enabled: bool,
+    app: AppHandle,
+) -> Result<Vec<profiles::ProfileInfo>> {
+    profiles::set_enabled(&app, &id, enabled)?;
+    profiles::list(&app)
+}
+
+#[tauri::command]
+async fn delete_profile(id: String, app: AppHandle) -> Result<Vec<profiles::ProfileInfo>> {
+    profiles::delete(&app, &id)?;
+    profiles::list(&app)
+}
+
+/// Aktives Profil wechseln. Wartet auf einen laufenden Sync, damit dieser nicht in den
+/// Namensraum des neuen Profils schreibt; die Clients des alten Profils werden verworfen —
+/// das Frontend verbindet danach neu und lädt die Notizliste.
+#[tauri::command]
+async fn switch_profile(
+    id: String,
+    app: AppHandle,
+    state: State<'_, WebDavState>,
+    s3_state: State<'_, S3State>,
+    sync_lock: State<'_, SyncLockState>,
+) -> Result<Vec<profiles::ProfileInfo>> {
+    let _guard = sync_lock.0.lock().await;
+    profiles::switch(&app, &id)?;
+    *lock_recover(&state.0) = None;
+    *lock_recover(&s3_state.0) = None;
+    profiles::list(&app)
+}
+
+/// Speicherziel wechseln und dabei alle Notizen, `folders.json` und `deletions.json` vom
+/// bisherigen zum neuen Ziel kopieren (bzw. den lokalen Stand hochladen, wenn bisher keins
+/// verbunden war). Die Settings werden erst umgestellt, wenn der Umzug vollständig geprüft
+/// ist — schlägt er fehl, bleibt alles beim alten Ziel.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn migrate_sync_target(
+    credentials: Option<Credentials>,
+    sync_folder: String,
+    sync_directory: Option<String>,
+    app: AppHandle,
+    device_id_state: State<'_, DeviceIdState>,
+    state: State<'_, WebDavState>,
+    s3_state: State<'_, S3State>,
+    sync_lock: State<'_, SyncLockState>,
+) -> Result<migration::MigrationReport> {
+    let _guard = sync_lock.0.lock().await;
+    let settings = load_settings(&app)?;
+    let from = sync_target(&app)?;
+    let directory = sync_directory.unwrap_or_else(|| settings.sync_directory.clone());
+
+    let profile = profiles::active_id(&app);
+    profiles::ensure_own_location(
+        &app,
+        &profile,
+        &profiles::ProfileSettings {
+            sync_folder: sync_folder.clone(),
+            sync_directory: directory.clone(),
+            ..profiles::ProfileSettings::from(&settings)
+        },
+    )?;
+
+    let mut new_pin = None;
+    let to = if settings.sync_backend == SYNC_BACKEND_DIRECTORY {
+        SyncTarget::Directory(LocalDirBackend::new(&directory, &sync_folder)?)
+    } else if settings.sync_backend == SYNC_BACKEND_GIT {
+        SyncTarget::Git(GitBackend::new(
+            &directory,
+            &sync_folder,
+            &settings.git_remote,
+        )?)
+    } else {
+        let credentials = credentials.as_ref().ok_or(AppError::InvalidCredentials)?;
+        let proxy = resolve_proxy_policy(&app)?;
+        let (tls, pin) = resolve_tls_policy(&app, &credentials.url, &proxy).await?;
+        new_pin = pin;
+        let (target, success) = if settings.sync_backend == SYNC_BACKEND_S3 {
+            let client =
+                S3Client::new(credentials, &sync_folder, &settings.s3_region, &tls, &proxy)?;
+            let success = client.test_connection().await?;
+            (SyncTarget::S3(client), success)
+        } else {
+            let client = WebDavClient::new(credentials, &sync_folder, &tls, &proxy)?;
+            let success = client.test_connection().await?;
+            (SyncTarget::WebDav(client), success)
+        };
+        if !success {
+            return Err(AppError::NotConnected);
+        }
+        target
+    };
+    if from.as_ref().map(SyncTarget::location) == Some(to.location()) {
+        return Err(AppError::Migration(
+            "Neuer Speicherort ist identisch mit dem bisherigen".to_string(),
+        ));
+    }
+
+    let device_id = get_or_create_device_id(&app, &device_id_state)?;
+    let report = profiles::scoped(
+        &profile,
+        migration::migrate_target(&app, from.as_ref(), &to, &device_id, TRASH_RETENTION_MS),
+    )
+    .await?;
+
+    // Erst jetzt auf das neue Ziel umschalten
+    if let Some(fingerprint) = new_pin {
+        save_pinned_fingerprint(&app, &fingerprint)?;
+    }
     let store = app
         .store("settings.json")
         .map_err(|e| AppError::StorageError(e.to_string()))?;
-
-    store.delete("server_url");
-    store.delete("username");
-    store.delete("password");
-
+    store.set("sync_folder", serde_json::json!(sync_folder));
+    store.set("sync_directory", serde_json::json!(directory));
     store
         .save()
         .map_err(|e| AppError::StorageError(e.to_string()))?;
-
-    Ok(())
+    if credentials.is_some() {
+        profiles::store_credentials(&app, credentials.as_ref())?;
+    }
+    let (webdav_client, s3_client) = match to {
+        SyncTarget::WebDav(client) => (Some(client), None),
+        SyncTarget::S3(client) => (None, Some(client)),
+        SyncTarget::Directory(_) | SyncTarget::Git(_) => (None, None),
+    };
+    *lock_recover(&state.0) = webdav_client;
+    *lock_recover(&s3_state.0) = s3_client;
+    scheduler::trigger_sync(&app);
+    Ok(report)
 }
 
 #[tauri::command]
@@ -375,6 +637,10 @@ async fn get_device_id(app: AppHandle, state: State<'_, DeviceIdState>) -> Resul
 
 #[tauri::command]
 async fn get_settings(app: AppHandle) -> Result<Settings> {
+    load_settings(&app)
+}
+
+pub(crate) fn load_settings<R: Runtime>(app: &AppHandle<R>) -> Result<Settings> {
     let store = app
         .store("settings.json")
         .map_err(|e| AppError::StorageError(e.to_string()))?;
@@ -390,6 +656,19 @@ async fn get_settings(app: AppHandle) -> Result<Settings> {
         "default_open_mode",
         "font_size",
         "offline_mode",
+        "tls_mode",
+        "tls_ca_file",
+        "tls_pinned_fingerprint",
+        "sync_concurrency",
+        "proxy_mode",
+        "proxy_url",
+        "proxy_username",
+        "proxy_password",
+        "proxy_bypass",
+        "sync_backend",
+        "sync_directory",
+        "s3_region",
+        "git_remote",
     ] {
         if let Some(val) = store.get(key) {
             map.insert(key.to_string(), val.clone());
@@ -609,7 +888,9 @@ async fn rename_folder(old_name: String, new_name: String, app: AppHandle) -> Re
         )));
     }
     let now = chrono::Utc::now().timestamp_millis();
-    // Notizen mit Server-Kopie sammeln — für Move-Cleanup der alten Server-Pfade
+    let is_local = local_store::is_local_only(&app, Some(&old_name));
+    // Notizen mit Server-Kopie sammeln — Fallback, falls der Server den Ordner nicht als
+    // Ganzes verschieben kann (MOVE je Notiz)
     let to_move: Vec<(String, Option<String>)> = local_store::list_notes(&app)
         .into_iter()
         .filter(|n| {
@@ -643,6 +924,9 @@ async fn rename_folder(old_name: String, new_name: String, app: AppHandle) -> Re
         }
     }
 
+    if !is_local {
+        sync_queue::enqueue_folder_rename(&app, &old_name, &new_name);
+    }
     if !to_move.is_empty() {
         sync_queue::enqueue_move_deletions(&app, &to_move);
     }
@@ -669,7 +953,7 @@ async fn delete_folder(name: String, keep_notes: bool, app: AppHandle) -> Result
             if note.trashed_at.is_some() {
                 continue;
             }
-            // Alte Server-Datei aufräumen wenn Notiz synchronisiert war
+            // Server-Datei in den Root verschieben wenn Notiz synchronisiert war
             if !is_local
                 && matches!(
                     note.sync_status,
@@ -725,7 +1009,7 @@ async fn move_notes(ids: Vec<String>, target_folder: Option<String>, app: AppHan
     let mut move_deletions: Vec<(String, Option<String>)> = Vec::new();
     for id in &ids {
         if let Some(mut note) = local_store::get_note(&app, id) {
-            // Alte Server-Datei aufräumen wenn Notiz eine Server-Kopie hatte und Ordner wechselt
+            // Server-Datei per MOVE mitnehmen wenn Notiz eine Server-Kopie hatte und Ordner wechselt
             // (Synced | Pending | Conflict — konsistent mit rename_folder/delete_folder).
             if matches!(
                 note.sync_status,
@@ -795,6 +1079,7 @@ async fn set_folder_local_only(
                 let mut n = note;
                 n.updated_at = now;
                 n.sync_status = SyncStatus::Pending;
+                local_store::stamp(&app, &mut n);
                 local_store::put_note(&app, &n);
             }
         }
@@ -808,58 +1093,248 @@ async fn set_folder_local_only(
     list_folders(app).await
 }
 
+/// Speicherziel laut Settings: lokales Sync-Verzeichnis, Git-Arbeitsverzeichnis oder der
+/// verbundene WebDAV- bzw. S3-Client. `None` = nicht verbunden.
+pub(crate) fn sync_target(app: &AppHandle) -> Result<Option<SyncTarget>> {
+    let settings = load_settings(app)?;
+    profiles::ensure_own_location(
+        app,
+        &profiles::active_id(app),
+        &profiles::ProfileSettings::from(&settings),
+    )?;
+    if let Some(target) = SyncTarget::local(&settings)? {
+        return Ok(Some(target));
+    }
+    if settings.sync_backend == SYNC_BACKEND_S3 {
+        let state = app.state::<S3State>();
+        let client = lock_recover(&state.0).clone();
+        return Ok(client.map(SyncTarget::S3));
+    }
+    let state = app.state::<WebDavState>();
+    let client = lock_recover(&state.0).clone();
+    Ok(client.map(SyncTarget::WebDav))
+}
+
+/// Speicherziel eines inaktiven Profils für den Hintergrund-Sync: Backend, Verzeichnis,
+/// Sync-Ordner, Credentials und Zertifikats-Vertrauen aus dem Profil, TLS-Modus und Proxy
+/// aus den gemeinsamen Settings. Im Pin-Modus ohne gespeicherten Fingerprint wird nicht
+/// synchronisiert (kein Trust-on-first-use im Hintergrund).
+pub(crate) fn profile_sync_target(
+    app: &AppHandle,
+    profile: &profiles::Profile,
+) -> Result<Option<SyncTarget>> {
+    let mut settings = load_settings(app)?;
+    profile.settings.apply_to(&mut settings);
+    profiles::ensure_own_location(app, &profile.id, &profile.settings)?;
+    if let Some(target) = SyncTarget::local(&settings)? {
+        return Ok(Some(target));
+    }
+    let folder = &settings.sync_folder;
+    let Some(credentials) = &profile.credentials else {
+        return Ok(None);
+    };
+    let tls = TlsPolicy::from_settings(&settings);
+    if tls == TlsPolicy::Pinned(None) {
+        return Ok(None);
+    }
+    let proxy = ProxyPolicy::from_settings(&settings);
+    if settings.sync_backend == SYNC_BACKEND_S3 {
+        let client = S3Client::new(credentials, folder, &settings.s3_region, &tls, &proxy)?;
+        return Ok(Some(SyncTarget::S3(client)));
+    }
+    let client = WebDavClient::new(credentials, folder, &tls, &proxy)?;
+    Ok(Some(SyncTarget::WebDav(client)))
+}
+
 #[tauri::command]
 async fn sync(
     app: AppHandle,
     device_id_state: State<'_, DeviceIdState>,
-    state: State<'_, WebDavState>,
     sync_lock: State<'_, SyncLockState>,
 ) -> Result<()> {
     let _guard = match sync_lock.0.try_lock() {
         Ok(g) => g,
         Err(_) => return Ok(()),
     };
-    let client = {
-        let lock = lock_recover(&state.0);
-        lock.clone()
-    };
-    let client = match client {
-        Some(c) => c,
+    let target = match sync_target(&app)? {
+        Some(t) => t,
         None => return Ok(()),
     };
     let device_id = get_or_create_device_id(&app, &device_id_state)?;
-    sync_engine::run_sync(&client, &app, &device_id, TRASH_RETENTION_MS).await;
+    let profile = profiles::active_id(&app);
+    profiles::scoped(
+        &profile,
+        target.run_sync(&app, &device_id, TRASH_RETENTION_MS),
+    )
+    .await;
     let _ = app.emit("notes-synced", ());
     Ok(())
 }
 
+/// Quota + Speicherbelegung der Notizen am Server (Settings-Dialog).
+#[tauri::command]
+async fn get_storage_usage(state: State<'_, WebDavState>) -> Result<webdav::StorageUsage> {
+    let client = {
+        let lock = lock_recover(&state.0);
+        lock.clone()
+    };
+    let client = client.ok_or(AppError::NotConnected)?;
+    client.storage_usage().await
+}
+
+/// Ältere Fassungen einer Notiz aus der Nextcloud-Versionierung (neueste zuerst).
+#[tauri::command]
+async fn list_server_versions(
+    id: String,
+    app: AppHandle,
+    state: State<'_, WebDavState>,
+) -> Result<Vec<webdav::ServerVersion>> {
+    let client = {
+        let lock = lock_recover(&state.0);
+        lock.clone()
+    };
+    let client = client.ok_or(AppError::NotConnected)?;
+    let folder = local_store::get_note(&app, &id).and_then(|n| n.folder_name);
+    client.list_note_versions(&id, folder.as_deref()).await
+}
+
+/// Eine Server-Version als Notiz (Vorschau, ändert nichts lokal).
+#[tauri::command]
+async fn get_server_version(
+    id: String,
+    version_id: String,
+    app: AppHandle,
+    state: State<'_, WebDavState>,
+) -> Result<Note> {
+    let client = {
+        let lock = lock_recover(&state.0);
+        lock.clone()
+    };
+    let client = client.ok_or(AppError::NotConnected)?;
+    let folder = local_store::get_note(&app, &id).and_then(|n| n.folder_name);
+    client
+        .get_note_version(&id, folder.as_deref(), &version_id)
+        .await
+}
+
+/// Stellt eine Server-Version wieder her: Titel und Inhalt werden als neue lokale Änderung
+/// (PENDING) übernommen und beim nächsten Sync regulär hochgeladen — die aktuelle Fassung
+/// landet dabei selbst als Version am Server.
+#[tauri::command]
+async fn restore_server_version(
+    id: String,
+    version_id: String,
+    app: AppHandle,
+    state: State<'_, WebDavState>,
+) -> Result<Note> {
+    let client = {
+        let lock = lock_recover(&state.0);
+        lock.clone()
+    };
+    let client = client.ok_or(AppError::NotConnected)?;
+    let current = local_store::get_note(&app, &id);
+    let folder = current.as_ref().and_then(|n| n.folder_name.clone());
+    let version = client
+        .get_note_version(&id, folder.as_deref(), &version_id)
+        .await?;
+    let mut note = match current {
+        Some(mut note) => {
+            note.restore_content_from(&version);
+            note
+        }
+        None => version,
+    };
+    note.updated_at = chrono::Utc::now().timestamp_millis();
+    local_store::mark_dirty(&app, &mut note);
+    local_store::put_note(&app, &note);
+    scheduler::trigger_sync(&app);
+    Ok(note)
+}
+
+/// Fassung am Speicherziel für eine Notiz im Konflikt (bei Verzeichnis/Git die andere Fassung
+/// aus Hauptdatei und Konfliktkopien). Nur lesend.
+async fn server_version(target: &SyncTarget, id: &str, local: Option<&Note>) -> Result<Note> {
+    let folder = local.and_then(|n| n.folder_name.clone());
+    match target {
+        SyncTarget::WebDav(client) => Ok(client.get_note_with_etag(id, folder.as_deref()).await?.0),
+        SyncTarget::S3(client) => Ok(client.get_note_with_etag(id, folder.as_deref()).await?.0),
+        SyncTarget::Directory(dir) => dir.other_version(id, folder.as_deref(), local),
+        SyncTarget::Git(repo) => repo.dir().other_version(id, folder.as_deref(), local),
+    }
+}
+
+/// Server-Fassung unter der ursprünglichen ID übernehmen (lokal SYNCED, neue Merge-Basis).
+async fn adopt_server_version(app: &AppHandle, id: &str) -> Result<Note> {
+    let target = sync_target(app)?.ok_or(AppError::NotConnected)?;
+    let local = local_store::get_note(app, id);
+    let folder = local.as_ref().and_then(|n| n.folder_name.clone());
+    let (mut note, etag) = match target {
+        SyncTarget::WebDav(client) => client.get_note_with_etag(id, folder.as_deref()).await?,
+        SyncTarget::Directory(dir) => {
+            dir.adopt_other_version(id, folder.as_deref(), local.as_ref())?
+        }
+        SyncTarget::Git(repo) => {
+            repo.dir()
+                .adopt_other_version(id, folder.as_deref(), local.as_ref())?
+        }
+        SyncTarget::S3(client) => client.get_note_with_etag(id, folder.as_deref()).await?,
+    };
+    note.sync_status = SyncStatus::Synced;
+    local_store::put_note(app, &note);
+    local_store::set_base(app, &note);
+    sync_engine::set_remote_state(
+        app,
+        id,
+        sync_engine::RemoteNoteState {
+            folder: note.folder_name.clone(),
+            etag,
+            last_modified: None,
+        },
+    );
+    Ok(note)
+}
+
+/// Konflikt auflösen: `keep_mine` (lokale Fassung hochladen), `use_server` (Server-Fassung
+/// übernehmen) oder `keep_both` (Server-Fassung unter der ID, die lokale als neue Notiz).
 #[tauri::command]
 async fn resolve_conflict(
     id: String,
     resolution: String,
     app: AppHandle,
-    state: State<'_, WebDavState>,
+    device_id_state: State<'_, DeviceIdState>,
 ) -> Result<()> {
     let now = chrono::Utc::now().timestamp_millis();
     match resolution.as_str() {
         "keep_mine" => {
-            if let Some(mut note) = local_store::get_note(&app, &id) {
-                note.sync_status = SyncStatus::Pending;
-                note.updated_at = now;
-                local_store::put_note(&app, &note);
+            if local_store::get_note(&app, &id).is_some() {
+                // Fassung ohne Konfliktmarker, falls der Sync schon zusammengeführt hat
+                let note = sync_engine::keep_local_version(&app, &id, now)?;
+                // Syncthing- bzw. Git-Konfliktkopien verwerfen, sonst meldet der nächste Sync
+                // erneut einen Konflikt
+                if let Ok(Some(target)) = sync_target(&app) {
+                    if let Some(dir) = target.local_dir() {
+                        dir.discard_conflict_copies(&id, note.folder_name.as_deref())?;
+                    }
+                }
                 scheduler::trigger_sync(&app);
             }
         }
         "use_server" => {
-            let client = {
-                let lock = lock_recover(&state.0);
-                lock.clone()
-            };
-            let client = client.ok_or(AppError::NotConnected)?;
-            let folder = local_store::get_note(&app, &id).and_then(|n| n.folder_name);
-            let mut note = client.get_note(&id, folder.as_deref()).await?;
-            note.sync_status = SyncStatus::Synced;
-            local_store::put_note(&app, &note);
+            adopt_server_version(&app, &id).await?;
+            local_store::clear_conflict_local(&app, &id);
+        }
+        "keep_both" => {
+            let local = local_store::get_note(&app, &id)
+                .ok_or_else(|| AppError::NoteNotFound(id.clone()))?;
+            // Fassung ohne Konfliktmarker, falls der Sync schon zusammengeführt hat
+            let mine = local_store::get_conflict_local(&app, &id).unwrap_or(local);
+            adopt_server_version(&app, &id).await?;
+            let device_id = get_or_create_device_id(&app, &device_id_state)?;
+            let mut copy = mine.fork_conflict_copy(&device_id, now);
+            local_store::mark_dirty(&app, &mut copy);
+            local_store::put_note(&app, &copy);
+            local_store::clear_conflict_local(&app, &id);
+            scheduler::trigger_sync(&app);
         }
         other => {
             return Err(AppError::WebDav(format!(
@@ -871,6 +1346,61 @@ async fn resolve_conflict(
     Ok(())
 }
 
+/// Lokale, Server- und (falls bekannt) Basis-Fassung einer Notiz im Konflikt, damit das
+/// Frontend sie vor der Auflösung gegenüberstellen kann.
+#[tauri::command]
+async fn get_conflict_versions(id: String, app: AppHandle) -> Result<models::ConflictVersions> {
+    let current =
+        local_store::get_note(&app, &id).ok_or_else(|| AppError::NoteNotFound(id.clone()))?;
+    let merged = local_store::get_conflict_local(&app, &id);
+    let text_conflict = merged.is_some() || merge::has_conflict_markers(&current.content);
+    let local = merged.unwrap_or(current);
+    let server = match sync_target(&app)? {
+        Some(target) => Some(server_version(&target, &id, Some(&local)).await?),
+        None => None,
+    };
+    Ok(models::ConflictVersions {
+        local,
+        server,
+        base: local_store::get_base(&app, &id),
+        text_conflict,
+    })
+}
+
+/// Fassung für `diff_notes` laden.
+async fn load_note_version(app: &AppHandle, version: diff::NoteVersion) -> Result<Note> {
+    use diff::NoteVersion;
+    match version {
+        NoteVersion::Note { note } => Ok(*note),
+        NoteVersion::Local { id } => {
+            local_store::get_note(app, &id).ok_or(AppError::NoteNotFound(id))
+        }
+        NoteVersion::Server { id } => {
+            let target = sync_target(app)?.ok_or(AppError::NotConnected)?;
+            let local = local_store::get_note(app, &id);
+            server_version(&target, &id, local.as_ref()).await
+        }
+        NoteVersion::Base { id } => {
+            local_store::get_base(app, &id).ok_or(AppError::NoteNotFound(id))
+        }
+        NoteVersion::ConflictLocal { id } => {
+            local_store::get_conflict_local(app, &id).ok_or(AppError::NoteNotFound(id))
+        }
+    }
+}
+
+/// Strukturierter Vergleich zweier Notiz-Fassungen (Inhalt, Felder, Checklist-Items).
+#[tauri::command]
+async fn diff_notes(
+    old: diff::NoteVersion,
+    new: diff::NoteVersion,
+    app: AppHandle,
+) -> Result<diff::NoteDiff> {
+    let old = load_note_version(&app, old).await?;
+    let new = load_note_version(&app, new).await?;
+    Ok(diff::diff_notes(&old, &new))
+}
+
 /// State to track minimize-to-tray setting at runtime
 struct TraySettings(Mutex<bool>);
 
@@ -946,6 +1476,7 @@ pub fn run() {
 
     builder
         .manage(WebDavState(Mutex::new(None)))
+        .manage(S3State(Mutex::new(None)))
         .manage(DeviceIdState(Mutex::new(None)))
         .manage(TraySettings(Mutex::new(false)))
         .manage(SyncLockState(tokio::sync::Mutex::new(())))
@@ -1113,6 +1644,9 @@ pub fn run() {
             connect,
             disconnect,
             test_connection,
+            diagnose_server,
+            start_nextcloud_login,
+            poll_nextcloud_login,
             is_connected,
             list_notes,
             get_note,
@@ -1127,6 +1661,13 @@ pub fn run() {
             get_credentials,
             save_credentials,
             clear_credentials,
+            list_profiles,
+            create_profile,
+            rename_profile,
+            set_profile_enabled,
+            delete_profile,
+            switch_profile,
+            migrate_sync_target,
             get_device_id,
             get_settings,
             save_settings,
@@ -1146,7 +1687,13 @@ pub fn run() {
             set_folder_local_only,
             move_notes,
             sync,
+            get_storage_usage,
+            list_server_versions,
+            get_server_version,
+            restore_server_version,
             resolve_conflict,
+            get_conflict_versions,
+            diff_notes,
             show_main_window,
         ])
         .run(tauri::generate_context!())
@@ -0,0 +1,853 @@
+//! Sync in ein lokales Verzeichnis, das ein externes Werkzeug (Syncthing, Dropbox, …)
+//! zwischen den Geräten verteilt.
+//!
+//! Layout wie auf dem WebDAV-Server, damit Android und Desktop dieselben Dateien lesen:
+//! `{dir}/{sync_folder}/{ordner/}{id}.json`, `{dir}/{sync_folder}-md/{ordner/}{titel}.md`,
+//! `{dir}/{sync_folder}/folders.json` und `{dir}/{sync_folder}/deletions.json`.
+//!
+//! - Version einer Notiz = Änderungszeit + Größe der JSON-Datei (Ersatz für den ETag)
+//! - Schreiben atomar per temporärer Datei + Rename; Syncthing ignoriert `.syncthing.*`
+//! - Syncthing-Konfliktkopien (`{id}.sync-conflict-….json`) werden als Notiz-Konflikt gemeldet
+//!   ([`SyncBackend::conflicted_notes`]); inhaltsgleiche Kopien werden still entfernt
+//!
+//! Dateizugriffe sind klein und laufen synchron.
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use std::time::UNIX_EPOCH;
+
+use regex::Regex;
+
+use crate::error::{AppError, Result};
+use crate::folders::{parse_folders_json, sanitize_folder_name, FolderMeta};
+use crate::hlc::Hlc;
+use crate::markdown;
+use crate::models::{DeletionLedger, Note, SyncStatus};
+use crate::sync_backend::SyncBackend;
+use crate::webdav::{
+    merge_deletion, sanitize_filename, sync_folder_name, CollectionDelta, NoteListing,
+    WriteCondition,
+};
+
+/// `{uuid}.json` bzw. Syncthing-Konfliktkopie `{uuid}.sync-conflict-{datum}-{zeit}-{gerät}.json`
+static NOTE_FILE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^([0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12})(\.sync-conflict-[^./]+)?\.json$",
+    )
+    .expect("note file pattern is valid")
+});
+
+const FOLDERS_FILE: &str = "folders.json";
+const DELETIONS_FILE: &str = "deletions.json";
+
+/// Lokales Sync-Verzeichnis als [`SyncBackend`].
+#[derive(Debug, Clone)]
+pub struct LocalDirBackend {
+    /// Vom Nutzer gewähltes Verzeichnis (enthält `{sync_folder}/` und `{sync_folder}-md/`)
+    dir: PathBuf,
+    sync_folder: String,
+}
+
+/// Eine Notiz-Datei im Verzeichnis-Listing.
+struct NoteFile {
+    id: String,
+    folder: Option<String>,
+    path: PathBuf,
+    /// Syncthing-Konfliktkopie statt Hauptdatei
+    conflict_copy: bool,
+}
+
+fn io_error(path: &Path, e: std::io::Error) -> AppError {
+    AppError::StorageError(format!("{}: {}", path.display(), e))
+}
+
+/// Version einer Datei aus Änderungszeit (ns) und Größe; `None` wenn sie fehlt.
+fn file_version(path: &Path) -> Result<Option<String>> {
+    let meta = match fs::metadata(path) {
+        Ok(meta) => meta,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(io_error(path, e)),
+    };
+    let modified = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    Ok(Some(format!("{}-{}", modified, meta.len())))
+}
+
+/// Schreibt atomar: temporäre Datei im selben Verzeichnis, dann Rename.
+fn write_atomic(path: &Path, content: &str) -> Result<()> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    fs::create_dir_all(dir).map_err(|e| io_error(dir, e))?;
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let tmp = dir.join(format!(".syncthing.{}.tmp", name));
+    fs::write(&tmp, content).map_err(|e| io_error(&tmp, e))?;
+    fs::rename(&tmp, path).map_err(|e| {
+        let _ = fs::remove_file(&tmp);
+        io_error(path, e)
+    })
+}
+
+/// Datei entfernen; fehlt sie, gilt das als Erfolg.
+fn remove_file(path: &Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(io_error(path, e)),
+    }
+}
+
+fn read_note(path: &Path, folder: Option<&str>) -> Result<Note> {
+    let text = fs::read_to_string(path).map_err(|e| io_error(path, e))?;
+    let mut note: Note =
+        serde_json::from_str(&text).map_err(|e| AppError::ParseError(e.to_string()))?;
+    note.fix_note_type();
+    // Pfad ist maßgebend — wie beim WebDAV-GET
+    note.folder_name = folder.map(str::to_owned);
+    Ok(note)
+}
+
+/// Gleiche Fassung einer Notiz (Inhalt ohne Ordner und lokalen Sync-Status)?
+fn same_version(a: &Note, b: &Note) -> bool {
+    let normalized = |note: &Note| {
+        let mut note = note.clone();
+        note.folder_name = None;
+        note.sync_status = SyncStatus::Synced;
+        serde_json::to_value(note).ok()
+    };
+    normalized(a) == normalized(b)
+}
+
+impl LocalDirBackend {
+    /// `dir` muss ein vorhandenes Verzeichnis sein — fehlt es (z.B. Laufwerk nicht
+    /// eingehängt), würde ein leeres Listing sonst wie „alle Notizen gelöscht" aussehen.
+    pub fn new(dir: &str, sync_folder: &str) -> Result<Self> {
+        let dir = dir.trim();
+        if dir.is_empty() {
+            return Err(AppError::StorageError(
+                "Kein Sync-Verzeichnis angegeben".to_string(),
+            ));
+        }
+        let dir = PathBuf::from(dir);
+        if !dir.is_dir() {
+            return Err(AppError::StorageError(format!(
+                "Sync-Verzeichnis {} nicht gefunden",
+                dir.display()
+            )));
+        }
+        Ok(Self {
+            dir,
+            sync_folder: sync_folder_name(sync_folder),
+        })
+    }
+
+    fn json_root(&self) -> PathBuf {
+        self.dir.join(&self.sync_folder)
+    }
+
+    fn md_root(&self) -> PathBuf {
+        self.dir.join(format!("{}-md", self.sync_folder))
+    }
+
+    fn json_dir(&self, folder: Option<&str>) -> PathBuf {
+        match folder {
+            Some(f) => self.json_root().join(f),
+            None => self.json_root(),
+        }
+    }
+
+    fn md_dir(&self, folder: Option<&str>) -> PathBuf {
+        match folder {
+            Some(f) => self.md_root().join(f),
+            None => self.md_root(),
+        }
+    }
+
+    fn note_path(&self, id: &str, folder: Option<&str>) -> PathBuf {
+        self.json_dir(folder).join(format!("{}.json", id))
+    }
+
+    fn md_path(&self, note: &Note, folder: Option<&str>) -> PathBuf {
+        self.md_dir(folder)
+            .join(format!("{}.md", sanitize_filename(&note.title, &note.id)))
+    }
+
+    /// Das gewählte Verzeichnis muss noch da sein (siehe `new`).
+    fn check_available(&self) -> Result<()> {
+        if self.dir.is_dir() {
+            Ok(())
+        } else {
+            Err(AppError::StorageError(format!(
+                "Sync-Verzeichnis {} nicht erreichbar",
+                self.dir.display()
+            )))
+        }
+    }
+
+    /// Unterverzeichnisse des JSON-Roots mit gültigem Ordnernamen.
+    fn folder_dirs(&self) -> Vec<String> {
+        let Ok(entries) = fs::read_dir(self.json_root()) else {
+            return Vec::new();
+        };
+        let mut folders: Vec<String> = entries
+            .flatten()
+            .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+            .filter_map(|e| e.file_name().to_str().map(str::to_owned))
+            .filter(|name| sanitize_folder_name(name).as_deref() == Some(name.as_str()))
+            .collect();
+        folders.sort();
+        folders
+    }
+
+    /// Alle Notiz-Dateien (Hauptdateien und Konfliktkopien) in Root und Ordnern.
+    fn note_files(&self) -> Result<Vec<NoteFile>> {
+        self.check_available()?;
+        let mut files = Vec::new();
+        let folders = std::iter::once(None).chain(self.folder_dirs().into_iter().map(Some));
+        for folder in folders {
+            let dir = self.json_dir(folder.as_deref());
+            let entries = match fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(io_error(&dir, e)),
+            };
+            for entry in entries.flatten() {
+                let name = entry.file_name();
+                let Some(caps) = name.to_str().and_then(|n| NOTE_FILE.captures(n)) else {
+                    continue;
+                };
+                files.push(NoteFile {
+                    id: caps[1].to_lowercase(),
+                    folder: folder.clone(),
+                    path: entry.path(),
+                    conflict_copy: caps.get(2).is_some(),
+                });
+            }
+        }
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(files)
+    }
+
+    /// Syncthing-Konfliktkopien einer Notiz.
+    fn conflict_copies(&self, id: &str, folder: Option<&str>) -> Vec<PathBuf> {
+        let Ok(entries) = fs::read_dir(self.json_dir(folder)) else {
+            return Vec::new();
+        };
+        let mut copies: Vec<PathBuf> = entries
+            .flatten()
+            .filter(|e| {
+                e.file_name()
+                    .to_str()
+                    .and_then(|n| NOTE_FILE.captures(n))
+                    .is_some_and(|c| c.get(2).is_some() && c[1].eq_ignore_ascii_case(id))
+            })
+            .map(|e| e.path())
+            .collect();
+        copies.sort();
+        copies
+    }
+
+    /// Entfernt die Konfliktkopien einer Notiz (Nutzer hat sich für eine Fassung entschieden).
+    pub fn discard_conflict_copies(&self, id: &str, folder: Option<&str>) -> Result<()> {
+        for copy in self.conflict_copies(id, folder) {
+            remove_file(&copy)?;
+        }
+        Ok(())
+    }
+
+    /// Die „andere" Fassung einer Notiz im Konflikt: die neueste Fassung aus Hauptdatei und
+    /// Konfliktkopien, die sich von `local` unterscheidet (sonst die Hauptdatei). Nur lesend.
+    pub fn other_version(
+        &self,
+        id: &str,
+        folder: Option<&str>,
+        local: Option<&Note>,
+    ) -> Result<Note> {
+        let main_path = self.note_path(id, folder);
+        let main = match read_note(&main_path, folder) {
+            Ok(note) => Some(note),
+            Err(_) if !main_path.exists() => None,
+            Err(e) => return Err(e),
+        };
+        let mut candidates: Vec<Note> = self
+            .conflict_copies(id, folder)
+            .iter()
+            .filter_map(|p| read_note(p, folder).ok())
+            .collect();
+        candidates.extend(main.clone());
+        candidates.retain(|n| local.is_none_or(|l| !same_version(n, l)));
+        candidates
+            .into_iter()
+            .max_by_key(|n| n.updated_at)
+            .or(main)
+            .ok_or_else(|| AppError::NoteNotFound(id.to_string()))
+    }
+
+    /// Löst einen Konflikt zugunsten der [anderen Fassung](Self::other_version) auf: sie wird
+    /// zur Hauptdatei, die Kopien werden entfernt.
+    pub fn adopt_other_version(
+        &self,
+        id: &str,
+        folder: Option<&str>,
+        local: Option<&Note>,
+    ) -> Result<(Note, Option<String>)> {
+        let chosen = self.other_version(id, folder, local)?;
+        let main_path = self.note_path(id, folder);
+        self.write_note(&chosen, &WriteCondition::Unconditional)?;
+        self.discard_conflict_copies(id, folder)?;
+        let version = file_version(&main_path)?;
+        Ok((chosen, version))
+    }
+
+    fn write_note(&self, note: &Note, condition: &WriteCondition) -> Result<Option<String>> {
+        let folder = note.folder_name.as_deref();
+        let path = self.note_path(&note.id, folder);
+        let current = file_version(&path)?;
+        let allowed = match (condition, &current) {
+            (WriteCondition::Unconditional, _) => true,
+            (WriteCondition::IfMatch(expected), Some(current)) => expected == current,
+            (WriteCondition::IfMatch(_), None) => false,
+            (WriteCondition::IfNoneMatch, current) => current.is_none(),
+        };
+        if !allowed {
+            return Err(AppError::Conflict(format!(
+                "{} wurde im Sync-Verzeichnis zwischenzeitlich geändert",
+                note.id
+            )));
+        }
+
+        // Alter Titel → alte .md erst nach dem JSON-Schreiben entfernen
+        let old_md = current
+            .as_ref()
+            .and_then(|_| read_note(&path, folder).ok())
+            .filter(|old| old.title != note.title)
+            .map(|old| self.md_path(&old, folder));
+
+        let json =
+            serde_json::to_string_pretty(note).map_err(|e| AppError::ParseError(e.to_string()))?;
+        write_atomic(&path, &json)?;
+        if let Some(old_md) = old_md {
+            remove_file(&old_md)?;
+        }
+
+        // Getrashte Notizen haben keinen Markdown-Export (Android-Parität)
+        let md = self.md_path(note, folder);
+        if note.trashed_at.is_some() {
+            remove_file(&md)?;
+        } else {
+            write_atomic(&md, &markdown::generate_markdown(note))?;
+        }
+        file_version(&path)
+    }
+
+    fn ensure_folder_now(&self, name: &str) -> Result<()> {
+        for dir in [self.json_dir(Some(name)), self.md_dir(Some(name))] {
+            fs::create_dir_all(&dir).map_err(|e| io_error(&dir, e))?;
+        }
+        Ok(())
+    }
+
+    fn read_ledger(&self) -> DeletionLedger {
+        fs::read_to_string(self.json_root().join(DELETIONS_FILE))
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    fn write_ledger(&self, ledger: &DeletionLedger) -> Result<()> {
+        let json = serde_json::to_string_pretty(ledger)
+            .map_err(|e| AppError::ParseError(e.to_string()))?;
+        write_atomic(&self.json_root().join(DELETIONS_FILE), &json)
+    }
+
+    fn move_note_now(&self, id: &str, from: Option<&str>, to: Option<&str>) -> Result<()> {
+        let old_path = self.note_path(id, from);
+        let note = match read_note(&old_path, from) {
+            Ok(note) => note,
+            Err(_) if !old_path.exists() => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        let old_md = self.md_path(&note, from);
+        let new_path = self.note_path(id, to);
+        if new_path.exists() {
+            // Ziel schon beschrieben: alter Pfad ist überholt
+            remove_file(&old_path)?;
+            return remove_file(&old_md);
+        }
+        if let Some(f) = to {
+            self.ensure_folder_now(f)?;
+        }
+        fs::rename(&old_path, &new_path).map_err(|e| io_error(&old_path, e))?;
+        for copy in self.conflict_copies(id, from) {
+            if let Some(name) = copy.file_name() {
+                let _ = fs::rename(&copy, self.json_dir(to).join(name));
+            }
+        }
+        if old_md.exists() {
+            let new_md = self.md_path(&note, to);
+            fs::rename(&old_md, &new_md).map_err(|e| io_error(&old_md, e))?;
+        }
+        Ok(())
+    }
+}
+
+impl SyncBackend for LocalDirBackend {
+    fn for_sync_run(&self, _concurrency: usize) -> Self {
+        self.clone()
+    }
+
+    fn concurrency(&self) -> usize {
+        1
+    }
+
+    fn retry_budget_exhausted(&self) -> bool {
+        false
+    }
+
+    fn location(&self) -> String {
+        format!("file://{}", self.json_root().display())
+    }
+
+    async fn supports_delta(&self) -> bool {
+        false
+    }
+
+    async fn changes_since(
+        &self,
+        _folder: Option<&str>,
+        _token: Option<&str>,
+    ) -> Result<CollectionDelta> {
+        Err(AppError::Unsupported(
+            "Sync-Verzeichnis ohne Änderungs-Token".to_string(),
+        ))
+    }
+
+    async fn list_notes(&self) -> Result<Vec<NoteListing>> {
+        let mut listing: Vec<NoteListing> = Vec::new();
+        for file in self.note_files()? {
+            if file.conflict_copy || listing.iter().any(|e| e.id == file.id) {
+                continue;
+            }
+            listing.push(NoteListing {
+                etag: file_version(&file.path)?,
+                id: file.id,
+                folder: file.folder,
+                last_modified: None,
+            });
+        }
+        Ok(listing)
+    }
+
+    async fn get_note(&self, id: &str, folder: Option<&str>) -> Result<(Note, Option<String>)> {
+        let path = self.note_path(id, folder);
+        let Some(version) = file_version(&path)? else {
+            return Err(AppError::NoteNotFound(id.to_string()));
+        };
+        Ok((read_note(&path, folder)?, Some(version)))
+    }
+
+    async fn put_note(&self, note: &Note, condition: WriteCondition) -> Result<Option<String>> {
+        self.check_available()?;
+        self.write_note(note, &condition)
+    }
+
+    async fn delete_note(&self, id: &str, folder: Option<&str>) -> Result<()> {
+        self.check_available()?;
+        let path = self.note_path(id, folder);
+        if let Ok(note) = read_note(&path, folder) {
+            remove_file(&self.md_path(&note, folder))?;
+        }
+        remove_file(&path)?;
+        self.discard_conflict_copies(id, folder)
+    }
+
+    async fn move_note(&self, id: &str, from: Option<&str>, to: Option<&str>) -> Result<()> {
+        self.check_available()?;
+        self.move_note_now(id, from, to)
+    }
+
+    async fn list_folders(&self) -> Vec<String> {
+        self.folder_dirs()
+    }
+
+    async fn ensure_folder(&self, name: &str) {
+        if let Err(e) = self.ensure_folder_now(name) {
+            eprintln!("[local_dir] Ordner {} nicht angelegt: {}", name, e);
+        }
+    }
+
+    async fn move_folder(&self, from: &str, to: &str) -> Result<bool> {
+        self.check_available()?;
+        if self.json_dir(Some(to)).exists() || self.md_dir(Some(to)).exists() {
+            return Ok(false);
+        }
+        for (old, new) in [
+            (self.json_dir(Some(from)), self.json_dir(Some(to))),
+            (self.md_dir(Some(from)), self.md_dir(Some(to))),
+        ] {
+            if old.exists() {
+                fs::rename(&old, &new).map_err(|e| io_error(&old, e))?;
+            }
+        }
+        Ok(true)
+    }
+
+    async fn delete_folder(&self, name: &str) {
+        for dir in [self.json_dir(Some(name)), self.md_dir(Some(name))] {
+            let _ = fs::remove_dir_all(dir);
+        }
+    }
+
+    async fn read_folders_meta(&self) -> Vec<FolderMeta> {
+        fs::read_to_string(self.json_root().join(FOLDERS_FILE))
+            .map(|text| parse_folders_json(&text))
+            .unwrap_or_default()
+    }
+
+    async fn write_folders_meta_merged<F>(&self, mut mutation: F) -> Result<Vec<FolderMeta>>
+    where
+        F: FnMut(Vec<FolderMeta>) -> Vec<FolderMeta> + Send,
+    {
+        self.check_available()?;
+        let updated = mutation(self.read_folders_meta().await);
+        let json = serde_json::to_string_pretty(&updated)
+            .map_err(|e| AppError::ParseError(e.to_string()))?;
+        write_atomic(&self.json_root().join(FOLDERS_FILE), &json)?;
+        Ok(updated)
+    }
+
+    async fn read_deletions(&self) -> DeletionLedger {
+        self.read_ledger()
+    }
+
+    async fn append_deletions(
+        &self,
+        ids: &[String],
+        device_id: &str,
+        now: i64,
+        stamp: Option<&Hlc>,
+        retention_ms: i64,
+    ) {
+        if ids.is_empty() {
+            return;
+        }
+        let mut ledger = self.read_ledger();
+        for id in ids {
+            ledger = merge_deletion(ledger, id, device_id, now, stamp, retention_ms);
+        }
+        if let Err(e) = self.write_ledger(&ledger) {
+            eprintln!("[local_dir] Lösch-Ledger nicht geschrieben: {}", e);
+        }
+    }
+
+    async fn remove_deletions(&self, ids: &[String]) {
+        if ids.is_empty() {
+            return;
+        }
+        let mut ledger = self.read_ledger();
+        ledger.deleted_notes.retain(|r| !ids.contains(&r.id));
+        if let Err(e) = self.write_ledger(&ledger) {
+            eprintln!("[local_dir] Lösch-Ledger nicht geschrieben: {}", e);
+        }
+    }
+
+    async fn conflicted_notes(&self) -> Vec<String> {
+        let Ok(files) = self.note_files() else {
+            return Vec::new();
+        };
+        let mut ids: Vec<String> = Vec::new();
+        for copy in files.iter().filter(|f| f.conflict_copy) {
+            if ids.contains(&copy.id) {
+                continue;
+            }
+            let folder = copy.folder.as_deref();
+            let main = read_note(&self.note_path(&copy.id, folder), folder).ok();
+            let diverged = match (read_note(&copy.path, folder), &main) {
+                (Ok(copied), Some(main)) => !same_version(&copied, main),
+                // Hauptdatei fehlt oder Kopie unlesbar → Nutzer entscheiden lassen
+                _ => true,
+            };
+            if diverged {
+                ids.push(copy.id.clone());
+            } else if let Err(e) = remove_file(&copy.path) {
+                eprintln!("[local_dir] Konfliktkopie nicht entfernt: {}", e);
+            }
+        }
+        ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Temporäres Sync-Verzeichnis, wird beim Drop entfernt.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new() -> Self {
+            let dir =
+                std::env::temp_dir().join(format!("simple-notes-dir-{}", uuid::Uuid::new_v4()));
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+
+        fn backend(&self) -> LocalDirBackend {
+            LocalDirBackend::new(self.0.to_str().unwrap(), "notes").unwrap()
+        }
+
+        fn path(&self, rel: &str) -> PathBuf {
+            self.0.join(rel)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn note(title: &str, folder: Option<&str>) -> Note {
+        let mut note = Note::new(title.to_string(), "tauri-test".to_string());
+        note.content = format!("{} Inhalt", title);
+        note.folder_name = folder.map(str::to_owned);
+        note
+    }
+
+    fn conflict_copy_name(id: &str) -> String {
+        format!("{}.sync-conflict-20250701-101010-ABCDEFG.json", id)
+    }
+
+    #[test]
+    fn test_new_requires_existing_directory() {
+        assert!(matches!(
+            LocalDirBackend::new("  ", "notes"),
+            Err(AppError::StorageError(_))
+        ));
+        let missing = std::env::temp_dir().join(format!("fehlt-{}", uuid::Uuid::new_v4()));
+        assert!(LocalDirBackend::new(missing.to_str().unwrap(), "notes").is_err());
+        let tmp = TempDir::new();
+        let backend = LocalDirBackend::new(tmp.0.to_str().unwrap(), "my notes!").unwrap();
+        assert_eq!(backend.json_root(), tmp.path("mynotes"));
+    }
+
+    #[tokio::test]
+    async fn test_put_writes_server_layout() {
+        let tmp = TempDir::new();
+        let backend = tmp.backend();
+        let root = note("Einkauf", None);
+        let work = note("Plan", Some("Work"));
+
+        backend
+            .put_note(&root, WriteCondition::IfNoneMatch)
+            .await
+            .unwrap();
+        backend
+            .put_note(&work, WriteCondition::IfNoneMatch)
+            .await
+            .unwrap();
+
+        assert!(tmp.path(&format!("notes/{}.json", root.id)).is_file());
+        assert!(tmp.path("notes-md/Einkauf.md").is_file());
+        assert!(tmp.path(&format!("notes/Work/{}.json", work.id)).is_file());
+        assert!(tmp.path("notes-md/Work/Plan.md").is_file());
+
+        let mut listing = backend.list_notes().await.unwrap();
+        listing.sort_by(|a, b| a.folder.cmp(&b.folder));
+        assert_eq!(listing.len(), 2);
+        assert_eq!(listing[1].folder.as_deref(), Some("Work"));
+        let (loaded, version) = backend.get_note(&work.id, Some("Work")).await.unwrap();
+        assert_eq!(loaded.content, "Plan Inhalt");
+        assert_eq!(version, listing[1].etag);
+        assert_eq!(backend.list_folders().await, vec!["Work".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_conditional_writes_use_file_version() {
+        let tmp = TempDir::new();
+        let backend = tmp.backend();
+        let mut n = note("A", None);
+        let version = backend
+            .put_note(&n, WriteCondition::IfNoneMatch)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(
+            backend.put_note(&n, WriteCondition::IfNoneMatch).await,
+            Err(AppError::Conflict(_))
+        ));
+
+        // Anderes Gerät (Syncthing) ersetzt die Datei → Version ändert sich
+        let mut other = n.clone();
+        other.content = "vom anderen Gerät, länger".to_string();
+        write_atomic(
+            &tmp.path(&format!("notes/{}.json", n.id)),
+            &serde_json::to_string(&other).unwrap(),
+        )
+        .unwrap();
+        n.content = "lokal".to_string();
+        assert!(matches!(
+            backend
+                .put_note(&n, WriteCondition::IfMatch(version.clone()))
+                .await,
+            Err(AppError::Conflict(_))
+        ));
+
+        let (_, current) = backend.get_note(&n.id, None).await.unwrap();
+        assert_ne!(current.as_deref(), Some(version.as_str()));
+        assert!(backend
+            .put_note(&n, WriteCondition::IfMatch(current.unwrap()))
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_title_change_replaces_markdown_twin() {
+        let tmp = TempDir::new();
+        let backend = tmp.backend();
+        let mut n = note("Alt", None);
+        backend
+            .put_note(&n, WriteCondition::Unconditional)
+            .await
+            .unwrap();
+        n.title = "Neu".to_string();
+        backend
+            .put_note(&n, WriteCondition::Unconditional)
+            .await
+            .unwrap();
+        assert!(!tmp.path("notes-md/Alt.md").exists());
+        assert!(tmp.path("notes-md/Neu.md").is_file());
+
+        backend.delete_note(&n.id, None).await.unwrap();
+        assert!(!tmp.path("notes-md/Neu.md").exists());
+        assert!(backend.list_notes().await.unwrap().is_empty());
+        // Fehlt schon → trotzdem Erfolg
+        backend.delete_note(&n.id, None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_move_note_and_folder() {
+        let tmp = TempDir::new();
+        let backend = tmp.backend();
+        let n = note("Wandert", Some("Old"));
+        backend
+            .put_note(&n, WriteCondition::Unconditional)
+            .await
+            .unwrap();
+
+        backend
+            .move_note(&n.id, Some("Old"), Some("Home"))
+            .await
+            .unwrap();
+        assert!(tmp.path(&format!("notes/Home/{}.json", n.id)).is_file());
+        assert!(tmp.path("notes-md/Home/Wandert.md").is_file());
+        assert!(!tmp.path(&format!("notes/Old/{}.json", n.id)).exists());
+        // Quelle fehlt → nichts zu tun
+        backend
+            .move_note(&n.id, Some("Old"), Some("Home"))
+            .await
+            .unwrap();
+
+        assert!(backend.move_folder("Home", "Privat").await.unwrap());
+        assert!(tmp.path(&format!("notes/Privat/{}.json", n.id)).is_file());
+        backend.ensure_folder("Work").await;
+        assert!(!backend.move_folder("Privat", "Work").await.unwrap());
+
+        backend.delete_folder("Privat").await;
+        assert!(!tmp.path("notes/Privat").exists());
+        assert!(!tmp.path("notes-md/Privat").exists());
+    }
+
+    #[tokio::test]
+    async fn test_shared_files_roundtrip() {
+        let tmp = TempDir::new();
+        let backend = tmp.backend();
+        let written = backend
+            .write_folders_meta_merged(|mut existing| {
+                existing.push(FolderMeta {
+                    name: "Work".to_string(),
+                    color: None,
+                    updated_at: 1,
+                    deleted: false,
+                    local_only: false,
+                    ..FolderMeta::default()
+                });
+                existing
+            })
+            .await
+            .unwrap();
+        assert_eq!(written.len(), 1);
+        assert_eq!(backend.read_folders_meta().await[0].name, "Work");
+
+        let ids = vec!["a".to_string(), "b".to_string()];
+        backend
+            .append_deletions(&ids, "dev", 1000, None, 10_000)
+            .await;
+        backend.remove_deletions(&ids[..1]).await;
+        let ledger = backend.read_deletions().await;
+        assert_eq!(ledger.deleted_notes.len(), 1);
+        assert_eq!(ledger.deleted_notes[0].id, "b");
+        assert!(tmp.path("notes/deletions.json").is_file());
+        assert!(tmp.path("notes/folders.json").is_file());
+    }
+
+    #[tokio::test]
+    async fn test_missing_directory_is_an_error() {
+        let tmp = TempDir::new();
+        let backend = tmp.backend();
+        fs::remove_dir_all(&tmp.0).unwrap();
+        // Kein leeres Listing — sonst hielte der Sync alle Notizen für gelöscht
+        assert!(backend.list_notes().await.is_err());
+        assert!(backend
+            .put_note(&note("X", None), WriteCondition::Unconditional)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sync_conflict_copies() {
+        let tmp = TempDir::new();
+        let backend = tmp.backend();
+        let n = note("Geteilt", None);
+        backend
+            .put_note(&n, WriteCondition::Unconditional)
+            .await
+            .unwrap();
+
+        // Inhaltsgleiche Kopie → still entfernt
+        let identical = tmp.path(&format!("notes/{}", conflict_copy_name(&n.id)));
+        fs::write(&identical, serde_json::to_string(&n).unwrap()).unwrap();
+        assert!(backend.conflicted_notes().await.is_empty());
+        assert!(!identical.exists());
+
+        // Abweichende Kopie → Konflikt, Listing enthält die Notiz nur einmal
+        let mut other = n.clone();
+        other.content = "vom anderen Gerät".to_string();
+        other.updated_at += 1000;
+        let copy = tmp.path(&format!("notes/{}", conflict_copy_name(&n.id)));
+        fs::write(&copy, serde_json::to_string(&other).unwrap()).unwrap();
+        assert_eq!(backend.conflicted_notes().await, vec![n.id.clone()]);
+        assert_eq!(backend.list_notes().await.unwrap().len(), 1);
+
+        // Auflösung „Server": die abweichende Fassung wird zur Hauptdatei
+        let (adopted, version) = backend.adopt_other_version(&n.id, None, Some(&n)).unwrap();
+        assert_eq!(adopted.content, "vom anderen Gerät");
+        assert!(!copy.exists());
+        let (main, current) = backend.get_note(&n.id, None).await.unwrap();
+        assert_eq!(main.content, "vom anderen Gerät");
+        assert_eq!(current, version);
+        assert!(backend.conflicted_notes().await.is_empty());
+
+        // Auflösung „meine": Kopien verwerfen
+        fs::write(&copy, serde_json::to_string(&n).unwrap()).unwrap();
+        backend.discard_conflict_copies(&n.id, None).unwrap();
+        assert!(!copy.exists());
+    }
+}
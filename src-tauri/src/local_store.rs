@@ -1,29 +1,42 @@
 use std::sync::Mutex;
 
-use tauri::AppHandle;
+use tauri::{AppHandle, Runtime};
 use tauri_plugin_store::StoreExt;
 
 use crate::folders::FolderMeta;
+use crate::hlc::{Hlc, Stamped};
 use crate::models::Note;
 
 // ponytail: global lock, per-key locks if throughput matters
 static STORE_LOCK: Mutex<()> = Mutex::new(());
+/// Eigener Lock für die HLC-Uhr — wird auch unter gehaltenem `STORE_LOCK` gestempelt
+static CLOCK_LOCK: Mutex<()> = Mutex::new(());
 
 const STORE_FILE: &str = "local.json";
 const KEY_FOLDERS: &str = "folders";
 const KEY_NOTES: &str = "notes";
+/// Zuletzt synchronisierte Fassung je Notiz (Basis für den Drei-Wege-Merge)
+const KEY_BASES: &str = "bases";
+/// Lokale Fassung vor einem Merge mit Konfliktmarkern (für "Meine behalten", "Beide behalten"
+/// und die Diff-Ansicht)
+const KEY_CONFLICT_LOCAL: &str = "conflict_local";
 const KEY_LOCAL_ONLY_RECONCILED: &str = "local_only_reconciled";
-
-fn load_folders(app: &AppHandle) -> Vec<FolderMeta> {
-    app.store(STORE_FILE)
+/// Zuletzt vergebener bzw. gesehener HLC-Stempel dieses Stores
+const KEY_CLOCK: &str = "hlc_clock";
+/// Notiz-Cache der alten Architektur — nur noch für [`migrate_from_note_cache`] gelesen
+const LEGACY_SYNC_STORE: &str = "sync_state.json";
+const LEGACY_KEY_NOTE_CACHE: &str = "note_cache";
+
+fn load_folders<R: Runtime>(app: &AppHandle<R>) -> Vec<FolderMeta> {
+    app.store(crate::profiles::store_file(app, STORE_FILE))
         .ok()
         .and_then(|s| s.get(KEY_FOLDERS))
         .and_then(|v| serde_json::from_value(v.clone()).ok())
         .unwrap_or_default()
 }
 
-fn save_folders(app: &AppHandle, folders: &Vec<FolderMeta>) {
-    if let Ok(store) = app.store(STORE_FILE) {
+fn save_folders<R: Runtime>(app: &AppHandle<R>, folders: &Vec<FolderMeta>) {
+    if let Ok(store) = app.store(crate::profiles::store_file(app, STORE_FILE)) {
         store.set(
             KEY_FOLDERS,
             serde_json::to_value(folders).unwrap_or(serde_json::Value::Array(vec![])),
@@ -32,23 +45,71 @@ fn save_folders(app: &AppHandle, folders: &Vec<FolderMeta>) {
     }
 }
 
-fn load_notes_map(app: &AppHandle) -> serde_json::Map<String, serde_json::Value> {
-    app.store(STORE_FILE)
+fn load_notes_map<R: Runtime>(app: &AppHandle<R>) -> serde_json::Map<String, serde_json::Value> {
+    app.store(crate::profiles::store_file(app, STORE_FILE))
         .ok()
         .and_then(|s| s.get(KEY_NOTES))
         .and_then(|v| v.as_object().cloned())
         .unwrap_or_default()
 }
 
-fn save_notes_map(app: &AppHandle, map: &serde_json::Map<String, serde_json::Value>) {
-    if let Ok(store) = app.store(STORE_FILE) {
+fn save_notes_map<R: Runtime>(
+    app: &AppHandle<R>,
+    map: &serde_json::Map<String, serde_json::Value>,
+) {
+    if let Ok(store) = app.store(crate::profiles::store_file(app, STORE_FILE)) {
         store.set(KEY_NOTES, serde_json::Value::Object(map.clone()));
         let _ = store.save();
     }
 }
 
+/// Notiz-Schnappschüsse je ID unter `key` (Merge-Basen, Konflikt-Fassungen).
+fn load_snapshots<R: Runtime>(
+    app: &AppHandle<R>,
+    key: &str,
+) -> serde_json::Map<String, serde_json::Value> {
+    app.store(crate::profiles::store_file(app, STORE_FILE))
+        .ok()
+        .and_then(|s| s.get(key))
+        .and_then(|v| v.as_object().cloned())
+        .unwrap_or_default()
+}
+
+fn save_snapshots<R: Runtime>(
+    app: &AppHandle<R>,
+    key: &str,
+    map: &serde_json::Map<String, serde_json::Value>,
+) {
+    if let Ok(store) = app.store(crate::profiles::store_file(app, STORE_FILE)) {
+        store.set(key, serde_json::Value::Object(map.clone()));
+        let _ = store.save();
+    }
+}
+
+fn set_snapshot<R: Runtime>(app: &AppHandle<R>, key: &str, note: &Note) {
+    let _g = STORE_LOCK.lock().unwrap_or_else(|p| p.into_inner());
+    let mut map = load_snapshots(app, key);
+    if let Ok(v) = serde_json::to_value(note) {
+        map.insert(note.id.clone(), v);
+    }
+    save_snapshots(app, key, &map);
+}
+
+fn get_snapshot<R: Runtime>(app: &AppHandle<R>, key: &str, id: &str) -> Option<Note> {
+    load_snapshots(app, key)
+        .get(id)
+        .and_then(|v| serde_json::from_value::<Note>(v.clone()).ok())
+}
+
+fn remove_snapshot<R: Runtime>(app: &AppHandle<R>, key: &str, id: &str) {
+    let mut map = load_snapshots(app, key);
+    if map.remove(id).is_some() {
+        save_snapshots(app, key, &map);
+    }
+}
+
 /// Prüft ob ein Ordner als local-only markiert ist (case-insensitiv, ignoriert Tombstones).
-pub fn is_local_only(app: &AppHandle, folder: Option<&str>) -> bool {
+pub fn is_local_only<R: Runtime>(app: &AppHandle<R>, folder: Option<&str>) -> bool {
     let name = match folder {
         Some(n) => n,
         None => return false,
@@ -59,7 +120,7 @@ pub fn is_local_only(app: &AppHandle, folder: Option<&str>) -> bool {
 }
 
 /// Alle aktiven (nicht tombstoneten) lokalen Ordner.
-pub fn active_folders(app: &AppHandle) -> Vec<FolderMeta> {
+pub fn active_folders<R: Runtime>(app: &AppHandle<R>) -> Vec<FolderMeta> {
     load_folders(app)
         .into_iter()
         .filter(|f| !f.deleted)
@@ -67,8 +128,8 @@ pub fn active_folders(app: &AppHandle) -> Vec<FolderMeta> {
 }
 
 /// Ordner anlegen, reaktivieren oder tombstonen.
-pub fn upsert_folder(
-    app: &AppHandle,
+pub fn upsert_folder<R: Runtime>(
+    app: &AppHandle<R>,
     name: &str,
     color: Option<String>,
     deleted: bool,
@@ -85,48 +146,115 @@ pub fn upsert_folder(
         folders[pos].deleted = deleted;
         folders[pos].updated_at = now;
         folders[pos].local_only = local_only;
+        stamp(app, &mut folders[pos]);
     } else {
-        folders.push(FolderMeta {
+        let mut meta = FolderMeta {
             name: name.to_string(),
             color,
             updated_at: now,
             deleted,
             local_only,
-        });
+            ..FolderMeta::default()
+        };
+        stamp(app, &mut meta);
+        folders.push(meta);
     }
     save_folders(app, &folders);
 }
 
-/// PENDING setzen, außer in local-only-Ordnern (dort LOCAL_ONLY, wird nie hochgeladen).
-pub fn mark_dirty(app: &AppHandle, note: &mut crate::models::Note) {
+/// PENDING setzen, außer in local-only-Ordnern (dort LOCAL_ONLY, wird nie hochgeladen),
+/// und die Änderung mit einem neuen HLC-Stempel versehen. Eine vor einem Merge-Konflikt
+/// gesicherte Fassung ist damit überholt.
+pub fn mark_dirty<R: Runtime>(app: &AppHandle<R>, note: &mut crate::models::Note) {
     use crate::models::SyncStatus;
     note.sync_status = if is_local_only(app, note.folder_name.as_deref()) {
         SyncStatus::LocalOnly
     } else {
         SyncStatus::Pending
     };
+    stamp(app, note);
+    clear_conflict_local(app, &note.id);
+}
+
+fn load_clock<R: Runtime>(app: &AppHandle<R>) -> Option<Hlc> {
+    app.store(crate::profiles::store_file(app, STORE_FILE))
+        .ok()
+        .and_then(|s| s.get(KEY_CLOCK))
+        .and_then(|v| v.as_str().and_then(Hlc::parse))
+}
+
+fn save_clock<R: Runtime>(app: &AppHandle<R>, clock: &Hlc) {
+    if let Ok(store) = app.store(crate::profiles::store_file(app, STORE_FILE)) {
+        store.set(KEY_CLOCK, serde_json::Value::String(clock.to_string()));
+        let _ = store.save();
+    }
+}
+
+/// Knoten-ID der Uhr: steckt im gespeicherten Stempel, beim ersten Stempel zufällig erzeugt.
+fn clock_node(last: Option<&Hlc>) -> String {
+    last.map(|l| l.node.clone())
+        .unwrap_or_else(|| uuid::Uuid::new_v4().simple().to_string()[..12].to_string())
+}
+
+/// Nächster HLC-Stempel für eine lokale Änderung; der Uhrstand wird fortgeschrieben.
+pub fn next_hlc<R: Runtime>(app: &AppHandle<R>) -> Hlc {
+    let _g = CLOCK_LOCK.lock().unwrap_or_else(|p| p.into_inner());
+    let last = load_clock(app);
+    let node = clock_node(last.as_ref());
+    let next = Hlc::tick(last.as_ref(), chrono::Utc::now().timestamp_millis(), &node);
+    save_clock(app, &next);
+    next
+}
+
+/// Fremden Stempel (vom Server) in die Uhr übernehmen, damit folgende lokale Änderungen
+/// höher stempeln.
+pub fn observe_hlc<R: Runtime>(app: &AppHandle<R>, remote: &impl Stamped) {
+    let Some(remote) = remote.hlc() else {
+        return;
+    };
+    let _g = CLOCK_LOCK.lock().unwrap_or_else(|p| p.into_inner());
+    let last = load_clock(app);
+    let node = clock_node(last.as_ref());
+    let clock = Hlc::observe(last.as_ref(), &remote, &node);
+    if last.as_ref() != Some(&clock) {
+        save_clock(app, &clock);
+    }
+}
+
+/// Notiz, Ordner-Metadaten oder Lösch-Eintrag mit einem neuen HLC-Stempel versehen.
+pub fn stamp<R: Runtime>(app: &AppHandle<R>, item: &mut impl Stamped) {
+    item.set_hlc(&next_hlc(app));
 }
 
 /// Einmalige Migration: alle note_cache-Einträge (alte Architektur) in den lokalen Store
 /// übernehmen, falls dort noch nicht vorhanden. Danach ist local_store die alleinige Quelle.
-pub fn migrate_from_note_cache(app: &AppHandle) {
-    {
-        let _g = STORE_LOCK.lock().unwrap_or_else(|p| p.into_inner());
-        let mut map = load_notes_map(app);
-        let mut changed = false;
-        for (id, entry) in crate::sync_engine::load_note_cache(app) {
-            if !map.contains_key(&id) {
-                if let Ok(v) = serde_json::to_value(&entry.note) {
-                    map.insert(id, v);
+pub fn migrate_from_note_cache<R: Runtime>(app: &AppHandle<R>) {
+    // Alter Cache in sync_state.json: `{ "<id>": { "note": {…}, "last_synced_at": …, "etag": … } }`.
+    // Server-Stände (ETag, Ordner) führt heute `remote_state` — hier zählt nur die Notiz.
+    if let Ok(sync_store) = app.store(crate::profiles::store_file(app, LEGACY_SYNC_STORE)) {
+        if let Some(serde_json::Value::Object(cache)) = sync_store.get(LEGACY_KEY_NOTE_CACHE) {
+            let _g = STORE_LOCK.lock().unwrap_or_else(|p| p.into_inner());
+            let mut map = load_notes_map(app);
+            let mut changed = false;
+            for (id, entry) in cache {
+                if map.contains_key(&id) {
+                    continue;
+                }
+                let Some(note) = entry.get("note") else {
+                    continue;
+                };
+                if serde_json::from_value::<Note>(note.clone()).is_ok() {
+                    map.insert(id, note.clone());
                     changed = true;
                 }
             }
+            if changed {
+                save_notes_map(app, &map);
+            }
         }
-        if changed {
-            save_notes_map(app, &map);
-        }
+        sync_store.delete(LEGACY_KEY_NOTE_CACHE);
+        let _ = sync_store.save();
     }
-    crate::sync_engine::clear_note_cache(app);
 
     // Befund 4: reine Offline-Installation → alle Ordner als local_only markieren.
     // upsert_folder nimmt intern den STORE_LOCK; kein Deadlock da wir ihn oben bereits freigegeben haben.
@@ -150,16 +278,16 @@ pub fn migrate_from_note_cache(app: &AppHandle) {
 }
 
 /// true, sobald die einmalige Server-Präsenz-Reconciliation gelaufen ist.
-pub fn local_only_reconciled(app: &AppHandle) -> bool {
-    app.store(STORE_FILE)
+pub fn local_only_reconciled<R: Runtime>(app: &AppHandle<R>) -> bool {
+    app.store(crate::profiles::store_file(app, STORE_FILE))
         .ok()
         .and_then(|s| s.get(KEY_LOCAL_ONLY_RECONCILED))
         .and_then(|v| v.as_bool())
         .unwrap_or(false)
 }
 
-fn set_local_only_reconciled(app: &AppHandle) {
-    if let Ok(store) = app.store(STORE_FILE) {
+fn set_local_only_reconciled<R: Runtime>(app: &AppHandle<R>) {
+    if let Ok(store) = app.store(crate::profiles::store_file(app, STORE_FILE)) {
         store.set(KEY_LOCAL_ONLY_RECONCILED, serde_json::json!(true));
         let _ = store.save();
     }
@@ -174,7 +302,10 @@ fn set_local_only_reconciled(app: &AppHandle) {
 /// `server_names` MUSS lowercased sein. Setzt anschließend den Marker.
 // ponytail: ein synchronisierter Ordner, der offline angelegt und noch nicht hochgeladen wurde,
 // wird einmalig fälschlich als local-only markiert — korrigierbar via set_folder_local_only(false).
-pub fn reconcile_local_only(app: &AppHandle, server_names: &std::collections::HashSet<String>) {
+pub fn reconcile_local_only<R: Runtime>(
+    app: &AppHandle<R>,
+    server_names: &std::collections::HashSet<String>,
+) {
     let _g = STORE_LOCK.lock().unwrap_or_else(|p| p.into_inner());
     let mut folders = load_folders(app);
     for f in folders.iter_mut() {
@@ -188,7 +319,7 @@ pub fn reconcile_local_only(app: &AppHandle, server_names: &std::collections::Ha
 }
 
 /// Farbe eines lokalen Ordners setzen.
-pub fn set_folder_color(app: &AppHandle, name: &str, color: Option<String>) {
+pub fn set_folder_color<R: Runtime>(app: &AppHandle<R>, name: &str, color: Option<String>) {
     let _g = STORE_LOCK.lock().unwrap_or_else(|p| p.into_inner());
     let now = chrono::Utc::now().timestamp_millis();
     let mut folders = load_folders(app);
@@ -198,12 +329,13 @@ pub fn set_folder_color(app: &AppHandle, name: &str, color: Option<String>) {
     {
         folders[pos].color = color;
         folders[pos].updated_at = now;
+        stamp(app, &mut folders[pos]);
         save_folders(app, &folders);
     }
 }
 
 /// Lokalen Ordner umbenennen: Meta + folder_name aller zugehörigen Notizen aktualisieren.
-pub fn rename_folder(app: &AppHandle, old_name: &str, new_name: &str) {
+pub fn rename_folder<R: Runtime>(app: &AppHandle<R>, old_name: &str, new_name: &str) {
     let _g = STORE_LOCK.lock().unwrap_or_else(|p| p.into_inner());
     let now = chrono::Utc::now().timestamp_millis();
 
@@ -213,6 +345,7 @@ pub fn rename_folder(app: &AppHandle, old_name: &str, new_name: &str) {
         if f.name.eq_ignore_ascii_case(old_name) {
             f.name = new_name.to_string();
             f.updated_at = now;
+            stamp(app, f);
         }
     }
     save_folders(app, &folders);
@@ -235,7 +368,7 @@ pub fn rename_folder(app: &AppHandle, old_name: &str, new_name: &str) {
 }
 
 /// Notiz speichern / überschreiben.
-pub fn put_note(app: &AppHandle, note: &Note) {
+pub fn put_note<R: Runtime>(app: &AppHandle<R>, note: &Note) {
     let _g = STORE_LOCK.lock().unwrap_or_else(|p| p.into_inner());
     let mut map = load_notes_map(app);
     if let Ok(v) = serde_json::to_value(note) {
@@ -245,7 +378,7 @@ pub fn put_note(app: &AppHandle, note: &Note) {
 }
 
 /// Notiz laden. Gibt `None` zurück wenn nicht vorhanden.
-pub fn get_note(app: &AppHandle, id: &str) -> Option<Note> {
+pub fn get_note<R: Runtime>(app: &AppHandle<R>, id: &str) -> Option<Note> {
     let map = load_notes_map(app);
     map.get(id)
         .and_then(|v| serde_json::from_value::<Note>(v.clone()).ok())
@@ -253,22 +386,59 @@ pub fn get_note(app: &AppHandle, id: &str) -> Option<Note> {
 
 /// Prüft ob eine Notiz im lokalen Store existiert (nach ID, unabhängig vom Ordner-Status).
 #[allow(dead_code)]
-pub fn has_note(app: &AppHandle, id: &str) -> bool {
+pub fn has_note<R: Runtime>(app: &AppHandle<R>, id: &str) -> bool {
     load_notes_map(app).contains_key(id)
 }
 
-/// Notiz permanent entfernen.
-pub fn remove_note(app: &AppHandle, id: &str) {
+/// Notiz permanent entfernen (samt Merge-Basis).
+pub fn remove_note<R: Runtime>(app: &AppHandle<R>, id: &str) {
     let _g = STORE_LOCK.lock().unwrap_or_else(|p| p.into_inner());
     let mut map = load_notes_map(app);
     map.remove(id);
     save_notes_map(app, &map);
+    remove_snapshot(app, KEY_BASES, id);
+    remove_snapshot(app, KEY_CONFLICT_LOCAL, id);
+}
+
+/// Fassung merken, die gerade mit dem Server übereinstimmt (nach Download, Upload oder
+/// Konfliktauflösung) — Basis für den nächsten Drei-Wege-Merge.
+pub fn set_base<R: Runtime>(app: &AppHandle<R>, note: &Note) {
+    let mut base = note.clone();
+    base.sync_status = crate::models::SyncStatus::Synced;
+    set_snapshot(app, KEY_BASES, &base);
+}
+
+/// Zuletzt synchronisierte Fassung. `None` bei Notizen aus der Zeit vor dem Merge-Support.
+pub fn get_base<R: Runtime>(app: &AppHandle<R>, id: &str) -> Option<Note> {
+    get_snapshot(app, KEY_BASES, id)
+}
+
+/// Lokale Fassung vor einem Merge mit Konfliktmarkern sichern (ersetzt die eines früheren
+/// Konflikts).
+pub fn set_conflict_local<R: Runtime>(app: &AppHandle<R>, note: &Note) {
+    set_snapshot(app, KEY_CONFLICT_LOCAL, note);
+}
+
+/// Lokale Fassung vor dem Konflikt; `None` wenn die Notiz keine Konfliktmarker bekommen hat.
+pub fn get_conflict_local<R: Runtime>(app: &AppHandle<R>, id: &str) -> Option<Note> {
+    get_snapshot(app, KEY_CONFLICT_LOCAL, id)
+}
+
+/// Konflikt aufgelöst bzw. Notiz seitdem bearbeitet oder synchronisiert — gesicherte lokale
+/// Fassung verwerfen.
+pub fn clear_conflict_local<R: Runtime>(app: &AppHandle<R>, id: &str) {
+    let _g = STORE_LOCK.lock().unwrap_or_else(|p| p.into_inner());
+    remove_snapshot(app, KEY_CONFLICT_LOCAL, id);
 }
 
 /// Markiert die Notiz als SYNCED — aber nur wenn sie sich seit dem Upload nicht verändert hat
 /// (gleicher updated_at, noch Pending/LocalOnly). Verhindert das Überschreiben eines Edits,
 /// der während des Upload-awaits eingetroffen ist.
-pub fn mark_synced_if_unchanged(app: &AppHandle, id: &str, uploaded_updated_at: i64) {
+pub fn mark_synced_if_unchanged<R: Runtime>(
+    app: &AppHandle<R>,
+    id: &str,
+    uploaded_updated_at: i64,
+) {
     use crate::models::SyncStatus;
     let _g = STORE_LOCK.lock().unwrap_or_else(|p| p.into_inner());
     let mut map = load_notes_map(app);
@@ -295,7 +465,7 @@ fn should_mark_synced(note: &Note, uploaded_updated_at: i64) -> bool {
 }
 
 /// Alle lokal gespeicherten Notizen laden.
-pub fn list_notes(app: &AppHandle) -> Vec<Note> {
+pub fn list_notes<R: Runtime>(app: &AppHandle<R>) -> Vec<Note> {
     load_notes_map(app)
         .values()
         .filter_map(|v| serde_json::from_value::<Note>(v.clone()).ok())
@@ -329,6 +499,31 @@ mod tests {
         assert!(!should_mark_synced(&note, 100));
     }
 
+    #[test]
+    fn test_migrate_from_note_cache_imports_legacy_entries() {
+        let app = crate::memory_backend::TestApp::new();
+        let legacy = Note::new("Alt".into(), "tauri-x".into());
+        let existing = Note::new("Schon da".into(), "tauri-x".into());
+        put_note(app.handle(), &existing);
+        let store = app.handle().store(LEGACY_SYNC_STORE).unwrap();
+        store.set(
+            LEGACY_KEY_NOTE_CACHE,
+            serde_json::json!({
+                legacy.id.clone(): { "note": legacy, "last_synced_at": 1, "etag": "e1" },
+                existing.id.clone(): { "note": Note::new("Überholt".into(), "tauri-x".into()), "last_synced_at": 1 },
+            }),
+        );
+
+        migrate_from_note_cache(app.handle());
+
+        assert_eq!(get_note(app.handle(), &legacy.id).unwrap().title, "Alt");
+        assert_eq!(
+            get_note(app.handle(), &existing.id).unwrap().title,
+            "Schon da"
+        );
+        assert!(store.get(LEGACY_KEY_NOTE_CACHE).is_none());
+    }
+
     #[test]
     fn test_should_mark_synced_already_synced() {
         let mut note = Note::new("x".into(), "tauri-x".into());
@@ -0,0 +1,386 @@
+//! In-Memory-Sync-Backend für Tests: Notizen je Ordner mit fortlaufender Version,
+//! Vorbedingungen wie bei WebDAV (`If-Match`/`If-None-Match` → `AppError::Conflict`),
+//! Ordner-Metadaten und Lösch-Ledger. Kein Delta-Support — `run_sync` listet immer komplett.
+//! Dazu eine Mock-App mit eigenem Store-Verzeichnis, damit `run_sync` ohne Fenster läuft.
+
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use tauri::test::{mock_builder, mock_context, noop_assets, MockRuntime};
+use tauri::{App, AppHandle, Manager};
+
+use crate::error::{AppError, Result};
+use crate::folders::FolderMeta;
+use crate::hlc::Hlc;
+use crate::models::{DeletionLedger, Note};
+use crate::sync_backend::SyncBackend;
+use crate::webdav::{merge_deletion, CollectionDelta, NoteListing, WriteCondition};
+
+struct StoredNote {
+    folder: Option<String>,
+    note: Note,
+    version: u64,
+}
+
+#[derive(Default)]
+struct State {
+    notes: Vec<StoredNote>,
+    folders: Vec<String>,
+    folders_meta: Vec<FolderMeta>,
+    ledger: DeletionLedger,
+    next_version: u64,
+    /// Schreibzugriffe auf Notizen scheitern (Server nicht erreichbar)
+    fail_puts: bool,
+    /// Protokoll der Operationen als `"OP id"` bzw. `"OP ordner"`
+    log: Vec<String>,
+}
+
+impl State {
+    fn position(&self, id: &str, folder: Option<&str>) -> Option<usize> {
+        self.notes
+            .iter()
+            .position(|s| s.note.id == id && same_folder(s.folder.as_deref(), folder))
+    }
+
+    fn bump(&mut self) -> u64 {
+        self.next_version += 1;
+        self.next_version
+    }
+}
+
+fn same_folder(a: Option<&str>, b: Option<&str>) -> bool {
+    a.map(str::to_lowercase) == b.map(str::to_lowercase)
+}
+
+fn etag(version: u64) -> String {
+    format!("\"m{}\"", version)
+}
+
+#[derive(Clone, Default)]
+pub struct MemoryBackend {
+    state: Arc<Mutex<State>>,
+}
+
+impl MemoryBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Notiz wie von einem anderen Gerät hochgeladen ablegen (neue Version).
+    pub fn put_server_note(&self, note: &Note) {
+        let mut state = self.state.lock().unwrap();
+        let version = state.bump();
+        match state.position(&note.id, note.folder_name.as_deref()) {
+            Some(pos) => {
+                state.notes[pos].note = note.clone();
+                state.notes[pos].version = version;
+            }
+            None => state.notes.push(StoredNote {
+                folder: note.folder_name.clone(),
+                note: note.clone(),
+                version,
+            }),
+        }
+    }
+
+    /// Notiz direkt entfernen (z.B. von einem anderen Gerät gelöscht).
+    pub fn remove_server_note(&self, id: &str) {
+        self.state.lock().unwrap().notes.retain(|s| s.note.id != id);
+    }
+
+    pub fn server_note(&self, id: &str) -> Option<Note> {
+        let state = self.state.lock().unwrap();
+        state
+            .notes
+            .iter()
+            .find(|s| s.note.id == id)
+            .map(|s| s.note.clone())
+    }
+
+    /// Ordner, in dem die Notiz am Server liegt (None = Root).
+    pub fn server_folder(&self, id: &str) -> Option<Option<String>> {
+        let state = self.state.lock().unwrap();
+        state
+            .notes
+            .iter()
+            .find(|s| s.note.id == id)
+            .map(|s| s.folder.clone())
+    }
+
+    /// Notiz-PUTs ab jetzt mit Netzwerkfehler scheitern lassen.
+    pub fn fail_puts(&self, fail: bool) {
+        self.state.lock().unwrap().fail_puts = fail;
+    }
+
+    pub fn folders(&self) -> Vec<String> {
+        self.state.lock().unwrap().folders.clone()
+    }
+
+    pub fn folders_meta(&self) -> Vec<FolderMeta> {
+        self.state.lock().unwrap().folders_meta.clone()
+    }
+
+    pub fn ledger_ids(&self) -> Vec<String> {
+        let state = self.state.lock().unwrap();
+        state
+            .ledger
+            .deleted_notes
+            .iter()
+            .map(|r| r.id.clone())
+            .collect()
+    }
+
+    /// Anzahl protokollierter Operationen `op` (z.B. `"GET"`, `"PUT"`).
+    pub fn count(&self, op: &str) -> usize {
+        let prefix = format!("{} ", op);
+        let state = self.state.lock().unwrap();
+        state.log.iter().filter(|l| l.starts_with(&prefix)).count()
+    }
+
+    fn log(&self, op: &str, target: &str) {
+        self.state
+            .lock()
+            .unwrap()
+            .log
+            .push(format!("{} {}", op, target));
+    }
+
+    fn ensure_folder_now(&self, name: &str) {
+        let mut state = self.state.lock().unwrap();
+        if !state.folders.iter().any(|f| f.eq_ignore_ascii_case(name)) {
+            state.folders.push(name.to_string());
+        }
+    }
+}
+
+impl SyncBackend for MemoryBackend {
+    fn for_sync_run(&self, _concurrency: usize) -> Self {
+        self.clone()
+    }
+
+    fn concurrency(&self) -> usize {
+        2
+    }
+
+    fn retry_budget_exhausted(&self) -> bool {
+        false
+    }
+
+    fn location(&self) -> String {
+        "memory:".to_string()
+    }
+
+    async fn supports_delta(&self) -> bool {
+        false
+    }
+
+    async fn changes_since(
+        &self,
+        _folder: Option<&str>,
+        _token: Option<&str>,
+    ) -> Result<CollectionDelta> {
+        Err(AppError::Unsupported(
+            "Kein Delta im In-Memory-Backend".to_string(),
+        ))
+    }
+
+    async fn list_notes(&self) -> Result<Vec<NoteListing>> {
+        self.log("LIST", "");
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .notes
+            .iter()
+            .map(|s| NoteListing {
+                id: s.note.id.clone(),
+                folder: s.folder.clone(),
+                etag: Some(etag(s.version)),
+                last_modified: None,
+            })
+            .collect())
+    }
+
+    async fn get_note(&self, id: &str, folder: Option<&str>) -> Result<(Note, Option<String>)> {
+        self.log("GET", id);
+        let state = self.state.lock().unwrap();
+        let pos = state
+            .position(id, folder)
+            .ok_or_else(|| AppError::NoteNotFound(id.to_string()))?;
+        let stored = &state.notes[pos];
+        let mut note = stored.note.clone();
+        note.folder_name = stored.folder.clone();
+        Ok((note, Some(etag(stored.version))))
+    }
+
+    async fn put_note(&self, note: &Note, condition: WriteCondition) -> Result<Option<String>> {
+        self.log("PUT", &note.id);
+        if let Some(folder) = &note.folder_name {
+            self.ensure_folder_now(folder);
+        }
+        let mut state = self.state.lock().unwrap();
+        if state.fail_puts {
+            return Err(AppError::NetworkError(format!("PUT {}", note.id)));
+        }
+        let existing = state.position(&note.id, note.folder_name.as_deref());
+        let current = existing.map(|pos| etag(state.notes[pos].version));
+        let allowed = match (&condition, &current) {
+            (WriteCondition::Unconditional, _) => true,
+            (WriteCondition::IfMatch(expected), Some(current)) => expected == current,
+            (WriteCondition::IfMatch(_), None) => false,
+            (WriteCondition::IfNoneMatch, current) => current.is_none(),
+        };
+        if !allowed {
+            return Err(AppError::Conflict(format!(
+                "{}: Vorbedingung {:?} verletzt",
+                note.id, condition
+            )));
+        }
+        let version = state.bump();
+        match existing {
+            Some(pos) => {
+                state.notes[pos].note = note.clone();
+                state.notes[pos].version = version;
+            }
+            None => state.notes.push(StoredNote {
+                folder: note.folder_name.clone(),
+                note: note.clone(),
+                version,
+            }),
+        }
+        Ok(Some(etag(version)))
+    }
+
+    async fn delete_note(&self, id: &str, folder: Option<&str>) -> Result<()> {
+        self.log("DELETE", id);
+        let mut state = self.state.lock().unwrap();
+        if let Some(pos) = state.position(id, folder) {
+            state.notes.remove(pos);
+        }
+        Ok(())
+    }
+
+    async fn move_note(&self, id: &str, from: Option<&str>, to: Option<&str>) -> Result<()> {
+        self.log("MOVE", id);
+        if let Some(folder) = to {
+            self.ensure_folder_now(folder);
+        }
+        let mut state = self.state.lock().unwrap();
+        let Some(pos) = state.position(id, from) else {
+            return Ok(());
+        };
+        if state.position(id, to).is_some() {
+            state.notes.remove(pos);
+            return Ok(());
+        }
+        let version = state.bump();
+        let stored = &mut state.notes[pos];
+        stored.folder = to.map(str::to_owned);
+        stored.note.folder_name = to.map(str::to_owned);
+        stored.version = version;
+        Ok(())
+    }
+
+    async fn list_folders(&self) -> Vec<String> {
+        self.folders()
+    }
+
+    async fn ensure_folder(&self, name: &str) {
+        self.ensure_folder_now(name);
+    }
+
+    async fn move_folder(&self, from: &str, to: &str) -> Result<bool> {
+        self.log("MOVE", from);
+        let mut state = self.state.lock().unwrap();
+        if state.folders.iter().any(|f| f.eq_ignore_ascii_case(to)) {
+            return Ok(false);
+        }
+        state.folders.retain(|f| !f.eq_ignore_ascii_case(from));
+        state.folders.push(to.to_string());
+        for stored in &mut state.notes {
+            if same_folder(stored.folder.as_deref(), Some(from)) {
+                stored.folder = Some(to.to_string());
+                stored.note.folder_name = Some(to.to_string());
+            }
+        }
+        Ok(true)
+    }
+
+    async fn delete_folder(&self, name: &str) {
+        self.log("DELETE", name);
+        let mut state = self.state.lock().unwrap();
+        state.folders.retain(|f| !f.eq_ignore_ascii_case(name));
+        state
+            .notes
+            .retain(|s| !same_folder(s.folder.as_deref(), Some(name)));
+    }
+
+    async fn read_folders_meta(&self) -> Vec<FolderMeta> {
+        self.folders_meta()
+    }
+
+    fn write_folders_meta_merged<F>(
+        &self,
+        mut mutation: F,
+    ) -> impl Future<Output = Result<Vec<FolderMeta>>> + Send
+    where
+        F: FnMut(Vec<FolderMeta>) -> Vec<FolderMeta> + Send,
+    {
+        let mut state = self.state.lock().unwrap();
+        let updated = mutation(state.folders_meta.clone());
+        state.folders_meta = updated.clone();
+        std::future::ready(Ok(updated))
+    }
+
+    async fn read_deletions(&self) -> DeletionLedger {
+        self.state.lock().unwrap().ledger.clone()
+    }
+
+    async fn append_deletions(
+        &self,
+        ids: &[String],
+        device_id: &str,
+        now: i64,
+        stamp: Option<&Hlc>,
+        retention_ms: i64,
+    ) {
+        let mut state = self.state.lock().unwrap();
+        for id in ids {
+            let ledger = std::mem::take(&mut state.ledger);
+            state.ledger = merge_deletion(ledger, id, device_id, now, stamp, retention_ms);
+        }
+    }
+
+    async fn remove_deletions(&self, ids: &[String]) {
+        let mut state = self.state.lock().unwrap();
+        state.ledger.deleted_notes.retain(|r| !ids.contains(&r.id));
+    }
+}
+
+/// Mock-App mit Store-Plugin. Eigener Identifier je Instanz → eigenes Store-Verzeichnis,
+/// das beim Drop entfernt wird (Tests laufen parallel).
+pub struct TestApp {
+    app: App<MockRuntime>,
+}
+
+impl TestApp {
+    pub fn new() -> Self {
+        let mut context = mock_context(noop_assets());
+        context.config_mut().identifier = format!("simple-notes-test-{}", uuid::Uuid::new_v4());
+        let app = mock_builder()
+            .plugin(tauri_plugin_store::Builder::new().build())
+            .build(context)
+            .expect("Mock-App");
+        Self { app }
+    }
+
+    pub fn handle(&self) -> &AppHandle<MockRuntime> {
+        self.app.handle()
+    }
+}
+
+impl Drop for TestApp {
+    fn drop(&mut self) {
+        if let Ok(dir) = self.app.path().app_data_dir() {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+}
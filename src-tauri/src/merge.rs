@@ -0,0 +1,666 @@
+//! Drei-Wege-Merge von Notizen gegen die zuletzt synchronisierte Fassung (Basis).
+//!
+//! Text (`title`, `content`) wird zeilenweise nach diff3 zusammengeführt: Änderungen nur auf
+//! einer Seite werden übernommen, identische Änderungen einmal. Nur wenn beide Seiten dieselben
+//! Zeilen unterschiedlich geändert haben, entsteht ein Konflikt — im Inhalt mit Konfliktmarkern
+//! (`<<<<<<< Mine` / `=======` / `>>>>>>> Server`), beim Titel bleibt der lokale stehen.
+//! Übrige Felder: geänderte Seite gewinnt, beidseitig verschieden geändert → Konflikt.
+//! Checklisten werden je Item (über die stabile Item-ID) zusammengeführt und konfliktfrei
+//! aufgelöst, siehe [`merge_checklist`].
+
+use std::collections::{HashMap, HashSet};
+
+use crate::hlc::{self, HLC_KEY};
+use crate::models::{ChecklistItem, Note};
+
+const MARKER_MINE: &str = "<<<<<<< Mine\n";
+const MARKER_SEPARATOR: &str = "=======\n";
+const MARKER_SERVER: &str = ">>>>>>> Server\n";
+
+/// Obergrenze für die LCS-Tabelle (Zeilen × Zeilen nach Abzug gemeinsamer Ränder). Darüber
+/// wird der geänderte Block als Ganzes behandelt — spart Speicher bei riesigen Notizen.
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+/// Ergebnis eines Text-Merges.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextMerge {
+    /// Zusammengeführter Text, überlappende Änderungen mit Konfliktmarkern
+    pub text: String,
+    /// Anzahl überlappender Änderungen (0 = sauber zusammengeführt)
+    pub conflicts: usize,
+}
+
+/// Ergebnis eines Notiz-Merges. `note` trägt die Felder von `local` (id, Status, …) mit den
+/// zusammengeführten Inhalten; der Aufrufer setzt Status und Zeitstempel.
+#[derive(Debug, Clone)]
+pub struct NoteMerge {
+    pub note: Note,
+    /// Überlappende Änderungen in Titel, Inhalt oder anderen Feldern
+    pub conflicts: usize,
+}
+
+impl NoteMerge {
+    pub fn is_clean(&self) -> bool {
+        self.conflicts == 0
+    }
+}
+
+/// Zeilen inkl. Zeilenende, damit der Merge Zeilenumbrüche (auch `\r\n`) exakt erhält.
+pub(crate) fn lines(text: &str) -> Vec<&str> {
+    text.split_inclusive('\n').collect()
+}
+
+/// Für jede Zeile von `base` die zugeordnete Zeile in `other` (längste gemeinsame Teilfolge).
+pub(crate) fn matching(base: &[&str], other: &[&str]) -> Vec<Option<usize>> {
+    let mut result = vec![None; base.len()];
+    let prefix = base.iter().zip(other).take_while(|(a, b)| a == b).count();
+    let suffix = base[prefix..]
+        .iter()
+        .rev()
+        .zip(other[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    for (i, slot) in result.iter_mut().enumerate().take(prefix) {
+        *slot = Some(i);
+    }
+    for k in 0..suffix {
+        result[base.len() - 1 - k] = Some(other.len() - 1 - k);
+    }
+
+    let a = &base[prefix..base.len() - suffix];
+    let b = &other[prefix..other.len() - suffix];
+    if a.is_empty() || b.is_empty() || a.len() * b.len() > MAX_LCS_CELLS {
+        return result;
+    }
+    // lcs[i][j] = Länge der LCS von a[i..] und b[j..]
+    let width = b.len() + 1;
+    let mut lcs = vec![0u32; (a.len() + 1) * width];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i * width + j] = if a[i] == b[j] {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            result[prefix + i] = Some(prefix + j);
+            i += 1;
+            j += 1;
+        } else if lcs[(i + 1) * width + j] >= lcs[i * width + j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    result
+}
+
+fn push_side(out: &mut String, side: &[&str]) {
+    for line in side {
+        out.push_str(line);
+    }
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+}
+
+/// Geänderten Block auflösen: nur eine Seite geändert oder beide gleich → übernehmen,
+/// sonst Konfliktmarker.
+fn resolve_chunk(out: &mut String, base: &[&str], mine: &[&str], theirs: &[&str]) -> bool {
+    let take = if mine == theirs || theirs == base {
+        mine
+    } else if mine == base {
+        theirs
+    } else {
+        out.push_str(MARKER_MINE);
+        push_side(out, mine);
+        out.push_str(MARKER_SEPARATOR);
+        push_side(out, theirs);
+        out.push_str(MARKER_SERVER);
+        return false;
+    };
+    for line in take {
+        out.push_str(line);
+    }
+    true
+}
+
+/// Zeilenbasierter Drei-Wege-Merge (diff3) von `mine` und `theirs` gegen `base`.
+pub fn merge_text(base: &str, mine: &str, theirs: &str) -> TextMerge {
+    if mine == theirs || theirs == base {
+        return TextMerge {
+            text: mine.to_string(),
+            conflicts: 0,
+        };
+    }
+    if mine == base {
+        return TextMerge {
+            text: theirs.to_string(),
+            conflicts: 0,
+        };
+    }
+    let (base, mine, theirs) = (lines(base), lines(mine), lines(theirs));
+    let in_mine = matching(&base, &mine);
+    let in_theirs = matching(&base, &theirs);
+
+    let mut out = String::new();
+    let mut conflicts = 0;
+    let (mut i, mut a, mut b) = (0, 0, 0);
+    loop {
+        // Nächste Basis-Zeile, die auf beiden Seiten unverändert vorkommt
+        let stable = (i..base.len()).find_map(|k| match (in_mine[k], in_theirs[k]) {
+            (Some(ka), Some(kb)) => Some((k, ka, kb)),
+            _ => None,
+        });
+        let Some((k, ka, kb)) = stable else {
+            if !resolve_chunk(&mut out, &base[i..], &mine[a..], &theirs[b..]) {
+                conflicts += 1;
+            }
+            break;
+        };
+        if (k, ka, kb) == (i, a, b) {
+            out.push_str(base[i]);
+            i += 1;
+            a += 1;
+            b += 1;
+            continue;
+        }
+        if !resolve_chunk(&mut out, &base[i..k], &mine[a..ka], &theirs[b..kb]) {
+            conflicts += 1;
+        }
+        (i, a, b) = (k, ka, kb);
+    }
+    TextMerge {
+        text: out,
+        conflicts,
+    }
+}
+
+/// Feld-Merge: geänderte Seite gewinnt; beidseitig verschieden geändert → lokal + Konflikt.
+fn pick<T: PartialEq + Clone>(base: &T, mine: &T, theirs: &T, conflicts: &mut usize) -> T {
+    if mine == theirs || theirs == base {
+        mine.clone()
+    } else if mine == base {
+        theirs.clone()
+    } else {
+        *conflicts += 1;
+        mine.clone()
+    }
+}
+
+/// Wie [`pick`], aber beidseitig verschieden geändert → `mine_wins` entscheidet (Last-Writer-Wins).
+fn pick_lww<T: PartialEq + Clone>(base: Option<&T>, mine: &T, theirs: &T, mine_wins: bool) -> T {
+    if mine == theirs || base == Some(theirs) {
+        mine.clone()
+    } else if base == Some(mine) || !mine_wins {
+        theirs.clone()
+    } else {
+        mine.clone()
+    }
+}
+
+/// Unbekannte Item-Felder schlüsselweise zusammenführen (fehlender Schlüssel = `None`).
+fn merge_extra(
+    base: Option<&serde_json::Map<String, serde_json::Value>>,
+    mine: &serde_json::Map<String, serde_json::Value>,
+    theirs: &serde_json::Map<String, serde_json::Value>,
+    mine_wins: bool,
+) -> serde_json::Map<String, serde_json::Value> {
+    let mut keys: Vec<&String> = mine.keys().chain(theirs.keys()).collect();
+    if let Some(base) = base {
+        keys.extend(base.keys());
+    }
+    keys.sort();
+    keys.dedup();
+    let mut merged = serde_json::Map::new();
+    for key in keys {
+        let base_value = base.map(|b| b.get(key).cloned());
+        let value = pick_lww(
+            base_value.as_ref(),
+            &mine.get(key).cloned(),
+            &theirs.get(key).cloned(),
+            mine_wins,
+        );
+        if let Some(value) = value {
+            merged.insert(key.clone(), value);
+        }
+    }
+    merged
+}
+
+/// Items nach `order` (bei Gleichstand nach ID — deterministisch).
+pub(crate) fn ordered(items: &[ChecklistItem]) -> Vec<&ChecklistItem> {
+    let mut sorted: Vec<&ChecklistItem> = items.iter().collect();
+    sorted.sort_by(|a, b| a.order.cmp(&b.order).then_with(|| a.id.cmp(&b.id)));
+    sorted
+}
+
+/// Hat `side` die Reihenfolge der gemeinsamen Items gegenüber `base` verändert?
+fn reordered(base: &[&ChecklistItem], side: &[&ChecklistItem]) -> bool {
+    let in_side: HashSet<&str> = side.iter().map(|i| i.id.as_str()).collect();
+    let in_base: HashSet<&str> = base.iter().map(|i| i.id.as_str()).collect();
+    let base_ids = base
+        .iter()
+        .map(|i| i.id.as_str())
+        .filter(|id| in_side.contains(id));
+    let side_ids = side
+        .iter()
+        .map(|i| i.id.as_str())
+        .filter(|id| in_base.contains(id));
+    !base_ids.eq(side_ids)
+}
+
+/// Item-Merge einer Checkliste gegen die Basis — ergibt nie einen Konflikt:
+/// - neue Items beider Seiten werden vereinigt
+/// - auf einer Seite gelöschte Items (in der Basis vorhanden) entfallen
+/// - `text`, `is_checked` und unbekannte Felder je Item: geänderte Seite gewinnt, beidseitig
+///   geändert gewinnt die neuere Notiz (`mine_wins`)
+/// - Reihenfolge: die Seite, die umsortiert hat (beide → neuere), gibt sie vor; Items der
+///   anderen Seite werden hinter ihrem dortigen Vorgänger eingefügt. Danach werden `order`
+///   und (falls verwendet) `original_order` fortlaufend neu vergeben.
+pub fn merge_checklist(
+    base: &[ChecklistItem],
+    mine: &[ChecklistItem],
+    theirs: &[ChecklistItem],
+    mine_wins: bool,
+) -> Vec<ChecklistItem> {
+    let base_by_id: HashMap<&str, &ChecklistItem> =
+        base.iter().map(|i| (i.id.as_str(), i)).collect();
+    let mine_by_id: HashMap<&str, &ChecklistItem> =
+        mine.iter().map(|i| (i.id.as_str(), i)).collect();
+    let theirs_by_id: HashMap<&str, &ChecklistItem> =
+        theirs.iter().map(|i| (i.id.as_str(), i)).collect();
+
+    // Überlebende Items mit zusammengeführten Feldern
+    let mut merged: HashMap<&str, ChecklistItem> = HashMap::new();
+    for id in mine_by_id.keys().chain(theirs_by_id.keys()) {
+        let b = base_by_id.get(id).copied();
+        let item = match (mine_by_id.get(id), theirs_by_id.get(id)) {
+            (Some(m), Some(t)) => ChecklistItem {
+                text: pick_lww(b.map(|b| &b.text), &m.text, &t.text, mine_wins),
+                is_checked: pick_lww(
+                    b.map(|b| &b.is_checked),
+                    &m.is_checked,
+                    &t.is_checked,
+                    mine_wins,
+                ),
+                extra: merge_extra(b.map(|b| &b.extra), &m.extra, &t.extra, mine_wins),
+                ..(*m).clone()
+            },
+            // Nur auf einer Seite: neu hinzugefügt — oder auf der anderen gelöscht
+            (Some(only), None) | (None, Some(only)) if b.is_none() => (*only).clone(),
+            _ => continue,
+        };
+        merged.insert(id, item);
+    }
+
+    let base_order = ordered(base);
+    let mine_order = ordered(mine);
+    let theirs_order = ordered(theirs);
+    let (primary, secondary) = match (
+        reordered(&base_order, &mine_order),
+        reordered(&base_order, &theirs_order),
+    ) {
+        (true, false) => (mine_order, theirs_order),
+        (true, true) if mine_wins => (mine_order, theirs_order),
+        _ => (theirs_order, mine_order),
+    };
+    let mut sequence: Vec<&str> = primary
+        .iter()
+        .map(|i| i.id.as_str())
+        .filter(|id| merged.contains_key(id))
+        .collect();
+    let mut anchor: Option<usize> = None;
+    for item in secondary {
+        let id = item.id.as_str();
+        if !merged.contains_key(id) {
+            continue;
+        }
+        anchor = Some(match sequence.iter().position(|s| *s == id) {
+            Some(pos) => pos,
+            None => {
+                let pos = anchor.map_or(0, |a| a + 1);
+                sequence.insert(pos, id);
+                pos
+            }
+        });
+    }
+
+    let uses_original_order = merged.values().any(|i| i.original_order.is_some());
+    sequence
+        .iter()
+        .enumerate()
+        .map(|(index, id)| {
+            let mut item = merged.remove(id).expect("Item aus merged");
+            item.order = index as i32;
+            if uses_original_order {
+                item.original_order = Some(index as i32);
+            }
+            item
+        })
+        .collect()
+}
+
+/// true, wenn der Text noch einen von [`merge_text`] gesetzten Konfliktblock enthält.
+pub fn has_conflict_markers(text: &str) -> bool {
+    let mut lines = text.lines();
+    lines.any(|l| l == MARKER_MINE.trim_end()) && lines.any(|l| l == MARKER_SERVER.trim_end())
+}
+
+/// Drei-Wege-Merge einer Notiz: `local` (Pending) und `server` (neuer) gegen `base`.
+pub fn merge_notes(base: &Note, local: &Note, server: &Note) -> NoteMerge {
+    let mut conflicts = 0;
+    let mut note = local.clone();
+
+    let title = merge_text(&base.title, &local.title, &server.title);
+    if title.conflicts == 0 {
+        note.title = title.text;
+    } else {
+        conflicts += 1;
+    }
+
+    let c = &mut conflicts;
+    note.note_type = pick(&base.note_type, &local.note_type, &server.note_type, c);
+    if let (Some(mine), Some(theirs)) = (&local.checklist_items, &server.checklist_items) {
+        let base_items = base.checklist_items.as_deref().unwrap_or_default();
+        let mine_wins = hlc::compare(local, server).is_gt();
+        // Inhalt ist bei Checklisten nur der Fallback-Text (Android, ältere Clients) → immer aus
+        // den zusammengeführten Items neu erzeugen, damit er zu `checklist_items` passt
+        note.checklist_items = Some(merge_checklist(base_items, mine, theirs, mine_wins));
+        note.content = note.generate_checklist_fallback();
+    } else {
+        let content = merge_text(&base.content, &local.content, &server.content);
+        *c += content.conflicts;
+        note.content = content.text;
+        note.checklist_items = pick(
+            &base.checklist_items,
+            &local.checklist_items,
+            &server.checklist_items,
+            c,
+        );
+    }
+    note.checklist_sort_option = pick(
+        &base.checklist_sort_option,
+        &local.checklist_sort_option,
+        &server.checklist_sort_option,
+        c,
+    );
+    note.color = pick(&base.color, &local.color, &server.color, c);
+    note.labels = pick(&base.labels, &local.labels, &server.labels, c);
+    note.is_pinned = pick(&base.is_pinned, &local.is_pinned, &server.is_pinned, c);
+    note.folder_name = pick(
+        &base.folder_name,
+        &local.folder_name,
+        &server.folder_name,
+        c,
+    );
+    note.trashed_at = pick(&base.trashed_at, &local.trashed_at, &server.trashed_at, c);
+    // Der HLC-Stempel ist keine Änderung; der Aufrufer stempelt das Ergebnis neu
+    let unstamped = |n: &Note| {
+        let mut extra = n.extra.clone();
+        extra.remove(HLC_KEY);
+        extra
+    };
+    note.extra = pick(&unstamped(base), &unstamped(local), &unstamped(server), c);
+    NoteMerge { note, conflicts }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SyncStatus;
+
+    #[test]
+    fn test_merge_text_non_overlapping_edits() {
+        let base = "Milch\nBrot\nEier\nKäse\n";
+        let mine = "Milch (laktosefrei)\nBrot\nEier\nKäse\n";
+        let theirs = "Milch\nBrot\nEier\nKäse\nÄpfel\n";
+        let merged = merge_text(base, mine, theirs);
+        assert_eq!(merged.conflicts, 0);
+        assert_eq!(
+            merged.text,
+            "Milch (laktosefrei)\nBrot\nEier\nKäse\nÄpfel\n"
+        );
+    }
+
+    #[test]
+    fn test_merge_text_one_side_or_same_change() {
+        assert_eq!(merge_text("a\n", "a\n", "b\n").text, "b\n");
+        assert_eq!(merge_text("a\n", "b\n", "a\n").text, "b\n");
+        let same = merge_text("a\nb\n", "a\nc\n", "a\nc\n");
+        assert_eq!(same.text, "a\nc\n");
+        assert_eq!(same.conflicts, 0);
+    }
+
+    #[test]
+    fn test_merge_text_deletion_and_insert_elsewhere() {
+        let merged = merge_text("1\n2\n3\n4\n", "1\n3\n4\n", "1\n2\n3\n4\n5\n");
+        assert_eq!(merged.conflicts, 0);
+        assert_eq!(merged.text, "1\n3\n4\n5\n");
+    }
+
+    #[test]
+    fn test_has_conflict_markers() {
+        let merged = merge_text("a\nb\n", "a\nmine\n", "a\ntheirs\n");
+        assert!(has_conflict_markers(&merged.text));
+        assert!(!has_conflict_markers("a\nb\n"));
+        // Nur vollständige Blöcke zählen, Reihenfolge beachten
+        assert!(!has_conflict_markers("<<<<<<< Mine\nx\n"));
+        assert!(!has_conflict_markers(">>>>>>> Server\n<<<<<<< Mine\n"));
+    }
+
+    #[test]
+    fn test_merge_text_overlap_gets_markers() {
+        let merged = merge_text("a\nb\nc\n", "a\nmine\nc\n", "a\ntheirs\nc\n");
+        assert_eq!(merged.conflicts, 1);
+        assert_eq!(
+            merged.text,
+            "a\n<<<<<<< Mine\nmine\n=======\ntheirs\n>>>>>>> Server\nc\n"
+        );
+    }
+
+    #[test]
+    fn test_merge_text_without_trailing_newline() {
+        let merged = merge_text("x", "mine", "theirs");
+        assert_eq!(merged.conflicts, 1);
+        assert_eq!(
+            merged.text,
+            "<<<<<<< Mine\nmine\n=======\ntheirs\n>>>>>>> Server\n"
+        );
+        let merged = merge_text("a\nm\nb", "a2\nm\nb", "a\nm\nb2");
+        assert_eq!(merged.conflicts, 0);
+        assert_eq!(merged.text, "a2\nm\nb2");
+    }
+
+    #[test]
+    fn test_merge_notes_fields() {
+        let mut base = Note::new("Einkauf".to_string(), "dev".to_string());
+        base.content = "Milch\nBrot\n".to_string();
+        let mut local = base.clone();
+        local.content = "Milch\nBrot\nButter\n".to_string();
+        local.color = Some("#ff0000".to_string());
+        local.sync_status = SyncStatus::Pending;
+        let mut server = base.clone();
+        server.title = "Wocheneinkauf".to_string();
+        server.content = "Hafermilch\nBrot\n".to_string();
+        server.is_pinned = Some(true);
+
+        let merged = merge_notes(&base, &local, &server);
+
+        assert!(merged.is_clean());
+        assert_eq!(merged.note.title, "Wocheneinkauf");
+        assert_eq!(merged.note.content, "Hafermilch\nBrot\nButter\n");
+        assert_eq!(merged.note.color.as_deref(), Some("#ff0000"));
+        assert_eq!(merged.note.is_pinned, Some(true));
+        assert_eq!(merged.note.sync_status, SyncStatus::Pending);
+    }
+
+    fn item(id: &str, text: &str, checked: bool, order: i32) -> ChecklistItem {
+        ChecklistItem {
+            id: id.to_string(),
+            text: text.to_string(),
+            is_checked: checked,
+            order,
+            original_order: Some(order),
+            extra: serde_json::Map::new(),
+        }
+    }
+
+    fn texts(items: &[ChecklistItem]) -> Vec<(&str, bool, i32)> {
+        items
+            .iter()
+            .map(|i| (i.text.as_str(), i.is_checked, i.order))
+            .collect()
+    }
+
+    #[test]
+    fn test_merge_checklist_adds_deletes_and_edits() {
+        let base = vec![
+            item("a", "Milch", false, 0),
+            item("b", "Brot", false, 1),
+            item("c", "Eier", false, 2),
+        ];
+        // Lokal: Brot abgehakt, Eier gelöscht, Butter ergänzt
+        let mut mine = vec![
+            item("a", "Milch", false, 0),
+            item("b", "Brot", true, 1),
+            item("d", "Butter", false, 2),
+        ];
+        mine[2]
+            .extra
+            .insert("createdAt".to_string(), serde_json::json!(7));
+        // Server: Milch umbenannt, Äpfel ergänzt
+        let mut theirs = vec![
+            item("a", "Hafermilch", false, 0),
+            item("b", "Brot", false, 1),
+            item("c", "Eier", false, 2),
+            item("e", "Äpfel", false, 3),
+        ];
+        theirs[0]
+            .extra
+            .insert("indentationLevel".to_string(), serde_json::json!(1));
+
+        let merged = merge_checklist(&base, &mine, &theirs, false);
+
+        assert_eq!(
+            texts(&merged),
+            vec![
+                ("Hafermilch", false, 0),
+                ("Brot", true, 1),
+                ("Butter", false, 2),
+                ("Äpfel", false, 3),
+            ]
+        );
+        assert_eq!(merged[0].extra["indentationLevel"], 1);
+        assert_eq!(merged[2].extra["createdAt"], 7);
+        assert_eq!(merged[3].original_order, Some(3));
+    }
+
+    #[test]
+    fn test_merge_checklist_same_item_edited_twice_newer_wins() {
+        let base = vec![item("a", "Milch", false, 0)];
+        let mine = vec![item("a", "Milch 1l", true, 0)];
+        let theirs = vec![item("a", "Milch 2l", false, 0)];
+
+        let merged = merge_checklist(&base, &mine, &theirs, true);
+        assert_eq!(texts(&merged), vec![("Milch 1l", true, 0)]);
+        // is_checked nur lokal geändert → bleibt auch, wenn der Server gewinnt
+        let merged = merge_checklist(&base, &mine, &theirs, false);
+        assert_eq!(texts(&merged), vec![("Milch 2l", true, 0)]);
+    }
+
+    #[test]
+    fn test_merge_checklist_keeps_reorder_and_inserts_after_predecessor() {
+        let base = vec![
+            item("a", "A", false, 0),
+            item("b", "B", false, 1),
+            item("c", "C", false, 2),
+        ];
+        // Server sortiert um, lokal wird hinter A ein Item eingefügt
+        let theirs = vec![
+            item("c", "C", false, 0),
+            item("a", "A", false, 1),
+            item("b", "B", false, 2),
+        ];
+        let mine = vec![
+            item("a", "A", false, 0),
+            item("x", "X", false, 1),
+            item("b", "B", false, 2),
+            item("c", "C", false, 3),
+        ];
+
+        let merged = merge_checklist(&base, &mine, &theirs, true);
+        let order: Vec<&str> = merged.iter().map(|i| i.id.as_str()).collect();
+        assert_eq!(order, vec!["c", "a", "x", "b"]);
+        // Deterministisch unabhängig von der Seite
+        let swapped = merge_checklist(&base, &theirs, &mine, false);
+        let order: Vec<&str> = swapped.iter().map(|i| i.id.as_str()).collect();
+        assert_eq!(order, vec!["c", "a", "x", "b"]);
+    }
+
+    #[test]
+    fn test_merge_notes_checklist_is_clean_and_regenerates_fallback() {
+        let mut base = Note::new_checklist("Einkauf".to_string(), "dev".to_string());
+        base.checklist_items = Some(vec![item("a", "Milch", false, 0)]);
+        base.content = base.generate_checklist_fallback();
+        let mut local = base.clone();
+        local.checklist_items = Some(vec![item("a", "Milch", true, 0)]);
+        local.content = local.generate_checklist_fallback();
+        let mut server = base.clone();
+        server.checklist_items = Some(vec![
+            item("a", "Milch", false, 0),
+            item("b", "Brot", false, 1),
+        ]);
+        server.content = server.generate_checklist_fallback();
+        server.updated_at += 1_000;
+
+        let merged = merge_notes(&base, &local, &server);
+
+        assert!(merged.is_clean());
+        assert_eq!(merged.note.content, "[x] Milch\n[ ] Brot");
+        assert_eq!(merged.note.checklist_items.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_merge_notes_checklist_content_follows_items_edited_only_locally() {
+        let mut base = Note::new_checklist("Einkauf".to_string(), "dev".to_string());
+        base.checklist_items = Some(vec![item("a", "Milch", false, 0)]);
+        base.content = base.generate_checklist_fallback();
+        // Lokal nur Items geändert, `content` (Fallback) nicht mitgezogen
+        let mut local = base.clone();
+        local.checklist_items = Some(vec![item("a", "Hafermilch", true, 0)]);
+        let mut server = base.clone();
+        server.checklist_items = Some(vec![
+            item("a", "Milch", false, 0),
+            item("b", "Brot", false, 1),
+        ]);
+        server.content = server.generate_checklist_fallback();
+        server.updated_at += 1_000;
+
+        let merged = merge_notes(&base, &local, &server);
+
+        assert!(merged.is_clean());
+        assert_eq!(merged.note.content, "[x] Hafermilch\n[ ] Brot");
+    }
+
+    #[test]
+    fn test_merge_notes_title_conflict_keeps_local_title() {
+        let base = Note::new("A".to_string(), "dev".to_string());
+        let mut local = base.clone();
+        local.title = "B".to_string();
+        local.color = Some("#111111".to_string());
+        let mut server = base.clone();
+        server.title = "C".to_string();
+        server.color = Some("#222222".to_string());
+
+        let merged = merge_notes(&base, &local, &server);
+
+        assert_eq!(merged.conflicts, 2);
+        assert_eq!(merged.note.title, "B");
+        assert_eq!(merged.note.color.as_deref(), Some("#111111"));
+    }
+}
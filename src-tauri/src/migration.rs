@@ -0,0 +1,424 @@
+//! Umzug auf ein anderes Speicherziel (anderer Server, Sync-Ordner oder Sync-Verzeichnis).
+//!
+//! Kopiert alle Notizen, `folders.json` und `deletions.json` vom bisherigen zum neuen Ziel —
+//! gibt es kein bisheriges Ziel (bisher offline), den lokalen Stand. Danach wird geprüft, ob
+//! am neuen Ziel alles angekommen ist; erst dann wird der lokale Sync-Stand (ETags, Status)
+//! auf das neue Ziel umgestellt. Das Umschalten der Settings übernimmt der Aufrufer.
+//!
+//! Schlägt ein Schritt fehl, bleibt der lokale Stand unverändert. Bereits kopierte Dateien
+//! am neuen Ziel stören nicht: ein erneuter Versuch überspringt vorhandene Notizen.
+
+use std::collections::{HashMap, HashSet};
+
+use futures_util::stream::{self, StreamExt};
+use serde::Serialize;
+use tauri::{AppHandle, Runtime};
+
+use crate::error::{AppError, Result};
+use crate::folders::{merge_by_name, FolderMeta};
+use crate::hlc::{Hlc, Stamped};
+use crate::local_dir::LocalDirBackend;
+use crate::local_store;
+use crate::models::{DeletionLedger, Note, SyncStatus};
+use crate::sync_backend::{SyncBackend, SyncTarget};
+use crate::sync_engine::{self, RemoteNoteState};
+use crate::webdav::WriteCondition;
+
+/// Ergebnis eines Umzugs (für die Rückmeldung im Settings-Dialog).
+#[derive(Debug, Default, Serialize)]
+pub struct MigrationReport {
+    /// Ans neue Ziel geschriebene Notizen
+    pub notes_copied: usize,
+    /// Notizen, die am neuen Ziel schon lagen (nicht überschrieben)
+    pub notes_existing: usize,
+    /// Übernommene Ordner
+    pub folders: usize,
+    /// Übernommene Einträge im Lösch-Ledger
+    pub deletions: usize,
+}
+
+/// Alles, was umzieht.
+struct Source {
+    notes: Vec<Note>,
+    folders: Vec<String>,
+    folders_meta: Vec<FolderMeta>,
+    ledger: DeletionLedger,
+}
+
+/// Stand des bisherigen Ziels. Jede nicht ladbare Notiz bricht ab — sonst ginge sie beim
+/// Umschalten verloren.
+async fn read_source<S: SyncBackend>(from: &S) -> Result<Source> {
+    let listing = from.list_notes().await?;
+    let mut fetched = stream::iter(listing)
+        .map(|entry| async move { from.get_note(&entry.id, entry.folder.as_deref()).await })
+        .buffered(from.concurrency());
+    let mut notes = Vec::new();
+    while let Some(result) = fetched.next().await {
+        notes.push(result?.0);
+    }
+    Ok(Source {
+        notes,
+        folders: from.list_folders().await,
+        folders_meta: from.read_folders_meta().await,
+        ledger: from.read_deletions().await,
+    })
+}
+
+/// Lokaler Stand, wenn es kein bisheriges Ziel gibt: was ein Sync hochladen würde.
+fn read_local<R: Runtime>(app: &AppHandle<R>) -> Source {
+    let notes = local_store::list_notes(app)
+        .into_iter()
+        .filter(|n| {
+            !matches!(
+                n.sync_status,
+                SyncStatus::LocalOnly | SyncStatus::DeletedOnServer
+            )
+        })
+        .collect();
+    let folders_meta: Vec<FolderMeta> = local_store::active_folders(app)
+        .into_iter()
+        .filter(|f| !f.local_only)
+        .collect();
+    Source {
+        notes,
+        folders: folders_meta.iter().map(|f| f.name.clone()).collect(),
+        folders_meta,
+        ledger: DeletionLedger::default(),
+    }
+}
+
+/// Umzug von `from` (`None` = lokaler Stand) nach `to`.
+pub async fn migrate<R: Runtime, S: SyncBackend, T: SyncBackend>(
+    app: &AppHandle<R>,
+    from: Option<&S>,
+    to: &T,
+    retention_ms: i64,
+) -> Result<MigrationReport> {
+    let source = match from {
+        Some(from) => read_source(from).await?,
+        None => read_local(app),
+    };
+    let mut report = MigrationReport::default();
+
+    // 1. Notizen: vorhandene am Ziel bleiben unangetastet (z.B. von einem anderen Gerät
+    //    schon umgezogen) — der nächste Sync gleicht sie über `updated_at` ab
+    let existing: HashSet<String> = to.list_notes().await?.into_iter().map(|l| l.id).collect();
+    let (present, to_copy): (Vec<&Note>, Vec<&Note>) =
+        source.notes.iter().partition(|n| existing.contains(&n.id));
+    report.notes_existing = present.len();
+    let mut copied: HashMap<String, RemoteNoteState> = HashMap::new();
+    let mut writes = stream::iter(to_copy)
+        .map(|note| async move { (note, to.put_note(note, WriteCondition::IfNoneMatch).await) })
+        .buffered(to.concurrency());
+    while let Some((note, result)) = writes.next().await {
+        match result {
+            Ok(etag) => {
+                copied.insert(
+                    note.id.clone(),
+                    RemoteNoteState {
+                        folder: note.folder_name.clone(),
+                        etag,
+                        last_modified: None,
+                    },
+                );
+            }
+            // Zwischen Listing und PUT von einem anderen Gerät angelegt
+            Err(AppError::Conflict(_)) => report.notes_existing += 1,
+            Err(e) => return Err(e),
+        }
+    }
+    report.notes_copied = copied.len();
+
+    // 2. Ordner samt Metadaten
+    let mut folders: Vec<String> = source.folders.clone();
+    for meta in source.folders_meta.iter().filter(|f| !f.deleted) {
+        if !folders.iter().any(|f| f.eq_ignore_ascii_case(&meta.name)) {
+            folders.push(meta.name.clone());
+        }
+    }
+    for name in &folders {
+        to.ensure_folder(name).await;
+    }
+    if !source.folders_meta.is_empty() {
+        let meta = source.folders_meta.clone();
+        to.write_folders_meta_merged(move |current| merge_by_name(current, meta.clone()))
+            .await?;
+    }
+    report.folders = folders.len();
+
+    // 3. Lösch-Ledger mit ursprünglichem Gerät, Zeitpunkt und HLC-Stempel
+    let mut groups: HashMap<(&str, i64, Option<Hlc>), Vec<String>> = HashMap::new();
+    for record in &source.ledger.deleted_notes {
+        groups
+            .entry((record.device_id.as_str(), record.deleted_at, record.hlc()))
+            .or_default()
+            .push(record.id.clone());
+    }
+    for ((device_id, deleted_at, stamp), ids) in &groups {
+        to.append_deletions(ids, device_id, *deleted_at, stamp.as_ref(), retention_ms)
+            .await;
+    }
+    report.deletions = source.ledger.deleted_notes.len();
+
+    // 4. Prüfen, bevor irgendetwas lokal umgestellt wird
+    verify(&source, to).await?;
+
+    // 5. Lokalen Sync-Stand auf das neue Ziel umstellen
+    reset_local_state(app, &source, copied, from.is_none());
+    Ok(report)
+}
+
+/// Alle Notizen und Ledger-Einträge der Quelle müssen am Ziel angekommen sein.
+async fn verify<T: SyncBackend>(source: &Source, to: &T) -> Result<()> {
+    let listed: HashSet<String> = to.list_notes().await?.into_iter().map(|l| l.id).collect();
+    let missing_notes = source
+        .notes
+        .iter()
+        .filter(|n| !listed.contains(&n.id))
+        .count();
+    if missing_notes > 0 {
+        return Err(AppError::Migration(format!(
+            "{} von {} Notizen fehlen am neuen Speicherort",
+            missing_notes,
+            source.notes.len()
+        )));
+    }
+    let ledger: HashSet<String> = to
+        .read_deletions()
+        .await
+        .deleted_notes
+        .into_iter()
+        .map(|r| r.id)
+        .collect();
+    let missing_deletions = source
+        .ledger
+        .deleted_notes
+        .iter()
+        .filter(|r| !ledger.contains(&r.id))
+        .count();
+    if missing_deletions > 0 {
+        return Err(AppError::Migration(format!(
+            "{} Lösch-Einträge fehlen am neuen Speicherort",
+            missing_deletions
+        )));
+    }
+    Ok(())
+}
+
+/// Server-Stand (ETags) gilt nur noch für die kopierten Notizen; am Ziel schon vorhandene
+/// werden beim nächsten Sync neu geladen. Als SYNCED markierte Notizen, die an der Quelle
+/// fehlten, werden hochgeladen statt als "am Server gelöscht" zu gelten.
+fn reset_local_state<R: Runtime>(
+    app: &AppHandle<R>,
+    source: &Source,
+    copied: HashMap<String, RemoteNoteState>,
+    from_local: bool,
+) {
+    let migrated: HashSet<&str> = source.notes.iter().map(|n| n.id.as_str()).collect();
+    for note in local_store::list_notes(app) {
+        if from_local && copied.contains_key(&note.id) {
+            local_store::mark_synced_if_unchanged(app, &note.id, note.updated_at);
+        } else if note.sync_status == SyncStatus::Synced && !migrated.contains(note.id.as_str()) {
+            let mut note = note;
+            note.sync_status = SyncStatus::Pending;
+            local_store::put_note(app, &note);
+        }
+    }
+    sync_engine::save_remote_states(app, &copied);
+}
+
+/// [`migrate`] für die Speicherziele aus den Settings. Ein Git-Ziel wird danach committet
+/// und gepusht, eine Git-Quelle vorher aktualisiert.
+pub async fn migrate_target<R: Runtime>(
+    app: &AppHandle<R>,
+    from: Option<&SyncTarget>,
+    to: &SyncTarget,
+    device_id: &str,
+    retention_ms: i64,
+) -> Result<MigrationReport> {
+    match to {
+        SyncTarget::WebDav(client) => {
+            migrate_from(app, from, client, device_id, retention_ms).await
+        }
+        SyncTarget::Directory(dir) => migrate_from(app, from, dir, device_id, retention_ms).await,
+        SyncTarget::S3(client) => migrate_from(app, from, client, device_id, retention_ms).await,
+        SyncTarget::Git(repo) => {
+            let report = migrate_from(app, from, repo.dir(), device_id, retention_ms).await?;
+            repo.commit_and_push(device_id).await?;
+            Ok(report)
+        }
+    }
+}
+
+async fn migrate_from<R: Runtime, T: SyncBackend>(
+    app: &AppHandle<R>,
+    from: Option<&SyncTarget>,
+    to: &T,
+    device_id: &str,
+    retention_ms: i64,
+) -> Result<MigrationReport> {
+    match from {
+        None => migrate(app, None::<&LocalDirBackend>, to, retention_ms).await,
+        Some(SyncTarget::WebDav(client)) => migrate(app, Some(client), to, retention_ms).await,
+        Some(SyncTarget::Directory(dir)) => migrate(app, Some(dir), to, retention_ms).await,
+        Some(SyncTarget::S3(client)) => migrate(app, Some(client), to, retention_ms).await,
+        Some(SyncTarget::Git(repo)) => {
+            if let Err(e) = repo.pull(device_id).await {
+                eprintln!("[migration] Git-Pull fehlgeschlagen: {}", e);
+            }
+            migrate(app, Some(repo.dir()), to, retention_ms).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory_backend::{MemoryBackend, TestApp};
+    use crate::TRASH_RETENTION_MS;
+
+    fn note(title: &str, folder: Option<&str>, status: SyncStatus) -> Note {
+        let mut note = Note::new(title.to_string(), "tauri-test".to_string());
+        note.folder_name = folder.map(str::to_owned);
+        note.sync_status = status;
+        note
+    }
+
+    fn folder_meta(name: &str) -> FolderMeta {
+        FolderMeta {
+            name: name.to_string(),
+            color: Some("#ff0000".to_string()),
+            updated_at: 1,
+            deleted: false,
+            local_only: false,
+            ..FolderMeta::default()
+        }
+    }
+
+    /// Server mit zwei Notizen (eine im Ordner), Ordner-Metadaten und Ledger; lokal
+    /// synchronisiert.
+    async fn synced_source(app: &TestApp) -> (MemoryBackend, Note, Note) {
+        let old = MemoryBackend::new();
+        let root = note("Einkauf", None, SyncStatus::Synced);
+        let work = note("Protokoll", Some("Arbeit"), SyncStatus::Synced);
+        for n in [&root, &work] {
+            old.put_server_note(n);
+            local_store::put_note(app.handle(), n);
+        }
+        old.ensure_folder("Arbeit").await;
+        old.write_folders_meta_merged(|_| vec![folder_meta("Arbeit")])
+            .await
+            .unwrap();
+        old.append_deletions(&["gone".to_string()], "device-b", 42, None, i64::MAX)
+            .await;
+        (old, root, work)
+    }
+
+    #[tokio::test]
+    async fn test_migrate_copies_notes_folders_and_ledger() {
+        let app = TestApp::new();
+        let (old, root, work) = synced_source(&app).await;
+        let new = MemoryBackend::new();
+
+        let report = migrate(app.handle(), Some(&old), &new, TRASH_RETENTION_MS)
+            .await
+            .unwrap();
+
+        assert_eq!(report.notes_copied, 2);
+        assert_eq!(report.notes_existing, 0);
+        assert_eq!(report.deletions, 1);
+        assert_eq!(
+            new.server_folder(&work.id),
+            Some(Some("Arbeit".to_string()))
+        );
+        assert_eq!(new.server_note(&root.id).unwrap().title, "Einkauf");
+        assert_eq!(new.folders(), vec!["Arbeit"]);
+        assert_eq!(new.folders_meta()[0].color.as_deref(), Some("#ff0000"));
+        let ledger = new.read_deletions().await;
+        assert_eq!(ledger.deleted_notes[0].device_id, "device-b");
+        assert_eq!(ledger.deleted_notes[0].deleted_at, 42);
+
+        // ETags zeigen aufs neue Ziel → der nächste Sync lädt nichts neu und löscht nichts
+        let states = sync_engine::load_remote_states(app.handle());
+        assert_eq!(states.len(), 2);
+        let summary =
+            sync_engine::run_sync(&new, app.handle(), "device-a", TRASH_RETENTION_MS).await;
+        assert_eq!(summary.notes_downloaded, 0);
+        assert_eq!(summary.notes_deleted_on_server, 0);
+        assert_eq!(summary.notes_uploaded, 0);
+    }
+
+    #[tokio::test]
+    async fn test_migrate_uploads_local_state_to_empty_target() {
+        let app = TestApp::new();
+        let pending = note("Entwurf", None, SyncStatus::Pending);
+        let private = note("Tagebuch", Some("Privat"), SyncStatus::LocalOnly);
+        local_store::put_note(app.handle(), &pending);
+        local_store::put_note(app.handle(), &private);
+        let new = MemoryBackend::new();
+
+        let report = migrate(
+            app.handle(),
+            None::<&MemoryBackend>,
+            &new,
+            TRASH_RETENTION_MS,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(report.notes_copied, 1);
+        assert!(new.server_note(&private.id).is_none());
+        let stored = local_store::get_note(app.handle(), &pending.id).unwrap();
+        assert_eq!(stored.sync_status, SyncStatus::Synced);
+        let stored = local_store::get_note(app.handle(), &private.id).unwrap();
+        assert_eq!(stored.sync_status, SyncStatus::LocalOnly);
+    }
+
+    #[tokio::test]
+    async fn test_migrate_keeps_existing_notes_and_reuploads_orphans() {
+        let app = TestApp::new();
+        let (old, root, _) = synced_source(&app).await;
+        // Am neuen Ziel liegt bereits eine (neuere) Fassung von einem anderen Gerät
+        let new = MemoryBackend::new();
+        let mut newer = root.clone();
+        newer.content = "vom anderen Gerät".to_string();
+        newer.updated_at += 1_000;
+        new.put_server_note(&newer);
+        // Lokal "synchronisiert", an der Quelle aber nicht (mehr) vorhanden
+        let orphan = note("Verwaist", None, SyncStatus::Synced);
+        local_store::put_note(app.handle(), &orphan);
+
+        let report = migrate(app.handle(), Some(&old), &new, TRASH_RETENTION_MS)
+            .await
+            .unwrap();
+
+        assert_eq!(report.notes_copied, 1);
+        assert_eq!(report.notes_existing, 1);
+        assert_eq!(
+            new.server_note(&root.id).unwrap().content,
+            "vom anderen Gerät"
+        );
+        let stored = local_store::get_note(app.handle(), &orphan.id).unwrap();
+        assert_eq!(stored.sync_status, SyncStatus::Pending);
+
+        let summary =
+            sync_engine::run_sync(&new, app.handle(), "device-a", TRASH_RETENTION_MS).await;
+        assert_eq!(summary.notes_downloaded, 1);
+        assert_eq!(summary.notes_uploaded, 1);
+        assert!(new.server_note(&orphan.id).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_failed_copy_leaves_local_state_untouched() {
+        let app = TestApp::new();
+        let (old, _, _) = synced_source(&app).await;
+        let before = sync_engine::load_remote_states(app.handle());
+        let new = MemoryBackend::new();
+        new.fail_puts(true);
+
+        let result = migrate(app.handle(), Some(&old), &new, TRASH_RETENTION_MS).await;
+
+        assert!(result.is_err());
+        assert_eq!(sync_engine::load_remote_states(app.handle()), before);
+    }
+}
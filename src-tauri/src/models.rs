@@ -30,7 +30,7 @@ pub enum NoteType {
 }
 
 /// Ein Item in einer Checkliste
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ChecklistItem {
     /// UUID v4 für das Item
@@ -183,6 +183,57 @@ impl Note {
             self.checklist_items = Some(Vec::new());
         }
     }
+
+    /// Übernimmt Titel und Inhalt (Text bzw. Checkliste) einer älteren Fassung. Ordner, Farbe,
+    /// Pin, Papierkorb-Status und unbekannte Felder bleiben vom aktuellen Stand.
+    pub fn restore_content_from(&mut self, version: &Note) {
+        self.title = version.title.clone();
+        self.content = version.content.clone();
+        self.note_type = version.note_type;
+        self.checklist_items = version.checklist_items.clone();
+        self.checklist_sort_option = version.checklist_sort_option.clone();
+    }
+
+    /// Konfliktkopie für "Beide behalten": neue ID, Titel mit Gerät und Zeitpunkt ergänzt;
+    /// Inhalt, Ordner, Labels und Farbe bleiben.
+    pub fn fork_conflict_copy(&self, device_id: &str, now: i64) -> Note {
+        let when = chrono::DateTime::from_timestamp_millis(now)
+            .map(|t| {
+                t.with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            })
+            .unwrap_or_default();
+        let mut copy = self.clone();
+        copy.id = Uuid::new_v4().to_string();
+        copy.title = format!("{} ({}, {})", self.title, device_id, when);
+        copy.created_at = now;
+        copy.updated_at = now;
+        copy.device_id = device_id.to_string();
+        copy.trashed_at = None;
+        copy
+    }
+
+    /// Erzeugt den Checklist-Fallback-String `[ ] text` / `[x] text` (Inhalt von Checklisten
+    /// für Apps ohne Checklisten-Support, auch nach dem Item-Merge)
+    pub fn generate_checklist_fallback(&self) -> String {
+        let Some(items) = &self.checklist_items else {
+            return String::new();
+        };
+        let mut sorted = items.clone();
+        sorted.sort_by_key(|i| i.order);
+        sorted
+            .iter()
+            .map(|i| {
+                if i.is_checked {
+                    format!("[x] {}", i.text)
+                } else {
+                    format!("[ ] {}", i.text)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
 }
 
 /// Metadaten für die Notizen-Liste (mit Preview)
@@ -240,14 +291,30 @@ impl From<&Note> for NoteMetadata {
     }
 }
 
+/// Die Fassungen einer Notiz im Konflikt — für die Gegenüberstellung vor der Auflösung.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConflictVersions {
+    /// Fassung auf diesem Gerät (vor einem Merge mit Konfliktmarkern, falls es einen gab)
+    pub local: Note,
+    /// Fassung am Server bzw. im Sync-Verzeichnis; `None` ohne Verbindung
+    pub server: Option<Note>,
+    /// Zuletzt synchronisierte gemeinsame Fassung, falls bekannt
+    pub base: Option<Note>,
+    /// Sync hat den Text zusammengeführt und überlappende Stellen mit Konfliktmarkern versehen
+    pub text_conflict: bool,
+}
+
 /// Ein Eintrag im gemeinsamen Lösch-Ledger (`deletions.json`).
 /// camelCase entspricht exakt dem Android-`DeletionTracker`-Format (cross-app contract).
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DeletionRecord {
     pub id: String,
     pub deleted_at: i64,
     pub device_id: String,
+    /// Weitere Felder (u.a. der HLC-Stempel `hlc`), 1:1 erhalten
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 /// Gemeinsames Lösch-Ledger für permanente Notiz-Löschungen (`deletions.json`).
@@ -285,26 +352,6 @@ impl Note {
     pub fn touch(&mut self) {
         self.updated_at = chrono::Utc::now().timestamp_millis();
     }
-
-    /// Erzeugt den Checklist-Fallback-String `[ ] text` / `[x] text` (nur für Tests)
-    pub fn generate_checklist_fallback(&self) -> String {
-        let Some(items) = &self.checklist_items else {
-            return String::new();
-        };
-        let mut sorted = items.clone();
-        sorted.sort_by_key(|i| i.order);
-        sorted
-            .iter()
-            .map(|i| {
-                if i.is_checked {
-                    format!("[x] {}", i.text)
-                } else {
-                    format!("[ ] {}", i.text)
-                }
-            })
-            .collect::<Vec<_>>()
-            .join("\n")
-    }
 }
 
 #[cfg(test)]
@@ -353,6 +400,45 @@ mod tests {
         assert!(note.updated_at > original);
     }
 
+    #[test]
+    fn test_restore_content_from_keeps_metadata() {
+        let mut current = Note::new("Neu".to_string(), "tauri-abc".to_string());
+        current.content = "neuer Text".to_string();
+        current.folder_name = Some("Work".to_string());
+        current.is_pinned = Some(true);
+        let mut old = Note::new_checklist("Alt".to_string(), "tauri-old".to_string());
+        old.id = current.id.clone();
+        old.checklist_items = Some(vec![ChecklistItem::new("Milch".to_string(), 0)]);
+
+        current.restore_content_from(&old);
+
+        assert_eq!(current.title, "Alt");
+        assert_eq!(current.note_type, NoteType::Checklist);
+        assert_eq!(current.checklist_items.as_ref().map(Vec::len), Some(1));
+        assert_eq!(current.folder_name.as_deref(), Some("Work"));
+        assert_eq!(current.is_pinned, Some(true));
+        assert_eq!(current.device_id, "tauri-abc");
+    }
+
+    #[test]
+    fn test_fork_conflict_copy() {
+        let mut note = Note::new("Einkauf".to_string(), "tauri-a".to_string());
+        note.folder_name = Some("Haushalt".to_string());
+        note.labels = Some(vec!["wichtig".to_string()]);
+        note.color = Some("#ffcc00".to_string());
+        note.sync_status = SyncStatus::Conflict;
+
+        let copy = note.fork_conflict_copy("tauri-b", 1_750_000_000_000);
+
+        assert_ne!(copy.id, note.id);
+        assert!(copy.title.starts_with("Einkauf (tauri-b, 2025-06-"));
+        assert_eq!(copy.folder_name, note.folder_name);
+        assert_eq!(copy.labels, note.labels);
+        assert_eq!(copy.color, note.color);
+        assert_eq!(copy.created_at, 1_750_000_000_000);
+        assert_eq!(copy.device_id, "tauri-b");
+    }
+
     #[test]
     fn test_generate_checklist_fallback() {
         let mut note = Note::new_checklist("List".to_string(), "tauri-abc".to_string());
@@ -0,0 +1,489 @@
+//! Parser für WebDAV-Multistatus-Antworten (PROPFIND, sync-collection REPORT).
+//!
+//! Namespace-aware: Elemente werden über den aufgelösten `DAV:`-Namespace erkannt,
+//! nicht über das Präfix — `d:`, `D:`, `lp1:` oder ein Default-xmlns funktionieren gleich.
+//! Aus dem ownCloud/Nextcloud-Namespace wird nur `oc:fileid` gelesen (Versions-Endpunkt).
+//! Ebenso gelesen: das Lock-Token aus dem Body einer LOCK-Antwort ([`parse_lock_token`]).
+
+use crate::error::{AppError, Result};
+use quick_xml::events::Event;
+use quick_xml::name::{Namespace, ResolveResult};
+use quick_xml::NsReader;
+
+const DAV_NS: &str = "DAV:";
+const OC_NS: &str = "http://owncloud.org/ns";
+
+/// Props eines `<propstat>`-Blocks samt dessen Status.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PropStat {
+    /// HTTP-Status des Blocks (200 = Props gefunden, 404 = Props fehlen)
+    pub status: Option<u16>,
+    /// `<resourcetype><collection/></resourcetype>`
+    pub collection: bool,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub content_length: Option<u64>,
+    /// `quota-used-bytes` (RFC 4331)
+    pub quota_used: Option<i64>,
+    /// `quota-available-bytes` (RFC 4331); Nextcloud meldet negative Werte für „unbegrenzt“/„unbekannt“
+    pub quota_available: Option<i64>,
+    /// `oc:fileid` (Nextcloud/ownCloud): stabile ID der Datei, auch über MOVE hinweg
+    pub file_id: Option<String>,
+}
+
+impl PropStat {
+    /// Gilt als gefunden, wenn der Status 2xx ist oder fehlt (manche Server lassen ihn weg).
+    fn found(&self) -> bool {
+        self.status.is_none_or(|s| (200..300).contains(&s))
+    }
+}
+
+/// Ein `<response>`-Eintrag der Multistatus-Antwort.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DavEntry {
+    /// `href` wie geliefert (URL-kodiert, ggf. absolute URL)
+    pub href: String,
+    /// Status direkt am `<response>` (sync-collection: 404 = entfernt, 507 = gekürzt)
+    pub status: Option<u16>,
+    pub propstats: Vec<PropStat>,
+}
+
+impl DavEntry {
+    fn found(&self) -> impl Iterator<Item = &PropStat> {
+        self.propstats.iter().filter(|p| p.found())
+    }
+
+    /// Verzeichnis? `resourcetype` mit `collection`, ersatzweise Trailing-Slash im href.
+    pub fn is_collection(&self) -> bool {
+        self.found().any(|p| p.collection) || self.href.ends_with('/')
+    }
+
+    pub fn etag(&self) -> Option<&str> {
+        self.found().find_map(|p| p.etag.as_deref())
+    }
+
+    pub fn last_modified(&self) -> Option<&str> {
+        self.found().find_map(|p| p.last_modified.as_deref())
+    }
+
+    #[allow(dead_code)]
+    pub fn content_length(&self) -> Option<u64> {
+        self.found().find_map(|p| p.content_length)
+    }
+
+    pub fn quota_used(&self) -> Option<i64> {
+        self.found().find_map(|p| p.quota_used)
+    }
+
+    pub fn quota_available(&self) -> Option<i64> {
+        self.found().find_map(|p| p.quota_available)
+    }
+
+    pub fn file_id(&self) -> Option<&str> {
+        self.found().find_map(|p| p.file_id.as_deref())
+    }
+
+    /// URL-dekodierter Pfad (ohne Schema/Host, ohne Trailing-Slash).
+    pub fn path(&self) -> String {
+        decoded_path(&self.href)
+    }
+
+    /// Letztes Pfadsegment (URL-dekodiert), z.B. `{uuid}.json` oder ein Ordnername.
+    pub fn name(&self) -> String {
+        self.path()
+            .rsplit('/')
+            .next()
+            .unwrap_or_default()
+            .to_string()
+    }
+}
+
+/// Geparste Multistatus-Antwort.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Multistatus {
+    pub responses: Vec<DavEntry>,
+    /// `sync-token` auf Multistatus-Ebene (nur sync-collection REPORT)
+    pub sync_token: Option<String>,
+}
+
+/// Pfadanteil einer URL oder eines hrefs, URL-dekodiert und ohne Trailing-Slash.
+pub fn decoded_path(href: &str) -> String {
+    let path = match href.find("://") {
+        Some(i) => {
+            let rest = &href[i + 3..];
+            rest.find('/').map(|j| &rest[j..]).unwrap_or("")
+        }
+        None => href,
+    };
+    let path = path.trim_end_matches('/');
+    urlencoding::decode(path)
+        .map(|p| p.into_owned())
+        .unwrap_or_else(|_| path.to_string())
+}
+
+/// Welches DAV-Element wird gerade gelesen (Text wird dem jeweils offenen Feld zugeordnet).
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Href,
+    Status,
+    Etag,
+    LastModified,
+    ContentLength,
+    QuotaUsed,
+    QuotaAvailable,
+    SyncToken,
+    FileId,
+}
+
+/// Parst eine Multistatus-Antwort. Unbekannte Elemente und fremde Namespaces werden ignoriert.
+pub fn parse(text: &str) -> Result<Multistatus> {
+    let mut reader = NsReader::from_str(text);
+
+    let mut result = Multistatus::default();
+    let mut entry: Option<DavEntry> = None;
+    let mut propstat: Option<PropStat> = None;
+    let mut in_resourcetype = false;
+    let mut field: Option<Field> = None;
+    let mut buf = String::new();
+
+    loop {
+        let (ns, event) = reader
+            .read_resolved_event()
+            .map_err(|e| AppError::ParseError(format!("Multistatus: {}", e)))?;
+        let is_dav = matches!(ns, ResolveResult::Bound(Namespace(DAV_NS)));
+        let is_oc = matches!(ns, ResolveResult::Bound(Namespace(OC_NS)));
+        match event {
+            Event::Start(e) if is_oc && e.local_name().as_ref() == "fileid" => {
+                field = Some(Field::FileId);
+                buf.clear();
+            }
+            Event::Start(e) if is_dav => match e.local_name().as_ref() {
+                "response" => entry = Some(DavEntry::default()),
+                "propstat" => propstat = Some(PropStat::default()),
+                "resourcetype" => in_resourcetype = true,
+                "collection" if in_resourcetype => {
+                    if let Some(p) = propstat.as_mut() {
+                        p.collection = true;
+                    }
+                }
+                name => {
+                    field = match name {
+                        "href" => Some(Field::Href),
+                        "status" => Some(Field::Status),
+                        "getetag" => Some(Field::Etag),
+                        "getlastmodified" => Some(Field::LastModified),
+                        "getcontentlength" => Some(Field::ContentLength),
+                        "quota-used-bytes" => Some(Field::QuotaUsed),
+                        "quota-available-bytes" => Some(Field::QuotaAvailable),
+                        "sync-token" => Some(Field::SyncToken),
+                        _ => None,
+                    };
+                    buf.clear();
+                }
+            },
+            Event::Empty(e)
+                if is_dav && in_resourcetype && e.local_name().as_ref() == "collection" =>
+            {
+                if let Some(p) = propstat.as_mut() {
+                    p.collection = true;
+                }
+            }
+            Event::Text(t) if field.is_some() => buf.push_str(&t.xml10_content()),
+            Event::CData(t) if field.is_some() => buf.push_str(&t.xml10_content()),
+            Event::GeneralRef(r) if field.is_some() => {
+                let raw = format!("&{};", r.xml10_content());
+                match quick_xml::escape::unescape(&raw) {
+                    Ok(v) => buf.push_str(&v),
+                    Err(_) => buf.push_str(&raw),
+                }
+            }
+            Event::End(e)
+                if is_oc && field == Some(Field::FileId) && e.local_name().as_ref() == "fileid" =>
+            {
+                field = None;
+                let value = buf.trim().to_string();
+                store_field(
+                    Field::FileId,
+                    value,
+                    &mut result,
+                    entry.as_mut(),
+                    propstat.as_mut(),
+                );
+            }
+            Event::End(e) if is_dav => match e.local_name().as_ref() {
+                "response" => {
+                    if let Some(done) = entry.take() {
+                        result.responses.push(done);
+                    }
+                }
+                "propstat" => {
+                    if let (Some(done), Some(resp)) = (propstat.take(), entry.as_mut()) {
+                        resp.propstats.push(done);
+                    }
+                }
+                "resourcetype" => in_resourcetype = false,
+                _ => {
+                    if let Some(f) = field.take() {
+                        let value = buf.trim().to_string();
+                        store_field(f, value, &mut result, entry.as_mut(), propstat.as_mut());
+                    }
+                }
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(result)
+}
+
+/// `DAV:locktoken/DAV:href` aus dem Body einer LOCK-Antwort (`<prop><lockdiscovery>…`).
+/// Andere `href`s (z.B. `lockroot`, `owner`) zählen nicht.
+pub fn parse_lock_token(text: &str) -> Option<String> {
+    let mut reader = NsReader::from_str(text);
+    let mut in_locktoken = false;
+    let mut in_href = false;
+    let mut buf = String::new();
+
+    loop {
+        let (ns, event) = reader.read_resolved_event().ok()?;
+        let is_dav = matches!(ns, ResolveResult::Bound(Namespace(DAV_NS)));
+        match event {
+            Event::Start(e) if is_dav => match e.local_name().as_ref() {
+                "locktoken" => in_locktoken = true,
+                "href" if in_locktoken => {
+                    in_href = true;
+                    buf.clear();
+                }
+                _ => {}
+            },
+            Event::Text(t) if in_href => buf.push_str(&t.xml10_content()),
+            Event::CData(t) if in_href => buf.push_str(&t.xml10_content()),
+            Event::End(e) if is_dav => match e.local_name().as_ref() {
+                "href" if in_href => {
+                    in_href = false;
+                    let token = buf.trim();
+                    if !token.is_empty() {
+                        return Some(token.to_string());
+                    }
+                }
+                "locktoken" => in_locktoken = false,
+                _ => {}
+            },
+            Event::Eof => return None,
+            _ => {}
+        }
+    }
+}
+
+fn store_field(
+    field: Field,
+    value: String,
+    result: &mut Multistatus,
+    entry: Option<&mut DavEntry>,
+    propstat: Option<&mut PropStat>,
+) {
+    let non_empty = Some(value.clone()).filter(|v| !v.is_empty());
+    match (field, propstat, entry) {
+        (Field::SyncToken, _, None) => result.sync_token = non_empty,
+        (Field::Status, Some(p), _) => p.status = parse_status(&value),
+        (Field::Status, None, Some(e)) => e.status = parse_status(&value),
+        (Field::Href, None, Some(e)) => e.href = value,
+        (Field::Etag, Some(p), _) => p.etag = non_empty,
+        (Field::LastModified, Some(p), _) => p.last_modified = non_empty,
+        (Field::ContentLength, Some(p), _) => p.content_length = value.parse().ok(),
+        (Field::QuotaUsed, Some(p), _) => p.quota_used = value.parse().ok(),
+        (Field::QuotaAvailable, Some(p), _) => p.quota_available = value.parse().ok(),
+        (Field::FileId, Some(p), _) => p.file_id = non_empty,
+        _ => {}
+    }
+}
+
+/// `HTTP/1.1 404 Not Found` → 404
+fn parse_status(line: &str) -> Option<u16> {
+    line.split_whitespace().nth(1).and_then(|c| c.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> Multistatus {
+        let text = match name {
+            "nextcloud" => include_str!("../tests/fixtures/propfind/nextcloud.xml"),
+            "owncloud" => include_str!("../tests/fixtures/propfind/owncloud.xml"),
+            "apache" => include_str!("../tests/fixtures/propfind/apache_mod_dav.xml"),
+            "nginx" => include_str!("../tests/fixtures/propfind/nginx_dav_ext.xml"),
+            "rclone" => include_str!("../tests/fixtures/propfind/rclone_serve.xml"),
+            _ => unreachable!(),
+        };
+        parse(text).expect("fixture parses")
+    }
+
+    #[test]
+    fn test_lock_token_default_namespace() {
+        let text = include_str!("../tests/fixtures/lock/apache_mod_dav.xml");
+        assert_eq!(
+            parse_lock_token(text).as_deref(),
+            Some("opaquelocktoken:8c6b0e2a-6f1d-4c57-9a43-0d2f8b6e41a7")
+        );
+    }
+
+    #[test]
+    fn test_lock_token_prefixed_namespace() {
+        let text = include_str!("../tests/fixtures/lock/nextcloud.xml");
+        assert_eq!(
+            parse_lock_token(text).as_deref(),
+            Some("opaquelocktoken:3f1c2a9e-52b4-4e0d-8f6a-b1d7c4e9a205")
+        );
+    }
+
+    #[test]
+    fn test_lock_token_ignores_foreign_namespace() {
+        let text = r#"<prop xmlns="DAV:" xmlns:x="urn:other"><lockdiscovery><activelock>
+            <x:locktoken><x:href>urn:uuid:fremd</x:href></x:locktoken>
+            </activelock></lockdiscovery></prop>"#;
+        assert_eq!(parse_lock_token(text), None);
+        assert_eq!(parse_lock_token(""), None);
+    }
+
+    /// Gemeinsame Erwartung aller Fixtures: Root-Collection, ein Unterordner `Work Stuff`,
+    /// eine Notiz `11111111-….json` und `folders.json`.
+    fn assert_common_layout(ms: &Multistatus) {
+        assert_eq!(ms.responses.len(), 4);
+        let root = &ms.responses[0];
+        assert!(root.is_collection());
+        assert_eq!(root.name(), "notes");
+
+        let dir = ms
+            .responses
+            .iter()
+            .find(|e| e.name() == "Work Stuff")
+            .expect("subdir");
+        assert!(dir.is_collection());
+
+        let note = ms
+            .responses
+            .iter()
+            .find(|e| e.name() == "11111111-1111-1111-1111-111111111111.json")
+            .expect("note");
+        assert!(!note.is_collection());
+        assert_eq!(note.content_length(), Some(245));
+        assert!(note.last_modified().is_some());
+    }
+
+    #[test]
+    fn test_nextcloud_fixture() {
+        let ms = fixture("nextcloud");
+        assert_common_layout(&ms);
+        let note = &ms.responses[2];
+        assert_eq!(note.etag(), Some("\"5f1b2c3d4e\""));
+        // Zweiter propstat (404 für getcontenttype) zählt nicht als gefunden
+        assert_eq!(note.propstats.len(), 2);
+        assert_eq!(note.propstats[1].status, Some(404));
+    }
+
+    #[test]
+    fn test_owncloud_fixture() {
+        let ms = fixture("owncloud");
+        assert_common_layout(&ms);
+        assert_eq!(ms.responses[2].etag(), Some("\"a1b2c3\""));
+    }
+
+    #[test]
+    fn test_apache_mod_dav_fixture_with_lp1_prefix() {
+        let ms = fixture("apache");
+        assert_common_layout(&ms);
+        assert_eq!(ms.responses[2].etag(), Some("\"f5-63b1c2d4e5f60\""));
+        assert_eq!(
+            ms.responses[2].last_modified(),
+            Some("Tue, 01 Jul 2025 10:00:00 GMT")
+        );
+    }
+
+    #[test]
+    fn test_nginx_dav_ext_fixture_without_etag() {
+        let ms = fixture("nginx");
+        assert_common_layout(&ms);
+        assert_eq!(ms.responses[2].etag(), None);
+    }
+
+    #[test]
+    fn test_rclone_fixture() {
+        let ms = fixture("rclone");
+        assert_common_layout(&ms);
+        assert_eq!(ms.responses[2].etag(), Some("\"18a2b3c4d5e6f7a8245\""));
+    }
+
+    #[test]
+    fn test_foreign_namespace_is_ignored() {
+        let text = r#"<d:multistatus xmlns:d="DAV:" xmlns:x="urn:other">
+  <d:response>
+    <d:href>/notes/a.json</d:href>
+    <x:href>/ignored</x:href>
+    <d:propstat><d:prop><x:getetag>"nope"</x:getetag></d:prop></d:propstat>
+  </d:response>
+</d:multistatus>"#;
+        let ms = parse(text).unwrap();
+        assert_eq!(ms.responses[0].href, "/notes/a.json");
+        assert_eq!(ms.responses[0].etag(), None);
+    }
+
+    #[test]
+    fn test_sync_token_and_response_status() {
+        let text = r#"<multistatus xmlns="DAV:">
+  <response><href>/notes/gone.json</href><status>HTTP/1.1 404 Not Found</status></response>
+  <sync-token>http://sabre.io/ns/sync/7</sync-token>
+</multistatus>"#;
+        let ms = parse(text).unwrap();
+        assert_eq!(ms.sync_token.as_deref(), Some("http://sabre.io/ns/sync/7"));
+        assert_eq!(ms.responses[0].status, Some(404));
+        assert!(ms.responses[0].propstats.is_empty());
+    }
+
+    #[test]
+    fn test_quota_properties() {
+        let text = r#"<d:multistatus xmlns:d="DAV:">
+  <d:response>
+    <d:href>/notes/</d:href>
+    <d:propstat>
+      <d:prop>
+        <d:quota-used-bytes>1048576</d:quota-used-bytes>
+        <d:quota-available-bytes>-3</d:quota-available-bytes>
+      </d:prop>
+      <d:status>HTTP/1.1 200 OK</d:status>
+    </d:propstat>
+  </d:response>
+</d:multistatus>"#;
+        let ms = parse(text).unwrap();
+        assert_eq!(ms.responses[0].quota_used(), Some(1_048_576));
+        assert_eq!(ms.responses[0].quota_available(), Some(-3));
+    }
+
+    #[test]
+    fn test_nextcloud_fileid() {
+        let ms = parse(include_str!(
+            "../tests/fixtures/versions/nextcloud_fileid.xml"
+        ))
+        .unwrap();
+        assert_eq!(ms.responses[0].file_id(), Some("4711"));
+        // Andere Fixtures ohne oc:fileid
+        assert_eq!(fixture("apache").responses[2].file_id(), None);
+    }
+
+    #[test]
+    fn test_invalid_xml_is_parse_error() {
+        assert!(matches!(
+            parse("<d:multistatus xmlns:d=\"DAV:\"><d:response></d:multistatus>"),
+            Err(AppError::ParseError(_))
+        ));
+    }
+
+    #[test]
+    fn test_decoded_path_strips_host_and_decodes() {
+        assert_eq!(
+            decoded_path("https://cloud.example.com/remote.php/dav/files/u/notes/Work%20Stuff/"),
+            "/remote.php/dav/files/u/notes/Work Stuff"
+        );
+        assert_eq!(decoded_path("/notes/"), "/notes");
+    }
+}
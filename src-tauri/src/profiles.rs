@@ -0,0 +1,653 @@
+//! Mehrere Server-Konten (Profile) in einer App-Instanz.
+//!
+//! Jedes Profil hat eigene Credentials, ein eigenes Speicherziel ([`ProfileSettings`]:
+//! Backend, Sync-Verzeichnis, Sync-Ordner, Git-Remote, CA-Datei und gepinntes Zertifikat) und
+//! einen eigenen Namensraum für die lokalen Stores (Notizen, Offline-Queue, Server-Stand) —
+//! Notizen eines Profils landen nie im Store eines anderen. Übrige Einstellungen (TLS-Modus,
+//! Proxy, …) gelten für alle Profile. Ein lokales Speicherziel, das schon ein anderes Profil
+//! nutzt, wird abgelehnt ([`ensure_own_location`]).
+//!
+//! Das aktive Profil hält Credentials und [`ProfileSettings`] in den bisherigen flachen Keys
+//! der `settings.json`, damit Commands und Frontend unverändert darauf arbeiten; inaktive
+//! Profile tragen ihren Stand im Eintrag unter `profiles`. [`switch`] tauscht beides. Das
+//! Standardprofil nutzt die bisherigen Store-Dateien, bestehende Installationen laufen also
+//! ohne Migration weiter.
+//!
+//! Welcher Namensraum gilt, entscheidet [`store_file`]: innerhalb von [`scoped`] das dort
+//! angegebene Profil (Hintergrund-Sync inaktiver Profile), sonst das aktive.
+
+use std::future::Future;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Runtime};
+use tauri_plugin_store::StoreExt;
+
+use crate::error::{AppError, Result};
+use crate::storage::{Credentials, Settings};
+use crate::sync_backend::{SYNC_BACKEND_DIRECTORY, SYNC_BACKEND_GIT};
+
+/// ID des Standardprofils (bisherige Store-Dateien, kann nicht gelöscht werden).
+pub const DEFAULT_PROFILE: &str = "default";
+
+const SETTINGS_FILE: &str = "settings.json";
+const KEY_PROFILES: &str = "profiles";
+const KEY_ACTIVE: &str = "active_profile";
+/// Store-Dateien, die je Profil getrennt sind.
+const PROFILE_STORES: &[&str] = &["local.json", "sync_state.json"];
+
+tokio::task_local! {
+    static SCOPE: String;
+}
+
+/// Gespeichertes Profil. Beim aktiven Profil sind `credentials`/`settings` veraltet —
+/// maßgeblich sind dann die flachen Keys.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    pub id: String,
+    pub name: String,
+    /// Vom Hintergrund-Sync berücksichtigt
+    #[serde(default = "enabled_default")]
+    pub enabled: bool,
+    #[serde(flatten)]
+    pub settings: ProfileSettings,
+    #[serde(default)]
+    pub credentials: Option<Credentials>,
+}
+
+impl Profile {
+    fn new(id: String, name: String) -> Self {
+        Self {
+            id,
+            name,
+            enabled: true,
+            settings: ProfileSettings::default(),
+            credentials: None,
+        }
+    }
+}
+
+fn enabled_default() -> bool {
+    true
+}
+
+/// Settings, die je Profil getrennt sind (gleichnamige Keys in [`Settings`]): Speicherziel
+/// und Vertrauen in dessen Server-Zertifikat.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileSettings {
+    pub sync_folder: String,
+    pub sync_backend: String,
+    pub sync_directory: String,
+    pub git_remote: String,
+    pub tls_ca_file: String,
+    pub tls_pinned_fingerprint: String,
+}
+
+impl Default for ProfileSettings {
+    fn default() -> Self {
+        Self::from(&Settings::default())
+    }
+}
+
+impl From<&Settings> for ProfileSettings {
+    fn from(settings: &Settings) -> Self {
+        Self {
+            sync_folder: settings.sync_folder.clone(),
+            sync_backend: settings.sync_backend.clone(),
+            sync_directory: settings.sync_directory.clone(),
+            git_remote: settings.git_remote.clone(),
+            tls_ca_file: settings.tls_ca_file.clone(),
+            tls_pinned_fingerprint: settings.tls_pinned_fingerprint.clone(),
+        }
+    }
+}
+
+impl ProfileSettings {
+    /// Gemeinsame Settings mit den Werten dieses Profils überschreiben.
+    pub fn apply_to(&self, settings: &mut Settings) {
+        settings.sync_folder = self.sync_folder.clone();
+        settings.sync_backend = self.sync_backend.clone();
+        settings.sync_directory = self.sync_directory.clone();
+        settings.git_remote = self.git_remote.clone();
+        settings.tls_ca_file = self.tls_ca_file.clone();
+        settings.tls_pinned_fingerprint = self.tls_pinned_fingerprint.clone();
+    }
+
+    /// Verzeichnis mit den Notizen bei lokalem Speicherziel (Sync-Verzeichnis bzw.
+    /// Git-Arbeitsverzeichnis + Sync-Ordner), sonst `None`.
+    fn local_location(&self) -> Option<PathBuf> {
+        [SYNC_BACKEND_DIRECTORY, SYNC_BACKEND_GIT]
+            .contains(&self.sync_backend.as_str())
+            .then(|| Path::new(self.sync_directory.trim()).join(self.sync_folder.trim()))
+    }
+}
+
+/// Profil für das Frontend (ohne Passwort).
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileInfo {
+    pub id: String,
+    pub name: String,
+    pub enabled: bool,
+    pub active: bool,
+    pub server_url: Option<String>,
+}
+
+fn settings_store<R: Runtime>(
+    app: &AppHandle<R>,
+) -> Result<std::sync::Arc<tauri_plugin_store::Store<R>>> {
+    app.store(SETTINGS_FILE)
+        .map_err(|e| AppError::StorageError(e.to_string()))
+}
+
+/// ID des aktiven Profils.
+pub fn active_id<R: Runtime>(app: &AppHandle<R>) -> String {
+    app.store(SETTINGS_FILE)
+        .ok()
+        .and_then(|s| s.get(KEY_ACTIVE))
+        .and_then(|v| v.as_str().map(str::to_owned))
+        .unwrap_or_else(|| DEFAULT_PROFILE.to_string())
+}
+
+/// Führt `f` im Namensraum des Profils `id` aus (Store-Zugriffe über [`store_file`]).
+pub async fn scoped<F: Future>(id: &str, f: F) -> F::Output {
+    SCOPE.scope(id.to_string(), f).await
+}
+
+/// Pfad einer Store-Datei im Namensraum des aktuellen Profils.
+pub fn store_file<R: Runtime>(app: &AppHandle<R>, file: &str) -> String {
+    let id = SCOPE
+        .try_with(|id| id.clone())
+        .unwrap_or_else(|_| active_id(app));
+    profile_store_file(&id, file)
+}
+
+fn profile_store_file(id: &str, file: &str) -> String {
+    if id == DEFAULT_PROFILE {
+        file.to_string()
+    } else {
+        format!("profiles/{}/{}", id, file)
+    }
+}
+
+/// Alle Profile; das Standardprofil ist immer enthalten.
+pub fn load<R: Runtime>(app: &AppHandle<R>) -> Vec<Profile> {
+    let mut profiles: Vec<Profile> = app
+        .store(SETTINGS_FILE)
+        .ok()
+        .and_then(|s| s.get(KEY_PROFILES))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default();
+    if !profiles.iter().any(|p| p.id == DEFAULT_PROFILE) {
+        profiles.insert(
+            0,
+            Profile::new(DEFAULT_PROFILE.to_string(), "Default".to_string()),
+        );
+    }
+    profiles
+}
+
+fn save<R: Runtime>(app: &AppHandle<R>, profiles: &[Profile]) -> Result<()> {
+    let store = settings_store(app)?;
+    let value = serde_json::to_value(profiles).map_err(|e| AppError::ParseError(e.to_string()))?;
+    store.set(KEY_PROFILES, value);
+    store
+        .save()
+        .map_err(|e| AppError::StorageError(e.to_string()))
+}
+
+/// [`ProfileSettings`] des aktiven Profils aus den flachen Keys.
+pub fn stored_settings<R: Runtime>(app: &AppHandle<R>) -> Result<ProfileSettings> {
+    let store = settings_store(app)?;
+    let mut map = serde_json::Map::new();
+    if let Ok(serde_json::Value::Object(defaults)) =
+        serde_json::to_value(ProfileSettings::default())
+    {
+        for key in defaults.keys() {
+            if let Some(val) = store.get(key) {
+                map.insert(key.clone(), val);
+            }
+        }
+    }
+    Ok(serde_json::from_value(serde_json::Value::Object(map)).unwrap_or_default())
+}
+
+/// [`ProfileSettings`] des aktiven Profils in die flachen Keys schreiben.
+fn store_settings<R: Runtime>(app: &AppHandle<R>, settings: &ProfileSettings) -> Result<()> {
+    let store = settings_store(app)?;
+    let value = serde_json::to_value(settings).map_err(|e| AppError::ParseError(e.to_string()))?;
+    if let serde_json::Value::Object(map) = value {
+        for (key, val) in map {
+            store.set(key, val);
+        }
+    }
+    Ok(())
+}
+
+/// Maßgebliche [`ProfileSettings`] eines Profils (beim aktiven aus den flachen Keys).
+pub fn profile_settings<R: Runtime>(
+    app: &AppHandle<R>,
+    profile: &Profile,
+) -> Result<ProfileSettings> {
+    if profile.id == active_id(app) {
+        stored_settings(app)
+    } else {
+        Ok(profile.settings.clone())
+    }
+}
+
+/// Lokales Speicherziel von Profil `id` ablehnen, wenn ein anderes Profil dasselbe
+/// Verzeichnis mit demselben Sync-Ordner nutzt — beide sähen sonst die Notizen des anderen
+/// und würden sie gegenseitig als gelöscht erkennen.
+pub fn ensure_own_location<R: Runtime>(
+    app: &AppHandle<R>,
+    id: &str,
+    settings: &ProfileSettings,
+) -> Result<()> {
+    let Some(location) = settings.local_location() else {
+        return Ok(());
+    };
+    for other in load(app).iter().filter(|p| p.id != id) {
+        if profile_settings(app, other)?.local_location().as_ref() == Some(&location) {
+            return Err(AppError::Profile(format!(
+                "Profil {} synchronisiert bereits {}",
+                other.name,
+                location.display()
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Credentials des aktiven Profils aus den flachen Keys.
+pub fn stored_credentials<R: Runtime>(app: &AppHandle<R>) -> Result<Option<Credentials>> {
+    let store = settings_store(app)?;
+    let text = |key: &str| store.get(key).and_then(|v| v.as_str().map(String::from));
+    // Fehlendes Schema (vor Einführung der Auth-Auswahl gespeichert) → Basic
+    let auth_scheme = store
+        .get("auth_scheme")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default();
+    match (text("server_url"), text("username"), text("password")) {
+        (Some(url), Some(username), Some(password)) => Ok(Some(Credentials {
+            url,
+            username,
+            password,
+            auth_scheme,
+        })),
+        _ => Ok(None),
+    }
+}
+
+/// Credentials des aktiven Profils in die flachen Keys schreiben (`None` = entfernen).
+pub fn store_credentials<R: Runtime>(
+    app: &AppHandle<R>,
+    credentials: Option<&Credentials>,
+) -> Result<()> {
+    let store = settings_store(app)?;
+    match credentials {
+        Some(c) => {
+            store.set("server_url", serde_json::json!(c.url));
+            store.set("username", serde_json::json!(c.username));
+            store.set("password", serde_json::json!(c.password));
+            store.set("auth_scheme", serde_json::json!(c.auth_scheme));
+        }
+        None => {
+            for key in ["server_url", "username", "password", "auth_scheme"] {
+                store.delete(key);
+            }
+        }
+    }
+    store
+        .save()
+        .map_err(|e| AppError::StorageError(e.to_string()))
+}
+
+/// Profilliste für das Frontend.
+pub fn list<R: Runtime>(app: &AppHandle<R>) -> Result<Vec<ProfileInfo>> {
+    let active = active_id(app);
+    let active_url = stored_credentials(app)?.map(|c| c.url);
+    Ok(load(app)
+        .into_iter()
+        .map(|p| {
+            let is_active = p.id == active;
+            ProfileInfo {
+                server_url: if is_active {
+                    active_url.clone()
+                } else {
+                    p.credentials.map(|c| c.url)
+                },
+                active: is_active,
+                id: p.id,
+                name: p.name,
+                enabled: p.enabled,
+            }
+        })
+        .collect())
+}
+
+fn validate_name(profiles: &[Profile], name: &str, except: Option<&str>) -> Result<String> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > 64 {
+        return Err(AppError::Profile(format!(
+            "Ungültiger Profilname: {}",
+            name
+        )));
+    }
+    if profiles
+        .iter()
+        .any(|p| Some(p.id.as_str()) != except && p.name.eq_ignore_ascii_case(name))
+    {
+        return Err(AppError::Profile(format!(
+            "Ein Profil namens {} existiert bereits",
+            name
+        )));
+    }
+    Ok(name.to_string())
+}
+
+fn position(profiles: &[Profile], id: &str) -> Result<usize> {
+    profiles
+        .iter()
+        .position(|p| p.id == id)
+        .ok_or_else(|| AppError::Profile(format!("Unbekanntes Profil: {}", id)))
+}
+
+/// Neues (leeres, nicht aktives) Profil anlegen.
+pub fn create<R: Runtime>(app: &AppHandle<R>, name: &str) -> Result<Profile> {
+    let mut profiles = load(app);
+    let name = validate_name(&profiles, name, None)?;
+    let uuid = uuid::Uuid::new_v4().simple().to_string();
+    // Neues Profil startet ohne Speicherziel (WebDAV ohne Credentials), teilt also nie das
+    // Verzeichnis eines anderen
+    let profile = Profile::new(format!("p-{}", &uuid[..12]), name);
+    profiles.push(profile.clone());
+    save(app, &profiles)?;
+    Ok(profile)
+}
+
+pub fn rename<R: Runtime>(app: &AppHandle<R>, id: &str, name: &str) -> Result<()> {
+    let mut profiles = load(app);
+    let pos = position(&profiles, id)?;
+    profiles[pos].name = validate_name(&profiles, name, Some(id))?;
+    save(app, &profiles)
+}
+
+/// Profil für den Hintergrund-Sync ein-/ausschalten.
+pub fn set_enabled<R: Runtime>(app: &AppHandle<R>, id: &str, enabled: bool) -> Result<()> {
+    let mut profiles = load(app);
+    let pos = position(&profiles, id)?;
+    profiles[pos].enabled = enabled;
+    save(app, &profiles)
+}
+
+/// Inaktives Profil samt seiner lokalen Notizen und Queue löschen. Server-Daten bleiben.
+pub fn delete<R: Runtime>(app: &AppHandle<R>, id: &str) -> Result<()> {
+    if id == DEFAULT_PROFILE || id == active_id(app) {
+        return Err(AppError::Profile(
+            "Standardprofil und aktives Profil können nicht gelöscht werden".to_string(),
+        ));
+    }
+    let mut profiles = load(app);
+    let pos = position(&profiles, id)?;
+    profiles.remove(pos);
+    for file in PROFILE_STORES {
+        if let Ok(store) = app.store(profile_store_file(id, file)) {
+            store.clear();
+            let _ = store.save();
+        }
+    }
+    save(app, &profiles)
+}
+
+/// Aktives Profil wechseln: Credentials und [`ProfileSettings`] des bisherigen Profils in
+/// dessen Eintrag sichern, die des neuen in die flachen Keys übernehmen. Der Aufrufer
+/// verwirft danach die verbundenen Clients und verbindet neu.
+pub fn switch<R: Runtime>(app: &AppHandle<R>, id: &str) -> Result<()> {
+    let current = active_id(app);
+    if current == id {
+        return Ok(());
+    }
+    let mut profiles = load(app);
+    let target = position(&profiles, id)?;
+    let store = settings_store(app)?;
+    if let Ok(pos) = position(&profiles, &current) {
+        profiles[pos].credentials = stored_credentials(app)?;
+        profiles[pos].settings = stored_settings(app)?;
+    }
+    // Nur eine Kopie der Credentials halten: die des aktiven Profils liegen in den flachen Keys
+    let next = std::mem::take(&mut profiles[target].credentials);
+    store_credentials(app, next.as_ref())?;
+    store_settings(app, &profiles[target].settings)?;
+    store.set(KEY_ACTIVE, serde_json::json!(id));
+    save(app, &profiles)
+}
+
+/// Inaktive Profile, die der Hintergrund-Sync mitnimmt.
+pub fn background_profiles<R: Runtime>(app: &AppHandle<R>) -> Vec<Profile> {
+    let active = active_id(app);
+    load(app)
+        .into_iter()
+        .filter(|p| p.enabled && p.id != active)
+        .collect()
+}
+
+/// Ob das aktive Profil am Hintergrund-Sync teilnimmt.
+pub fn active_enabled<R: Runtime>(app: &AppHandle<R>) -> bool {
+    let active = active_id(app);
+    load(app)
+        .iter()
+        .find(|p| p.id == active)
+        .is_none_or(|p| p.enabled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::local_store;
+    use crate::memory_backend::{MemoryBackend, TestApp};
+    use crate::models::{Note, SyncStatus};
+    use crate::storage::AuthScheme;
+    use crate::sync_queue;
+
+    fn credentials(url: &str) -> Credentials {
+        Credentials {
+            url: url.to_string(),
+            username: "me".to_string(),
+            password: "secret".to_string(),
+            auth_scheme: AuthScheme::Basic,
+        }
+    }
+
+    fn note(title: &str) -> Note {
+        let mut note = Note::new(title.to_string(), "tauri-test".to_string());
+        note.sync_status = SyncStatus::Pending;
+        note
+    }
+
+    fn titles<R: Runtime>(app: &AppHandle<R>) -> Vec<String> {
+        let mut titles: Vec<String> = local_store::list_notes(app)
+            .into_iter()
+            .map(|n| n.title)
+            .collect();
+        titles.sort();
+        titles
+    }
+
+    #[test]
+    fn test_default_profile_keeps_existing_store_files() {
+        let app = TestApp::new();
+        let profiles = load(app.handle());
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(profiles[0].id, DEFAULT_PROFILE);
+        assert_eq!(active_id(app.handle()), DEFAULT_PROFILE);
+        assert_eq!(store_file(app.handle(), "local.json"), "local.json");
+        assert_eq!(
+            profile_store_file("p-1", "sync_state.json"),
+            "profiles/p-1/sync_state.json"
+        );
+    }
+
+    #[test]
+    fn test_profile_names_are_validated() {
+        let app = TestApp::new();
+        let work = create(app.handle(), " Work Nextcloud ").unwrap();
+        assert_eq!(work.name, "Work Nextcloud");
+        assert!(create(app.handle(), "work nextcloud").is_err());
+        assert!(create(app.handle(), "   ").is_err());
+        rename(app.handle(), &work.id, "Work").unwrap();
+        assert!(rename(app.handle(), DEFAULT_PROFILE, "work").is_err());
+        assert!(rename(app.handle(), "p-unknown", "Other").is_err());
+    }
+
+    #[test]
+    fn test_switch_swaps_credentials_and_sync_folder() {
+        let app = TestApp::new();
+        let handle = app.handle();
+        store_credentials(handle, Some(&credentials("https://home.example/dav"))).unwrap();
+        settings_store(handle)
+            .unwrap()
+            .set("sync_folder", serde_json::json!("private"));
+        let work = create(handle, "Work").unwrap();
+
+        switch(handle, &work.id).unwrap();
+        assert_eq!(active_id(handle), work.id);
+        assert!(stored_credentials(handle).unwrap().is_none());
+        store_credentials(handle, Some(&credentials("https://work.example/dav"))).unwrap();
+        let infos = list(handle).unwrap();
+        assert_eq!(
+            infos[0].server_url.as_deref(),
+            Some("https://home.example/dav")
+        );
+        assert!(infos[1].active);
+        assert_eq!(
+            infos[1].server_url.as_deref(),
+            Some("https://work.example/dav")
+        );
+
+        switch(handle, DEFAULT_PROFILE).unwrap();
+        let restored = stored_credentials(handle).unwrap().unwrap();
+        assert_eq!(restored.url, "https://home.example/dav");
+        assert_eq!(
+            settings_store(handle).unwrap().get("sync_folder"),
+            Some(serde_json::json!("private"))
+        );
+        let background = background_profiles(handle);
+        assert_eq!(background.len(), 1);
+        assert_eq!(
+            background[0].credentials.as_ref().unwrap().url,
+            "https://work.example/dav"
+        );
+        // Aktive Credentials liegen nur in den flachen Keys
+        assert!(load(handle)[0].credentials.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_notes_and_queue_never_leak_between_profiles() {
+        let app = TestApp::new();
+        let handle = app.handle();
+        let work = create(handle, "Work").unwrap();
+        let private = note("Privat");
+        local_store::put_note(handle, &private);
+        sync_queue::enqueue_deletions(handle, &[("gone".to_string(), None)]);
+
+        switch(handle, &work.id).unwrap();
+        assert!(titles(handle).is_empty());
+        let business = note("Dienstlich");
+        local_store::put_note(handle, &business);
+        assert_eq!(titles(handle), vec!["Dienstlich"]);
+
+        // Hintergrund-Sync des inaktiven Standardprofils sieht nur dessen Notizen und Queue
+        let backend = MemoryBackend::new();
+        let summary = scoped(
+            DEFAULT_PROFILE,
+            crate::sync_engine::run_sync(&backend, handle, "device-a", crate::TRASH_RETENTION_MS),
+        )
+        .await;
+        assert_eq!(summary.notes_uploaded, 1);
+        assert!(backend.server_note(&private.id).is_some());
+        assert!(backend.server_note(&business.id).is_none());
+        assert_eq!(titles(handle), vec!["Dienstlich"]);
+
+        switch(handle, DEFAULT_PROFILE).unwrap();
+        assert_eq!(titles(handle), vec!["Privat"]);
+        delete(handle, &work.id).unwrap();
+        assert!(delete(handle, DEFAULT_PROFILE).is_err());
+        assert!(scoped(&work.id, async { titles(handle) }).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_directory_profiles_do_not_see_each_others_notes() {
+        let app = TestApp::new();
+        let handle = app.handle();
+        let dir = std::env::temp_dir().join(format!("simple-notes-dir-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let directory = ProfileSettings {
+            sync_backend: SYNC_BACKEND_DIRECTORY.to_string(),
+            sync_directory: dir.to_string_lossy().into_owned(),
+            ..ProfileSettings::default()
+        };
+        let target = |settings: &ProfileSettings| {
+            let mut all = Settings::default();
+            settings.apply_to(&mut all);
+            crate::sync_backend::SyncTarget::local(&all)
+                .unwrap()
+                .unwrap()
+        };
+        store_settings(handle, &directory).unwrap();
+        local_store::put_note(handle, &note("Privat"));
+        let work = create(handle, "Work").unwrap();
+
+        switch(handle, &work.id).unwrap();
+        assert_eq!(stored_settings(handle).unwrap(), ProfileSettings::default());
+        // Gleiches Verzeichnis, gleicher Sync-Ordner → abgelehnt
+        assert!(ensure_own_location(handle, &work.id, &directory).is_err());
+        let work_dir = ProfileSettings {
+            sync_folder: "work".to_string(),
+            ..directory.clone()
+        };
+        ensure_own_location(handle, &work.id, &work_dir).unwrap();
+        store_settings(handle, &work_dir).unwrap();
+        local_store::put_note(handle, &note("Dienstlich"));
+
+        // Aktives Profil und Standardprofil im Hintergrund synchronisieren, jeweils zweimal
+        for _ in 0..2 {
+            let active = stored_settings(handle).unwrap();
+            scoped(&work.id, target(&active).run_sync(handle, "device-a", 1)).await;
+            let default = background_profiles(handle).pop().unwrap();
+            assert_eq!(default.settings, directory);
+            ensure_own_location(handle, &default.id, &default.settings).unwrap();
+            scoped(
+                DEFAULT_PROFILE,
+                target(&default.settings).run_sync(handle, "device-a", 1),
+            )
+            .await;
+        }
+
+        assert_eq!(titles(handle), vec!["Dienstlich"]);
+        switch(handle, DEFAULT_PROFILE).unwrap();
+        assert_eq!(stored_settings(handle).unwrap(), directory);
+        assert_eq!(titles(handle), vec!["Privat"]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_disabled_profiles_are_skipped_by_background_sync() {
+        let app = TestApp::new();
+        let handle = app.handle();
+        let work = create(handle, "Work").unwrap();
+        let travel = create(handle, "Travel").unwrap();
+        set_enabled(handle, &travel.id, false).unwrap();
+        let ids: Vec<String> = background_profiles(handle)
+            .into_iter()
+            .map(|p| p.id)
+            .collect();
+        assert_eq!(ids, vec![work.id.clone()]);
+
+        set_enabled(handle, DEFAULT_PROFILE, false).unwrap();
+        assert!(!active_enabled(handle));
+        switch(handle, &work.id).unwrap();
+        assert!(active_enabled(handle));
+        assert!(background_profiles(handle).is_empty());
+    }
+}
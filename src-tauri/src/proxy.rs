@@ -0,0 +1,201 @@
+//! Proxy-Einstellungen für den WebDAV-Client.
+//!
+//! - `system`: Umgebungsvariablen (`HTTPS_PROXY`, `ALL_PROXY`, `NO_PROXY`, …) bzw.
+//!   Systemeinstellungen unter Windows/macOS, wie von reqwest erkannt (Standard)
+//! - `none`: immer direkt verbinden, auch wenn Umgebungsvariablen gesetzt sind
+//! - `http`: expliziter HTTP(S)-Proxy (https-Ziele per CONNECT-Tunnel)
+//! - `socks5`: SOCKS5-Proxy, Namensauflösung über den Proxy (`socks5h`)
+//!
+//! Zugangsdaten und Ausnahmeliste (`NO_PROXY`-Syntax) gelten für `http` und `socks5`.
+
+use reqwest::{ClientBuilder, NoProxy, Proxy, Url};
+
+use crate::error::{AppError, Result};
+use crate::storage::Settings;
+
+pub const PROXY_MODE_NONE: &str = "none";
+pub const PROXY_MODE_HTTP: &str = "http";
+pub const PROXY_MODE_SOCKS5: &str = "socks5";
+
+/// Explizit konfigurierter Proxy-Server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProxyServer {
+    /// `host:port` oder vollständige URL
+    pub url: String,
+    pub username: String,
+    pub password: String,
+    /// Kommagetrennte Hosts, Domains (`.corp.example`) und IP-Netze ohne Proxy
+    pub bypass: String,
+}
+
+/// Aus den Settings abgeleitete Proxy-Richtlinie.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProxyPolicy {
+    System,
+    None,
+    Http(ProxyServer),
+    Socks5(ProxyServer),
+}
+
+impl ProxyPolicy {
+    pub fn from_settings(settings: &Settings) -> Self {
+        let server = || ProxyServer {
+            url: settings.proxy_url.trim().to_string(),
+            username: settings.proxy_username.trim().to_string(),
+            password: settings.proxy_password.clone(),
+            bypass: settings.proxy_bypass.trim().to_string(),
+        };
+        match settings.proxy_mode.as_str() {
+            PROXY_MODE_NONE => ProxyPolicy::None,
+            PROXY_MODE_HTTP => ProxyPolicy::Http(server()),
+            PROXY_MODE_SOCKS5 => ProxyPolicy::Socks5(server()),
+            _ => ProxyPolicy::System,
+        }
+    }
+
+    /// Konfiguriert den reqwest-Builder entsprechend der Richtlinie.
+    pub fn apply(&self, builder: ClientBuilder) -> Result<ClientBuilder> {
+        let (server, url) = match self {
+            ProxyPolicy::System => return Ok(builder),
+            ProxyPolicy::None => return Ok(builder.no_proxy()),
+            ProxyPolicy::Http(server) => {
+                (server, proxy_url(&server.url, "http", &["http", "https"])?)
+            }
+            ProxyPolicy::Socks5(server) => (
+                server,
+                proxy_url(&server.url, "socks5h", &["socks5", "socks5h"])?,
+            ),
+        };
+        let mut proxy =
+            Proxy::all(url.as_str()).map_err(|e| AppError::ProxyConfig(e.to_string()))?;
+        if !server.username.is_empty() {
+            proxy = proxy.basic_auth(&server.username, &server.password);
+        }
+        proxy = proxy.no_proxy(NoProxy::from_string(&server.bypass));
+        Ok(builder.proxy(proxy))
+    }
+}
+
+/// Proxy-Adresse als URL; ohne Schema wird `default_scheme` angenommen.
+fn proxy_url(input: &str, default_scheme: &str, allowed: &[&str]) -> Result<Url> {
+    if input.is_empty() {
+        return Err(AppError::ProxyConfig(
+            "Keine Proxy-Adresse angegeben".to_string(),
+        ));
+    }
+    let with_scheme = if input.contains("://") {
+        input.to_string()
+    } else {
+        format!("{}://{}", default_scheme, input)
+    };
+    let url = Url::parse(&with_scheme)
+        .map_err(|e| AppError::ProxyConfig(format!("Proxy-Adresse {}: {}", input, e)))?;
+    if !allowed.contains(&url.scheme()) {
+        return Err(AppError::ProxyConfig(format!(
+            "Proxy-Adresse {}: Schema {} nicht unterstützt (erlaubt: {})",
+            input,
+            url.scheme(),
+            allowed.join(", ")
+        )));
+    }
+    if url.host_str().is_none_or(str::is_empty) {
+        return Err(AppError::ProxyConfig(format!(
+            "Proxy-Adresse {}: Host fehlt",
+            input
+        )));
+    }
+    Ok(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(mode: &str, url: &str) -> Settings {
+        Settings {
+            proxy_mode: mode.to_string(),
+            proxy_url: url.to_string(),
+            proxy_username: " alice ".to_string(),
+            proxy_password: "s3cret".to_string(),
+            proxy_bypass: "localhost, .corp.example".to_string(),
+            ..Settings::default()
+        }
+    }
+
+    #[test]
+    fn test_policy_from_settings() {
+        assert_eq!(
+            ProxyPolicy::from_settings(&Settings::default()),
+            ProxyPolicy::System
+        );
+        assert_eq!(
+            ProxyPolicy::from_settings(&settings("none", "proxy:3128")),
+            ProxyPolicy::None
+        );
+        assert_eq!(
+            ProxyPolicy::from_settings(&settings("bogus", "")),
+            ProxyPolicy::System
+        );
+        assert_eq!(
+            ProxyPolicy::from_settings(&settings("http", " proxy.corp:3128 ")),
+            ProxyPolicy::Http(ProxyServer {
+                url: "proxy.corp:3128".to_string(),
+                username: "alice".to_string(),
+                password: "s3cret".to_string(),
+                bypass: "localhost, .corp.example".to_string(),
+            })
+        );
+        assert!(matches!(
+            ProxyPolicy::from_settings(&settings("socks5", "127.0.0.1:1080")),
+            ProxyPolicy::Socks5(_)
+        ));
+    }
+
+    #[test]
+    fn test_proxy_url_defaults_and_validation() {
+        let http = ["http", "https"];
+        assert_eq!(
+            proxy_url("proxy.corp:3128", "http", &http)
+                .unwrap()
+                .as_str(),
+            "http://proxy.corp:3128/"
+        );
+        assert_eq!(
+            proxy_url("https://proxy.corp", "http", &http)
+                .unwrap()
+                .scheme(),
+            "https"
+        );
+        assert_eq!(
+            proxy_url("127.0.0.1:1080", "socks5h", &["socks5", "socks5h"])
+                .unwrap()
+                .scheme(),
+            "socks5h"
+        );
+        for bad in ["", "socks5://proxy:1080", "http://", "http://:3128"] {
+            assert!(
+                matches!(proxy_url(bad, "http", &http), Err(AppError::ProxyConfig(_))),
+                "{:?} sollte abgelehnt werden",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn test_apply_builds_client() {
+        for policy in [
+            ProxyPolicy::System,
+            ProxyPolicy::None,
+            ProxyPolicy::from_settings(&settings("http", "proxy.corp:3128")),
+            ProxyPolicy::from_settings(&settings("socks5", "127.0.0.1:1080")),
+        ] {
+            let builder = policy.apply(reqwest::Client::builder()).unwrap();
+            assert!(builder.build().is_ok(), "{:?}", policy);
+        }
+        let missing = ProxyPolicy::from_settings(&settings("http", ""));
+        assert!(matches!(
+            missing.apply(reqwest::Client::builder()),
+            Err(AppError::ProxyConfig(_))
+        ));
+    }
+}
@@ -0,0 +1,403 @@
+//! Minimaler In-Process-S3-Server für Tests: ein Bucket `/bucket` (path-style), GET/PUT/DELETE
+//! auf Objekte mit ETags und If-Match/If-None-Match sowie ListObjectsV2 mit `prefix`,
+//! `delimiter` und Paginierung über `continuation-token`. Jeder Request wird per SigV4 gegen
+//! feste Zugangsdaten geprüft (403 `SignatureDoesNotMatch` bei Abweichung).
+//! Eine Verbindung pro Request.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+use quick_xml::escape::escape;
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::s3::{canonical_query, Signer};
+
+pub const BUCKET: &str = "bucket";
+pub const ACCESS_KEY: &str = "test-access-key";
+pub const SECRET_KEY: &str = "test-secret-key";
+pub const REGION: &str = "us-east-1";
+
+struct State {
+    /// Key → (Inhalt, Version); ETag = `"v{version}"`
+    objects: BTreeMap<String, (String, u64)>,
+    next_version: u64,
+    /// Maximale Einträge (Objekte + Common-Prefixes) je ListObjectsV2-Seite
+    page_size: usize,
+    /// Protokoll aller Requests als `"METHOD /pfad"` bzw. `"LIST prefix"`
+    log: Vec<String>,
+}
+
+pub struct S3TestServer {
+    /// Endpoint samt Bucket (path-style), wie er in den Settings steht
+    pub url: String,
+    state: Arc<Mutex<State>>,
+}
+
+struct Request {
+    method: String,
+    /// Pfad wie gesendet (URL-kodiert), für die Signaturprüfung
+    raw_path: String,
+    query: Vec<(String, String)>,
+    headers: HashMap<String, String>,
+    body: String,
+}
+
+impl S3TestServer {
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/{}", listener.local_addr().unwrap(), BUCKET);
+        let state = Arc::new(Mutex::new(State {
+            objects: BTreeMap::new(),
+            next_version: 0,
+            page_size: 1000,
+            log: Vec::new(),
+        }));
+        let shared = state.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(handle(stream, shared.clone()));
+            }
+        });
+        Self { url, state }
+    }
+
+    pub fn set_page_size(&self, page_size: usize) {
+        self.state.lock().unwrap().page_size = page_size;
+    }
+
+    pub fn put_object(&self, key: &str, body: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.next_version += 1;
+        let version = state.next_version;
+        state
+            .objects
+            .insert(key.to_string(), (body.to_string(), version));
+    }
+
+    pub fn object(&self, key: &str) -> Option<String> {
+        self.state
+            .lock()
+            .unwrap()
+            .objects
+            .get(key)
+            .map(|(body, _)| body.clone())
+    }
+
+    pub fn keys(&self) -> Vec<String> {
+        self.state.lock().unwrap().objects.keys().cloned().collect()
+    }
+
+    pub fn count(&self, method: &str) -> usize {
+        self.state
+            .lock()
+            .unwrap()
+            .log
+            .iter()
+            .filter(|l| l.split(' ').next() == Some(method))
+            .count()
+    }
+}
+
+async fn handle(mut stream: TcpStream, state: Arc<Mutex<State>>) {
+    let Some(request) = read_request(&mut stream).await else {
+        return;
+    };
+    let (status, headers, body) = respond(&request, &state);
+    let mut response = format!(
+        "HTTP/1.1 {} X\r\nContent-Length: {}\r\nConnection: close\r\n",
+        status,
+        body.len()
+    );
+    for (name, value) in headers {
+        response.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    response.push_str("\r\n");
+    response.push_str(&body);
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+async fn read_request(stream: &mut TcpStream) -> Option<Request> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let header_end = loop {
+        let n = stream.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+    let head = String::from_utf8_lossy(&buf[..header_end]).to_string();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next()?.split(' ');
+    let method = request_line.next()?.to_string();
+    let target = request_line.next()?;
+    let (raw_path, raw_query) = target.split_once('?').unwrap_or((target, ""));
+    let query = raw_query
+        .split('&')
+        .filter(|p| !p.is_empty())
+        .filter_map(|p| {
+            let (k, v) = p.split_once('=').unwrap_or((p, ""));
+            Some((
+                urlencoding::decode(k).ok()?.into_owned(),
+                urlencoding::decode(v).ok()?.into_owned(),
+            ))
+        })
+        .collect();
+    let headers: HashMap<String, String> = lines
+        .filter_map(|l| l.split_once(':'))
+        .map(|(k, v)| (k.trim().to_ascii_lowercase(), v.trim().to_string()))
+        .collect();
+    let length: usize = headers
+        .get("content-length")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+    while buf.len() < header_end + length {
+        let n = stream.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+    let body = String::from_utf8_lossy(&buf[header_end..]).to_string();
+    Some(Request {
+        method,
+        raw_path: raw_path.to_string(),
+        query,
+        headers,
+        body,
+    })
+}
+
+type Response = (u16, Vec<(&'static str, String)>, String);
+
+fn error(status: u16, code: &str) -> Response {
+    let body = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <Error><Code>{}</Code><Message>{}</Message></Error>",
+        code, code
+    );
+    (
+        status,
+        vec![("Content-Type", "application/xml".to_string())],
+        body,
+    )
+}
+
+/// Prüft die SigV4-Signatur des Requests gegen die festen Test-Zugangsdaten.
+fn verify_signature(request: &Request) -> std::result::Result<(), Response> {
+    let Some(authorization) = request.headers.get("authorization") else {
+        return Err(error(403, "AccessDenied"));
+    };
+    let field = |name: &str| {
+        authorization
+            .split([' ', ','])
+            .find_map(|part| part.strip_prefix(name))
+            .unwrap_or_default()
+    };
+    let credential: Vec<&str> = field("Credential=").split('/').collect();
+    if credential.first() != Some(&ACCESS_KEY) {
+        return Err(error(403, "InvalidAccessKeyId"));
+    }
+    let signed: Vec<(String, String)> = field("SignedHeaders=")
+        .split(';')
+        .map(|name| {
+            let value = request.headers.get(name).cloned().unwrap_or_default();
+            (name.to_string(), value)
+        })
+        .collect();
+    let payload_hash = request
+        .headers
+        .get("x-amz-content-sha256")
+        .cloned()
+        .unwrap_or_default();
+    let actual_hash: String = Sha256::digest(request.body.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    if payload_hash != actual_hash {
+        return Err(error(400, "XAmzContentSHA256Mismatch"));
+    }
+    let amz_date = request
+        .headers
+        .get("x-amz-date")
+        .cloned()
+        .unwrap_or_default();
+    let query: Vec<(&str, &str)> = request
+        .query
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .collect();
+    let signer = Signer {
+        access_key: ACCESS_KEY.to_string(),
+        secret_key: SECRET_KEY.to_string(),
+        region: REGION.to_string(),
+        service: "s3".to_string(),
+    };
+    let expected = signer.authorization(
+        &request.method,
+        &request.raw_path,
+        &canonical_query(&query),
+        &signed,
+        &payload_hash,
+        &amz_date,
+    );
+    if *authorization != expected {
+        return Err(error(403, "SignatureDoesNotMatch"));
+    }
+    Ok(())
+}
+
+fn respond(request: &Request, state: &Mutex<State>) -> Response {
+    if let Err(response) = verify_signature(request) {
+        return response;
+    }
+    let Some(path) = urlencoding::decode(&request.raw_path)
+        .ok()
+        .map(|p| p.into_owned())
+    else {
+        return error(400, "InvalidURI");
+    };
+    let bucket_root = format!("/{}", BUCKET);
+    let key = match path.strip_prefix(&bucket_root) {
+        Some("") | Some("/") => None,
+        Some(rest) if rest.starts_with('/') => Some(rest[1..].to_string()),
+        _ => return error(404, "NoSuchBucket"),
+    };
+    let mut state = state.lock().unwrap();
+    let etag = |version: u64| format!("\"v{}\"", version);
+
+    let Some(key) = key else {
+        let param = |name: &str| {
+            request
+                .query
+                .iter()
+                .find(|(k, _)| k == name)
+                .map(|(_, v)| v.as_str())
+        };
+        if request.method != "GET" || param("list-type") != Some("2") {
+            return error(405, "MethodNotAllowed");
+        }
+        let prefix = param("prefix").unwrap_or_default();
+        state.log.push(format!("LIST {}", prefix));
+        return list_objects(
+            &state,
+            prefix,
+            param("delimiter"),
+            param("continuation-token"),
+            param("max-keys")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(state.page_size)
+                .min(state.page_size),
+        );
+    };
+    state.log.push(format!("{} /{}", request.method, key));
+
+    match request.method.as_str() {
+        "GET" => match state.objects.get(&key) {
+            Some((body, version)) => (200, vec![("ETag", etag(*version))], body.clone()),
+            None => error(404, "NoSuchKey"),
+        },
+        "PUT" => {
+            let current = state.objects.get(&key).map(|(_, v)| etag(*v));
+            if let Some(expected) = request.headers.get("if-match") {
+                if current.as_ref() != Some(expected) {
+                    return error(412, "PreconditionFailed");
+                }
+            }
+            if request.headers.get("if-none-match").map(String::as_str) == Some("*")
+                && current.is_some()
+            {
+                return error(412, "PreconditionFailed");
+            }
+            state.next_version += 1;
+            let version = state.next_version;
+            state.objects.insert(key, (request.body.clone(), version));
+            (200, vec![("ETag", etag(version))], String::new())
+        }
+        "DELETE" => {
+            state.objects.remove(&key);
+            (204, vec![], String::new())
+        }
+        _ => error(405, "MethodNotAllowed"),
+    }
+}
+
+/// ListObjectsV2: Objekte und (mit `delimiter`) Common-Prefixes in Key-Reihenfolge;
+/// `continuation-token` ist der letzte Eintrag der Vorseite.
+fn list_objects(
+    state: &State,
+    prefix: &str,
+    delimiter: Option<&str>,
+    token: Option<&str>,
+    page_size: usize,
+) -> Response {
+    // (Eintrag, ist Common-Prefix)
+    let mut entries: Vec<(String, bool)> = Vec::new();
+    for key in state.objects.keys().filter(|k| k.starts_with(prefix)) {
+        let common = delimiter.and_then(|d| {
+            key[prefix.len()..]
+                .find(d)
+                .map(|i| key[..prefix.len() + i + d.len()].to_string())
+        });
+        match common {
+            Some(p) if entries.last().is_some_and(|(last, _)| *last == p) => {}
+            Some(p) => entries.push((p, true)),
+            None => entries.push((key.clone(), false)),
+        }
+    }
+    let remaining: Vec<&(String, bool)> = entries
+        .iter()
+        .filter(|(name, _)| token.is_none_or(|t| name.as_str() > t))
+        .collect();
+    let truncated = remaining.len() > page_size;
+    let page = &remaining[..remaining.len().min(page_size)];
+
+    let mut body = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <ListBucketResult xmlns=\"http://s3.amazonaws.com/doc/2006-03-01/\">",
+    );
+    body.push_str(&format!(
+        "<Name>{}</Name><Prefix>{}</Prefix><KeyCount>{}</KeyCount><IsTruncated>{}</IsTruncated>",
+        BUCKET,
+        escape(prefix),
+        page.len(),
+        truncated
+    ));
+    if truncated {
+        if let Some((last, _)) = page.last() {
+            body.push_str(&format!(
+                "<NextContinuationToken>{}</NextContinuationToken>",
+                escape(last.as_str())
+            ));
+        }
+    }
+    for (name, is_prefix) in page {
+        if *is_prefix {
+            body.push_str(&format!(
+                "<CommonPrefixes><Prefix>{}</Prefix></CommonPrefixes>",
+                escape(name.as_str())
+            ));
+        } else {
+            let (content, version) = &state.objects[name];
+            body.push_str(&format!(
+                "<Contents><Key>{}</Key><ETag>&quot;v{}&quot;</ETag>\
+                 <Size>{}</Size></Contents>",
+                escape(name.as_str()),
+                version,
+                content.len()
+            ));
+        }
+    }
+    body.push_str("</ListBucketResult>");
+    (
+        200,
+        vec![("Content-Type", "application/xml".to_string())],
+        body,
+    )
+}
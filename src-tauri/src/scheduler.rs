@@ -4,6 +4,8 @@ use tauri::{AppHandle, Emitter, Manager};
 use tauri_plugin_store::StoreExt;
 use tokio::sync::Notify;
 
+use crate::profiles;
+
 pub struct SyncTrigger(pub Arc<Notify>);
 
 const DEBOUNCE: Duration = Duration::from_secs(5);
@@ -32,16 +34,12 @@ pub fn spawn(app: AppHandle, notify: Arc<Notify>) {
     });
 }
 
+/// Ein Hintergrund-Lauf: aktives Profil, danach alle übrigen aktivierten Profile — jeweils
+/// im eigenen Store-Namensraum.
 pub async fn run_once(app: &AppHandle) {
     if settings_offline(app) {
         return;
     }
-    let client = {
-        let s = app.state::<crate::WebDavState>();
-        let guard = crate::lock_recover(&s.0);
-        guard.clone()
-    };
-    let Some(client) = client else { return };
     let lock = app.state::<crate::SyncLockState>();
     let _g = match lock.0.try_lock() {
         Ok(g) => g,
@@ -60,8 +58,30 @@ pub async fn run_once(app: &AppHandle) {
             Err(_) => return,
         }
     };
-    crate::sync_engine::run_sync(&client, app, &dev, crate::TRASH_RETENTION_MS).await;
-    let _ = app.emit("notes-synced", ());
+    if profiles::active_enabled(app) {
+        if let Ok(Some(target)) = crate::sync_target(app) {
+            let active = profiles::active_id(app);
+            profiles::scoped(
+                &active,
+                target.run_sync(app, &dev, crate::TRASH_RETENTION_MS),
+            )
+            .await;
+            let _ = app.emit("notes-synced", ());
+        }
+    }
+    for profile in profiles::background_profiles(app) {
+        match crate::profile_sync_target(app, &profile) {
+            Ok(Some(target)) => {
+                profiles::scoped(
+                    &profile.id,
+                    target.run_sync(app, &dev, crate::TRASH_RETENTION_MS),
+                )
+                .await;
+            }
+            Ok(None) => {}
+            Err(e) => eprintln!("[sync] Profil {} übersprungen: {}", profile.name, e),
+        }
+    }
 }
 
 fn settings_offline(app: &AppHandle) -> bool {
@@ -1,11 +1,28 @@
 use serde::{Deserialize, Serialize};
 
+/// WebDAV Authentifizierungsverfahren
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthScheme {
+    /// HTTP Basic (Standard, auch für alte gespeicherte Credentials ohne Schema)
+    #[default]
+    Basic,
+    /// HTTP Digest (RFC 7616), Challenge/Response
+    Digest,
+    /// Statischer Bearer-Token; `password` enthält den Token, `username` ist optional
+    Bearer,
+    /// Nextcloud Login Flow v2; `password` ist das dabei erzeugte App-Passwort
+    NextcloudLoginFlow,
+}
+
 /// WebDAV Credentials
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Credentials {
     pub url: String,
     pub username: String,
     pub password: String,
+    #[serde(default)]
+    pub auth_scheme: AuthScheme,
 }
 
 /// App Settings
@@ -21,6 +38,19 @@ pub struct Settings {
     pub default_open_mode: String, // "edit" | "preview" — open text notes in edit vs preview
     pub font_size: String,      // "system"|"small"|"normal"|"large"|"xlarge"
     pub offline_mode: bool,     // Offline-Modus: kein Server nötig (Android-Parität, default true)
+    pub tls_mode: String,       // "system" | "custom_ca" | "pinned" | "insecure" (siehe tls.rs)
+    pub tls_ca_file: String,    // PEM-Datei mit eigener CA (nur tls_mode "custom_ca")
+    pub tls_pinned_fingerprint: String, // SHA-256 des Server-Zertifikats (TOFU bei connect)
+    pub sync_concurrency: usize, // Parallele Requests beim Sync (1–8, default 4)
+    pub proxy_mode: String,     // "system" | "none" | "http" | "socks5" (siehe proxy.rs)
+    pub proxy_url: String,      // host:port oder URL des Proxys (nur "http"/"socks5")
+    pub proxy_username: String, // optionale Proxy-Anmeldung
+    pub proxy_password: String,
+    pub proxy_bypass: String, // Kommagetrennte Hosts/Domains ohne Proxy (NO_PROXY-Syntax)
+    pub sync_backend: String, // "webdav" | "directory" | "s3" | "git" (siehe sync_backend.rs)
+    pub sync_directory: String, // Lokales Sync-Verzeichnis bzw. Git-Arbeitsverzeichnis
+    pub s3_region: String,    // SigV4-Region (nur sync_backend "s3")
+    pub git_remote: String,   // Remote-URL für Push/Pull (nur "git", leer = nur lokal)
 }
 
 impl Default for Settings {
@@ -35,6 +65,19 @@ impl Default for Settings {
             default_open_mode: "edit".to_string(),
             font_size: "system".to_string(),
             offline_mode: true,
+            tls_mode: "system".to_string(),
+            tls_ca_file: String::new(),
+            tls_pinned_fingerprint: String::new(),
+            sync_concurrency: 4,
+            proxy_mode: "system".to_string(),
+            proxy_url: String::new(),
+            proxy_username: String::new(),
+            proxy_password: String::new(),
+            proxy_bypass: String::new(),
+            sync_backend: "webdav".to_string(),
+            sync_directory: String::new(),
+            s3_region: "us-east-1".to_string(),
+            git_remote: String::new(),
         }
     }
 }
@@ -54,6 +97,13 @@ mod tests {
         assert!(settings.update_notifications);
         assert_eq!(settings.font_size, "system");
         assert!(settings.offline_mode);
+        assert_eq!(settings.tls_mode, "system");
+        assert!(settings.tls_pinned_fingerprint.is_empty());
+        assert_eq!(settings.sync_concurrency, 4);
+        assert_eq!(settings.sync_backend, "webdav");
+        assert!(settings.sync_directory.is_empty());
+        assert_eq!(settings.s3_region, "us-east-1");
+        assert!(settings.git_remote.is_empty());
     }
 
     #[test]
@@ -68,6 +118,19 @@ mod tests {
             default_open_mode: "edit".to_string(),
             font_size: "large".to_string(),
             offline_mode: false,
+            tls_mode: "system".to_string(),
+            tls_ca_file: String::new(),
+            tls_pinned_fingerprint: String::new(),
+            sync_concurrency: 4,
+            proxy_mode: "system".to_string(),
+            proxy_url: String::new(),
+            proxy_username: String::new(),
+            proxy_password: String::new(),
+            proxy_bypass: String::new(),
+            sync_backend: "directory".to_string(),
+            sync_directory: "/home/me/Sync".to_string(),
+            s3_region: "eu-central-1".to_string(),
+            git_remote: "git@example.com:me/notes.git".to_string(),
         };
 
         let json = serde_json::to_string(&settings).unwrap();
@@ -80,6 +143,10 @@ mod tests {
         assert_eq!(parsed.sync_folder, "my-notes");
         assert!(!parsed.update_notifications);
         assert_eq!(parsed.font_size, "large");
+        assert_eq!(parsed.sync_backend, "directory");
+        assert_eq!(parsed.sync_directory, "/home/me/Sync");
+        assert_eq!(parsed.s3_region, "eu-central-1");
+        assert_eq!(parsed.git_remote, "git@example.com:me/notes.git");
     }
 
     #[test]
@@ -111,6 +178,19 @@ mod tests {
                 default_open_mode: "edit".to_string(),
                 font_size: "system".to_string(),
                 offline_mode: true,
+                tls_mode: "system".to_string(),
+                tls_ca_file: String::new(),
+                tls_pinned_fingerprint: String::new(),
+                sync_concurrency: 4,
+                proxy_mode: "system".to_string(),
+                proxy_url: String::new(),
+                proxy_username: String::new(),
+                proxy_password: String::new(),
+                proxy_bypass: String::new(),
+                sync_backend: "webdav".to_string(),
+                sync_directory: String::new(),
+                s3_region: "us-east-1".to_string(),
+                git_remote: String::new(),
             };
 
             let json = serde_json::to_string(&settings).unwrap();
@@ -141,6 +221,19 @@ mod tests {
             default_open_mode: "edit".to_string(),
             font_size: "xlarge".to_string(),
             offline_mode: true,
+            tls_mode: "system".to_string(),
+            tls_ca_file: String::new(),
+            tls_pinned_fingerprint: String::new(),
+            sync_concurrency: 4,
+            proxy_mode: "system".to_string(),
+            proxy_url: String::new(),
+            proxy_username: String::new(),
+            proxy_password: String::new(),
+            proxy_bypass: String::new(),
+            sync_backend: "webdav".to_string(),
+            sync_directory: String::new(),
+            s3_region: "us-east-1".to_string(),
+            git_remote: String::new(),
         };
 
         let json = serde_json::to_string(&settings).unwrap();
@@ -159,14 +252,24 @@ mod tests {
             url: "http://localhost:8080".to_string(),
             username: "user".to_string(),
             password: "pass".to_string(),
+            auth_scheme: AuthScheme::Digest,
         };
 
         let json = serde_json::to_string(&creds).unwrap();
+        assert!(json.contains("\"auth_scheme\":\"digest\""));
         let parsed: Credentials = serde_json::from_str(&json).unwrap();
 
         assert_eq!(parsed.url, creds.url);
         assert_eq!(parsed.username, creds.username);
         assert_eq!(parsed.password, creds.password);
+        assert_eq!(parsed.auth_scheme, AuthScheme::Digest);
+    }
+
+    #[test]
+    fn test_credentials_without_scheme_default_to_basic() {
+        let parsed: Credentials =
+            serde_json::from_str(r#"{"url":"u","username":"a","password":"b"}"#).unwrap();
+        assert_eq!(parsed.auth_scheme, AuthScheme::Basic);
     }
 
     #[test]
@@ -181,6 +284,19 @@ mod tests {
             default_open_mode: "edit".to_string(),
             font_size: "normal".to_string(),
             offline_mode: false,
+            tls_mode: "system".to_string(),
+            tls_ca_file: String::new(),
+            tls_pinned_fingerprint: String::new(),
+            sync_concurrency: 4,
+            proxy_mode: "system".to_string(),
+            proxy_url: String::new(),
+            proxy_username: String::new(),
+            proxy_password: String::new(),
+            proxy_bypass: String::new(),
+            sync_backend: "webdav".to_string(),
+            sync_directory: String::new(),
+            s3_region: "us-east-1".to_string(),
+            git_remote: String::new(),
         };
 
         let cloned = settings.clone();
@@ -217,6 +333,19 @@ mod tests {
             "default_open_mode",
             "font_size",
             "offline_mode",
+            "tls_mode",
+            "tls_ca_file",
+            "tls_pinned_fingerprint",
+            "sync_concurrency",
+            "proxy_mode",
+            "proxy_url",
+            "proxy_username",
+            "proxy_password",
+            "proxy_bypass",
+            "sync_backend",
+            "sync_directory",
+            "s3_region",
+            "git_remote",
         ]
         .iter()
         .map(|s| s.to_string())
@@ -0,0 +1,348 @@
+//! Speicherziel des Syncs.
+//!
+//! `run_sync` und die Offline-Queue sprechen nur mit [`SyncBackend`]: Notizen listen, laden,
+//! schreiben, löschen und verschieben, Ordner-Metadaten (`folders.json`) und das gemeinsame
+//! Lösch-Ledger (`deletions.json`). WebDAV-Client, lokales Verzeichnis und S3-Client
+//! implementieren den Trait (das Git-Backend nutzt das lokale Verzeichnis); weitere Speicherziele kommen hinzu, ohne die Sync-Logik
+//! anzufassen.
+
+use std::future::Future;
+
+use tauri::{AppHandle, Runtime};
+
+use crate::error::Result;
+use crate::folders::FolderMeta;
+use crate::git_backend::GitBackend;
+use crate::hlc::Hlc;
+use crate::local_dir::LocalDirBackend;
+use crate::models::{DeletionLedger, Note};
+use crate::s3::S3Client;
+use crate::storage::Settings;
+use crate::sync_engine::{self, SyncSummary};
+use crate::webdav::{
+    CollectionDelta, NoteListing, WebDavClient, WriteCondition, SYNC_RETRY_BUDGET,
+};
+
+/// Wert von `Settings::sync_backend` für das lokale Sync-Verzeichnis; alles andere ist WebDAV.
+pub const SYNC_BACKEND_DIRECTORY: &str = "directory";
+/// Wert von `Settings::sync_backend` für S3-kompatiblen Objektspeicher.
+pub const SYNC_BACKEND_S3: &str = "s3";
+/// Wert von `Settings::sync_backend` für ein Git-Arbeitsverzeichnis.
+pub const SYNC_BACKEND_GIT: &str = "git";
+
+/// Das in den Settings gewählte Speicherziel.
+#[derive(Clone)]
+pub enum SyncTarget {
+    WebDav(WebDavClient),
+    Directory(LocalDirBackend),
+    S3(S3Client),
+    Git(GitBackend),
+}
+
+impl SyncTarget {
+    /// Lokales Speicherziel laut Settings (Sync-Verzeichnis bzw. Git-Arbeitsverzeichnis);
+    /// `None` bei WebDAV und S3, deren Client erst verbunden werden muss.
+    pub fn local(settings: &Settings) -> Result<Option<SyncTarget>> {
+        Ok(match settings.sync_backend.as_str() {
+            SYNC_BACKEND_DIRECTORY => Some(SyncTarget::Directory(LocalDirBackend::new(
+                &settings.sync_directory,
+                &settings.sync_folder,
+            )?)),
+            SYNC_BACKEND_GIT => Some(SyncTarget::Git(GitBackend::new(
+                &settings.sync_directory,
+                &settings.sync_folder,
+                &settings.git_remote,
+            )?)),
+            _ => None,
+        })
+    }
+
+    /// Verzeichnis auf der Platte, falls das Speicherziel eins ist (Konfliktkopien dort).
+    pub fn local_dir(&self) -> Option<&LocalDirBackend> {
+        match self {
+            SyncTarget::Directory(dir) => Some(dir),
+            SyncTarget::Git(repo) => Some(repo.dir()),
+            SyncTarget::WebDav(_) | SyncTarget::S3(_) => None,
+        }
+    }
+
+    /// Identität des Speicherziels (siehe [`SyncBackend::location`]).
+    pub fn location(&self) -> String {
+        match self {
+            SyncTarget::WebDav(client) => client.location(),
+            SyncTarget::Directory(dir) => dir.location(),
+            SyncTarget::S3(client) => client.location(),
+            SyncTarget::Git(repo) => repo.dir().location(),
+        }
+    }
+
+    /// Ein Sync-Lauf. Beim Git-Backend wird vorher vom Remote geholt und danach
+    /// committet + gepusht; Git-Fehler brechen den Lauf nicht ab, sondern werden beim
+    /// nächsten Lauf nachgeholt.
+    pub async fn run_sync<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        device_id: &str,
+        retention_ms: i64,
+    ) -> SyncSummary {
+        match self {
+            SyncTarget::WebDav(client) => {
+                sync_engine::run_sync(client, app, device_id, retention_ms).await
+            }
+            SyncTarget::Directory(dir) => {
+                sync_engine::run_sync(dir, app, device_id, retention_ms).await
+            }
+            SyncTarget::S3(client) => {
+                sync_engine::run_sync(client, app, device_id, retention_ms).await
+            }
+            SyncTarget::Git(repo) => {
+                if let Err(e) = repo.pull(device_id).await {
+                    eprintln!("[git] Pull fehlgeschlagen: {}", e);
+                }
+                let summary = sync_engine::run_sync(repo.dir(), app, device_id, retention_ms).await;
+                if let Err(e) = repo.commit_and_push(device_id).await {
+                    eprintln!("[git] Commit/Push fehlgeschlagen: {}", e);
+                }
+                summary
+            }
+        }
+    }
+}
+
+/// Speicherziel für den Sync. Futures sind `Send`, damit Sync-Läufe in Tasks laufen können.
+///
+/// Versionen (`Option<String>`) sind opak — bei WebDAV der ETag. Backends ohne Versionen
+/// liefern `None`; der Sync vergleicht dann ersatzweise `last_modified` bzw. lädt immer.
+pub trait SyncBackend: Clone + Send + Sync {
+    /// Kopie für einen Sync-Lauf: gemeinsames Wiederholungs-Budget, `concurrency` parallele
+    /// Requests.
+    fn for_sync_run(&self, concurrency: usize) -> Self;
+
+    /// Obergrenze gleichzeitiger Requests bei Massen-Operationen.
+    fn concurrency(&self) -> usize;
+
+    /// Wiederholungs-Budget des Laufs aufgebraucht → weitere Uploads sind aussichtslos.
+    fn retry_budget_exhausted(&self) -> bool;
+
+    /// Identität des Speicherziels (z.B. Server-URL + Sync-Ordner). Sync-Tokens gelten nur
+    /// für diese Location — ändert sie sich, wird neu initialisiert.
+    fn location(&self) -> String;
+
+    // ── Notizen ──────────────────────────────────────────────────────────────
+
+    /// Kann das Backend Änderungen seit einem Token liefern ([`Self::changes_since`])?
+    fn supports_delta(&self) -> impl Future<Output = bool> + Send;
+
+    /// Änderungen der Root-Collection (`folder = None`) bzw. eines Ordners seit `token`
+    /// (`None` = alle Member). Ungültiger Token → `AppError::InvalidSyncToken`,
+    /// nicht unterstützt → `AppError::Unsupported`.
+    fn changes_since(
+        &self,
+        folder: Option<&str>,
+        token: Option<&str>,
+    ) -> impl Future<Output = Result<CollectionDelta>> + Send;
+
+    /// Alle Notizen mit Ordner und Version.
+    fn list_notes(&self) -> impl Future<Output = Result<Vec<NoteListing>>> + Send;
+
+    /// Notiz laden, samt aktueller Version.
+    fn get_note(
+        &self,
+        id: &str,
+        folder: Option<&str>,
+    ) -> impl Future<Output = Result<(Note, Option<String>)>> + Send;
+
+    /// Notiz im Ordner `note.folder_name` speichern. Verletzte Vorbedingung →
+    /// `AppError::Conflict`. Liefert die neue Version.
+    fn put_note(
+        &self,
+        note: &Note,
+        condition: WriteCondition,
+    ) -> impl Future<Output = Result<Option<String>>> + Send;
+
+    /// Notiz löschen; fehlt sie bereits, gilt das als Erfolg.
+    fn delete_note(
+        &self,
+        id: &str,
+        folder: Option<&str>,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// Notiz in einen anderen Ordner verschieben; fehlt sie am alten Pfad, ist nichts zu tun.
+    fn move_note(
+        &self,
+        id: &str,
+        from: Option<&str>,
+        to: Option<&str>,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    // ── Ordner ───────────────────────────────────────────────────────────────
+
+    /// Vorhandene Ordner (auch ohne Eintrag in den Metadaten). Best-effort.
+    fn list_folders(&self) -> impl Future<Output = Vec<String>> + Send;
+
+    /// Ordner anlegen, falls nicht vorhanden. Best-effort.
+    fn ensure_folder(&self, name: &str) -> impl Future<Output = ()> + Send;
+
+    /// Ordner als Ganzes umbenennen. `Ok(false)` wenn das nicht möglich war — der Aufrufer
+    /// verschiebt die Notizen dann einzeln.
+    fn move_folder(&self, from: &str, to: &str) -> impl Future<Output = Result<bool>> + Send;
+
+    /// Ordner samt Inhalt entfernen. Best-effort.
+    fn delete_folder(&self, name: &str) -> impl Future<Output = ()> + Send;
+
+    /// Ordner-Metadaten; nicht lesbar → leer.
+    fn read_folders_meta(&self) -> impl Future<Output = Vec<FolderMeta>> + Send;
+
+    /// Read-Modify-Write der Ordner-Metadaten: `mutation` wird auf den frisch gelesenen
+    /// Stand angewendet (ggf. mehrfach, wenn ein anderes Gerät dazwischen schreibt).
+    fn write_folders_meta_merged<F>(
+        &self,
+        mutation: F,
+    ) -> impl Future<Output = Result<Vec<FolderMeta>>> + Send
+    where
+        F: FnMut(Vec<FolderMeta>) -> Vec<FolderMeta> + Send;
+
+    // ── Lösch-Ledger ─────────────────────────────────────────────────────────
+
+    /// Gemeinsames Lösch-Ledger; nicht lesbar → leer.
+    fn read_deletions(&self) -> impl Future<Output = DeletionLedger> + Send;
+
+    /// IDs ins Ledger eintragen (mit HLC-Stempel `stamp`, falls vorhanden), Einträge älter als
+    /// `retention_ms` bereinigen. Best-effort.
+    fn append_deletions(
+        &self,
+        ids: &[String],
+        device_id: &str,
+        now: i64,
+        stamp: Option<&Hlc>,
+        retention_ms: i64,
+    ) -> impl Future<Output = ()> + Send;
+
+    /// IDs aus dem Ledger streichen (z.B. nach erneutem Upload). Best-effort.
+    fn remove_deletions(&self, ids: &[String]) -> impl Future<Output = ()> + Send;
+
+    // ── Konflikte ────────────────────────────────────────────────────────────
+
+    /// Notizen mit konkurrierenden Fassungen, die das Speicherziel selbst angelegt hat
+    /// (z.B. Syncthing-Konfliktkopien). Der Sync markiert sie als Konflikt.
+    fn conflicted_notes(&self) -> impl Future<Output = Vec<String>> + Send {
+        std::future::ready(Vec::new())
+    }
+}
+
+impl SyncBackend for WebDavClient {
+    fn for_sync_run(&self, concurrency: usize) -> Self {
+        self.with_retry_budget(SYNC_RETRY_BUDGET)
+            .with_concurrency(concurrency)
+    }
+
+    fn concurrency(&self) -> usize {
+        WebDavClient::concurrency(self)
+    }
+
+    fn retry_budget_exhausted(&self) -> bool {
+        WebDavClient::retry_budget_exhausted(self)
+    }
+
+    fn location(&self) -> String {
+        self.root_url()
+    }
+
+    fn supports_delta(&self) -> impl Future<Output = bool> + Send {
+        self.supports_sync_collection()
+    }
+
+    fn changes_since(
+        &self,
+        folder: Option<&str>,
+        token: Option<&str>,
+    ) -> impl Future<Output = Result<CollectionDelta>> + Send {
+        self.sync_collection(folder, token)
+    }
+
+    fn list_notes(&self) -> impl Future<Output = Result<Vec<NoteListing>>> + Send {
+        self.list_notes_with_folders()
+    }
+
+    fn get_note(
+        &self,
+        id: &str,
+        folder: Option<&str>,
+    ) -> impl Future<Output = Result<(Note, Option<String>)>> + Send {
+        self.get_note_with_etag(id, folder)
+    }
+
+    fn put_note(
+        &self,
+        note: &Note,
+        condition: WriteCondition,
+    ) -> impl Future<Output = Result<Option<String>>> + Send {
+        self.save_note(note, condition)
+    }
+
+    fn delete_note(
+        &self,
+        id: &str,
+        folder: Option<&str>,
+    ) -> impl Future<Output = Result<()>> + Send {
+        self.delete_note_by_id_folder(id, folder)
+    }
+
+    fn move_note(
+        &self,
+        id: &str,
+        from: Option<&str>,
+        to: Option<&str>,
+    ) -> impl Future<Output = Result<()>> + Send {
+        self.move_note_file(id, from, to)
+    }
+
+    fn list_folders(&self) -> impl Future<Output = Vec<String>> + Send {
+        self.discover_folders()
+    }
+
+    fn ensure_folder(&self, name: &str) -> impl Future<Output = ()> + Send {
+        self.ensure_folder_dirs(name)
+    }
+
+    fn move_folder(&self, from: &str, to: &str) -> impl Future<Output = Result<bool>> + Send {
+        self.move_folder_dirs(from, to)
+    }
+
+    fn delete_folder(&self, name: &str) -> impl Future<Output = ()> + Send {
+        self.delete_folder_dirs(name)
+    }
+
+    fn read_folders_meta(&self) -> impl Future<Output = Vec<FolderMeta>> + Send {
+        WebDavClient::read_folders_meta(self)
+    }
+
+    fn write_folders_meta_merged<F>(
+        &self,
+        mutation: F,
+    ) -> impl Future<Output = Result<Vec<FolderMeta>>> + Send
+    where
+        F: FnMut(Vec<FolderMeta>) -> Vec<FolderMeta> + Send,
+    {
+        WebDavClient::write_folders_meta_merged(self, mutation)
+    }
+
+    fn read_deletions(&self) -> impl Future<Output = DeletionLedger> + Send {
+        WebDavClient::read_deletions(self)
+    }
+
+    fn append_deletions(
+        &self,
+        ids: &[String],
+        device_id: &str,
+        now: i64,
+        stamp: Option<&Hlc>,
+        retention_ms: i64,
+    ) -> impl Future<Output = ()> + Send {
+        WebDavClient::append_deletions(self, ids, device_id, now, stamp, retention_ms)
+    }
+
+    fn remove_deletions(&self, ids: &[String]) -> impl Future<Output = ()> + Send {
+        WebDavClient::remove_deletions(self, ids)
+    }
+}
@@ -1,105 +1,397 @@
 use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
 
+use futures_util::stream::{self, StreamExt};
 use serde::{Deserialize, Serialize};
-use tauri::AppHandle;
+use tauri::{AppHandle, Runtime};
 use tauri_plugin_store::StoreExt;
 
+use crate::error::AppError;
 use crate::folders::FolderMeta;
+use crate::hlc;
 use crate::local_store;
-use crate::models::{Note, SyncStatus};
+use crate::merge;
+use crate::models::{DeletionRecord, Note, SyncStatus};
+use crate::sync_backend::SyncBackend;
 use crate::sync_queue;
-use crate::webdav::WebDavClient;
+use crate::webdav::{CollectionDelta, NoteListing, WriteCondition};
 
 const SYNC_STORE: &str = "sync_state.json";
-const KEY_NOTE_CACHE: &str = "note_cache";
 const KEY_LAST_SYNC: &str = "last_sync_at";
+const KEY_REMOTE_STATE: &str = "remote_state";
+const KEY_SYNC_TOKENS: &str = "sync_tokens";
 
-/// Ein Eintrag im lokalen Notiz-Cache (für Migration aus alter Architektur).
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct NoteCacheEntry {
-    pub note: Note,
-    pub last_synced_at: i64,
+/// Zuletzt gesehener Server-Stand einer Notiz: Ordner-Pfad + ETag/Last-Modified der JSON-Datei.
+/// Grundlage für Conditional PUTs (`If-Match`) und den Delta-Sync (nur geänderte Notizen laden).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemoteNoteState {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub folder: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub etag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+}
+
+/// Sync-Tokens (RFC 6578) je Collection: Root-Sync-Ordner + ein Token pro Ordner-Verzeichnis.
+/// Gilt nur für `collection_url` — bei anderem Server/Sync-Ordner wird neu initialisiert.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncTokens {
+    #[serde(default)]
+    pub collection_url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root: Option<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub folders: HashMap<String, String>,
+    /// Server kennt den sync-collection REPORT nicht → immer PROPFIND-Scan
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub unsupported: bool,
 }
 
 /// Ergebnis eines Sync-Laufs (für Logging / späteres Frontend-Feedback).
 #[derive(Debug, Default)]
 pub struct SyncSummary {
     pub notes_downloaded: usize,
+    /// Notizen, deren GET dank unverändertem ETag übersprungen wurde
+    pub notes_unchanged: usize,
     pub notes_uploaded: usize,
     pub conflicts_detected: usize,
+    /// Beidseitig bearbeitete Notizen, die per Drei-Wege-Merge ohne Konflikt zusammengeführt wurden
+    pub notes_merged: usize,
     pub notes_deleted_on_server: usize,
 }
 
-// ── Cache-Zugriff (für Migration) ───────────────────────────────────────────
+fn save_last_sync_at<R: Runtime>(app: &AppHandle<R>, ts: i64) {
+    if let Ok(store) = app.store(crate::profiles::store_file(app, SYNC_STORE)) {
+        store.set(KEY_LAST_SYNC, serde_json::json!(ts));
+        let _ = store.save();
+    }
+}
+
+// ── Server-Stand (ETags) ─────────────────────────────────────────────────────
 
-pub fn load_note_cache(app: &AppHandle) -> HashMap<String, NoteCacheEntry> {
-    app.store(SYNC_STORE)
+pub fn load_remote_states<R: Runtime>(app: &AppHandle<R>) -> HashMap<String, RemoteNoteState> {
+    app.store(crate::profiles::store_file(app, SYNC_STORE))
         .ok()
-        .and_then(|s| s.get(KEY_NOTE_CACHE))
+        .and_then(|s| s.get(KEY_REMOTE_STATE))
         .and_then(|v| serde_json::from_value(v.clone()).ok())
         .unwrap_or_default()
 }
 
-#[allow(dead_code)]
-pub fn save_note_cache(app: &AppHandle, cache: &HashMap<String, NoteCacheEntry>) {
-    if let Ok(store) = app.store(SYNC_STORE) {
+pub fn save_remote_states<R: Runtime>(
+    app: &AppHandle<R>,
+    states: &HashMap<String, RemoteNoteState>,
+) {
+    if let Ok(store) = app.store(crate::profiles::store_file(app, SYNC_STORE)) {
         store.set(
-            KEY_NOTE_CACHE,
-            serde_json::to_value(cache).unwrap_or_default(),
+            KEY_REMOTE_STATE,
+            serde_json::to_value(states).unwrap_or_default(),
         );
         let _ = store.save();
     }
 }
 
-/// note_cache-Key löschen — wird nach der einmaligen Migration aufgerufen.
-pub fn clear_note_cache(app: &AppHandle) {
-    if let Ok(store) = app.store(SYNC_STORE) {
-        store.delete(KEY_NOTE_CACHE);
+/// Server-Stand einer einzelnen Notiz aktualisieren (z.B. nach "use_server").
+pub fn set_remote_state<R: Runtime>(app: &AppHandle<R>, id: &str, state: RemoteNoteState) {
+    let mut states = load_remote_states(app);
+    states.insert(id.to_string(), state);
+    save_remote_states(app, &states);
+}
+
+// ── Sync-Tokens (sync-collection) ─────────────────────────────────────────────
+
+fn load_sync_tokens<R: Runtime>(app: &AppHandle<R>, collection_url: &str) -> SyncTokens {
+    app.store(crate::profiles::store_file(app, SYNC_STORE))
+        .ok()
+        .and_then(|s| s.get(KEY_SYNC_TOKENS))
+        .and_then(|v| serde_json::from_value::<SyncTokens>(v.clone()).ok())
+        .filter(|t| t.collection_url == collection_url)
+        .unwrap_or_else(|| SyncTokens {
+            collection_url: collection_url.to_string(),
+            ..Default::default()
+        })
+}
+
+fn save_sync_tokens<R: Runtime>(app: &AppHandle<R>, tokens: &SyncTokens) {
+    if let Ok(store) = app.store(crate::profiles::store_file(app, SYNC_STORE)) {
+        store.set(
+            KEY_SYNC_TOKENS,
+            serde_json::to_value(tokens).unwrap_or_default(),
+        );
         let _ = store.save();
     }
 }
 
-fn save_last_sync_at(app: &AppHandle, ts: i64) {
-    if let Ok(store) = app.store(SYNC_STORE) {
-        store.set(KEY_LAST_SYNC, serde_json::json!(ts));
-        let _ = store.save();
+/// Vorbedingung für den Upload einer Notiz aus dem zuletzt gesehenen Server-Stand:
+/// - bekannter ETag am selben Pfad → `If-Match`
+/// - am Zielpfad liegt (laut letztem Scan) keine Datei → `If-None-Match: *` (Neuanlage)
+/// - Datei vorhanden, aber ohne ETag (Server ohne ETag-Support) → unbedingt
+fn upload_condition(note: &Note, remote: Option<&RemoteNoteState>) -> WriteCondition {
+    let Some(remote) = remote else {
+        return WriteCondition::IfNoneMatch;
+    };
+    let same_path = remote.folder.as_deref().map(str::to_lowercase)
+        == note.folder_name.as_deref().map(str::to_lowercase);
+    match (&remote.etag, same_path) {
+        (_, false) => WriteCondition::IfNoneMatch,
+        (Some(etag), true) => WriteCondition::IfMatch(etag.clone()),
+        (None, true) => WriteCondition::Unconditional,
     }
 }
 
 // ── Sync-Logik ───────────────────────────────────────────────────────────────
 
-/// Alle Server-Notizen abrufen (PROPFIND + GET je UUID).
-async fn fetch_server_notes(client: &WebDavClient) -> crate::error::Result<Vec<Note>> {
-    let note_locations = client.list_notes_with_folders().await?;
-    let mut notes = Vec::new();
-    for (id, folder) in note_locations {
-        match client.get_note(&id, folder.as_deref()).await {
-            Ok(note) => notes.push(note),
-            Err(e) => eprintln!("[sync] get_note {} fehlgeschlagen: {}", id, e),
+/// Muss die Notiz neu geladen werden? Nur wenn sich ihr Server-Stand seit dem letzten
+/// Sync geändert hat: ETag-Vergleich, ersatzweise `getlastmodified`. Ohne vergleichbare
+/// Props (Server liefert keine) wird immer geladen — wie vor dem Delta-Sync.
+fn remote_changed(previous: Option<&RemoteNoteState>, listing: &NoteListing) -> bool {
+    let Some(prev) = previous else {
+        return true;
+    };
+    if prev.folder.as_deref().map(str::to_lowercase)
+        != listing.folder.as_deref().map(str::to_lowercase)
+    {
+        return true;
+    }
+    match (&prev.etag, &listing.etag) {
+        (Some(a), Some(b)) => return a != b,
+        (Some(_), None) | (None, Some(_)) => return true,
+        (None, None) => {}
+    }
+    match (&prev.last_modified, &listing.last_modified) {
+        (Some(a), Some(b)) => a != b,
+        _ => true,
+    }
+}
+
+/// Wendet das Delta einer Collection auf den zuletzt gesehenen Server-Stand an und
+/// liefert das vollständige Listing dieser Collection. Bei `incremental = false`
+/// (initialer REPORT ohne Token) enthält das Delta bereits alle Member.
+fn apply_delta(
+    previous: &HashMap<String, RemoteNoteState>,
+    folder: Option<&str>,
+    incremental: bool,
+    delta: &CollectionDelta,
+) -> Vec<NoteListing> {
+    let folder_key = folder.map(str::to_lowercase);
+    let mut listing: Vec<NoteListing> = if incremental {
+        previous
+            .iter()
+            .filter(|(_, s)| s.folder.as_deref().map(str::to_lowercase) == folder_key)
+            .map(|(id, s)| NoteListing {
+                id: id.clone(),
+                folder: folder.map(str::to_owned),
+                etag: s.etag.clone(),
+                last_modified: s.last_modified.clone(),
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+    listing.retain(|e| !delta.removed.contains(&e.id));
+    for entry in &delta.changed {
+        listing.retain(|e| e.id != entry.id);
+        listing.push(entry.clone());
+    }
+    listing.sort_by(|a, b| a.id.cmp(&b.id));
+    listing
+}
+
+/// Vollständiges Listing aus sync-collection-Deltas: Root-Collection, dann jedes bekannte
+/// bzw. neu gemeldete Ordner-Verzeichnis. Liefert zusätzlich die neuen Tokens.
+/// Jeder Fehler bricht ab — der Aufrufer fällt dann auf den PROPFIND-Scan zurück.
+async fn delta_listing<B: SyncBackend>(
+    backend: &B,
+    previous: &HashMap<String, RemoteNoteState>,
+    tokens: &SyncTokens,
+) -> crate::error::Result<(Vec<NoteListing>, SyncTokens)> {
+    let mut next = SyncTokens {
+        collection_url: tokens.collection_url.clone(),
+        ..Default::default()
+    };
+
+    let root = backend.changes_since(None, tokens.root.as_deref()).await?;
+    let mut listing = apply_delta(previous, None, tokens.root.is_some(), &root);
+    next.root = Some(root.token.clone());
+
+    // Ordner: bisher bekannte (nur bei inkrementellem Root-Sync gültig) ∪ neu gemeldete,
+    // abzüglich entfernter Verzeichnisse
+    let mut folders: Vec<String> = if tokens.root.is_some() {
+        tokens.folders.keys().cloned().collect()
+    } else {
+        Vec::new()
+    };
+    for dir in &root.changed_dirs {
+        if !folders.iter().any(|f| f.eq_ignore_ascii_case(dir)) {
+            folders.push(dir.clone());
+        }
+    }
+    folders.retain(|f| !root.removed_dirs.iter().any(|d| d.eq_ignore_ascii_case(f)));
+    folders.sort();
+
+    for folder in folders {
+        let token = if tokens.root.is_some() {
+            tokens.folders.get(&folder).map(String::as_str)
+        } else {
+            None
+        };
+        let delta = backend.changes_since(Some(&folder), token).await?;
+        for entry in apply_delta(previous, Some(&folder), token.is_some(), &delta) {
+            if !listing.iter().any(|e| e.id == entry.id) {
+                listing.push(entry);
+            }
+        }
+        next.folders.insert(folder, delta.token);
+    }
+    Ok((listing, next))
+}
+
+/// Server-Listing: bevorzugt per sync-collection REPORT (nur Änderungen seit dem letzten
+/// Sync), sonst — Server ohne Support oder verworfener Token — per PROPFIND-Scan.
+/// Die neuen Tokens werden zurückgegeben und erst nach erfolgreichem Laden gespeichert.
+async fn list_server_notes<B: SyncBackend, R: Runtime>(
+    backend: &B,
+    app: &AppHandle<R>,
+    previous: &HashMap<String, RemoteNoteState>,
+) -> crate::error::Result<(Vec<NoteListing>, Option<SyncTokens>)> {
+    let mut tokens = load_sync_tokens(app, &backend.location());
+    if !tokens.unsupported && tokens.root.is_none() && !backend.supports_delta().await {
+        tokens.unsupported = true;
+        save_sync_tokens(app, &tokens);
+    }
+    if !tokens.unsupported {
+        match delta_listing(backend, previous, &tokens).await {
+            Ok((listing, next)) => return Ok((listing, Some(next))),
+            Err(AppError::InvalidSyncToken) => {
+                eprintln!("[sync] Sync-Token verworfen → vollständiger Scan, Tokens neu");
+                save_sync_tokens(
+                    app,
+                    &SyncTokens {
+                        collection_url: tokens.collection_url.clone(),
+                        ..Default::default()
+                    },
+                );
+            }
+            Err(AppError::Unsupported(msg)) => {
+                eprintln!("[sync] {} → PROPFIND-Scan", msg);
+                tokens.unsupported = true;
+                save_sync_tokens(app, &tokens);
+            }
+            Err(e) => eprintln!(
+                "[sync] sync-collection fehlgeschlagen ({}) → PROPFIND-Scan",
+                e
+            ),
+        }
+    }
+    Ok((backend.list_notes().await?, None))
+}
+
+/// Ergebnis des Server-Scans.
+struct ServerScan {
+    /// Per GET geladene (neue oder geänderte) Notizen
+    changed: Vec<Note>,
+    /// Alle auf dem Server gelisteten Notiz-IDs (auch unveränderte)
+    ids: HashSet<String>,
+    /// Neuer Server-Stand je gelisteter Notiz
+    states: HashMap<String, RemoteNoteState>,
+    unchanged: usize,
+    /// Neue Sync-Tokens — None bei PROPFIND-Fallback oder fehlgeschlagenen GETs
+    /// (dann liefert der alte Token die Änderungen beim nächsten Lauf erneut)
+    tokens: Option<SyncTokens>,
+}
+
+/// Server-Notizen abrufen: Listing (sync-collection-Delta oder PROPFIND), dann GET nur
+/// für Notizen, deren ETag sich seit dem letzten Sync geändert hat oder die lokal fehlen.
+/// Schlägt ein GET fehl, bleibt der alte Server-Stand gespeichert — die Notiz gilt
+/// damit beim nächsten Lauf weiterhin als geändert und wird erneut geladen.
+/// Die GETs laufen mit `backend.concurrency()` parallel, ausgewertet wird in Listing-Reihenfolge.
+async fn fetch_server_notes<B: SyncBackend, R: Runtime>(
+    backend: &B,
+    app: &AppHandle<R>,
+    previous: &HashMap<String, RemoteNoteState>,
+) -> crate::error::Result<ServerScan> {
+    let (listing, tokens) = list_server_notes(backend, app, previous).await?;
+    let mut scan = ServerScan {
+        changed: Vec::new(),
+        ids: HashSet::new(),
+        states: HashMap::new(),
+        unchanged: 0,
+        tokens,
+    };
+    let mut to_fetch = Vec::new();
+    for entry in listing {
+        scan.ids.insert(entry.id.clone());
+        let listed_state = RemoteNoteState {
+            folder: entry.folder.clone(),
+            etag: entry.etag.clone(),
+            last_modified: entry.last_modified.clone(),
+        };
+        if !remote_changed(previous.get(&entry.id), &entry)
+            && local_store::get_note(app, &entry.id).is_some()
+        {
+            scan.unchanged += 1;
+            scan.states.insert(entry.id, listed_state);
+            continue;
+        }
+        to_fetch.push((entry, listed_state));
+    }
+
+    let mut fetched = stream::iter(to_fetch)
+        .map(|(entry, listed_state)| async move {
+            let result = backend.get_note(&entry.id, entry.folder.as_deref()).await;
+            (entry, listed_state, result)
+        })
+        .buffered(backend.concurrency());
+    while let Some((entry, listed_state, result)) = fetched.next().await {
+        let prev = previous.get(&entry.id);
+        match result {
+            Ok((note, etag)) => {
+                scan.changed.push(note);
+                scan.states.insert(
+                    entry.id,
+                    RemoteNoteState {
+                        etag: etag.or(listed_state.etag.clone()),
+                        ..listed_state
+                    },
+                );
+            }
+            Err(e) => {
+                eprintln!("[sync] get_note {} fehlgeschlagen: {}", entry.id, e);
+                scan.tokens = None;
+                let kept = match prev {
+                    Some(p) => RemoteNoteState {
+                        folder: listed_state.folder,
+                        ..p.clone()
+                    },
+                    None => RemoteNoteState {
+                        folder: listed_state.folder,
+                        ..Default::default()
+                    },
+                };
+                scan.states.insert(entry.id, kept);
+            }
         }
     }
-    Ok(notes)
+    Ok(scan)
 }
 
 /// Menge aller Ordnernamen, die auf dem Server existieren (lowercased).
 /// Quelle: Ordner aus Notiz-Pfaden ∪ folders.json ∪ physische Verzeichnisse.
-async fn collect_server_folder_names(
-    client: &WebDavClient,
+async fn collect_server_folder_names<B: SyncBackend>(
+    backend: &B,
 ) -> crate::error::Result<HashSet<String>> {
     let mut names = HashSet::new();
-    for (_id, folder) in client.list_notes_with_folders().await? {
-        if let Some(f) = folder {
+    for entry in backend.list_notes().await? {
+        if let Some(f) = entry.folder {
             names.insert(f.to_lowercase());
         }
     }
-    for m in client.read_folders_meta().await {
+    for m in backend.read_folders_meta().await {
         if !m.deleted {
             names.insert(m.name.to_lowercase());
         }
     }
-    for d in client.discover_folders().await {
+    for d in backend.list_folders().await {
         names.insert(d.to_lowercase());
     }
     Ok(names)
@@ -107,21 +399,22 @@ async fn collect_server_folder_names(
 
 /// Ordner-Sync: lokale (nicht local-only) Ordner mit Server-folders.json LWW-mergen
 /// und fehlende Server-Verzeichnisse anlegen.
-async fn sync_folders(client: &WebDavClient, app: &AppHandle) {
-    let server_meta = client.read_folders_meta().await;
+async fn sync_folders<B: SyncBackend, R: Runtime>(backend: &B, app: &AppHandle<R>) {
+    let server_meta = backend.read_folders_meta().await;
 
     // Lokale nicht-local-only Ordner für den Merge aufbereiten
     let local_meta: Vec<FolderMeta> = local_store::active_folders(app)
         .into_iter()
         .filter(|f| !f.local_only)
         .map(|f| FolderMeta {
-            name: f.name,
-            color: f.color,
-            updated_at: f.updated_at,
             deleted: false,
             local_only: false,
+            ..f
         })
         .collect();
+    for meta in &server_meta {
+        local_store::observe_hlc(app, meta);
+    }
 
     // LWW-Merge
     let merged = crate::folders::merge_by_name(local_meta, server_meta);
@@ -141,7 +434,7 @@ async fn sync_folders(client: &WebDavClient, app: &AppHandle) {
     // Server-Verzeichnisse für aktive lokale Nicht-local-only-Ordner anlegen
     for f in local_store::active_folders(app) {
         if !f.local_only {
-            client.ensure_folder_dirs(&f.name).await;
+            backend.ensure_folder(&f.name).await;
         }
     }
 
@@ -152,50 +445,99 @@ async fn sync_folders(client: &WebDavClient, app: &AppHandle) {
     // Read (oben) und diesem Write geschrieben hat.
     let to_write: Vec<FolderMeta> = merged.into_iter().filter(|m| !m.local_only).collect();
     if !to_write.is_empty() {
-        let _ = client
+        let _ = backend
             .write_folders_meta_merged(move |existing| {
-                crate::folders::merge_by_name(to_write, existing)
+                crate::folders::merge_by_name(to_write.clone(), existing)
             })
             .await;
     }
 }
 
+/// "Meine behalten": lokale Fassung einer Notiz im Konflikt zum erneuten Hochladen vormerken.
+/// Hat der Sync mit Konfliktmarkern zusammengeführt, gilt die Fassung davor — Marker ohne
+/// diese Fassung werden nicht hochgeladen, sondern müssen erst bearbeitet werden.
+pub fn keep_local_version<R: Runtime>(
+    app: &AppHandle<R>,
+    id: &str,
+    now: i64,
+) -> crate::error::Result<Note> {
+    let current =
+        local_store::get_note(app, id).ok_or_else(|| AppError::NoteNotFound(id.to_string()))?;
+    let mut note = match local_store::get_conflict_local(app, id) {
+        Some(mine) => mine,
+        None if merge::has_conflict_markers(&current.content) => {
+            return Err(AppError::Conflict(format!(
+                "Notiz {} enthält noch Konfliktmarker",
+                id
+            )));
+        }
+        None => current,
+    };
+    note.sync_status = SyncStatus::Pending;
+    note.updated_at = now;
+    local_store::stamp(app, &mut note);
+    local_store::put_note(app, &note);
+    local_store::clear_conflict_local(app, id);
+    Ok(note)
+}
+
 /// Server-Sync: local_store ↔ Server reconcilen.
 ///
 /// Port von Android's `WebDavSyncService.syncNotes()`.
 /// Sicherheitswächter verhindern Massen-Löschungen durch leere PROPFIND-Antworten.
-pub async fn run_sync(
-    client: &WebDavClient,
-    app: &AppHandle,
+pub async fn run_sync<B: SyncBackend, R: Runtime>(
+    backend: &B,
+    app: &AppHandle<R>,
     device_id: &str,
     retention_ms: i64,
 ) -> SyncSummary {
     let mut summary = SyncSummary::default();
     let now = chrono::Utc::now().timestamp_millis();
+    // Gemeinsames Wiederholungs-Budget für alle Requests dieses Laufs; Parallelität laut Settings
+    let concurrency = crate::load_settings(app)
+        .map(|s| s.sync_concurrency)
+        .unwrap_or_else(|_| backend.concurrency());
+    let backend = &backend.for_sync_run(concurrency);
 
-    // 1. Offline-Queue abarbeiten (ausstehende Löschungen + Move-Cleanups + Ordner-Tombstones)
-    sync_queue::drain_sync_queue(client, app, device_id, retention_ms).await;
+    // 1. Offline-Queue abarbeiten (Ordner-Umbenennungen, Moves, Löschungen, Ordner-Tombstones)
+    sync_queue::drain_sync_queue(backend, app, device_id, retention_ms).await;
 
     // 1.5 Einmalige local_only-Reconciliation (nur bei erreichbarem Server)
     if !local_store::local_only_reconciled(app) {
-        if let Ok(server_names) = collect_server_folder_names(client).await {
+        if let Ok(server_names) = collect_server_folder_names(backend).await {
             local_store::reconcile_local_only(app, &server_names);
         }
         // Err → Server nicht erreichbar → Marker NICHT setzen, nächster Lauf versucht es erneut
     }
 
     // 2. Ordner-Sync
-    sync_folders(client, app).await;
+    sync_folders(backend, app).await;
 
-    // 3. Server-Notizen abrufen
-    let server_notes = match fetch_server_notes(client).await {
+    // 3. Server-Notizen abrufen (Delta: GET nur für geänderte ETags)
+    let previous_states = load_remote_states(app);
+    let scan = match fetch_server_notes(backend, app, &previous_states).await {
         Ok(v) => v,
+        Err(e) if e.is_retryable() => {
+            eprintln!(
+                "[sync] fetch vorübergehend fehlgeschlagen, nächster Sync versucht es erneut: {}",
+                e
+            );
+            return summary;
+        }
         Err(e) => {
             eprintln!("[sync] fetch fehlgeschlagen: {}", e);
             return summary;
         }
     };
-    let server_ids: HashSet<String> = server_notes.iter().map(|n| n.id.clone()).collect();
+    // Server-Stand (Pfad + ETag) für Delta-Sync und Conditional PUTs festhalten
+    let mut remote_states = scan.states;
+    save_remote_states(app, &remote_states);
+    if let Some(tokens) = &scan.tokens {
+        save_sync_tokens(app, tokens);
+    }
+    summary.notes_unchanged = scan.unchanged;
+    let server_notes = scan.changed;
+    let server_ids = scan.ids;
 
     // Local-only-Ordner einmal vorberechnen
     let local_only_set: HashSet<String> = local_store::active_folders(app)
@@ -204,11 +546,14 @@ pub async fn run_sync(
         .map(|f| f.name.to_lowercase())
         .collect();
 
-    let ledger = client.read_deletions().await;
-    let deletion_map: HashMap<String, i64> = ledger
+    let ledger = backend.read_deletions().await;
+    for record in &ledger.deleted_notes {
+        local_store::observe_hlc(app, record);
+    }
+    let deletion_map: HashMap<&str, &DeletionRecord> = ledger
         .deleted_notes
         .iter()
-        .map(|r| (r.id.clone(), r.deleted_at))
+        .map(|r| (r.id.as_str(), r))
         .collect();
 
     // Sicherheitswächter 1: leerer Server-Scan bei gefülltem Store → keine Löscherkennung
@@ -224,7 +569,7 @@ pub async fn run_sync(
                     .unwrap_or(false)
         })
         .collect();
-    let abort_deletion = server_notes.is_empty() && !local_synced.is_empty();
+    let abort_deletion = server_ids.is_empty() && !local_synced.is_empty();
     if abort_deletion {
         eprintln!(
             "[sync] Sicherheitswächter: Server lieferte 0 Notizen, {} lokale SYNCED — Löscherkennung übersprungen",
@@ -232,8 +577,10 @@ pub async fn run_sync(
         );
     }
 
-    // 4. Download / LWW-Merge → in local_store schreiben
+    // 4. Download / LWW-Merge → in local_store schreiben. Verglichen wird per HLC-Stempel,
+    // solange beide Seiten einen tragen, sonst per `updated_at`.
     for sn in &server_notes {
+        local_store::observe_hlc(app, sn);
         if sn
             .folder_name
             .as_deref()
@@ -247,10 +594,40 @@ pub async fn run_sync(
                 let mut n = sn.clone();
                 n.sync_status = SyncStatus::Synced;
                 local_store::put_note(app, &n);
+                local_store::set_base(app, sn);
                 summary.notes_downloaded += 1;
             }
             Some(local) => {
-                if sn.updated_at > local.updated_at {
+                // Beide Seiten seit dem letzten Sync bearbeitet und Basis bekannt → Drei-Wege-Merge,
+                // unabhängig davon, welche Uhr weiter vorgeht
+                let base = (local.sync_status == SyncStatus::Pending)
+                    .then(|| local_store::get_base(app, &sn.id))
+                    .flatten()
+                    .filter(|base| hlc::compare(base, sn).is_ne());
+                if let Some(base) = base {
+                    let merged = merge::merge_notes(&base, &local, sn);
+                    let mut m = merged.note;
+                    m.updated_at = now.max(local.updated_at).max(sn.updated_at + 1);
+                    local_store::stamp(app, &mut m);
+                    if merged.conflicts == 0 {
+                        m.sync_status = SyncStatus::Pending;
+                        summary.notes_merged += 1;
+                        eprintln!("[sync] {} zusammengeführt", sn.id);
+                    } else {
+                        // Überlappende Änderungen: Konfliktmarker im Inhalt, Auflösung über
+                        // resolve_conflict. Die Fassung ohne Marker bleibt für "Meine behalten"
+                        // und "Beide behalten".
+                        local_store::set_conflict_local(app, &local);
+                        m.sync_status = SyncStatus::Conflict;
+                        summary.conflicts_detected += 1;
+                        eprintln!(
+                            "[sync] Konflikt beim Zusammenführen von {} ({} Stellen)",
+                            sn.id, merged.conflicts
+                        );
+                    }
+                    local_store::put_note(app, &m);
+                    local_store::set_base(app, sn);
+                } else if hlc::compare(sn, &local).is_gt() {
                     if matches!(
                         local.sync_status,
                         SyncStatus::Pending | SyncStatus::Conflict
@@ -266,6 +643,8 @@ pub async fn run_sync(
                         let mut n = sn.clone();
                         n.sync_status = SyncStatus::Synced;
                         local_store::put_note(app, &n);
+                        local_store::set_base(app, sn);
+                        local_store::clear_conflict_local(app, &sn.id);
                         summary.notes_downloaded += 1;
                     }
                 }
@@ -274,6 +653,19 @@ pub async fn run_sync(
         }
     }
 
+    // 4b. Vom Speicherziel gemeldete Konflikte (z.B. Syncthing-Konfliktkopien): Notiz als
+    // Konflikt markieren — nicht hochladen, bis der Nutzer sich entschieden hat
+    for id in backend.conflicted_notes().await {
+        if let Some(mut c) = local_store::get_note(app, &id) {
+            if c.sync_status != SyncStatus::Conflict {
+                c.sync_status = SyncStatus::Conflict;
+                local_store::put_note(app, &c);
+                summary.conflicts_detected += 1;
+                eprintln!("[sync] Konfliktkopie für {}", id);
+            }
+        }
+    }
+
     // 5. Löscherkennung: SYNCED-Notizen, die nicht (mehr) am Server sind
     if !abort_deletion {
         let missing: Vec<&Note> = local_synced
@@ -294,8 +686,8 @@ pub async fn run_sync(
             for n in missing {
                 let intentional = n.trashed_at.is_some()
                     || deletion_map
-                        .get(&n.id)
-                        .map(|&d| d >= n.updated_at)
+                        .get(n.id.as_str())
+                        .map(|&d| hlc::compare(d, n).is_ge())
                         .unwrap_or(false);
                 if intentional {
                     local_store::remove_note(app, &n.id);
@@ -314,37 +706,102 @@ pub async fn run_sync(
         }
     }
 
-    // 6. Upload: PENDING (nicht local-only-Ordner) → Server, dann SYNCED
+    // 6. Upload: PENDING (nicht local-only-Ordner) → Server, dann SYNCED.
+    // PUTs laufen parallel; Ergebnisse werden der Reihe nach in local_store übernommen.
+    let uploads: Vec<(Note, WriteCondition)> = local_store::list_notes(app)
+        .into_iter()
+        .filter(|n| {
+            let skip = n
+                .folder_name
+                .as_deref()
+                .map(|f| local_only_set.contains(&f.to_lowercase()))
+                .unwrap_or(false);
+            !skip && matches!(n.sync_status, SyncStatus::Pending | SyncStatus::LocalOnly)
+        })
+        .map(|n| {
+            let condition = upload_condition(&n, remote_states.get(&n.id));
+            (n, condition)
+        })
+        .collect();
+    // Gesetzt sobald ein vorübergehender Fehler das Retry-Budget erschöpft hat oder der
+    // Server-Speicher voll ist (507): noch nicht gestartete Uploads entfallen (laufende werden nicht abgebrochen, sonst wäre unklar,
+    // ob der PUT am Server angekommen ist).
+    let give_up = AtomicBool::new(false);
+    let give_up = &give_up;
+    let mut results = stream::iter(uploads)
+        .map(|(n, condition)| async move {
+            if give_up.load(Ordering::SeqCst) {
+                return (n, None);
+            }
+            let result = backend.put_note(&n, condition).await;
+            let hopeless = match &result {
+                Err(AppError::InsufficientStorage(_)) => true,
+                Err(e) => e.is_retryable() && backend.retry_budget_exhausted(),
+                Ok(_) => false,
+            };
+            if hopeless {
+                give_up.store(true, Ordering::SeqCst);
+            }
+            (n, Some(result))
+        })
+        .buffered(backend.concurrency());
     let mut uploaded_ids: Vec<String> = Vec::new();
-    for n in local_store::list_notes(app) {
-        let skip = n
-            .folder_name
-            .as_deref()
-            .map(|f| local_only_set.contains(&f.to_lowercase()))
-            .unwrap_or(false);
-        if skip || !matches!(n.sync_status, SyncStatus::Pending | SyncStatus::LocalOnly) {
+    let mut skipped = 0;
+    while let Some((n, result)) = results.next().await {
+        let Some(result) = result else {
+            skipped += 1;
             continue;
-        }
-        match client.save_note(&n).await {
-            Ok(()) => {
+        };
+        match result {
+            Ok(etag) => {
                 local_store::mark_synced_if_unchanged(app, &n.id, n.updated_at);
+                local_store::set_base(app, &n);
+                local_store::clear_conflict_local(app, &n.id);
+                remote_states.insert(
+                    n.id.clone(),
+                    RemoteNoteState {
+                        folder: n.folder_name.clone(),
+                        etag,
+                        last_modified: None,
+                    },
+                );
                 uploaded_ids.push(n.id.clone());
                 summary.notes_uploaded += 1;
             }
+            Err(AppError::Conflict(msg)) => {
+                // Anderes Gerät hat zwischen unserem GET und dem PUT hochgeladen → Konflikt
+                // statt stillem Überschreiben. Auflösung über resolve_conflict.
+                if let Some(mut c) = local_store::get_note(app, &n.id) {
+                    c.sync_status = SyncStatus::Conflict;
+                    local_store::put_note(app, &c);
+                }
+                summary.conflicts_detected += 1;
+                eprintln!("[sync] Konflikt beim Upload: {}", msg);
+            }
             Err(e) => eprintln!("[sync] upload {} fehlgeschlagen: {}", n.id, e),
         }
     }
+    if skipped > 0 {
+        // Server überlastet/unerreichbar oder voll: restliche Uploads bleiben PENDING
+        eprintln!(
+            "[sync] Uploads abgebrochen (Retry-Budget erschöpft oder Speicher voll) — {} beim nächsten Sync",
+            skipped
+        );
+    }
+    save_remote_states(app, &remote_states);
     // Frisch (wieder-)hochgeladene Notizen aus dem Server-Lösch-Ledger streichen,
     // damit ein alter Tombstone sie nicht beim nächsten Sync wieder „löscht".
     if !uploaded_ids.is_empty() {
-        client.remove_deletions(&uploaded_ids).await;
+        backend.remove_deletions(&uploaded_ids).await;
     }
 
     save_last_sync_at(app, now);
     eprintln!(
-        "[sync] Abgeschlossen: {} heruntergeladen, {} hochgeladen, {} Konflikte, {} auf Server gelöscht",
+        "[sync] Abgeschlossen: {} heruntergeladen, {} unverändert, {} hochgeladen, {} zusammengeführt, {} Konflikte, {} auf Server gelöscht",
         summary.notes_downloaded,
+        summary.notes_unchanged,
         summary.notes_uploaded,
+        summary.notes_merged,
         summary.conflicts_detected,
         summary.notes_deleted_on_server
     );
@@ -355,35 +812,724 @@ pub async fn run_sync(
 mod tests {
     use super::*;
 
+    fn remote(folder: Option<&str>, etag: Option<&str>) -> RemoteNoteState {
+        RemoteNoteState {
+            folder: folder.map(str::to_owned),
+            etag: etag.map(str::to_owned),
+            last_modified: None,
+        }
+    }
+
+    fn listing(folder: Option<&str>, etag: Option<&str>, modified: Option<&str>) -> NoteListing {
+        NoteListing {
+            id: "11111111-1111-1111-1111-111111111111".to_string(),
+            folder: folder.map(str::to_owned),
+            etag: etag.map(str::to_owned),
+            last_modified: modified.map(str::to_owned),
+        }
+    }
+
     #[test]
-    fn test_note_cache_entry_serde() {
-        let now = 1700000000000i64;
-        let note = Note::new("Test".to_string(), "tauri-abc".to_string());
-        let entry = NoteCacheEntry {
-            note: note.clone(),
-            last_synced_at: now,
-            etag: Some("etag-123".to_string()),
+    fn test_remote_changed_same_etag_is_unchanged() {
+        let prev = remote(Some("Work"), Some("\"e1\""));
+        assert!(!remote_changed(
+            Some(&prev),
+            &listing(Some("work"), Some("\"e1\""), None)
+        ));
+    }
+
+    #[test]
+    fn test_remote_changed_new_etag_or_unknown_note() {
+        let prev = remote(None, Some("\"e1\""));
+        assert!(remote_changed(
+            Some(&prev),
+            &listing(None, Some("\"e2\""), None)
+        ));
+        assert!(remote_changed(None, &listing(None, Some("\"e1\""), None)));
+    }
+
+    #[test]
+    fn test_remote_changed_folder_move() {
+        let prev = remote(None, Some("\"e1\""));
+        assert!(remote_changed(
+            Some(&prev),
+            &listing(Some("Work"), Some("\"e1\""), None)
+        ));
+    }
+
+    #[test]
+    fn test_remote_changed_falls_back_to_last_modified() {
+        let mut prev = remote(None, None);
+        prev.last_modified = Some("Tue, 01 Jul 2025 10:00:00 GMT".to_string());
+        assert!(!remote_changed(
+            Some(&prev),
+            &listing(None, None, Some("Tue, 01 Jul 2025 10:00:00 GMT"))
+        ));
+        assert!(remote_changed(
+            Some(&prev),
+            &listing(None, None, Some("Tue, 01 Jul 2025 11:00:00 GMT"))
+        ));
+        // Keine vergleichbaren Props → immer laden
+        assert!(remote_changed(
+            Some(&remote(None, None)),
+            &listing(None, None, None)
+        ));
+    }
+
+    #[test]
+    fn test_upload_condition_known_etag_same_path() {
+        let mut note = Note::new("X".to_string(), "tauri-x".to_string());
+        note.folder_name = Some("Work".to_string());
+        let state = remote(Some("work"), Some("\"abc\""));
+        assert_eq!(
+            upload_condition(&note, Some(&state)),
+            WriteCondition::IfMatch("\"abc\"".to_string())
+        );
+    }
+
+    #[test]
+    fn test_upload_condition_new_note() {
+        let note = Note::new("X".to_string(), "tauri-x".to_string());
+        assert_eq!(upload_condition(&note, None), WriteCondition::IfNoneMatch);
+    }
+
+    #[test]
+    fn test_upload_condition_moved_note_creates_at_new_path() {
+        // Ordnerwechsel: ETag gehört zum alten Pfad → am neuen Pfad nur anlegen
+        let mut note = Note::new("X".to_string(), "tauri-x".to_string());
+        note.folder_name = Some("Home".to_string());
+        let state = remote(None, Some("\"abc\""));
+        assert_eq!(
+            upload_condition(&note, Some(&state)),
+            WriteCondition::IfNoneMatch
+        );
+    }
+
+    #[test]
+    fn test_upload_condition_without_server_etag() {
+        let note = Note::new("X".to_string(), "tauri-x".to_string());
+        let state = remote(None, None);
+        assert_eq!(
+            upload_condition(&note, Some(&state)),
+            WriteCondition::Unconditional
+        );
+    }
+
+    #[test]
+    fn test_remote_note_state_serde_skips_none() {
+        let json = serde_json::to_string(&remote(None, None)).unwrap();
+        assert_eq!(json, "{}");
+        let parsed: RemoteNoteState = serde_json::from_str(r#"{"etag":"\"e1\""}"#).unwrap();
+        assert_eq!(parsed.etag.as_deref(), Some("\"e1\""));
+        assert_eq!(parsed.folder, None);
+    }
+
+    // ── sync-collection Delta ───────────────────────────────────────────────
+
+    fn states(entries: &[(&str, Option<&str>, &str)]) -> HashMap<String, RemoteNoteState> {
+        entries
+            .iter()
+            .map(|(id, folder, etag)| (id.to_string(), remote(*folder, Some(etag))))
+            .collect()
+    }
+
+    #[test]
+    fn test_apply_delta_incremental_keeps_unchanged_members() {
+        let previous = states(&[("a", None, "1"), ("b", None, "1"), ("w", Some("Work"), "1")]);
+        let delta = CollectionDelta {
+            token: "t2".to_string(),
+            changed: vec![NoteListing {
+                id: "c".to_string(),
+                ..listing(None, Some("2"), None)
+            }],
+            removed: vec!["b".to_string()],
+            ..Default::default()
+        };
+        let result = apply_delta(&previous, None, true, &delta);
+        let ids: Vec<&str> = result.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "c"]);
+        assert_eq!(result[0].etag.as_deref(), Some("1"));
+    }
+
+    #[test]
+    fn test_apply_delta_initial_ignores_previous() {
+        let previous = states(&[("a", None, "1")]);
+        let delta = CollectionDelta {
+            token: "t1".to_string(),
+            ..Default::default()
         };
+        assert!(apply_delta(&previous, None, false, &delta).is_empty());
+    }
+
+    #[test]
+    fn test_apply_delta_folder_match_is_case_insensitive() {
+        let previous = states(&[("w", Some("work"), "1"), ("r", None, "1")]);
+        let delta = CollectionDelta::default();
+        let result = apply_delta(&previous, Some("Work"), true, &delta);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].id, "w");
+        assert_eq!(result[0].folder.as_deref(), Some("Work"));
+    }
+
+    #[test]
+    fn test_sync_tokens_serde_defaults() {
+        let tokens: SyncTokens =
+            serde_json::from_str(r#"{"collection_url":"http://s/notes/"}"#).unwrap();
+        assert_eq!(tokens.root, None);
+        assert!(!tokens.unsupported);
+        let json = serde_json::to_string(&tokens).unwrap();
+        assert_eq!(json, r#"{"collection_url":"http://s/notes/"}"#);
+    }
+
+    // ── run_sync mit In-Memory-Backend ──────────────────────────────────────
 
-        let json = serde_json::to_string(&entry).unwrap();
-        let restored: NoteCacheEntry = serde_json::from_str(&json).unwrap();
+    use crate::memory_backend::{MemoryBackend, TestApp};
 
-        assert_eq!(restored.note.id, note.id);
-        assert_eq!(restored.last_synced_at, now);
-        assert_eq!(restored.etag.as_deref(), Some("etag-123"));
+    fn note_in(title: &str, folder: Option<&str>, status: SyncStatus) -> Note {
+        let mut note = Note::new(title.to_string(), "tauri-test".to_string());
+        note.folder_name = folder.map(str::to_owned);
+        note.sync_status = status;
+        note
+    }
+
+    async fn sync<B: SyncBackend>(backend: &B, app: &TestApp) -> SyncSummary {
+        run_sync(
+            backend,
+            app.handle(),
+            "tauri-test",
+            crate::TRASH_RETENTION_MS,
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_run_sync_downloads_and_uploads() {
+        let app = TestApp::new();
+        let backend = MemoryBackend::new();
+        let server = note_in("Vom Server", Some("Work"), SyncStatus::Synced);
+        backend.put_server_note(&server);
+        let local = note_in("Lokal", None, SyncStatus::Pending);
+        local_store::put_note(app.handle(), &local);
+
+        let summary = sync(&backend, &app).await;
+
+        assert_eq!(summary.notes_downloaded, 1);
+        assert_eq!(summary.notes_uploaded, 1);
+        let downloaded = local_store::get_note(app.handle(), &server.id).unwrap();
+        assert_eq!(downloaded.sync_status, SyncStatus::Synced);
+        assert_eq!(downloaded.folder_name.as_deref(), Some("Work"));
+        let uploaded = local_store::get_note(app.handle(), &local.id).unwrap();
+        assert_eq!(uploaded.sync_status, SyncStatus::Synced);
+        assert_eq!(backend.server_note(&local.id).unwrap().title, "Lokal");
+    }
+
+    #[tokio::test]
+    async fn test_run_sync_skips_unchanged_notes() {
+        let app = TestApp::new();
+        let backend = MemoryBackend::new();
+        backend.put_server_note(&note_in("A", None, SyncStatus::Synced));
+        backend.put_server_note(&note_in("B", None, SyncStatus::Synced));
+
+        assert_eq!(sync(&backend, &app).await.notes_downloaded, 2);
+        let summary = sync(&backend, &app).await;
+
+        assert_eq!(summary.notes_downloaded, 0);
+        assert_eq!(summary.notes_unchanged, 2);
+        assert_eq!(summary.notes_uploaded, 0);
+        assert_eq!(backend.count("GET"), 2);
+        assert_eq!(backend.count("PUT"), 0);
+    }
+
+    #[tokio::test]
+    async fn test_run_sync_detects_conflict() {
+        let app = TestApp::new();
+        let backend = MemoryBackend::new();
+        let mut note = note_in("Original", None, SyncStatus::Synced);
+        note.updated_at -= 10_000;
+        backend.put_server_note(&note);
+        sync(&backend, &app).await;
+
+        // Lokal bearbeitet, währenddessen vom anderen Gerät (neuer) hochgeladen
+        let mut local = local_store::get_note(app.handle(), &note.id).unwrap();
+        local.content = "lokal".to_string();
+        local.updated_at += 1_000;
+        local.sync_status = SyncStatus::Pending;
+        local_store::put_note(app.handle(), &local);
+        let mut remote = note.clone();
+        remote.content = "anderes Gerät".to_string();
+        remote.updated_at += 5_000;
+        backend.put_server_note(&remote);
+
+        let summary = sync(&backend, &app).await;
+
+        assert_eq!(summary.conflicts_detected, 1);
+        let stored = local_store::get_note(app.handle(), &note.id).unwrap();
+        assert_eq!(stored.sync_status, SyncStatus::Conflict);
+        assert_eq!(
+            stored.content,
+            "<<<<<<< Mine\nlokal\n=======\nanderes Gerät\n>>>>>>> Server\n"
+        );
+        // Fassung ohne Marker bleibt für "Meine/Beide behalten" / die Gegenüberstellung erhalten
+        let mine = local_store::get_conflict_local(app.handle(), &note.id).unwrap();
+        assert_eq!(mine.content, "lokal");
+        assert_eq!(
+            backend.server_note(&note.id).unwrap().content,
+            "anderes Gerät"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_keep_local_version_after_merge_conflict_restores_clean_text() {
+        let app = TestApp::new();
+        let backend = MemoryBackend::new();
+        let mut note = note_in("Original", None, SyncStatus::Synced);
+        note.updated_at -= 10_000;
+        backend.put_server_note(&note);
+        sync(&backend, &app).await;
+        let mut local = local_store::get_note(app.handle(), &note.id).unwrap();
+        local.content = "lokal".to_string();
+        local.updated_at += 1_000;
+        local.sync_status = SyncStatus::Pending;
+        local_store::put_note(app.handle(), &local);
+        let mut remote = note.clone();
+        remote.content = "anderes Gerät".to_string();
+        remote.updated_at += 5_000;
+        backend.put_server_note(&remote);
+        sync(&backend, &app).await;
+
+        let now = chrono::Utc::now().timestamp_millis();
+        let kept = keep_local_version(app.handle(), &note.id, now).unwrap();
+
+        assert_eq!(kept.content, "lokal");
+        let stored = local_store::get_note(app.handle(), &note.id).unwrap();
+        assert_eq!(stored.content, "lokal");
+        assert_eq!(stored.sync_status, SyncStatus::Pending);
+        assert!(local_store::get_conflict_local(app.handle(), &note.id).is_none());
+        sync(&backend, &app).await;
+        assert_eq!(backend.server_note(&note.id).unwrap().content, "lokal");
+    }
+
+    #[tokio::test]
+    async fn test_keep_local_version_uses_latest_conflict_after_manual_fix() {
+        let app = TestApp::new();
+        let backend = MemoryBackend::new();
+        let mut note = note_in("Original", None, SyncStatus::Synced);
+        note.updated_at -= 20_000;
+        backend.put_server_note(&note);
+        sync(&backend, &app).await;
+
+        // Beide Seiten ersetzen dieselbe Zeile → Merge mit Konfliktmarkern
+        async fn conflict(
+            app: &TestApp,
+            backend: &MemoryBackend,
+            id: &str,
+            mine: &str,
+            theirs: &str,
+        ) {
+            let mut local = local_store::get_note(app.handle(), id).unwrap();
+            local.content = mine.to_string();
+            local.updated_at += 1_000;
+            local.sync_status = SyncStatus::Pending;
+            local_store::put_note(app.handle(), &local);
+            let mut remote = backend.server_note(id).unwrap();
+            remote.content = theirs.to_string();
+            remote.updated_at += 5_000;
+            backend.put_server_note(&remote);
+            assert_eq!(sync(backend, app).await.conflicts_detected, 1);
+        }
+        conflict(&app, &backend, &note.id, "lokal", "anderes Gerät").await;
+
+        // Marker von Hand entfernt und gespeichert (wie save_note) → normal hochgeladen
+        let mut fixed = local_store::get_note(app.handle(), &note.id).unwrap();
+        fixed.content = "bereinigt".to_string();
+        fixed.updated_at = chrono::Utc::now().timestamp_millis();
+        local_store::mark_dirty(app.handle(), &mut fixed);
+        local_store::put_note(app.handle(), &fixed);
+        assert!(local_store::get_conflict_local(app.handle(), &note.id).is_none());
+        assert_eq!(sync(&backend, &app).await.notes_uploaded, 1);
+        assert_eq!(backend.server_note(&note.id).unwrap().content, "bereinigt");
+
+        conflict(&app, &backend, &note.id, "lokal 2", "anderes Gerät 2").await;
+        let now = chrono::Utc::now().timestamp_millis();
+        let kept = keep_local_version(app.handle(), &note.id, now + 10_000).unwrap();
+
+        assert_eq!(kept.content, "lokal 2");
     }
 
     #[test]
-    fn test_note_cache_entry_no_etag() {
-        let entry = NoteCacheEntry {
-            note: Note::new("X".to_string(), "tauri-x".to_string()),
-            last_synced_at: 0,
-            etag: None,
+    fn test_keep_local_version_rejects_markers_without_clean_version() {
+        let app = TestApp::new();
+        let mut note = note_in("Konflikt", None, SyncStatus::Conflict);
+        note.content = "<<<<<<< Mine\nlokal\n=======\nServer\n>>>>>>> Server\n".to_string();
+        local_store::put_note(app.handle(), &note);
+
+        let result = keep_local_version(app.handle(), &note.id, note.updated_at + 1);
+
+        assert!(matches!(result, Err(AppError::Conflict(_))));
+        let stored = local_store::get_note(app.handle(), &note.id).unwrap();
+        assert_eq!(stored.sync_status, SyncStatus::Conflict);
+    }
+
+    #[tokio::test]
+    async fn test_run_sync_merges_non_overlapping_edits() {
+        let app = TestApp::new();
+        let backend = MemoryBackend::new();
+        let mut note = note_in("Einkauf", None, SyncStatus::Synced);
+        note.content = "Milch\nBrot\nEier\n".to_string();
+        note.updated_at -= 10_000;
+        backend.put_server_note(&note);
+        sync(&backend, &app).await;
+
+        // Lokal eine Zeile ergänzt — mit einer Uhr, die hinter dem anderen Gerät liegt
+        let mut local = local_store::get_note(app.handle(), &note.id).unwrap();
+        local.content = "Milch\nBrot\nEier\nButter\n".to_string();
+        local.updated_at += 1_000;
+        local.sync_status = SyncStatus::Pending;
+        local_store::put_note(app.handle(), &local);
+        let mut remote = note.clone();
+        remote.title = "Wocheneinkauf".to_string();
+        remote.content = "Hafermilch\nBrot\nEier\n".to_string();
+        remote.updated_at += 5_000;
+        backend.put_server_note(&remote);
+
+        let summary = sync(&backend, &app).await;
+
+        assert_eq!(summary.notes_merged, 1);
+        assert_eq!(summary.conflicts_detected, 0);
+        assert_eq!(summary.notes_uploaded, 1);
+        let server = backend.server_note(&note.id).unwrap();
+        assert_eq!(server.title, "Wocheneinkauf");
+        assert_eq!(server.content, "Hafermilch\nBrot\nEier\nButter\n");
+        let stored = local_store::get_note(app.handle(), &note.id).unwrap();
+        assert_eq!(stored.sync_status, SyncStatus::Synced);
+        assert_eq!(
+            local_store::get_base(app.handle(), &note.id)
+                .unwrap()
+                .content,
+            server.content
+        );
+
+        // Lokale Uhr vorne: Merge trotzdem statt stillem Überschreiben der Server-Änderung
+        let mut local = stored;
+        local.content.push_str("Kaffee\n");
+        local.updated_at += 60_000;
+        local.sync_status = SyncStatus::Pending;
+        local_store::put_note(app.handle(), &local);
+        let mut remote = server;
+        remote.content = remote.content.replace("Brot", "Vollkornbrot");
+        remote.updated_at += 1;
+        backend.put_server_note(&remote);
+
+        let summary = sync(&backend, &app).await;
+
+        assert_eq!(summary.notes_merged, 1);
+        assert_eq!(
+            backend.server_note(&note.id).unwrap().content,
+            "Hafermilch\nVollkornbrot\nEier\nButter\nKaffee\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_sync_merges_checklist_items() {
+        use crate::models::ChecklistItem;
+        let app = TestApp::new();
+        let backend = MemoryBackend::new();
+        let mut note = Note::new_checklist("Einkauf".to_string(), "tauri-test".to_string());
+        note.checklist_items = Some(vec![
+            ChecklistItem::new("Milch".to_string(), 0),
+            ChecklistItem::new("Brot".to_string(), 1),
+        ]);
+        note.updated_at -= 10_000;
+        backend.put_server_note(&note);
+        sync(&backend, &app).await;
+
+        // Lokal Milch abgehakt, auf dem anderen Gerät Käse ergänzt
+        let mut local = local_store::get_note(app.handle(), &note.id).unwrap();
+        local.checklist_items.as_mut().unwrap()[0].is_checked = true;
+        local.updated_at += 1_000;
+        local.sync_status = SyncStatus::Pending;
+        local_store::put_note(app.handle(), &local);
+        let mut remote = note.clone();
+        let items = remote.checklist_items.as_mut().unwrap();
+        items.push(ChecklistItem::new("Käse".to_string(), 2));
+        remote.updated_at += 5_000;
+        backend.put_server_note(&remote);
+
+        let summary = sync(&backend, &app).await;
+
+        assert_eq!(summary.notes_merged, 1);
+        assert_eq!(summary.conflicts_detected, 0);
+        let server = backend.server_note(&note.id).unwrap();
+        let items = server.checklist_items.unwrap();
+        let state: Vec<(&str, bool)> = items
+            .iter()
+            .map(|i| (i.text.as_str(), i.is_checked))
+            .collect();
+        assert_eq!(
+            state,
+            vec![("Milch", true), ("Brot", false), ("Käse", false)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_sync_without_base_keeps_whole_note_conflict() {
+        let app = TestApp::new();
+        let backend = MemoryBackend::new();
+        let mut note = note_in("Alt", None, SyncStatus::Pending);
+        note.content = "lokal\n".to_string();
+        local_store::put_note(app.handle(), &note);
+        let mut remote = note.clone();
+        remote.content = "Server\n".to_string();
+        remote.updated_at += 5_000;
+        backend.put_server_note(&remote);
+
+        let summary = sync(&backend, &app).await;
+
+        assert_eq!(summary.conflicts_detected, 1);
+        assert_eq!(summary.notes_merged, 0);
+        let stored = local_store::get_note(app.handle(), &note.id).unwrap();
+        assert_eq!(stored.content, "lokal\n");
+        assert_eq!(stored.sync_status, SyncStatus::Conflict);
+    }
+
+    #[tokio::test]
+    async fn test_run_sync_server_deletions_respect_ledger() {
+        let app = TestApp::new();
+        let backend = MemoryBackend::new();
+        let kept = note_in("Bleibt", None, SyncStatus::Synced);
+        let deleted = note_in("Gelöscht", None, SyncStatus::Synced);
+        let vanished = note_in("Verschwunden", None, SyncStatus::Synced);
+        for n in [&kept, &deleted, &vanished] {
+            backend.put_server_note(n);
+        }
+        sync(&backend, &app).await;
+
+        backend.remove_server_note(&deleted.id);
+        backend.remove_server_note(&vanished.id);
+        let now = chrono::Utc::now().timestamp_millis();
+        backend
+            .append_deletions(
+                std::slice::from_ref(&deleted.id),
+                "android",
+                now,
+                None,
+                crate::TRASH_RETENTION_MS,
+            )
+            .await;
+
+        let summary = sync(&backend, &app).await;
+
+        assert_eq!(summary.notes_deleted_on_server, 2);
+        assert!(local_store::get_note(app.handle(), &deleted.id).is_none());
+        let zombie = local_store::get_note(app.handle(), &vanished.id).unwrap();
+        assert_eq!(zombie.sync_status, SyncStatus::DeletedOnServer);
+        assert!(zombie.trashed_at.is_some());
+        assert!(local_store::get_note(app.handle(), &kept.id).is_some());
+    }
+
+    /// Stempel eines Geräts, dessen Uhr 10 Minuten vorgeht
+    fn fast_clock_stamp(logical: u32, node: &str) -> (i64, crate::hlc::Hlc) {
+        let ahead = chrono::Utc::now().timestamp_millis() + 600_000;
+        let stamp = crate::hlc::Hlc {
+            physical: ahead,
+            logical,
+            node: node.to_string(),
         };
-        let json = serde_json::to_string(&entry).unwrap();
-        assert!(
-            !json.contains("\"etag\""),
-            "etag-Feld darf bei None nicht serialisiert werden"
+        (ahead, stamp)
+    }
+
+    #[tokio::test]
+    async fn test_run_sync_hlc_beats_fast_wall_clock() {
+        use crate::hlc::Stamped;
+        let app = TestApp::new();
+        let backend = MemoryBackend::new();
+        let note = note_in("Notiz", None, SyncStatus::Synced);
+        backend.put_server_note(&note);
+        sync(&backend, &app).await;
+
+        // Gerät mit vorgehender Uhr bearbeitet, wir übernehmen die Fassung beim Sync
+        let mut remote = note.clone();
+        remote.content = "vom schnellen Gerät".to_string();
+        let (ahead, stamp) = fast_clock_stamp(0, "fast");
+        remote.updated_at = ahead;
+        remote.set_hlc(&stamp);
+        backend.put_server_note(&remote);
+        assert_eq!(sync(&backend, &app).await.notes_downloaded, 1);
+
+        // Danach lokal bearbeitet: Wanduhr liegt zurück, der HLC-Stempel nicht
+        let mut local = local_store::get_note(app.handle(), &note.id).unwrap();
+        local.content = "lokal danach".to_string();
+        local.updated_at = chrono::Utc::now().timestamp_millis();
+        local_store::mark_dirty(app.handle(), &mut local);
+        local_store::put_note(app.handle(), &local);
+        assert!(local.hlc().unwrap() > stamp);
+
+        let summary = sync(&backend, &app).await;
+
+        assert_eq!(summary.conflicts_detected, 0);
+        assert_eq!(summary.notes_uploaded, 1);
+        assert_eq!(
+            backend.server_note(&note.id).unwrap().content,
+            "lokal danach"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_sync_ledger_compares_hlc() {
+        use crate::hlc::Stamped;
+        let app = TestApp::new();
+        let backend = MemoryBackend::new();
+        let mut note = note_in("Notiz", None, SyncStatus::Synced);
+        let (ahead, stamp) = fast_clock_stamp(0, "fast");
+        note.updated_at = ahead;
+        note.set_hlc(&stamp);
+        backend.put_server_note(&note);
+        backend.put_server_note(&note_in("Bleibt", None, SyncStatus::Synced));
+        sync(&backend, &app).await;
+
+        // Anderes Gerät hat die Fassung gesehen und löscht danach — Wanduhr vor `updated_at`
+        backend.remove_server_note(&note.id);
+        let deletion_stamp = crate::hlc::Hlc {
+            logical: 1,
+            node: "other".to_string(),
+            ..stamp
+        };
+        backend
+            .append_deletions(
+                std::slice::from_ref(&note.id),
+                "other",
+                chrono::Utc::now().timestamp_millis(),
+                Some(&deletion_stamp),
+                crate::TRASH_RETENTION_MS,
+            )
+            .await;
+
+        let summary = sync(&backend, &app).await;
+
+        assert_eq!(summary.notes_deleted_on_server, 1);
+        assert!(local_store::get_note(app.handle(), &note.id).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_run_sync_drains_queue_and_syncs_folders() {
+        let app = TestApp::new();
+        let backend = MemoryBackend::new();
+        let gone = note_in("Weg", Some("Old"), SyncStatus::Synced);
+        let moved = note_in("Umgezogen", Some("Old"), SyncStatus::Synced);
+        backend.put_server_note(&gone);
+        backend.put_server_note(&moved);
+        sync(&backend, &app).await;
+
+        // Offline: Ordner „Home" angelegt, eine Notiz gelöscht, die andere nach „Home"
+        // verschoben, Ordner „Old" entfernt
+        local_store::upsert_folder(app.handle(), "Home", None, false, false);
+        local_store::remove_note(app.handle(), &gone.id);
+        sync_queue::enqueue_deletions(app.handle(), &[(gone.id.clone(), Some("Old".to_string()))]);
+        let mut local = local_store::get_note(app.handle(), &moved.id).unwrap();
+        local.folder_name = Some("Home".to_string());
+        local_store::put_note(app.handle(), &local);
+        sync_queue::enqueue_move_deletions(
+            app.handle(),
+            &[(moved.id.clone(), Some("Old".to_string()))],
+        );
+        sync_queue::enqueue_folder_tombstone(app.handle(), "Old");
+
+        sync(&backend, &app).await;
+
+        assert!(backend.server_note(&gone.id).is_none());
+        assert_eq!(backend.ledger_ids(), vec![gone.id.clone()]);
+        assert_eq!(
+            backend.server_folder(&moved.id),
+            Some(Some("Home".to_string()))
         );
+        assert!(backend.folders().iter().any(|f| f == "Home"));
+        assert!(!backend.folders().iter().any(|f| f == "Old"));
+        let meta = backend.folders_meta();
+        assert!(meta.iter().any(|m| m.name == "Home" && !m.deleted));
+        assert!(meta.iter().any(|m| m.name == "Old" && m.deleted));
+        assert!(sync_queue::all_deletions(app.handle()).is_empty());
+        assert!(sync_queue::all_folder_tombstones(app.handle()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_run_sync_surfaces_sync_conflict_copies() {
+        let app = TestApp::new();
+        let dir = std::env::temp_dir().join(format!("simple-notes-dir-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let backend =
+            crate::local_dir::LocalDirBackend::new(dir.to_str().unwrap(), "notes").unwrap();
+        let note = note_in("Geteilt", None, SyncStatus::Pending);
+        local_store::put_note(app.handle(), &note);
+        assert_eq!(sync(&backend, &app).await.notes_uploaded, 1);
+
+        // Syncthing legt die Fassung eines anderen Geräts als Konfliktkopie ab
+        let mut other = note.clone();
+        other.content = "anderes Gerät".to_string();
+        std::fs::write(
+            dir.join("notes").join(format!(
+                "{}.sync-conflict-20250701-101010-ABCDEFG.json",
+                note.id
+            )),
+            serde_json::to_string(&other).unwrap(),
+        )
+        .unwrap();
+
+        let summary = sync(&backend, &app).await;
+        assert_eq!(summary.conflicts_detected, 1);
+        let stored = local_store::get_note(app.handle(), &note.id).unwrap();
+        assert_eq!(stored.sync_status, SyncStatus::Conflict);
+
+        // Bleibt Konflikt, ohne erneut gezählt oder hochgeladen zu werden
+        let summary = sync(&backend, &app).await;
+        assert_eq!(summary.conflicts_detected, 0);
+        assert_eq!(summary.notes_uploaded, 0);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_run_sync_against_s3() {
+        use crate::s3::S3Client;
+        use crate::s3_test_server::{S3TestServer, ACCESS_KEY, REGION, SECRET_KEY};
+        use crate::storage::{AuthScheme, Credentials};
+
+        let s3 = S3TestServer::start().await;
+        s3.set_page_size(1);
+        let credentials = Credentials {
+            url: s3.url.clone(),
+            username: ACCESS_KEY.to_string(),
+            password: SECRET_KEY.to_string(),
+            auth_scheme: AuthScheme::Basic,
+        };
+        let backend = S3Client::new(
+            &credentials,
+            "notes",
+            REGION,
+            &crate::tls::TlsPolicy::System,
+            &crate::proxy::ProxyPolicy::None,
+        )
+        .unwrap();
+        let app = TestApp::new();
+        let server = note_in("Vom Server", Some("Work"), SyncStatus::Synced);
+        backend
+            .put_note(&server, WriteCondition::IfNoneMatch)
+            .await
+            .unwrap();
+        let local = note_in("Lokal", Some("Privat"), SyncStatus::Pending);
+        local_store::put_note(app.handle(), &local);
+
+        let summary = sync(&backend, &app).await;
+        assert_eq!(summary.notes_downloaded, 1);
+        assert_eq!(summary.notes_uploaded, 1);
+        let downloaded = local_store::get_note(app.handle(), &server.id).unwrap();
+        assert_eq!(downloaded.folder_name.as_deref(), Some("Work"));
+        assert!(s3
+            .object(&format!("notes/Privat/{}.json", local.id))
+            .is_some());
+        assert!(s3.object("notes-md/Privat/Lokal.md").is_some());
+
+        // ETags aus dem Listing → zweiter Lauf lädt nichts erneut
+        let summary = sync(&backend, &app).await;
+        assert_eq!(summary.notes_downloaded, 0);
+        assert_eq!(summary.notes_uploaded, 0);
     }
 }
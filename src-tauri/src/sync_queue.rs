@@ -1,13 +1,17 @@
 use serde::{Deserialize, Serialize};
-use tauri::AppHandle;
+use tauri::{AppHandle, Runtime};
 use tauri_plugin_store::StoreExt;
 
 use crate::folders::FolderMeta;
-use crate::webdav::WebDavClient;
+use crate::hlc::Stamped;
+use crate::local_store;
+use crate::models::Note;
+use crate::sync_backend::SyncBackend;
 
 const STORE_FILE: &str = "sync_state.json";
 const KEY_DELETIONS: &str = "pending_deletions";
 const KEY_TOMBSTONES: &str = "pending_folder_tombstones";
+const KEY_RENAMES: &str = "pending_folder_renames";
 
 /// Eine in der Offline-Queue gespeicherte Löschoperation.
 /// `folder` ist der Server-Ordner-Pfad zum Zeitpunkt der Einreihung —
@@ -16,23 +20,58 @@ const KEY_TOMBSTONES: &str = "pending_folder_tombstones";
 pub struct PendingDeletion {
     pub id: String,
     pub folder: Option<String>,
-    /// true → nur alte Datei löschen, NICHT ins Lösch-Ledger schreiben (Move-Cleanup).
+    /// true → Notiz per MOVE in ihren aktuellen Ordner verschieben, NICHT ins Lösch-Ledger
+    /// schreiben (Move-Cleanup).
     #[serde(default)]
     pub is_move: bool,
 }
 
+/// Ausstehende Ordner-Umbenennung am Server (MOVE der ganzen Collection).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PendingFolderRename {
+    pub from: String,
+    pub to: String,
+}
+
+/// Was beim Drain mit einem Move-Eintrag passiert.
+#[derive(Debug, PartialEq, Eq)]
+enum MoveAction {
+    /// Per MOVE in diesen Ordner verschieben
+    Move(Option<String>),
+    /// Nur alte Server-Datei löschen (Notiz lokal weg oder jetzt in einem local-only-Ordner)
+    Delete,
+    /// Notiz liegt wieder im Ausgangsordner — nichts zu tun
+    Done,
+}
+
+/// Ziel eines Move-Eintrags aus dem aktuellen lokalen Stand der Notiz.
+fn move_action(from: Option<&str>, note: Option<&Note>, target_local_only: bool) -> MoveAction {
+    let Some(note) = note else {
+        return MoveAction::Delete;
+    };
+    if target_local_only {
+        return MoveAction::Delete;
+    }
+    let to = note.folder_name.as_deref();
+    if to.map(str::to_lowercase) == from.map(str::to_lowercase) {
+        MoveAction::Done
+    } else {
+        MoveAction::Move(to.map(str::to_owned))
+    }
+}
+
 // ── Interne Lade-/Speicherfunktionen ────────────────────────────────────────
 
-fn load_deletions(app: &AppHandle) -> Vec<PendingDeletion> {
-    app.store(STORE_FILE)
+fn load_deletions<R: Runtime>(app: &AppHandle<R>) -> Vec<PendingDeletion> {
+    app.store(crate::profiles::store_file(app, STORE_FILE))
         .ok()
         .and_then(|s| s.get(KEY_DELETIONS))
         .and_then(|v| serde_json::from_value(v.clone()).ok())
         .unwrap_or_default()
 }
 
-fn save_deletions(app: &AppHandle, items: &[PendingDeletion]) {
-    if let Ok(store) = app.store(STORE_FILE) {
+fn save_deletions<R: Runtime>(app: &AppHandle<R>, items: &[PendingDeletion]) {
+    if let Ok(store) = app.store(crate::profiles::store_file(app, STORE_FILE)) {
         store.set(
             KEY_DELETIONS,
             serde_json::to_value(items).unwrap_or_default(),
@@ -41,16 +80,16 @@ fn save_deletions(app: &AppHandle, items: &[PendingDeletion]) {
     }
 }
 
-fn load_tombstones(app: &AppHandle) -> Vec<String> {
-    app.store(STORE_FILE)
+fn load_tombstones<R: Runtime>(app: &AppHandle<R>) -> Vec<String> {
+    app.store(crate::profiles::store_file(app, STORE_FILE))
         .ok()
         .and_then(|s| s.get(KEY_TOMBSTONES))
         .and_then(|v| serde_json::from_value(v.clone()).ok())
         .unwrap_or_default()
 }
 
-fn save_tombstones(app: &AppHandle, names: &[String]) {
-    if let Ok(store) = app.store(STORE_FILE) {
+fn save_tombstones<R: Runtime>(app: &AppHandle<R>, names: &[String]) {
+    if let Ok(store) = app.store(crate::profiles::store_file(app, STORE_FILE)) {
         store.set(
             KEY_TOMBSTONES,
             serde_json::to_value(names).unwrap_or_default(),
@@ -59,11 +98,26 @@ fn save_tombstones(app: &AppHandle, names: &[String]) {
     }
 }
 
+fn load_renames<R: Runtime>(app: &AppHandle<R>) -> Vec<PendingFolderRename> {
+    app.store(crate::profiles::store_file(app, STORE_FILE))
+        .ok()
+        .and_then(|s| s.get(KEY_RENAMES))
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default()
+}
+
+fn save_renames<R: Runtime>(app: &AppHandle<R>, items: &[PendingFolderRename]) {
+    if let Ok(store) = app.store(crate::profiles::store_file(app, STORE_FILE)) {
+        store.set(KEY_RENAMES, serde_json::to_value(items).unwrap_or_default());
+        let _ = store.save();
+    }
+}
+
 // ── Öffentliche API ──────────────────────────────────────────────────────────
 
 /// Hängt Notiz-IDs (mit Ordner-Zuordnung) in die Offline-Lösch-Queue ein.
 /// Dedupliziert nach ID — idempotent bei wiederholtem Aufruf.
-pub fn enqueue_deletions(app: &AppHandle, items: &[(String, Option<String>)]) {
+pub fn enqueue_deletions<R: Runtime>(app: &AppHandle<R>, items: &[(String, Option<String>)]) {
     if items.is_empty() {
         return;
     }
@@ -80,8 +134,9 @@ pub fn enqueue_deletions(app: &AppHandle, items: &[(String, Option<String>)]) {
     save_deletions(app, &existing);
 }
 
-/// Hängt Move-Cleanup-Einträge in die Queue ein (löscht alte Server-Datei, kein Ledger-Eintrag).
-pub fn enqueue_move_deletions(app: &AppHandle, items: &[(String, Option<String>)]) {
+/// Hängt Move-Einträge in die Queue ein (`folder` = alter Server-Ordner). Beim Drain wird die
+/// Server-Datei in den dann aktuellen Ordner der Notiz verschoben, kein Ledger-Eintrag.
+pub fn enqueue_move_deletions<R: Runtime>(app: &AppHandle<R>, items: &[(String, Option<String>)]) {
     if items.is_empty() {
         return;
     }
@@ -99,12 +154,12 @@ pub fn enqueue_move_deletions(app: &AppHandle, items: &[(String, Option<String>)
 }
 
 /// Alle ausstehenden Löschoperationen.
-pub fn all_deletions(app: &AppHandle) -> Vec<PendingDeletion> {
+pub fn all_deletions<R: Runtime>(app: &AppHandle<R>) -> Vec<PendingDeletion> {
     load_deletions(app)
 }
 
 /// Entfernt erfolgreich ausgeführte Löschungen aus der Queue.
-pub fn remove_deletions(app: &AppHandle, ids: &[String]) {
+pub fn remove_deletions<R: Runtime>(app: &AppHandle<R>, ids: &[String]) {
     if ids.is_empty() {
         return;
     }
@@ -118,7 +173,7 @@ pub fn remove_deletions(app: &AppHandle, ids: &[String]) {
 
 /// Hängt einen Ordner-Tombstone in die Offline-Queue ein.
 /// Dedupliziert nach Name (case-insensitiv).
-pub fn enqueue_folder_tombstone(app: &AppHandle, name: &str) {
+pub fn enqueue_folder_tombstone<R: Runtime>(app: &AppHandle<R>, name: &str) {
     let mut names = load_tombstones(app);
     if !names.iter().any(|n| n.eq_ignore_ascii_case(name)) {
         names.push(name.to_string());
@@ -126,13 +181,30 @@ pub fn enqueue_folder_tombstone(app: &AppHandle, name: &str) {
     save_tombstones(app, &names);
 }
 
+/// Hängt eine Ordner-Umbenennung in die Queue ein. Eine Kette A → B → C wird zu A → C
+/// zusammengefasst; B → A hebt A → B auf.
+pub fn enqueue_folder_rename<R: Runtime>(app: &AppHandle<R>, from: &str, to: &str) {
+    let mut renames = load_renames(app);
+    match renames.iter().position(|r| r.to.eq_ignore_ascii_case(from)) {
+        Some(pos) if renames[pos].from.eq_ignore_ascii_case(to) => {
+            renames.remove(pos);
+        }
+        Some(pos) => renames[pos].to = to.to_string(),
+        None => renames.push(PendingFolderRename {
+            from: from.to_string(),
+            to: to.to_string(),
+        }),
+    }
+    save_renames(app, &renames);
+}
+
 /// Alle ausstehenden Ordner-Tombstones.
-pub fn all_folder_tombstones(app: &AppHandle) -> Vec<String> {
+pub fn all_folder_tombstones<R: Runtime>(app: &AppHandle<R>) -> Vec<String> {
     load_tombstones(app)
 }
 
 /// Entfernt einen Ordner-Tombstone aus der Queue.
-pub fn remove_folder_tombstone(app: &AppHandle, name: &str) {
+pub fn remove_folder_tombstone<R: Runtime>(app: &AppHandle<R>, name: &str) {
     let names = load_tombstones(app);
     let updated: Vec<_> = names
         .into_iter()
@@ -144,7 +216,7 @@ pub fn remove_folder_tombstone(app: &AppHandle, name: &str) {
 /// Entfernt alle Queue-Einträge die zu einem bestimmten Ordner gehören.
 /// Wird aufgerufen wenn ein Ordner wieder in den Sync aufgenommen wird
 /// (Phase 2 / includeFoldersInSync-Parität mit Android).
-pub fn cancel_folder_deletions(app: &AppHandle, folder_name: &str) {
+pub fn cancel_folder_deletions<R: Runtime>(app: &AppHandle<R>, folder_name: &str) {
     let existing = load_deletions(app);
     let updated: Vec<_> = existing
         .into_iter()
@@ -161,27 +233,66 @@ pub fn cancel_folder_deletions(app: &AppHandle, folder_name: &str) {
 
 // ── Drain ────────────────────────────────────────────────────────────────────
 
-/// Verarbeitet die Offline-Queue: führt ausstehende Löschungen und
-/// Ordner-Tombstones aus, schreibt erfolgreich gelöschte IDs ins Lösch-Ledger.
+/// Verarbeitet die Offline-Queue: führt ausstehende Ordner-Umbenennungen, Moves, Löschungen
+/// und Ordner-Tombstones aus, schreibt erfolgreich gelöschte IDs ins Lösch-Ledger.
 /// Port von Android's `processPendingServerDeletions`.
-pub async fn drain_sync_queue(
-    client: &WebDavClient,
-    app: &AppHandle,
+pub async fn drain_sync_queue<B: SyncBackend, R: Runtime>(
+    backend: &B,
+    app: &AppHandle<R>,
     device_id: &str,
     retention_ms: i64,
 ) {
-    // Ausstehende Löschungen
+    // Ausstehende Ordner-Umbenennungen zuerst: danach liegen die Notizen bereits am Ziel,
+    // ihre Move-Einträge finden am alten Pfad nichts mehr und sind sofort erledigt.
+    // Klappt das MOVE der Collection nicht, verschieben die Move-Einträge einzeln.
+    for rename in load_renames(app) {
+        match backend.move_folder(&rename.from, &rename.to).await {
+            Ok(moved) => {
+                if !moved {
+                    eprintln!(
+                        "[drain_sync_queue] Ordner '{}' → '{}' nicht als Ganzes verschiebbar",
+                        rename.from, rename.to
+                    );
+                }
+                let remaining: Vec<_> = load_renames(app)
+                    .into_iter()
+                    .filter(|r| *r != rename)
+                    .collect();
+                save_renames(app, &remaining);
+            }
+            Err(e) => eprintln!(
+                "[drain_sync_queue] MOVE Ordner '{}' fehlgeschlagen, bleibt in Queue: {}",
+                rename.from, e
+            ),
+        }
+    }
+
+    // Ausstehende Löschungen und Moves
     let deletions = all_deletions(app);
     if !deletions.is_empty() {
         let now = chrono::Utc::now().timestamp_millis();
         let mut ledger_ids: Vec<String> = Vec::new(); // echte Löschungen → ins Ledger
-        let mut move_ids: Vec<String> = Vec::new(); // Move-Cleanup → kein Ledger
+        let mut move_ids: Vec<String> = Vec::new(); // Moves → kein Ledger
 
         for d in &deletions {
-            match client
-                .delete_note_by_id_folder(&d.id, d.folder.as_deref())
-                .await
-            {
+            let result = if d.is_move {
+                let note = local_store::get_note(app, &d.id);
+                let target_local_only = note
+                    .as_ref()
+                    .is_some_and(|n| local_store::is_local_only(app, n.folder_name.as_deref()));
+                match move_action(d.folder.as_deref(), note.as_ref(), target_local_only) {
+                    MoveAction::Move(to) => {
+                        backend
+                            .move_note(&d.id, d.folder.as_deref(), to.as_deref())
+                            .await
+                    }
+                    MoveAction::Delete => backend.delete_note(&d.id, d.folder.as_deref()).await,
+                    MoveAction::Done => Ok(()),
+                }
+            } else {
+                backend.delete_note(&d.id, d.folder.as_deref()).await
+            };
+            match result {
                 Ok(()) => {
                     if d.is_move {
                         move_ids.push(d.id.clone());
@@ -190,15 +301,16 @@ pub async fn drain_sync_queue(
                     }
                 }
                 Err(e) => eprintln!(
-                    "[drain_sync_queue] delete {} fehlgeschlagen, bleibt in Queue: {}",
+                    "[drain_sync_queue] delete/move {} fehlgeschlagen, bleibt in Queue: {}",
                     d.id, e
                 ),
             }
         }
 
         if !ledger_ids.is_empty() {
-            client
-                .append_deletions(&ledger_ids, device_id, now, retention_ms)
+            let stamp = local_store::next_hlc(app);
+            backend
+                .append_deletions(&ledger_ids, device_id, now, Some(&stamp), retention_ms)
                 .await;
         }
         let all_done: Vec<String> = ledger_ids.into_iter().chain(move_ids).collect();
@@ -210,8 +322,9 @@ pub async fn drain_sync_queue(
     // Ausstehende Ordner-Tombstones
     for name in all_folder_tombstones(app) {
         let now = chrono::Utc::now().timestamp_millis();
+        let stamp = local_store::next_hlc(app);
         let name_c = name.clone();
-        match client
+        match backend
             .write_folders_meta_merged(move |mut existing| {
                 if let Some(pos) = existing
                     .iter()
@@ -219,14 +332,16 @@ pub async fn drain_sync_queue(
                 {
                     existing[pos].deleted = true;
                     existing[pos].updated_at = now;
+                    existing[pos].set_hlc(&stamp);
                 } else {
-                    existing.push(FolderMeta {
+                    let mut meta = FolderMeta {
                         name: name_c.clone(),
-                        color: None,
                         updated_at: now,
                         deleted: true,
-                        local_only: false,
-                    });
+                        ..FolderMeta::default()
+                    };
+                    meta.set_hlc(&stamp);
+                    existing.push(meta);
                 }
                 existing
             })
@@ -234,7 +349,7 @@ pub async fn drain_sync_queue(
         {
             Ok(_) => {
                 remove_folder_tombstone(app, &name);
-                client.delete_folder_dirs(&name).await;
+                backend.delete_folder(&name).await;
             }
             Err(e) => eprintln!(
                 "[drain_sync_queue] tombstone '{}' fehlgeschlagen: {}",
@@ -274,6 +389,41 @@ mod tests {
         assert_eq!(restored.folder, None);
     }
 
+    #[test]
+    fn test_move_action_targets_current_folder() {
+        let mut note = Note::new("T".to_string(), "dev".to_string());
+        note.folder_name = Some("Home".to_string());
+        assert_eq!(
+            move_action(Some("Work"), Some(&note), false),
+            MoveAction::Move(Some("Home".to_string()))
+        );
+        note.folder_name = None;
+        assert_eq!(
+            move_action(Some("Work"), Some(&note), false),
+            MoveAction::Move(None)
+        );
+    }
+
+    #[test]
+    fn test_move_action_back_to_origin_is_noop() {
+        let mut note = Note::new("T".to_string(), "dev".to_string());
+        note.folder_name = Some("work".to_string());
+        assert_eq!(
+            move_action(Some("Work"), Some(&note), false),
+            MoveAction::Done
+        );
+    }
+
+    #[test]
+    fn test_move_action_deletes_for_missing_note_or_local_only_target() {
+        let note = Note::new("T".to_string(), "dev".to_string());
+        assert_eq!(move_action(Some("Work"), None, false), MoveAction::Delete);
+        assert_eq!(
+            move_action(Some("Work"), Some(&note), true),
+            MoveAction::Delete
+        );
+    }
+
     #[test]
     fn test_pending_deletion_is_move_default() {
         // Alte JSON-Einträge ohne is_move-Feld müssen als false deserialisiert werden
@@ -0,0 +1,426 @@
+//! Minimaler In-Process-WebDAV-Server für Tests: GET/PUT/DELETE mit ETags und
+//! If-Match/If-None-Match, LOCK/UNLOCK und MOVE (abschaltbar), OPTIONS, PROPFIND und MKCOL.
+//! Eine Verbindung pro Request. Requests in Absolute-Form (`GET http://host/pfad`) werden
+//! wie ein Forward-Proxy angenommen, damit Proxy-Einstellungen testbar sind.
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+#[derive(Default)]
+struct State {
+    /// Pfad → (Inhalt, Version); ETag = `"v{version}"`
+    files: HashMap<String, (String, u64)>,
+    /// Verzeichnisse (mit Trailing-Slash)
+    dirs: BTreeSet<String>,
+    /// Pfad → Lock-Token
+    locks: HashMap<String, String>,
+    next_version: u64,
+    lock_support: bool,
+    /// MOVE mit 405 ablehnen (Server ohne MOVE)
+    move_disabled: bool,
+    /// `quota-used-bytes`/`quota-available-bytes` der per PROPFIND abgefragten Collection
+    quota: Option<(i64, i64)>,
+    /// Verzögerung der GET-Antworten (Stand wird beim Empfang gelesen), um zwei
+    /// Schreiber zuverlässig überlappen zu lassen
+    get_delay: Duration,
+    /// Protokoll aller Requests als `"METHOD /pfad"`
+    log: Vec<String>,
+    /// Aufgezeichnete Antworten `(METHOD, Pfad)` → (Status, Body), vor der Simulation geprüft
+    recorded: HashMap<(String, String), (u16, String)>,
+    /// `Proxy-Authorization` aller in Absolute-Form (also über einen Proxy) empfangenen Requests
+    proxied: Vec<Option<String>>,
+}
+
+pub struct TestServer {
+    pub url: String,
+    state: Arc<Mutex<State>>,
+}
+
+struct Request {
+    method: String,
+    path: String,
+    /// Request-Target war eine absolute URL
+    proxied: bool,
+    headers: HashMap<String, String>,
+    body: String,
+}
+
+impl TestServer {
+    pub async fn start(lock_support: bool) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let state = Arc::new(Mutex::new(State {
+            lock_support,
+            ..State::default()
+        }));
+        let shared = state.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(handle(stream, shared.clone()));
+            }
+        });
+        Self { url, state }
+    }
+
+    pub fn set_get_delay(&self, delay: Duration) {
+        self.state.lock().unwrap().get_delay = delay;
+    }
+
+    pub fn disable_move(&self) {
+        self.state.lock().unwrap().move_disabled = true;
+    }
+
+    pub fn set_quota(&self, used: i64, available: i64) {
+        self.state.lock().unwrap().quota = Some((used, available));
+    }
+
+    /// Beantwortet `method` auf `path` fortan mit einer aufgezeichneten Server-Antwort
+    /// (z.B. Nextcloud-spezifische Endpunkte, die der Test-Server nicht nachbildet).
+    pub fn record(&self, method: &str, path: &str, status: u16, body: &str) {
+        self.state.lock().unwrap().recorded.insert(
+            (method.to_string(), path.to_string()),
+            (status, body.to_string()),
+        );
+    }
+
+    pub fn put_file(&self, path: &str, body: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.next_version += 1;
+        let version = state.next_version;
+        state
+            .files
+            .insert(path.to_string(), (body.to_string(), version));
+    }
+
+    pub fn mkdir(&self, path: &str) {
+        self.state.lock().unwrap().dirs.insert(path.to_string());
+    }
+
+    pub fn file_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.state.lock().unwrap().files.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    pub fn dir_names(&self) -> Vec<String> {
+        self.state.lock().unwrap().dirs.iter().cloned().collect()
+    }
+
+    pub fn file(&self, path: &str) -> Option<String> {
+        self.state
+            .lock()
+            .unwrap()
+            .files
+            .get(path)
+            .map(|(body, _)| body.clone())
+    }
+
+    pub fn count(&self, method: &str) -> usize {
+        self.state
+            .lock()
+            .unwrap()
+            .log
+            .iter()
+            .filter(|l| l.split(' ').next() == Some(method))
+            .count()
+    }
+
+    pub fn is_locked(&self, path: &str) -> bool {
+        self.state.lock().unwrap().locks.contains_key(path)
+    }
+
+    pub fn proxied(&self) -> Vec<Option<String>> {
+        self.state.lock().unwrap().proxied.clone()
+    }
+}
+
+async fn handle(mut stream: TcpStream, state: Arc<Mutex<State>>) {
+    let Some(request) = read_request(&mut stream).await else {
+        return;
+    };
+    let (status, headers, body, delay) = respond(&request, &state);
+    if !delay.is_zero() {
+        tokio::time::sleep(delay).await;
+    }
+    let mut response = format!(
+        "HTTP/1.1 {} X\r\nContent-Length: {}\r\nConnection: close\r\n",
+        status,
+        body.len()
+    );
+    for (name, value) in headers {
+        response.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    response.push_str("\r\n");
+    response.push_str(&body);
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+async fn read_request(stream: &mut TcpStream) -> Option<Request> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let header_end = loop {
+        let n = stream.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+    let head = String::from_utf8_lossy(&buf[..header_end]).to_string();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next()?.split(' ');
+    let method = request_line.next()?.to_string();
+    let mut target = request_line.next()?;
+    let proxied = target.starts_with("http://");
+    if proxied {
+        let rest = &target["http://".len()..];
+        target = rest.find('/').map_or("/", |i| &rest[i..]);
+    }
+    let path = urlencoding::decode(target.split('?').next()?)
+        .ok()?
+        .into_owned();
+    let headers: HashMap<String, String> = lines
+        .filter_map(|l| l.split_once(':'))
+        .map(|(k, v)| (k.trim().to_ascii_lowercase(), v.trim().to_string()))
+        .collect();
+    let length: usize = headers
+        .get("content-length")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+    while buf.len() < header_end + length {
+        let n = stream.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+    let body = String::from_utf8_lossy(&buf[header_end..]).to_string();
+    Some(Request {
+        method,
+        path,
+        proxied,
+        headers,
+        body,
+    })
+}
+
+type Response = (u16, Vec<(&'static str, String)>, String, Duration);
+
+fn respond(request: &Request, state: &Mutex<State>) -> Response {
+    let mut state = state.lock().unwrap();
+    state
+        .log
+        .push(format!("{} {}", request.method, request.path));
+    if request.proxied {
+        let auth = request.headers.get("proxy-authorization").cloned();
+        state.proxied.push(auth);
+    }
+    let path = request.path.as_str();
+    if let Some((status, body)) = state
+        .recorded
+        .get(&(request.method.clone(), path.to_string()))
+    {
+        return (*status, vec![], body.clone(), Duration::ZERO);
+    }
+    let etag = |version: u64| format!("\"v{}\"", version);
+    let none = Duration::ZERO;
+
+    match request.method.as_str() {
+        "GET" => match state.files.get(path) {
+            Some((body, version)) => (
+                200,
+                vec![("ETag", etag(*version))],
+                body.clone(),
+                state.get_delay,
+            ),
+            None => (404, vec![], String::new(), state.get_delay),
+        },
+        "PUT" | "DELETE" => {
+            if let Some(token) = state.locks.get(path) {
+                let submitted = request.headers.get("if").is_some_and(|h| h.contains(token));
+                if !submitted {
+                    return (423, vec![], String::new(), none);
+                }
+            }
+            let current = state.files.get(path).map(|(_, v)| etag(*v));
+            if let Some(expected) = request.headers.get("if-match") {
+                if current.as_ref() != Some(expected) {
+                    return (412, vec![], String::new(), none);
+                }
+            }
+            if request.headers.get("if-none-match").map(String::as_str) == Some("*")
+                && current.is_some()
+            {
+                return (412, vec![], String::new(), none);
+            }
+            if request.method == "DELETE" {
+                let removed = state.files.remove(path).is_some() || state.dirs.remove(path);
+                let status = if removed { 204 } else { 404 };
+                return (status, vec![], String::new(), none);
+            }
+            state.next_version += 1;
+            let version = state.next_version;
+            state
+                .files
+                .insert(path.to_string(), (request.body.clone(), version));
+            let status = if current.is_some() { 204 } else { 201 };
+            (status, vec![("ETag", etag(version))], String::new(), none)
+        }
+        "OPTIONS" => {
+            let dav = if state.lock_support { "1, 2" } else { "1" };
+            let date = chrono::Utc::now()
+                .format("%a, %d %b %Y %H:%M:%S GMT")
+                .to_string();
+            let headers = vec![
+                ("DAV", dav.to_string()),
+                (
+                    "Allow",
+                    "OPTIONS, GET, PUT, DELETE, PROPFIND, MKCOL, MOVE, LOCK, UNLOCK".to_string(),
+                ),
+                ("Date", date),
+                ("Server", "test-server".to_string()),
+            ];
+            (200, headers, String::new(), none)
+        }
+        "MKCOL" => {
+            if state.dirs.contains(path) {
+                return (405, vec![], String::new(), none);
+            }
+            state.dirs.insert(path.to_string());
+            (201, vec![], String::new(), none)
+        }
+        "PROPFIND" => {
+            if !state.dirs.contains(path) {
+                return (404, vec![], String::new(), none);
+            }
+            let mut body = String::from(r#"<d:multistatus xmlns:d="DAV:">"#);
+            let collection = "<d:resourcetype><d:collection/></d:resourcetype>".to_string();
+            let quota = state.quota.map(|(used, available)| {
+                format!(
+                    "<d:quota-used-bytes>{}</d:quota-used-bytes>\
+                     <d:quota-available-bytes>{}</d:quota-available-bytes>",
+                    used, available
+                )
+            });
+            body.push_str(&propfind_response(
+                path,
+                &format!("{}{}", collection, quota.unwrap_or_default()),
+            ));
+            if request.headers.get("depth").map(String::as_str) != Some("0") {
+                let is_child = |p: &str| {
+                    p.strip_prefix(path)
+                        .is_some_and(|rest| !rest.trim_end_matches('/').contains('/'))
+                };
+                for dir in state.dirs.iter().filter(|d| *d != path && is_child(d)) {
+                    body.push_str(&propfind_response(dir, &collection));
+                }
+                for (file, (content, version)) in state.files.iter().filter(|(f, _)| is_child(f)) {
+                    let props = format!(
+                        "<d:resourcetype/><d:getetag>{}</d:getetag>\
+                         <d:getcontentlength>{}</d:getcontentlength>",
+                        etag(*version),
+                        content.len()
+                    );
+                    body.push_str(&propfind_response(file, &props));
+                }
+            }
+            body.push_str("</d:multistatus>");
+            (
+                207,
+                vec![("Content-Type", "application/xml".to_string())],
+                body,
+                none,
+            )
+        }
+        "MOVE" if state.move_disabled => (405, vec![], String::new(), none),
+        "MOVE" => {
+            // Destination ist eine absolute URL — nur der Pfad zählt
+            let Some(target) = request
+                .headers
+                .get("destination")
+                .and_then(|d| d.splitn(4, '/').nth(3))
+                .and_then(|p| urlencoding::decode(p).ok())
+                .map(|p| format!("/{}", p))
+            else {
+                return (400, vec![], String::new(), none);
+            };
+            let exists = |p: &str| state.files.contains_key(p) || state.dirs.contains(p);
+            if !exists(path) {
+                return (404, vec![], String::new(), none);
+            }
+            if exists(&target) {
+                return (412, vec![], String::new(), none);
+            }
+            // Datei bzw. Collection samt Inhalt umhängen; Versionen (ETags) bleiben
+            let rename = |p: &str| {
+                p.strip_prefix(path)
+                    .map(|rest| format!("{}{}", target, rest))
+            };
+            let files: Vec<String> = state
+                .files
+                .keys()
+                .filter(|f| rename(f).is_some())
+                .cloned()
+                .collect();
+            for file in files {
+                let entry = state.files.remove(&file).unwrap();
+                state.files.insert(rename(&file).unwrap(), entry);
+            }
+            let dirs: Vec<String> = state
+                .dirs
+                .iter()
+                .filter(|d| rename(d).is_some())
+                .cloned()
+                .collect();
+            for dir in dirs {
+                state.dirs.remove(&dir);
+                state.dirs.insert(rename(&dir).unwrap());
+            }
+            (201, vec![], String::new(), none)
+        }
+        "LOCK" if !state.lock_support => (405, vec![], String::new(), none),
+        "LOCK" => {
+            if state.locks.contains_key(path) {
+                return (423, vec![], String::new(), none);
+            }
+            state.next_version += 1;
+            let token = format!("opaquelocktoken:test-{}", state.next_version);
+            state.locks.insert(path.to_string(), token.clone());
+            (
+                200,
+                vec![("Lock-Token", format!("<{}>", token))],
+                String::new(),
+                none,
+            )
+        }
+        "UNLOCK" => {
+            let submitted = request
+                .headers
+                .get("lock-token")
+                .cloned()
+                .unwrap_or_default();
+            match state.locks.get(path) {
+                Some(token) if submitted.contains(token.as_str()) => {
+                    state.locks.remove(path);
+                    (204, vec![], String::new(), none)
+                }
+                _ => (409, vec![], String::new(), none),
+            }
+        }
+        _ => (405, vec![], String::new(), none),
+    }
+}
+
+fn propfind_response(href: &str, props: &str) -> String {
+    format!(
+        "<d:response><d:href>{}</d:href><d:propstat><d:prop>{}</d:prop>\
+         <d:status>HTTP/1.1 200 OK</d:status></d:propstat></d:response>",
+        href, props
+    )
+}
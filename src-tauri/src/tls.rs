@@ -0,0 +1,369 @@
+//! TLS-Vertrauensrichtlinie für den WebDAV-Client.
+//!
+//! - `system`: Betriebssystem-Zertifikatsspeicher (Standard)
+//! - `custom_ca`: zusätzlich eine eigene CA aus einer PEM-Datei (Self-Hosting)
+//! - `pinned`: nur ein Zertifikat mit festem SHA-256-Fingerprint, beim ersten
+//!   `connect` per Trust-on-first-use übernommen
+//! - `insecure`: keine Prüfung — nur als ausdrückliches Opt-in
+
+use std::sync::{Arc, Mutex};
+
+use reqwest::ClientBuilder;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{CertificateError, DigitallySignedStruct, OtherError, SignatureScheme};
+use sha2::{Digest, Sha256};
+
+use crate::error::{AppError, Result};
+use crate::proxy::ProxyPolicy;
+use crate::storage::Settings;
+
+pub const TLS_MODE_CUSTOM_CA: &str = "custom_ca";
+pub const TLS_MODE_PINNED: &str = "pinned";
+pub const TLS_MODE_INSECURE: &str = "insecure";
+
+/// Aus den Settings abgeleitete TLS-Richtlinie.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TlsPolicy {
+    System,
+    /// Pfad zu einer PEM-Datei mit einem oder mehreren CA-Zertifikaten
+    CustomCa(String),
+    /// SHA-256-Fingerprint des Server-Zertifikats; None = noch nicht gepinnt (TOFU ausstehend)
+    Pinned(Option<String>),
+    Insecure,
+}
+
+impl TlsPolicy {
+    pub fn from_settings(settings: &Settings) -> Self {
+        match settings.tls_mode.as_str() {
+            TLS_MODE_CUSTOM_CA => TlsPolicy::CustomCa(settings.tls_ca_file.trim().to_string()),
+            TLS_MODE_PINNED => {
+                TlsPolicy::Pinned(normalize_fingerprint(&settings.tls_pinned_fingerprint))
+            }
+            TLS_MODE_INSECURE => TlsPolicy::Insecure,
+            _ => TlsPolicy::System,
+        }
+    }
+
+    /// Konfiguriert den reqwest-Builder entsprechend der Richtlinie.
+    pub fn apply(&self, builder: ClientBuilder) -> Result<ClientBuilder> {
+        match self {
+            TlsPolicy::System => Ok(builder),
+            TlsPolicy::CustomCa(path) => {
+                if path.is_empty() {
+                    return Err(AppError::TlsConfig("Keine CA-Datei angegeben".to_string()));
+                }
+                let pem = std::fs::read(path)
+                    .map_err(|e| AppError::TlsConfig(format!("CA-Datei {}: {}", path, e)))?;
+                let certs = reqwest::Certificate::from_pem_bundle(&pem)
+                    .map_err(|e| AppError::TlsConfig(format!("CA-Datei {}: {}", path, e)))?;
+                if certs.is_empty() {
+                    return Err(AppError::TlsConfig(format!(
+                        "CA-Datei {} enthält kein Zertifikat",
+                        path
+                    )));
+                }
+                Ok(certs
+                    .into_iter()
+                    .fold(builder, |b, cert| b.add_root_certificate(cert)))
+            }
+            TlsPolicy::Pinned(Some(expected)) => Ok(builder
+                .use_preconfigured_tls(pinned_config(PinVerifier::new(Some(expected.clone())))?)),
+            TlsPolicy::Pinned(None) => Err(AppError::TlsConfig(
+                "Zertifikat noch nicht gepinnt — bitte neu verbinden".to_string(),
+            )),
+            TlsPolicy::Insecure => {
+                eprintln!("[TLS] WARNUNG: Zertifikatsprüfung deaktiviert (insecure-Modus)");
+                Ok(builder.danger_accept_invalid_certs(true))
+            }
+        }
+    }
+}
+
+/// SHA-256-Fingerprint eines DER-Zertifikats als `AB:CD:…` (Großbuchstaben, Doppelpunkte).
+pub fn fingerprint(der: &[u8]) -> String {
+    Sha256::digest(der)
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// Vom Nutzer eingegebenen Fingerprint normalisieren (mit/ohne Doppelpunkte, beliebige
+/// Groß-/Kleinschreibung). Ungültige oder leere Eingaben → None.
+pub fn normalize_fingerprint(input: &str) -> Option<String> {
+    let hex: String = input
+        .chars()
+        .filter(|c| !matches!(c, ':' | ' ' | '-'))
+        .collect();
+    if hex.len() != 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let upper = hex.to_ascii_uppercase();
+    Some(
+        upper
+            .as_bytes()
+            .chunks(2)
+            .map(|c| String::from_utf8_lossy(c).into_owned())
+            .collect::<Vec<_>>()
+            .join(":"),
+    )
+}
+
+/// Fehler des Pin-Verifiers; wird über die Fehlerkette bis zu `AppError::CertificateMismatch`
+/// durchgereicht.
+#[derive(Debug)]
+struct PinMismatch {
+    actual: String,
+}
+
+impl std::fmt::Display for PinMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Zertifikat-Fingerprint {} entspricht nicht dem Pin",
+            self.actual
+        )
+    }
+}
+
+impl std::error::Error for PinMismatch {}
+
+/// Sucht in der Fehlerkette eines Requests nach einer Pin-Abweichung und liefert den
+/// Fingerprint des tatsächlich präsentierten Zertifikats.
+pub fn pin_mismatch(err: &(dyn std::error::Error + 'static)) -> Option<String> {
+    let mut current = Some(err);
+    while let Some(e) = current {
+        if let Some(m) = e.downcast_ref::<PinMismatch>() {
+            return Some(m.actual.clone());
+        }
+        // tokio-rustls verpackt den rustls-Fehler in einen io::Error, dessen source()
+        // den inneren Fehler überspringt
+        let inner = e
+            .downcast_ref::<std::io::Error>()
+            .and_then(|io| io.get_ref())
+            .map(|i| i as &(dyn std::error::Error + 'static));
+        for candidate in [Some(e), inner].into_iter().flatten() {
+            if let Some(rustls::Error::InvalidCertificate(CertificateError::Other(other))) =
+                candidate.downcast_ref::<rustls::Error>()
+            {
+                if let Some(m) = other.0.downcast_ref::<PinMismatch>() {
+                    return Some(m.actual.clone());
+                }
+            }
+        }
+        current = e.source();
+    }
+    None
+}
+
+/// Prüft das Server-Zertifikat ausschließlich gegen den Pin (Hostname, Ablaufdatum und
+/// Aussteller sind bei gepinnten Self-Signed-Zertifikaten bewusst irrelevant). Die
+/// Handshake-Signatur wird weiterhin geprüft — ohne passenden privaten Schlüssel hilft
+/// das gepinnte Zertifikat einem Angreifer nicht. `expected = None` → Capture-Modus
+/// für den TOFU-Probe: jedes Zertifikat akzeptieren und den Fingerprint merken.
+#[derive(Debug)]
+struct PinVerifier {
+    expected: Option<String>,
+    seen: Arc<Mutex<Option<String>>>,
+    provider: Arc<CryptoProvider>,
+}
+
+impl PinVerifier {
+    fn new(expected: Option<String>) -> Self {
+        Self {
+            expected,
+            seen: Arc::new(Mutex::new(None)),
+            provider: Arc::new(rustls::crypto::ring::default_provider()),
+        }
+    }
+}
+
+impl ServerCertVerifier for PinVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> std::result::Result<ServerCertVerified, rustls::Error> {
+        let actual = fingerprint(end_entity.as_ref());
+        *crate::lock_recover(&self.seen) = Some(actual.clone());
+        match &self.expected {
+            Some(expected) if *expected != actual => Err(rustls::Error::InvalidCertificate(
+                CertificateError::Other(OtherError(Arc::new(PinMismatch { actual }))),
+            )),
+            _ => Ok(ServerCertVerified::assertion()),
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+fn pinned_config(verifier: PinVerifier) -> Result<rustls::ClientConfig> {
+    let config = rustls::ClientConfig::builder_with_provider(verifier.provider.clone())
+        .with_safe_default_protocol_versions()
+        .map_err(|e| AppError::TlsConfig(e.to_string()))?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(verifier))
+        .with_no_client_auth();
+    Ok(config)
+}
+
+/// Trust-on-first-use: Verbindet sich ohne Zugangsdaten mit `url` (über `proxy`) und liefert
+/// den SHA-256-Fingerprint des präsentierten Server-Zertifikats.
+pub async fn probe_certificate(url: &str, proxy: &ProxyPolicy) -> Result<String> {
+    if !url.trim().to_ascii_lowercase().starts_with("https://") {
+        return Err(AppError::TlsConfig(
+            "Zertifikat-Pinning erfordert eine https://-URL".to_string(),
+        ));
+    }
+    let verifier = PinVerifier::new(None);
+    let seen = verifier.seen.clone();
+    let client = proxy
+        .apply(reqwest::Client::builder())?
+        .use_preconfigured_tls(pinned_config(verifier)?)
+        .connect_timeout(std::time::Duration::from_secs(5))
+        .timeout(std::time::Duration::from_secs(15))
+        .build()
+        .map_err(|e| AppError::NetworkError(e.to_string()))?;
+    // Antwort-Status egal (401 ohne Zugangsdaten erwartet) — es zählt nur der Handshake
+    let result = client.request(reqwest::Method::OPTIONS, url).send().await;
+    let captured = crate::lock_recover(&seen).clone();
+    match (captured, result) {
+        (Some(fp), _) => Ok(fp),
+        (None, Err(e)) => Err(AppError::NetworkError(e.to_string())),
+        (None, Ok(_)) => Err(AppError::TlsConfig(
+            "Server hat kein Zertifikat präsentiert".to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(mode: &str, ca: &str, pin: &str) -> Settings {
+        Settings {
+            tls_mode: mode.to_string(),
+            tls_ca_file: ca.to_string(),
+            tls_pinned_fingerprint: pin.to_string(),
+            ..Settings::default()
+        }
+    }
+
+    #[test]
+    fn test_policy_from_settings() {
+        assert_eq!(
+            TlsPolicy::from_settings(&Settings::default()),
+            TlsPolicy::System
+        );
+        assert_eq!(
+            TlsPolicy::from_settings(&settings("custom_ca", " /etc/ca.pem ", "")),
+            TlsPolicy::CustomCa("/etc/ca.pem".to_string())
+        );
+        assert_eq!(
+            TlsPolicy::from_settings(&settings("pinned", "", "")),
+            TlsPolicy::Pinned(None)
+        );
+        assert_eq!(
+            TlsPolicy::from_settings(&settings("insecure", "", "")),
+            TlsPolicy::Insecure
+        );
+        // Unbekannter Modus fällt auf System-Trust zurück, nie auf insecure
+        assert_eq!(
+            TlsPolicy::from_settings(&settings("bogus", "", "")),
+            TlsPolicy::System
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_format() {
+        let fp = fingerprint(b"hello");
+        assert_eq!(fp.len(), 32 * 3 - 1);
+        assert!(fp.starts_with("2C:F2:4D:BA"));
+    }
+
+    #[test]
+    fn test_normalize_fingerprint_variants() {
+        let fp = fingerprint(b"hello");
+        let plain = fp.replace(':', "").to_lowercase();
+        assert_eq!(normalize_fingerprint(&plain), Some(fp.clone()));
+        assert_eq!(normalize_fingerprint(&fp), Some(fp));
+        assert_eq!(normalize_fingerprint("AB:CD"), None);
+        assert_eq!(normalize_fingerprint(""), None);
+    }
+
+    #[test]
+    fn test_pin_verifier_mismatch_and_capture() {
+        let cert = CertificateDer::from(b"not-really-a-cert".to_vec());
+        let name = ServerName::try_from("example.com").unwrap();
+        let now = UnixTime::now();
+
+        let ok = PinVerifier::new(Some(fingerprint(b"not-really-a-cert")));
+        assert!(ok.verify_server_cert(&cert, &[], &name, &[], now).is_ok());
+
+        let bad = PinVerifier::new(Some(fingerprint(b"other")));
+        let err = bad
+            .verify_server_cert(&cert, &[], &name, &[], now)
+            .unwrap_err();
+        // Wie tokio-rustls den Fehler verpackt
+        let wrapped = std::io::Error::new(std::io::ErrorKind::InvalidData, err);
+        assert_eq!(
+            pin_mismatch(&wrapped),
+            Some(fingerprint(b"not-really-a-cert"))
+        );
+
+        let capture = PinVerifier::new(None);
+        assert!(capture
+            .verify_server_cert(&cert, &[], &name, &[], now)
+            .is_ok());
+        assert_eq!(
+            *capture.seen.lock().unwrap(),
+            Some(fingerprint(b"not-really-a-cert"))
+        );
+    }
+
+    #[test]
+    fn test_custom_ca_missing_file_is_config_error() {
+        let policy = TlsPolicy::CustomCa("/nonexistent/ca.pem".to_string());
+        assert!(matches!(
+            policy.apply(reqwest::Client::builder()),
+            Err(AppError::TlsConfig(_))
+        ));
+    }
+}
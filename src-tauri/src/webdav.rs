@@ -1,80 +1,1072 @@
 use crate::error::{AppError, Result};
 use crate::folders::{parse_folders_json, sanitize_folder_name, FolderMeta};
+use crate::hlc::{self, Hlc, Stamped};
 use crate::markdown;
 use crate::models::{DeletionLedger, DeletionRecord, Note};
+use crate::multistatus::{self, DavEntry, Multistatus};
+use crate::proxy::ProxyPolicy;
+use crate::storage::{AuthScheme, Credentials};
+use crate::tls::{self, TlsPolicy};
 use base64::{engine::general_purpose::STANDARD, Engine};
+use futures_util::stream::{self, StreamExt};
+use md5::Md5;
 use regex::Regex;
-use reqwest::{Client, Method, StatusCode};
-use std::sync::LazyLock;
-
-/// UUID.json Pattern – compiled once at program start
-static UUID_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+use reqwest::header::{HeaderValue, AUTHORIZATION, WWW_AUTHENTICATE};
+use reqwest::{Client, Method, Request, RequestBuilder, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+
+/// Dateiname einer Notiz: `{uuid}.json` – compiled once at program start
+pub(crate) static UUID_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(
-        r"([0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12})\.json",
+        r"^([0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12})\.json$",
     )
     .expect("UUID pattern is valid")
 });
 
-/// Regex zum Extrahieren von WebDAV `<d:href>` (Namespace-Prefix-Varianten: d/D)
-static HREF_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
-    Regex::new(r"<[Dd]:[Hh][Rr][Ee][Ff]>([^<]+)</[Dd]:[Hh][Rr][Ee][Ff]>")
-        .expect("HREF pattern is valid")
-});
-
 /// PROPFIND and MKCOL are not in reqwest's built-in Method constants — define them once here
 /// rather than calling from_bytes().unwrap() at every call site.
-static PROPFIND: LazyLock<Method> =
+pub(crate) static PROPFIND: LazyLock<Method> =
     LazyLock::new(|| Method::from_bytes(b"PROPFIND").expect("PROPFIND is a valid HTTP method"));
-static MKCOL: LazyLock<Method> =
+pub(crate) static MKCOL: LazyLock<Method> =
     LazyLock::new(|| Method::from_bytes(b"MKCOL").expect("MKCOL is a valid HTTP method"));
+pub(crate) static REPORT: LazyLock<Method> =
+    LazyLock::new(|| Method::from_bytes(b"REPORT").expect("REPORT is a valid HTTP method"));
+pub(crate) static LOCK: LazyLock<Method> =
+    LazyLock::new(|| Method::from_bytes(b"LOCK").expect("LOCK is a valid HTTP method"));
+pub(crate) static UNLOCK: LazyLock<Method> =
+    LazyLock::new(|| Method::from_bytes(b"UNLOCK").expect("UNLOCK is a valid HTTP method"));
+pub(crate) static MOVE: LazyLock<Method> =
+    LazyLock::new(|| Method::from_bytes(b"MOVE").expect("MOVE is a valid HTTP method"));
+
+/// Lock-Timeout — danach gibt der Server die Datei auch ohne UNLOCK frei
+const LOCK_TIMEOUT_SECS: u64 = 60;
+/// Versuche, einen von einem anderen Gerät gehaltenen LOCK abzuwarten
+const LOCK_ATTEMPTS: u32 = 5;
+/// Merge-Versuche für geteilte Dateien ohne LOCK (bei 412 erneut lesen und mergen)
+pub(crate) const SHARED_FILE_ATTEMPTS: u32 = 5;
+
+/// User-Agent für Login Flow v2 — Nextcloud benennt das App-Passwort danach
+const LOGIN_FLOW_USER_AGENT: &str = "Simple Notes Desktop";
+
+/// Gleichzeitige Requests bei Massen-Operationen, falls nicht per Settings vorgegeben
+pub(crate) const DEFAULT_CONCURRENCY: usize = 4;
+/// Mehr parallele Requests bringen kaum Durchsatz, lösen aber Rate-Limits gehosteter Server aus
+pub(crate) const MAX_CONCURRENCY: usize = 8;
+
+/// Obergrenze für Folge-Requests bei gekürzten sync-collection-Antworten (507)
+const MAX_SYNC_PAGES: usize = 50;
+
+/// Vorbedingung für den JSON-Upload einer Notiz (Conditional PUT, RFC 7232).
+/// Verhindert, dass ein zwischenzeitlicher Upload eines anderen Geräts stillschweigend
+/// überschrieben wird — der Server antwortet dann mit 412 → `AppError::Conflict`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WriteCondition {
+    /// Unbedingter PUT (ETag unbekannt, z.B. Server ohne ETag-Support)
+    Unconditional,
+    /// Nur überschreiben, wenn die Server-Datei noch diesen ETag hat (`If-Match`)
+    IfMatch(String),
+    /// Nur anlegen, wenn am Pfad noch keine Datei existiert (`If-None-Match: *`)
+    IfNoneMatch,
+}
+
+/// Request-Fehler → `AppError`. Eine Pin-Abweichung irgendwo in der Fehlerkette wird zu
+/// `CertificateMismatch`; sonst `NetworkError` mit der vollständigen Ursachenkette
+/// (reqwest selbst meldet nur "error sending request for url …").
+pub(crate) fn network_error(err: reqwest::Error) -> AppError {
+    classify_network_error(&err)
+}
+
+pub(crate) fn classify_network_error(err: &reqwest::Error) -> AppError {
+    if let Some(actual) = tls::pin_mismatch(err) {
+        return AppError::CertificateMismatch(actual);
+    }
+    let mut message = err.to_string();
+    let mut source = std::error::Error::source(err);
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    AppError::NetworkError(message)
+}
+
+// ── Wiederholungen ──────────────────────────────────────────────────────────────
+
+/// Versuche pro Request (1 + Wiederholungen)
+pub(crate) const MAX_ATTEMPTS: u32 = 4;
+/// Basis des exponentiellen Backoffs (0.5 s, 1 s, 2 s, …)
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+/// Obergrenze eines einzelnen Backoff-Schritts
+const RETRY_MAX_BACKOFF: Duration = Duration::from_secs(8);
+/// Längeres `Retry-After` wird nicht abgewartet — der Fehler geht an den Aufrufer
+pub(crate) const RETRY_AFTER_LIMIT: Duration = Duration::from_secs(60);
+/// Wiederholungen pro Sync-Lauf über alle Requests (siehe `with_retry_budget`)
+pub const SYNC_RETRY_BUDGET: u32 = 20;
+
+/// Vorübergehende HTTP-Status: Rate-Limit, Gateway-/Backend-Ausfall
+pub(crate) fn is_transient_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::REQUEST_TIMEOUT
+            | StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// Nur idempotente Methoden werden wiederholt (RFC 9110 §9.2.2; REPORT ist lesend).
+/// LOCK, MOVE und POST könnten bei einem verlorenen Response doppelt wirken.
+fn is_idempotent(method: &Method) -> bool {
+    *method == Method::GET
+        || *method == Method::HEAD
+        || *method == Method::OPTIONS
+        || *method == Method::PUT
+        || *method == Method::DELETE
+        || *method == *PROPFIND
+        || *method == *MKCOL
+        || *method == *REPORT
+}
+
+/// Fehlerhafter HTTP-Status → `AppError`; vorübergehende Status werden zu `ServerBusy`,
+/// damit Aufrufer sie per `is_retryable` von dauerhaften Fehlern unterscheiden können.
+/// 507 (Quota voll) wird zu `InsufficientStorage` — wiederholen hilft erst nach Aufräumen.
+pub(crate) fn status_error(status: StatusCode, message: String) -> AppError {
+    if is_transient_status(status) {
+        AppError::ServerBusy(message)
+    } else if status == StatusCode::INSUFFICIENT_STORAGE {
+        AppError::InsufficientStorage(message)
+    } else {
+        AppError::WebDav(message)
+    }
+}
+
+/// `Retry-After` als Sekunden oder HTTP-Datum (RFC 9110 §10.2.3).
+pub(crate) fn parse_retry_after(
+    value: &str,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (date.with_timezone(&chrono::Utc) - now)
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+/// Exponentieller Backoff mit Jitter: zufällig in [d/2, d] mit d = base · 2^attempt,
+/// damit mehrere Geräte nach einem Ausfall nicht im Gleichschritt wiederkommen.
+pub(crate) fn backoff_delay(attempt: u32, jitter: f64) -> Duration {
+    let full = RETRY_BASE_DELAY
+        .saturating_mul(1 << attempt.min(16))
+        .min(RETRY_MAX_BACKOFF);
+    full.mul_f64(0.5 + 0.5 * jitter.clamp(0.0, 1.0))
+}
+
+/// Zufallswert in [0, 1) für den Backoff-Jitter
+pub(crate) fn jitter() -> f64 {
+    (uuid::Uuid::new_v4().as_u128() as u64 >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// ETag aus den Response-Headern (unverändert inkl. Anführungszeichen / `W/`-Präfix).
+pub(crate) fn etag_of(response: &reqwest::Response) -> Option<String> {
+    response
+        .headers()
+        .get(reqwest::header::ETAG)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_owned)
+}
+
+/// Eine Notiz-Datei aus dem PROPFIND-Listing (ohne Inhalt).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteListing {
+    pub id: String,
+    /// Ordner laut Server-Pfad (None = Root-Ebene)
+    pub folder: Option<String>,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+/// Notiz-ID aus einem Multistatus-Eintrag (`{uuid}.json`, lowercased), sonst None.
+fn note_id(entry: &DavEntry) -> Option<String> {
+    if entry.is_collection() {
+        return None;
+    }
+    UUID_PATTERN
+        .captures(&entry.name())
+        .map(|c| c[1].to_lowercase())
+}
+
+/// Alle `{uuid}.json`-Einträge samt `getetag`/`getlastmodified` einer Depth-1-PROPFIND-Antwort.
+fn parse_note_listing(ms: &Multistatus, folder: Option<&str>) -> Vec<NoteListing> {
+    let mut result: Vec<NoteListing> = Vec::new();
+    for entry in &ms.responses {
+        let Some(id) = note_id(entry) else {
+            continue;
+        };
+        if !result.iter().any(|e| e.id == id) {
+            result.push(NoteListing {
+                id,
+                folder: folder.map(str::to_owned),
+                etag: entry.etag().map(str::to_owned),
+                last_modified: entry.last_modified().map(str::to_owned),
+            });
+        }
+    }
+    result
+}
+
+/// Unter diesem freien Platz warnt die App vor vollem Speicher
+const LOW_SPACE_BYTES: i64 = 10 * 1024 * 1024;
+
+/// Belegung eines Ordners (None = Root-Ebene): Notiz-JSONs und Markdown-Spiegel getrennt.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct FolderUsage {
+    pub folder: Option<String>,
+    pub notes: usize,
+    pub json_bytes: u64,
+    pub markdown_bytes: u64,
+}
+
+/// Speicherbelegung der Notizen am Server plus Quota des Sync-Ordners (RFC 4331).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct StorageUsage {
+    pub quota_used_bytes: Option<i64>,
+    /// Negativ (Nextcloud: unbegrenzt/unbekannt) oder None = kein Limit bekannt
+    pub quota_available_bytes: Option<i64>,
+    /// Freier Platz unter `LOW_SPACE_BYTES` oder unter 5 % der Quota — Uploads scheitern bald mit 507
+    pub low_space: bool,
+    pub notes: usize,
+    pub json_bytes: u64,
+    pub markdown_bytes: u64,
+    pub folders: Vec<FolderUsage>,
+}
+
+/// Wird der Platz knapp? Nur bei bekanntem, nicht-negativem `available`.
+fn is_low_space(used: Option<i64>, available: Option<i64>) -> bool {
+    match available {
+        Some(available) if available >= 0 => {
+            let total = used.unwrap_or(0).max(0) + available;
+            available < LOW_SPACE_BYTES || available * 20 < total
+        }
+        _ => false,
+    }
+}
+
+/// Anzahl und Gesamtgröße (`getcontentlength`) der Dateien eines Listings, die `matches` erfüllen.
+fn tally(listing: &Multistatus, matches: impl Fn(&DavEntry) -> bool) -> (usize, u64) {
+    listing
+        .responses
+        .iter()
+        .filter(|e| !e.is_collection() && matches(e))
+        .fold((0, 0), |(count, bytes), e| {
+            (count + 1, bytes + e.content_length().unwrap_or(0))
+        })
+}
+
+/// Eine ältere Fassung der JSON-Datei einer Notiz, wie sie die Nextcloud-Versionierung aufbewahrt.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ServerVersion {
+    /// Letztes Pfadsegment im Versions-Endpunkt (bei Nextcloud der Unix-Zeitstempel)
+    pub version_id: String,
+    /// Zeitpunkt der Version in ms seit Epoch
+    pub timestamp: i64,
+    pub size: Option<u64>,
+}
+
+/// Versions-Wurzel des Nutzers aus einer Nextcloud-WebDAV-URL
+/// (`…/remote.php/dav/files/{user}` → `…/remote.php/dav/versions/{user}/versions`).
+/// Die alte `remote.php/webdav`-URL enthält die Nutzer-ID nicht und wird nicht unterstützt.
+fn nextcloud_versions_root(base_url: &str) -> Option<String> {
+    const FILES: &str = "/remote.php/dav/files/";
+    let start = base_url.find(FILES)?;
+    let user = base_url[start + FILES.len()..].split('/').next()?;
+    if user.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "{}/remote.php/dav/versions/{}/versions",
+        &base_url[..start],
+        user
+    ))
+}
+
+/// Versionen aus dem PROPFIND-Listing des Versions-Endpunkts, neueste zuerst.
+/// Zeitpunkt aus `getlastmodified`, ersatzweise aus dem Zeitstempel im Namen.
+fn parse_versions(listing: &Multistatus) -> Vec<ServerVersion> {
+    let mut versions: Vec<ServerVersion> = listing
+        .responses
+        .iter()
+        .filter(|e| !e.is_collection())
+        .filter_map(|e| {
+            let version_id = e.name();
+            let timestamp = e
+                .last_modified()
+                .and_then(|d| chrono::DateTime::parse_from_rfc2822(d).ok())
+                .map(|d| d.timestamp_millis())
+                .or_else(|| version_id.parse::<i64>().ok().map(|s| s * 1000))?;
+            Some(ServerVersion {
+                version_id,
+                timestamp,
+                size: e.content_length(),
+            })
+        })
+        .collect();
+    versions.sort_by_key(|v| std::cmp::Reverse(v.timestamp));
+    versions
+}
+
+/// Änderungen einer Collection seit einem Sync-Token (sync-collection REPORT, RFC 6578).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CollectionDelta {
+    /// Neuer Sync-Token für den nächsten REPORT
+    pub token: String,
+    /// Neue oder geänderte Notiz-Dateien (mit ETag/Last-Modified)
+    pub changed: Vec<NoteListing>,
+    /// IDs entfernter Notiz-Dateien (404-Member)
+    pub removed: Vec<String>,
+    /// Neue oder geänderte Unterordner (nur für die Root-Collection relevant)
+    pub changed_dirs: Vec<String>,
+    /// Entfernte Unterordner
+    pub removed_dirs: Vec<String>,
+}
+
+impl CollectionDelta {
+    /// Hängt eine Folgeseite an — spätere Einträge überschreiben frühere.
+    fn absorb(&mut self, next: CollectionDelta) {
+        self.token = next.token;
+        for entry in next.changed {
+            self.removed.retain(|id| *id != entry.id);
+            self.changed.retain(|e| e.id != entry.id);
+            self.changed.push(entry);
+        }
+        for id in next.removed {
+            self.changed.retain(|e| e.id != id);
+            if !self.removed.contains(&id) {
+                self.removed.push(id);
+            }
+        }
+        for dir in next.changed_dirs {
+            self.removed_dirs.retain(|d| *d != dir);
+            if !self.changed_dirs.contains(&dir) {
+                self.changed_dirs.push(dir);
+            }
+        }
+        for dir in next.removed_dirs {
+            self.changed_dirs.retain(|d| *d != dir);
+            if !self.removed_dirs.contains(&dir) {
+                self.removed_dirs.push(dir);
+            }
+        }
+    }
+}
+
+/// Wertet eine sync-collection-Antwort aus. `folder` = Collection (None = Root),
+/// `skip_segment` = letzter Pfadteil der Collection selbst (kein Unterordner).
+/// Rückgabe: Delta + ob der Server das Ergebnis gekürzt hat (507 → Folge-REPORT nötig).
+fn parse_sync_collection(
+    ms: &Multistatus,
+    folder: Option<&str>,
+    skip_segment: &str,
+) -> (CollectionDelta, bool) {
+    let mut delta = CollectionDelta {
+        token: ms.sync_token.clone().unwrap_or_default(),
+        ..Default::default()
+    };
+    let mut truncated = false;
+
+    for entry in &ms.responses {
+        if entry.status == Some(507) {
+            truncated = true;
+            continue;
+        }
+        let removed = entry.status == Some(404);
+        let name = entry.name();
+
+        if removed {
+            // Entfernte Member haben keine Props — Notiz oder Verzeichnis nur am Namen erkennbar
+            match UUID_PATTERN.captures(&name) {
+                Some(c) => delta.removed.push(c[1].to_lowercase()),
+                None => {
+                    if let Some(dir) = sanitize_folder_name(&name).filter(|d| d != skip_segment) {
+                        delta.removed_dirs.push(dir);
+                    }
+                }
+            }
+            continue;
+        }
+
+        if let Some(id) = note_id(entry) {
+            delta.changed.push(NoteListing {
+                id,
+                folder: folder.map(str::to_owned),
+                etag: entry.etag().map(str::to_owned),
+                last_modified: entry.last_modified().map(str::to_owned),
+            });
+        } else if entry.is_collection() && name != skip_segment {
+            if let Some(dir) = sanitize_folder_name(&name) {
+                delta.changed_dirs.push(dir);
+            }
+        }
+    }
+    (delta, truncated)
+}
+
+/// Stand einer geteilten Datei beim Lesen — bestimmt die Vorbedingung des PUT
+enum SharedFileVersion {
+    /// 404: Datei existiert (noch) nicht
+    Missing,
+    ETag(String),
+    /// Datei existiert, Server liefert aber keinen ETag
+    Unversioned,
+}
+
+/// Ergebnis eines `MOVE` (RFC 4918 §9.9) mit `Overwrite: F`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MoveOutcome {
+    Moved,
+    /// 404: Quelle existiert nicht (mehr)
+    SourceMissing,
+    /// 412: Am Ziel liegt bereits eine Ressource
+    TargetExists,
+    /// 405/501: Server kennt MOVE nicht
+    Unsupported,
+}
+
+/// Lock-Token aus `Lock-Token: <…>`-Header oder LOCK-Antwort-Body
+pub(crate) fn parse_lock_token(header: Option<&str>, body: &str) -> Option<String> {
+    header
+        .map(|h| {
+            h.trim()
+                .trim_start_matches('<')
+                .trim_end_matches('>')
+                .trim()
+        })
+        .filter(|t| !t.is_empty())
+        .map(str::to_owned)
+        .or_else(|| multistatus::parse_lock_token(body))
+}
+
+/// Maskiert Text für ein XML-Element (z.B. den Sync-Token im REPORT-Body).
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+// ── Authentifizierung ───────────────────────────────────────────────────────────
+
+/// Authentifizierung eines Clients, abgeleitet aus `Credentials::auth_scheme`.
+#[derive(Clone)]
+enum Auth {
+    /// Fester Authorization-Header: Basic (auch Nextcloud-App-Passwort) oder Bearer
+    Header(HeaderValue),
+    /// HTTP Digest — der Header hängt von Methode, URI und Server-Nonce ab
+    Digest(Arc<DigestAuth>),
+}
+
+impl Auth {
+    fn from_credentials(credentials: &Credentials) -> Result<Self> {
+        let value = match credentials.auth_scheme {
+            AuthScheme::Basic | AuthScheme::NextcloudLoginFlow => {
+                let auth = format!("{}:{}", credentials.username, credentials.password);
+                format!("Basic {}", STANDARD.encode(auth))
+            }
+            AuthScheme::Bearer => format!("Bearer {}", credentials.password.trim()),
+            AuthScheme::Digest => {
+                return Ok(Auth::Digest(Arc::new(DigestAuth::new(
+                    &credentials.username,
+                    &credentials.password,
+                ))))
+            }
+        };
+        let mut header = HeaderValue::from_str(&value).map_err(|_| AppError::InvalidCredentials)?;
+        header.set_sensitive(true);
+        Ok(Auth::Header(header))
+    }
+}
+
+/// Hash-Verfahren einer Digest-Challenge (RFC 7616 §3.3)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DigestAlgorithm {
+    Md5,
+    Md5Sess,
+    Sha256,
+    Sha256Sess,
+}
+
+impl DigestAlgorithm {
+    fn parse(name: Option<&str>) -> Option<Self> {
+        match name.map(str::to_ascii_uppercase).as_deref() {
+            None | Some("MD5") => Some(Self::Md5),
+            Some("MD5-SESS") => Some(Self::Md5Sess),
+            Some("SHA-256") => Some(Self::Sha256),
+            Some("SHA-256-SESS") => Some(Self::Sha256Sess),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Md5 => "MD5",
+            Self::Md5Sess => "MD5-sess",
+            Self::Sha256 => "SHA-256",
+            Self::Sha256Sess => "SHA-256-sess",
+        }
+    }
+
+    fn is_session(self) -> bool {
+        matches!(self, Self::Md5Sess | Self::Sha256Sess)
+    }
+
+    fn hash(self, data: &str) -> String {
+        let bytes = match self {
+            Self::Md5 | Self::Md5Sess => Md5::digest(data.as_bytes()).to_vec(),
+            Self::Sha256 | Self::Sha256Sess => Sha256::digest(data.as_bytes()).to_vec(),
+        };
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
+
+/// Vom Server erhaltene Digest-Challenge. Die Nonce wird für Folge-Requests
+/// wiederverwendet (mit steigendem `nc`), bis der Server sie als `stale` verwirft.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DigestChallenge {
+    realm: String,
+    nonce: String,
+    opaque: Option<String>,
+    algorithm: DigestAlgorithm,
+    /// Server bietet `qop=auth` an (auth-int wird nicht unterstützt)
+    qop_auth: bool,
+    stale: bool,
+}
+
+/// Parst die Parameter eines `WWW-Authenticate: Digest …`-Headers.
+/// `None` bei anderem Schema, fehlender Nonce oder unbekanntem Algorithmus.
+fn parse_digest_challenge(header: &str) -> Option<DigestChallenge> {
+    let (scheme, rest) = header.trim().split_once(' ')?;
+    if !scheme.eq_ignore_ascii_case("Digest") {
+        return None;
+    }
+
+    let mut params = std::collections::HashMap::new();
+    let mut chars = rest.chars().peekable();
+    loop {
+        while chars.peek().is_some_and(|c| *c == ',' || c.is_whitespace()) {
+            chars.next();
+        }
+        let key: String = chars
+            .by_ref()
+            .take_while(|c| *c != '=')
+            .collect::<String>()
+            .trim()
+            .to_ascii_lowercase();
+        if key.is_empty() {
+            break;
+        }
+        let mut value = String::new();
+        if chars.peek() == Some(&'"') {
+            chars.next();
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => value.extend(chars.next()),
+                    '"' => break,
+                    c => value.push(c),
+                }
+            }
+        } else {
+            value = chars
+                .by_ref()
+                .take_while(|c| *c != ',')
+                .collect::<String>()
+                .trim()
+                .to_string();
+        }
+        params.insert(key, value);
+    }
+
+    Some(DigestChallenge {
+        realm: params.get("realm").cloned().unwrap_or_default(),
+        nonce: params.get("nonce").filter(|n| !n.is_empty())?.clone(),
+        opaque: params.get("opaque").cloned(),
+        algorithm: DigestAlgorithm::parse(params.get("algorithm").map(String::as_str))?,
+        qop_auth: params
+            .get("qop")
+            .is_some_and(|q| q.split(',').any(|o| o.trim().eq_ignore_ascii_case("auth"))),
+        stale: params
+            .get("stale")
+            .is_some_and(|s| s.eq_ignore_ascii_case("true")),
+    })
+}
+
+/// Digest-Zustand eines Clients: zuletzt akzeptierte Challenge und Nonce-Zähler.
+struct DigestAuth {
+    username: String,
+    password: String,
+    state: Mutex<Option<(DigestChallenge, u32)>>,
+}
+
+impl DigestAuth {
+    fn new(username: &str, password: &str) -> Self {
+        Self {
+            username: username.to_string(),
+            password: password.to_string(),
+            state: Mutex::new(None),
+        }
+    }
+
+    /// Authorization-Header für `method uri` mit der gecachten Challenge
+    /// (`None` solange der Server noch keine Challenge geschickt hat).
+    fn authorization(&self, method: &str, uri: &str) -> Option<String> {
+        let (challenge, nc) = {
+            let mut state = crate::lock_recover(&self.state);
+            let (challenge, nc) = state.as_mut()?;
+            *nc += 1;
+            (challenge.clone(), *nc)
+        };
+        let cnonce = uuid::Uuid::new_v4().simple().to_string();
+        Some(self.response_header(&challenge, method, uri, nc, &cnonce))
+    }
+
+    fn response_header(
+        &self,
+        challenge: &DigestChallenge,
+        method: &str,
+        uri: &str,
+        nc: u32,
+        cnonce: &str,
+    ) -> String {
+        let algorithm = challenge.algorithm;
+        let mut ha1 = algorithm.hash(&format!(
+            "{}:{}:{}",
+            self.username, challenge.realm, self.password
+        ));
+        if algorithm.is_session() {
+            ha1 = algorithm.hash(&format!("{}:{}:{}", ha1, challenge.nonce, cnonce));
+        }
+        let ha2 = algorithm.hash(&format!("{}:{}", method, uri));
+        let nc = format!("{:08x}", nc);
+        let response = if challenge.qop_auth {
+            algorithm.hash(&format!(
+                "{}:{}:{}:{}:auth:{}",
+                ha1, challenge.nonce, nc, cnonce, ha2
+            ))
+        } else {
+            algorithm.hash(&format!("{}:{}:{}", ha1, challenge.nonce, ha2))
+        };
+
+        let quote = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+        let mut header = format!(
+            r#"Digest username="{}", realm="{}", nonce="{}", uri="{}", algorithm={}, response="{}""#,
+            quote(&self.username),
+            quote(&challenge.realm),
+            quote(&challenge.nonce),
+            uri,
+            algorithm.name(),
+            response
+        );
+        if challenge.qop_auth {
+            header.push_str(&format!(r#", qop=auth, nc={}, cnonce="{}""#, nc, cnonce));
+        }
+        if let Some(opaque) = &challenge.opaque {
+            header.push_str(&format!(r#", opaque="{}""#, quote(opaque)));
+        }
+        header
+    }
+
+    /// Übernimmt die Challenge aus einer 401-Antwort. `true` wenn sich ein erneuter
+    /// Versuch lohnt: erste Challenge, neue Nonce oder `stale=true`. Dieselbe Nonce
+    /// ohne `stale` heißt: Zugangsdaten falsch.
+    fn accept_challenge(&self, response: &Response) -> bool {
+        let Some(challenge) = response
+            .headers()
+            .get_all(WWW_AUTHENTICATE)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .find_map(parse_digest_challenge)
+        else {
+            return false;
+        };
+        let mut state = crate::lock_recover(&self.state);
+        let retry = match state.as_ref() {
+            Some((current, _)) => challenge.stale || current.nonce != challenge.nonce,
+            None => true,
+        };
+        if retry {
+            *state = Some((challenge, 0));
+        }
+        retry
+    }
+
+    /// Setzt den Authorization-Header eines fertig gebauten Requests.
+    fn authorize(&self, request: &mut Request) {
+        let url = request.url();
+        let uri = match url.query() {
+            Some(q) => format!("{}?{}", url.path(), q),
+            None => url.path().to_string(),
+        };
+        if let Some(value) = self
+            .authorization(request.method().as_str(), &uri)
+            .and_then(|v| HeaderValue::from_str(&v).ok())
+        {
+            request.headers_mut().insert(AUTHORIZATION, value);
+        }
+    }
+}
+
+// ── Nextcloud Login Flow v2 ─────────────────────────────────────────────────────
+
+/// Laufender Nextcloud Login Flow v2: `login_url` wird im Browser geöffnet, danach liefert
+/// der Poll-Endpoint nach Bestätigung durch den Nutzer ein App-Passwort.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoginFlow {
+    pub login_url: String,
+    pub poll_endpoint: String,
+    pub poll_token: String,
+}
+
+/// Nextcloud-Basis-URL aus einer WebDAV-URL (`…/remote.php/dav/files/user` → `…`).
+fn nextcloud_base_url(url: &str) -> &str {
+    let url = url.trim_end_matches('/');
+    match url.find("/remote.php") {
+        Some(i) => &url[..i],
+        None => url,
+    }
+}
+
+/// WebDAV-URL für die Zugangsdaten aus dem Login-Flow: eine bereits eingetragene
+/// `remote.php`-URL bleibt erhalten, sonst der Nextcloud-Standardpfad des Nutzers.
+fn nextcloud_dav_url(entered: &str, server: &str, login_name: &str) -> String {
+    if entered.contains("/remote.php") {
+        entered.trim_end_matches('/').to_string()
+    } else {
+        format!(
+            "{}/remote.php/dav/files/{}",
+            server.trim_end_matches('/'),
+            urlencoding::encode(login_name)
+        )
+    }
+}
+
+/// Startet Login Flow v2 (`POST /index.php/login/v2`). Der User-Agent erscheint in
+/// Nextcloud als Name des erzeugten App-Passworts.
+pub async fn start_login_flow(
+    url: &str,
+    tls: &TlsPolicy,
+    proxy: &ProxyPolicy,
+) -> Result<LoginFlow> {
+    #[derive(Deserialize)]
+    struct Poll {
+        token: String,
+        endpoint: String,
+    }
+    #[derive(Deserialize)]
+    struct Init {
+        poll: Poll,
+        login: String,
+    }
+
+    let endpoint = format!("{}/index.php/login/v2", nextcloud_base_url(url));
+    let response = http_client(tls, proxy)?
+        .post(&endpoint)
+        .header("User-Agent", LOGIN_FLOW_USER_AGENT)
+        .send()
+        .await
+        .map_err(network_error)?;
+
+    match response.status() {
+        s if s.is_success() => {
+            let init: Init = response
+                .json()
+                .await
+                .map_err(|e| AppError::ParseError(e.to_string()))?;
+            Ok(LoginFlow {
+                login_url: init.login,
+                poll_endpoint: init.poll.endpoint,
+                poll_token: init.poll.token,
+            })
+        }
+        StatusCode::NOT_FOUND | StatusCode::METHOD_NOT_ALLOWED => {
+            Err(AppError::Unsupported("Nextcloud Login Flow v2".to_string()))
+        }
+        s => Err(AppError::WebDav(format!("Login flow failed: {}", s))),
+    }
+}
+
+/// Fragt den Poll-Endpoint einmal ab. `Ok(None)` solange die Anmeldung im Browser noch
+/// nicht bestätigt wurde (Nextcloud antwortet bis dahin mit 404).
+pub async fn poll_login_flow(
+    url: &str,
+    flow: &LoginFlow,
+    tls: &TlsPolicy,
+    proxy: &ProxyPolicy,
+) -> Result<Option<Credentials>> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Grant {
+        server: String,
+        login_name: String,
+        app_password: String,
+    }
+
+    let response = http_client(tls, proxy)?
+        .post(&flow.poll_endpoint)
+        .header("User-Agent", LOGIN_FLOW_USER_AGENT)
+        .form(&[("token", flow.poll_token.as_str())])
+        .send()
+        .await
+        .map_err(network_error)?;
+
+    match response.status() {
+        StatusCode::NOT_FOUND => Ok(None),
+        s if s.is_success() => {
+            let grant: Grant = response
+                .json()
+                .await
+                .map_err(|e| AppError::ParseError(e.to_string()))?;
+            Ok(Some(Credentials {
+                url: nextcloud_dav_url(url, &grant.server, &grant.login_name),
+                username: grant.login_name,
+                password: grant.app_password,
+                auth_scheme: AuthScheme::NextcloudLoginFlow,
+            }))
+        }
+        s => Err(AppError::WebDav(format!("Login flow poll failed: {}", s))),
+    }
+}
+
+/// Antwort eines Diagnose-Requests (`WebDavClient::probe`), unabhängig vom Status.
+pub struct ProbeResponse {
+    pub status: StatusCode,
+    pub headers: reqwest::header::HeaderMap,
+    pub body: String,
+}
+
+impl ProbeResponse {
+    /// Alle Werte eines Headers, kommagetrennt zusammengefasst
+    pub fn header(&self, name: &str) -> Option<String> {
+        let values: Vec<&str> = self
+            .headers
+            .get_all(name)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .collect();
+        (!values.is_empty()).then(|| values.join(", "))
+    }
+}
 
 #[derive(Clone)]
 /// WebDAV Client für Server-Kommunikation
 pub struct WebDavClient {
     client: Client,
     base_url: String,
-    auth_header: String,
+    auth: Auth,
     /// Sync folder name (default: "notes"). JSON stored in `/{sync_folder}/`, Markdown in `/{sync_folder}-md/`.
     sync_folder: String,
+    /// Verbleibende Wiederholungen für alle Requests dieses Clients (None = nur `MAX_ATTEMPTS`)
+    retry_budget: Option<Arc<AtomicU32>>,
+    /// Obergrenze gleichzeitiger Requests bei Massen-Operationen (Listing, Sync)
+    concurrency: usize,
+    /// Server hat LOCK abgelehnt → geteilte Dateien nur noch per bedingtem PUT
+    lock_unsupported: Arc<AtomicBool>,
+    /// Server hat MOVE abgelehnt (405/501) → Verschieben per Copy-then-Delete
+    move_unsupported: Arc<AtomicBool>,
+}
+
+/// HTTP-Client mit Zertifikatsprüfung gemäß `tls` (System-Trust, eigene CA, Pin oder explizit
+/// insecure) und Proxy gemäß `proxy`.
+pub(crate) fn http_client(tls: &TlsPolicy, proxy: &ProxyPolicy) -> Result<Client> {
+    tls.apply(proxy.apply(Client::builder())?)?
+        // connect_timeout: schnelles Fehlschlagen wenn der Server nicht erreichbar ist
+        // (sonst hängt "Test connection" bis zum 30s-Request-Timeout).
+        .connect_timeout(std::time::Duration::from_secs(5))
+        .timeout(std::time::Duration::from_secs(30))
+        .build()
+        .map_err(network_error)
+}
+
+/// Name des Sync-Ordners: nur ASCII-Buchstaben/-Ziffern, `_` und `-` (Android-Parität),
+/// höchstens 50 Zeichen, leer → `notes`.
+pub(crate) fn sync_folder_name(raw: &str) -> String {
+    // Must use is_ascii_alphanumeric() — is_alphanumeric() accepts Unicode letters which
+    // would produce a different path than the JS frontend's /[^a-zA-Z0-9_-]/g regex.
+    let sanitized_folder = raw
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == '-')
+        .collect::<String>();
+    if sanitized_folder.is_empty() {
+        "notes".to_string()
+    } else {
+        sanitized_folder.chars().take(50).collect()
+    }
 }
 
 impl WebDavClient {
     /// Erstellt einen neuen WebDAV Client
-    pub fn new(url: &str, username: &str, password: &str, sync_folder: &str) -> Result<Self> {
-        let client = Client::builder()
-            .danger_accept_invalid_certs(true)
-            // connect_timeout: schnelles Fehlschlagen wenn der Server nicht erreichbar ist
-            // (sonst hängt "Test connection" bis zum 30s-Request-Timeout).
-            .connect_timeout(std::time::Duration::from_secs(5))
-            .timeout(std::time::Duration::from_secs(30))
-            .build()
-            .map_err(|e| AppError::NetworkError(e.to_string()))?;
-
-        let auth = format!("{}:{}", username, password);
-        let auth_header = format!("Basic {}", STANDARD.encode(auth));
-        let base_url = url.trim_end_matches('/').to_string();
-
-        // Sanitize sync folder: only allow ASCII alphanumeric, underscore, dash (Android parity).
-        // Must use is_ascii_alphanumeric() — is_alphanumeric() accepts Unicode letters which
-        // would produce a different path than the JS frontend's /[^a-zA-Z0-9_-]/g regex.
-        let sanitized_folder = sync_folder
-            .chars()
-            .filter(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == '-')
-            .collect::<String>();
-        let sync_folder = if sanitized_folder.is_empty() {
-            "notes".to_string()
-        } else {
-            sanitized_folder.chars().take(50).collect()
-        };
+    /// Authentifizierung gemäß `credentials.auth_scheme`, Zertifikatsprüfung gemäß `tls`,
+    /// Verbindung direkt oder über den Proxy gemäß `proxy`.
+    pub fn new(
+        credentials: &Credentials,
+        sync_folder: &str,
+        tls: &TlsPolicy,
+        proxy: &ProxyPolicy,
+    ) -> Result<Self> {
+        let client = http_client(tls, proxy)?;
+        let auth = Auth::from_credentials(credentials)?;
+        let base_url = credentials.url.trim_end_matches('/').to_string();
 
         Ok(Self {
             client,
             base_url,
-            auth_header,
-            sync_folder,
+            auth,
+            sync_folder: sync_folder_name(sync_folder),
+            retry_budget: None,
+            concurrency: DEFAULT_CONCURRENCY,
+            lock_unsupported: Arc::new(AtomicBool::new(false)),
+            move_unsupported: Arc::new(AtomicBool::new(false)),
         })
     }
 
+    /// Kopie des Clients mit anderer Parallelität (auf 1..=`MAX_CONCURRENCY` begrenzt).
+    pub fn with_concurrency(&self, concurrency: usize) -> Self {
+        Self {
+            concurrency: concurrency.clamp(1, MAX_CONCURRENCY),
+            ..self.clone()
+        }
+    }
+
+    /// Obergrenze gleichzeitiger Requests für Massen-Operationen
+    pub fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    /// Server-URL ohne Trailing-Slash
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Bereinigter Name des Sync-Ordners
+    pub fn sync_folder(&self) -> &str {
+        &self.sync_folder
+    }
+
+    /// Kopie des Clients mit eigenem Wiederholungs-Budget (z.B. pro Sync-Lauf), damit ein
+    /// überlasteter Server nicht bei jedem einzelnen Request erneut minutenlang bedrängt wird.
+    pub fn with_retry_budget(&self, retries: u32) -> Self {
+        Self {
+            retry_budget: Some(Arc::new(AtomicU32::new(retries))),
+            ..self.clone()
+        }
+    }
+
+    /// Verbraucht eine Wiederholung aus dem Budget; `false` wenn es aufgebraucht ist.
+    fn take_retry(&self) -> bool {
+        match &self.retry_budget {
+            None => true,
+            Some(budget) => budget
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_ok(),
+        }
+    }
+
+    /// Wiederholungs-Budget aufgebraucht — weitere vorübergehende Fehler werden sofort gemeldet.
+    pub fn retry_budget_exhausted(&self) -> bool {
+        self.retry_budget
+            .as_ref()
+            .is_some_and(|b| b.load(Ordering::SeqCst) == 0)
+    }
+
+    /// Sendet einen Request mit Authentifizierung. Idempotente Requests werden bei
+    /// Verbindungsfehlern und vorübergehenden Status (429/502/503/504) mit Backoff
+    /// wiederholt; `Retry-After` des Servers hat Vorrang vor dem eigenen Backoff.
+    /// Kam ein Conditional PUT trotz Verbindungsabbruch an, liefert die Wiederholung 412 —
+    /// das endet als Konflikt, nie als stilles Überschreiben.
+    async fn send(&self, request: RequestBuilder) -> reqwest::Result<Response> {
+        let template = request.build()?;
+        let idempotent = is_idempotent(template.method());
+        let mut attempt = 0;
+        loop {
+            // Streaming-Bodies sind nicht klonbar → genau ein Versuch
+            let Some(request) = template.try_clone() else {
+                return self.execute(template).await;
+            };
+            let result = self.execute(request).await;
+            let delay = match &result {
+                Ok(response) if is_transient_status(response.status()) => Some(
+                    response
+                        .headers()
+                        .get(reqwest::header::RETRY_AFTER)
+                        .and_then(|v| v.to_str().ok())
+                        .and_then(|v| parse_retry_after(v, chrono::Utc::now()))
+                        .unwrap_or_else(|| backoff_delay(attempt, jitter())),
+                ),
+                Err(e) if !e.is_builder() && classify_network_error(e).is_retryable() => {
+                    Some(backoff_delay(attempt, jitter()))
+                }
+                _ => None,
+            };
+            attempt += 1;
+            let Some(delay) = delay else {
+                return result;
+            };
+            if !idempotent
+                || attempt >= MAX_ATTEMPTS
+                || delay > RETRY_AFTER_LIMIT
+                || !self.take_retry()
+            {
+                return result;
+            }
+            let reason = match &result {
+                Ok(response) => response.status().to_string(),
+                Err(e) => e.to_string(),
+            };
+            eprintln!(
+                "[WebDAV] {} {}: {} → Versuch {}/{} in {} ms",
+                template.method(),
+                template.url().path(),
+                reason,
+                attempt + 1,
+                MAX_ATTEMPTS,
+                delay.as_millis()
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Führt einen Request einmal aus. Bei Digest wird eine 401-Challenge (erste Anfrage
+    /// oder abgelaufene Nonce) beantwortet und der Request einmal wiederholt.
+    async fn execute(&self, mut request: Request) -> reqwest::Result<Response> {
+        let digest = match &self.auth {
+            Auth::Header(value) => {
+                request.headers_mut().insert(AUTHORIZATION, value.clone());
+                return self.client.execute(request).await;
+            }
+            Auth::Digest(digest) => digest,
+        };
+
+        let retry = request.try_clone();
+        digest.authorize(&mut request);
+        let response = self.client.execute(request).await?;
+        if response.status() != StatusCode::UNAUTHORIZED {
+            return Ok(response);
+        }
+        match retry {
+            Some(mut retry) if digest.accept_challenge(&response) => {
+                digest.authorize(&mut retry);
+                self.client.execute(retry).await
+            }
+            _ => Ok(response),
+        }
+    }
+
     // ── URL-Builder ─────────────────────────────────────────────────────────────
 
     /// JSON-URL einer Notiz: `{base}/{sync_folder}/{enc(folder)/}{id}.json`
@@ -144,16 +1136,16 @@ impl WebDavClient {
     /// Fehler (z.B. 405 Method Not Allowed wenn das Verzeichnis bereits existiert) werden ignoriert.
     pub async fn ensure_folder_dirs(&self, folder: &str) {
         let _ = self
-            .client
-            .request(MKCOL.clone(), self.folder_json_dir_url(folder))
-            .header("Authorization", &self.auth_header)
-            .send()
+            .send(
+                self.client
+                    .request(MKCOL.clone(), self.folder_json_dir_url(folder)),
+            )
             .await;
         let _ = self
-            .client
-            .request(MKCOL.clone(), self.folder_md_dir_url(folder))
-            .header("Authorization", &self.auth_header)
-            .send()
+            .send(
+                self.client
+                    .request(MKCOL.clone(), self.folder_md_dir_url(folder)),
+            )
             .await;
     }
 
@@ -161,16 +1153,10 @@ impl WebDavClient {
     /// Fehler werden ignoriert (404 = bereits gelöscht, 409 = nicht leer, etc.).
     pub async fn delete_folder_dirs(&self, folder: &str) {
         let _ = self
-            .client
-            .delete(self.folder_json_dir_url(folder))
-            .header("Authorization", &self.auth_header)
-            .send()
+            .send(self.client.delete(self.folder_json_dir_url(folder)))
             .await;
         let _ = self
-            .client
-            .delete(self.folder_md_dir_url(folder))
-            .header("Authorization", &self.auth_header)
-            .send()
+            .send(self.client.delete(self.folder_md_dir_url(folder)))
             .await;
     }
 
@@ -181,13 +1167,13 @@ impl WebDavClient {
         let url = format!("{}/{}/", self.base_url, self.sync_folder);
 
         let response = self
-            .client
-            .request(PROPFIND.clone(), &url)
-            .header("Authorization", &self.auth_header)
-            .header("Depth", "0")
-            .send()
+            .send(
+                self.client
+                    .request(PROPFIND.clone(), &url)
+                    .header("Depth", "0"),
+            )
             .await
-            .map_err(|e| AppError::NetworkError(e.to_string()))?;
+            .map_err(network_error)?;
 
         match response.status() {
             StatusCode::OK | StatusCode::MULTI_STATUS => Ok(true),
@@ -196,10 +1182,10 @@ impl WebDavClient {
                 self.ensure_directories().await?;
                 Ok(true)
             }
-            status => Err(AppError::WebDav(format!(
-                "Connection test failed: {}",
-                status
-            ))),
+            status => Err(status_error(
+                status,
+                format!("Connection test failed: {}", status),
+            )),
         }
     }
 
@@ -207,18 +1193,12 @@ impl WebDavClient {
     pub async fn ensure_directories(&self) -> Result<()> {
         let notes_url = format!("{}/{}/", self.base_url, self.sync_folder);
         let _ = self
-            .client
-            .request(MKCOL.clone(), &notes_url)
-            .header("Authorization", &self.auth_header)
-            .send()
+            .send(self.client.request(MKCOL.clone(), &notes_url))
             .await;
 
         let notes_md_url = format!("{}/{}-md/", self.base_url, self.sync_folder);
         let _ = self
-            .client
-            .request(MKCOL.clone(), &notes_md_url)
-            .header("Authorization", &self.auth_header)
-            .send()
+            .send(self.client.request(MKCOL.clone(), &notes_md_url))
             .await;
 
         Ok(())
@@ -226,48 +1206,35 @@ impl WebDavClient {
 
     // ── Notiz-Listing ────────────────────────────────────────────────────────────
 
-    /// Listet alle Notizen mit ihrer Ordner-Zuordnung.
-    /// Gibt `(id, folder_name)` zurück — folder_name ist None für Root-Notizen.
-    pub async fn list_notes_with_folders(&self) -> Result<Vec<(String, Option<String>)>> {
+    /// Listet alle Notizen mit ihrer Ordner-Zuordnung und den Änderungs-Props
+    /// (`getetag`, `getlastmodified`) aus dem PROPFIND — ohne die Notizen selbst zu laden.
+    pub async fn list_notes_with_folders(&self) -> Result<Vec<NoteListing>> {
         let root_url = format!("{}/{}/", self.base_url, self.sync_folder);
-        let text = self.propfind_text(&root_url, "1").await?;
-
-        let mut result: Vec<(String, Option<String>)> = Vec::new();
+        let listing = self.propfind(&root_url, "1").await?;
 
         // Schritt 1: Root-Notizen aus dem Depth-1 PROPFIND (nur direkte Kinder)
-        let decoded_text = urlencoding::decode(&text).unwrap_or_else(|_| text.clone().into());
-        for cap in UUID_PATTERN.captures_iter(&decoded_text) {
-            let id = cap[1].to_lowercase();
-            if !result.iter().any(|(i, _)| i == &id) {
-                result.push((id, None));
-            }
-        }
-        for cap in UUID_PATTERN.captures_iter(&text) {
-            let id = cap[1].to_lowercase();
-            if !result.iter().any(|(i, _)| i == &id) {
-                result.push((id, None));
-            }
-        }
+        let mut result = parse_note_listing(&listing, None);
 
-        // Schritt 2: Unterordner aus href-Werten extrahieren
-        let subdirs = self.extract_subdirs_from_propfind(&text);
+        // Schritt 2: Unterordner (Collection-Einträge) ermitteln
+        let subdirs = self.extract_subdirs_from_propfind(&listing);
 
-        for folder_name in subdirs {
-            let subdir_url = self.folder_json_dir_url(&folder_name);
-            match self.propfind_text(&subdir_url, "1").await {
-                Ok(sub_text) => {
-                    let sub_decoded =
-                        urlencoding::decode(&sub_text).unwrap_or_else(|_| sub_text.clone().into());
-                    for cap in UUID_PATTERN.captures_iter(&sub_decoded) {
-                        let id = cap[1].to_lowercase();
-                        if !result.iter().any(|(i, _)| i == &id) {
-                            result.push((id, Some(folder_name.clone())));
-                        }
-                    }
-                    for cap in UUID_PATTERN.captures_iter(&sub_text) {
-                        let id = cap[1].to_lowercase();
-                        if !result.iter().any(|(i, _)| i == &id) {
-                            result.push((id, Some(folder_name.clone())));
+        // Schritt 3: Unterordner parallel abfragen; Ergebnisse in Ordner-Reihenfolge mergen
+        let sub_listings: Vec<_> = stream::iter(subdirs)
+            .map(|folder_name| async move {
+                let subdir_url = self.folder_json_dir_url(&folder_name);
+                let sub_listing = self.propfind(&subdir_url, "1").await;
+                (folder_name, sub_listing)
+            })
+            .buffered(self.concurrency)
+            .collect()
+            .await;
+
+        for (folder_name, sub_listing) in sub_listings {
+            match sub_listing {
+                Ok(sub_listing) => {
+                    for entry in parse_note_listing(&sub_listing, Some(&folder_name)) {
+                        if !result.iter().any(|e| e.id == entry.id) {
+                            result.push(entry);
                         }
                     }
                 }
@@ -281,32 +1248,20 @@ impl WebDavClient {
     }
 
     /// Extrahiert direkte Unterordner-Namen aus einer PROPFIND-Antwort auf das Root-Verzeichnis.
-    fn extract_subdirs_from_propfind(&self, text: &str) -> Vec<String> {
+    fn extract_subdirs_from_propfind(&self, listing: &Multistatus) -> Vec<String> {
+        let root_path = multistatus::decoded_path(&self.root_url());
         let mut subdirs: Vec<String> = Vec::new();
 
-        for cap in HREF_PATTERN.captures_iter(text) {
-            let href = cap[1].trim();
-            if !href.ends_with('/') {
-                continue;
-            }
-
-            // URL-decode und letztes Pfadsegment ermitteln
-            let decoded = urlencoding::decode(href.trim_end_matches('/'))
-                .unwrap_or_else(|_| href.trim_end_matches('/').into())
-                .into_owned();
-
-            let last_seg = match decoded.rsplit('/').next() {
-                Some(s) if !s.is_empty() => s,
-                _ => continue,
-            };
-
-            // Root-Ordner selbst und sync_folder überspringen
-            if last_seg == self.sync_folder {
+        for entry in listing.responses.iter().filter(|e| e.is_collection()) {
+            let name = entry.name();
+            // Root-Ordner selbst überspringen (Pfadvergleich; Name als Fallback für
+            // Reverse-Proxies, die den Pfad-Präfix umschreiben)
+            if name.is_empty() || name == self.sync_folder || entry.path() == root_path {
                 continue;
             }
 
             // Validieren und bereinigen
-            if let Some(folder_name) = sanitize_folder_name(last_seg) {
+            if let Some(folder_name) = sanitize_folder_name(&name) {
                 if !subdirs.contains(&folder_name) {
                     subdirs.push(folder_name);
                 }
@@ -320,29 +1275,316 @@ impl WebDavClient {
     #[allow(dead_code)]
     pub async fn discover_folders(&self) -> Vec<String> {
         let root_url = format!("{}/{}/", self.base_url, self.sync_folder);
-        match self.propfind_text(&root_url, "1").await {
-            Ok(text) => self.extract_subdirs_from_propfind(&text),
+        match self.propfind(&root_url, "1").await {
+            Ok(listing) => self.extract_subdirs_from_propfind(&listing),
             Err(_) => Vec::new(),
         }
     }
 
+    // ── Speicherbelegung ────────────────────────────────────────────────────────
+
+    /// Quota des Sync-Ordners und Größe aller Notizen (JSON + Markdown-Spiegel) je Ordner.
+    /// Ein fehlendes Markdown-Verzeichnis zählt als leer; nicht lesbare Ordner werden übersprungen.
+    pub async fn storage_usage(&self) -> Result<StorageUsage> {
+        let root_url = self.root_url();
+        let listing = self.propfind(&root_url, "1").await?;
+        let root_path = multistatus::decoded_path(&root_url);
+        let root_entry = listing
+            .responses
+            .iter()
+            .find(|e| e.is_collection() && e.path() == root_path)
+            .or_else(|| listing.responses.first());
+        let quota_used_bytes = root_entry.and_then(|e| e.quota_used());
+        let quota_available_bytes = root_entry.and_then(|e| e.quota_available());
+
+        // Root-Notizen stehen schon im Listing; Unterordner brauchen je einen PROPFIND
+        let md_root_url = format!("{}/{}-md/", self.base_url, self.sync_folder);
+        let mut folders = vec![(
+            None,
+            Some(tally(&listing, |e| note_id(e).is_some())),
+            root_url.clone(),
+            md_root_url,
+        )];
+        for f in self.extract_subdirs_from_propfind(&listing) {
+            let (json_url, md_url) = (self.folder_json_dir_url(&f), self.folder_md_dir_url(&f));
+            folders.push((Some(f), None, json_url, md_url));
+        }
+
+        let usages: Vec<Option<FolderUsage>> = stream::iter(folders)
+            .map(|(folder, known, json_url, md_url)| async move {
+                let (notes, json_bytes) = match known {
+                    Some(t) => t,
+                    None => match self.propfind(&json_url, "1").await {
+                        Ok(l) => tally(&l, |e| note_id(e).is_some()),
+                        Err(e) => {
+                            eprintln!("[WebDAV] PROPFIND {} failed: {}", json_url, e);
+                            return None;
+                        }
+                    },
+                };
+                let (_, markdown_bytes) = self
+                    .propfind(&md_url, "1")
+                    .await
+                    .map(|l| tally(&l, |e| e.name().ends_with(".md")))
+                    .unwrap_or_default();
+                Some(FolderUsage {
+                    folder,
+                    notes,
+                    json_bytes,
+                    markdown_bytes,
+                })
+            })
+            .buffered(self.concurrency)
+            .collect()
+            .await;
+
+        let folders: Vec<FolderUsage> = usages.into_iter().flatten().collect();
+        Ok(StorageUsage {
+            quota_used_bytes,
+            quota_available_bytes,
+            low_space: is_low_space(quota_used_bytes, quota_available_bytes),
+            notes: folders.iter().map(|f| f.notes).sum(),
+            json_bytes: folders.iter().map(|f| f.json_bytes).sum(),
+            markdown_bytes: folders.iter().map(|f| f.markdown_bytes).sum(),
+            folders,
+        })
+    }
+
+    // ── Nextcloud-Versionen ─────────────────────────────────────────────────────
+
+    /// Versions-Collection der JSON-Datei einer Notiz. Die Datei-ID (`oc:fileid`) bleibt über
+    /// MOVE erhalten, daher gehören auch Versionen aus einem früheren Ordner dazu.
+    async fn note_versions_url(&self, id: &str, folder: Option<&str>) -> Result<String> {
+        let root = nextcloud_versions_root(&self.base_url).ok_or_else(|| {
+            AppError::Unsupported(
+                "Versionen (nur Nextcloud mit …/remote.php/dav/files/{user})".to_string(),
+            )
+        })?;
+        let listing = self
+            .propfind_existing(&self.note_json_url(folder, id), "0")
+            .await?
+            .ok_or_else(|| AppError::NoteNotFound(id.to_string()))?;
+        let file_id = listing
+            .responses
+            .first()
+            .and_then(|e| e.file_id())
+            .ok_or_else(|| AppError::Unsupported("Versionen (keine oc:fileid)".to_string()))?;
+        Ok(format!("{}/{}", root, urlencoding::encode(file_id)))
+    }
+
+    /// Ältere Fassungen einer Notiz aus der Nextcloud-Versionierung, neueste zuerst.
+    /// Die aktuelle Fassung ist nicht enthalten.
+    pub async fn list_note_versions(
+        &self,
+        id: &str,
+        folder: Option<&str>,
+    ) -> Result<Vec<ServerVersion>> {
+        let url = self.note_versions_url(id, folder).await?;
+        let listing = self
+            .propfind_existing(&format!("{}/", url), "1")
+            .await?
+            .ok_or_else(|| {
+                AppError::Unsupported("Versionen (Versions-App deaktiviert?)".to_string())
+            })?;
+        Ok(parse_versions(&listing))
+    }
+
+    /// Lädt eine Version als Notiz. Der Ordner wird wie bei `get_note_with_etag` vom
+    /// angegebenen Pfad übernommen, nicht aus dem JSON der alten Fassung.
+    pub async fn get_note_version(
+        &self,
+        id: &str,
+        folder: Option<&str>,
+        version_id: &str,
+    ) -> Result<Note> {
+        let url = format!(
+            "{}/{}",
+            self.note_versions_url(id, folder).await?,
+            urlencoding::encode(version_id)
+        );
+        let response = self
+            .send(self.client.get(&url))
+            .await
+            .map_err(network_error)?;
+        match response.status() {
+            StatusCode::OK => {
+                let mut note: Note = response
+                    .json()
+                    .await
+                    .map_err(|e| AppError::ParseError(e.to_string()))?;
+                if note.id != id {
+                    return Err(AppError::ParseError(format!(
+                        "Version {} gehört zu Notiz {}, nicht {}",
+                        version_id, note.id, id
+                    )));
+                }
+                note.fix_note_type();
+                note.folder_name = folder.map(str::to_owned);
+                Ok(note)
+            }
+            StatusCode::NOT_FOUND => Err(AppError::NoteNotFound(format!("{}@{}", id, version_id))),
+            status => Err(status_error(status, format!("GET failed: {}", status))),
+        }
+    }
+
+    // ── Sync-Collection (RFC 6578) ──────────────────────────────────────────────
+
+    /// URL der Root-Collection `{base}/{sync_folder}/` — identifiziert den Sync-Stand
+    /// (gespeicherte Sync-Tokens gelten nur für genau diese Collection).
+    pub fn root_url(&self) -> String {
+        format!("{}/{}/", self.base_url, self.sync_folder)
+    }
+
+    /// Prüft per OPTIONS, ob der Server den sync-collection REPORT anbietet:
+    /// `sync-collection` im DAV-Header oder `REPORT` im Allow-Header (SabreDAV/Nextcloud).
+    pub async fn supports_sync_collection(&self) -> bool {
+        let response = match self
+            .send(self.client.request(Method::OPTIONS, self.root_url()))
+            .await
+        {
+            Ok(r) if r.status().is_success() => r,
+            _ => return false,
+        };
+        let header = |name: &str| {
+            response
+                .headers()
+                .get_all(name)
+                .iter()
+                .filter_map(|v| v.to_str().ok())
+                .collect::<Vec<_>>()
+                .join(",")
+                .to_ascii_lowercase()
+        };
+        header("DAV").contains("sync-collection")
+            || header("Allow").split(',').any(|m| m.trim() == "report")
+    }
+
+    /// Änderungen einer Collection seit `token` per sync-collection REPORT.
+    /// `folder` = None → Root-Collection; `token` = None → initialer Sync (alle Member).
+    /// Gekürzte Antworten (507) werden mit dem neuen Token fortgesetzt.
+    ///
+    /// Fehler: `InvalidSyncToken` wenn der Server den Token verwirft,
+    /// `Unsupported` wenn er den REPORT nicht kennt.
+    pub async fn sync_collection(
+        &self,
+        folder: Option<&str>,
+        token: Option<&str>,
+    ) -> Result<CollectionDelta> {
+        let url = match folder {
+            Some(f) => self.folder_json_dir_url(f),
+            None => self.root_url(),
+        };
+        let skip_segment = folder.unwrap_or(&self.sync_folder);
+
+        let mut delta = CollectionDelta::default();
+        let mut next_token = token.map(str::to_owned);
+        for _ in 0..MAX_SYNC_PAGES {
+            let text = self
+                .sync_collection_text(&url, next_token.as_deref())
+                .await?;
+            let (page, truncated) =
+                parse_sync_collection(&multistatus::parse(&text)?, folder, skip_segment);
+            if page.token.is_empty() {
+                return Err(AppError::WebDav(
+                    "sync-collection: Antwort ohne sync-token".to_string(),
+                ));
+            }
+            next_token = Some(page.token.clone());
+            delta.absorb(page);
+            if !truncated {
+                return Ok(delta);
+            }
+        }
+        eprintln!(
+            "[WebDAV] sync-collection {}: mehr als {} Seiten, Rest folgt beim nächsten Sync",
+            url, MAX_SYNC_PAGES
+        );
+        Ok(delta)
+    }
+
+    pub(crate) async fn sync_collection_text(
+        &self,
+        url: &str,
+        token: Option<&str>,
+    ) -> Result<String> {
+        let token_element = match token {
+            Some(t) => format!("<d:sync-token>{}</d:sync-token>", xml_escape(t)),
+            None => "<d:sync-token/>".to_string(),
+        };
+        let body = format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<d:sync-collection xmlns:d="DAV:">
+  {}
+  <d:sync-level>1</d:sync-level>
+  <d:prop>
+    <d:getetag/>
+    <d:getlastmodified/>
+    <d:resourcetype/>
+  </d:prop>
+</d:sync-collection>"#,
+            token_element
+        );
+
+        let response = self
+            .send(
+                self.client
+                    .request(REPORT.clone(), url)
+                    .header("Depth", "0")
+                    .header("Content-Type", "application/xml")
+                    .body(body),
+            )
+            .await
+            .map_err(network_error)?;
+
+        let status = response.status();
+        let text = response.text().await.map_err(network_error)?;
+        match status {
+            StatusCode::MULTI_STATUS => Ok(text),
+            StatusCode::UNAUTHORIZED => Err(AppError::InvalidCredentials),
+            // RFC 6578 §3.2: ungültiger Token → Precondition DAV:valid-sync-token
+            s if token.is_some() && text.contains("valid-sync-token") => {
+                eprintln!("[WebDAV] sync-token verworfen ({})", s);
+                Err(AppError::InvalidSyncToken)
+            }
+            StatusCode::BAD_REQUEST
+            | StatusCode::FORBIDDEN
+            | StatusCode::NOT_IMPLEMENTED
+            | StatusCode::METHOD_NOT_ALLOWED
+            | StatusCode::UNSUPPORTED_MEDIA_TYPE
+            | StatusCode::UNPROCESSABLE_ENTITY => Err(AppError::Unsupported(format!(
+                "sync-collection REPORT: {}",
+                status
+            ))),
+            s => Err(status_error(s, format!("REPORT failed: {}", s))),
+        }
+    }
+
     // ── Einzel-Notiz ────────────────────────────────────────────────────────────
 
     /// Lädt eine einzelne Notiz aus dem angegebenen Ordner.
     /// `folder` = None → Root-Ebene; der path ist maßgebend für `note.folder_name`.
     pub async fn get_note(&self, id: &str, folder: Option<&str>) -> Result<Note> {
+        self.get_note_with_etag(id, folder)
+            .await
+            .map(|(note, _etag)| note)
+    }
+
+    /// Wie `get_note`, liefert zusätzlich den ETag der Server-Datei (falls vom Server gesendet).
+    pub async fn get_note_with_etag(
+        &self,
+        id: &str,
+        folder: Option<&str>,
+    ) -> Result<(Note, Option<String>)> {
         let url = self.note_json_url(folder, id);
 
         let response = self
-            .client
-            .get(&url)
-            .header("Authorization", &self.auth_header)
-            .send()
+            .send(self.client.get(&url))
             .await
-            .map_err(|e| AppError::NetworkError(e.to_string()))?;
+            .map_err(network_error)?;
 
         match response.status() {
             StatusCode::OK => {
+                let etag = etag_of(&response);
                 let mut note: Note = response
                     .json()
                     .await
@@ -354,17 +1596,24 @@ impl WebDavClient {
                 // Pfad ist maßgebend — überschreibt was im JSON-Body steht
                 note.folder_name = folder.map(str::to_owned);
 
-                Ok(note)
+                Ok((note, etag))
             }
             StatusCode::NOT_FOUND => Err(AppError::NoteNotFound(id.to_string())),
-            status => Err(AppError::WebDav(format!("GET failed: {}", status))),
+            status => Err(status_error(status, format!("GET failed: {}", status))),
         }
     }
 
     /// Speichert eine Notiz (Dual-Write: JSON + Markdown), ordner-bewusst.
     ///
     /// Löscht die alte `.md`-Datei wenn der Titel geändert wurde.
-    pub async fn save_note(&self, note: &Note) -> Result<()> {
+    /// Der JSON-PUT wird mit `condition` abgesichert; schlägt die Vorbedingung fehl,
+    /// bleibt der Server-Stand (inkl. Markdown) unangetastet und es kommt `AppError::Conflict`.
+    /// Gibt den neuen ETag der JSON-Datei zurück (falls der Server ihn mitsendet).
+    pub async fn save_note(
+        &self,
+        note: &Note,
+        condition: WriteCondition,
+    ) -> Result<Option<String>> {
         let folder = note.folder_name.as_deref();
 
         // MKCOL Unterverzeichnisse, falls Notiz in einem Ordner liegt
@@ -372,54 +1621,70 @@ impl WebDavClient {
             self.ensure_folder_dirs(f).await;
         }
 
-        // Titel-Diff: alte .md entfernen wenn der Titel sich geändert hat.
-        if let Ok(existing) = self.get_note(&note.id, folder).await {
-            if existing.title != note.title {
-                let old_safe = sanitize_filename(&existing.title, &note.id);
-                let old_md_url = self.note_md_url(folder, &old_safe);
-                let _ = self
-                    .client
-                    .delete(&old_md_url)
-                    .header("Authorization", &self.auth_header)
-                    .send()
-                    .await;
-            }
+        // Titel-Diff: alter Titel wird vor dem PUT gelesen, die alte .md aber erst nach
+        // erfolgreichem JSON-PUT entfernt (bei 412 darf nichts verändert werden).
+        let old_title = if condition == WriteCondition::IfNoneMatch {
+            None
+        } else {
+            self.get_note(&note.id, folder)
+                .await
+                .ok()
+                .map(|existing| existing.title)
+                .filter(|t| *t != note.title)
+        };
+
+        let etag = self.save_json(note, &condition).await?;
+
+        if let Some(old_title) = old_title {
+            let old_safe = sanitize_filename(&old_title, &note.id);
+            let old_md_url = self.note_md_url(folder, &old_safe);
+            let _ = self.send(self.client.delete(&old_md_url)).await;
         }
 
-        self.save_json(note).await?;
         self.save_markdown(note).await?;
-        Ok(())
+        Ok(etag)
     }
 
-    async fn save_json(&self, note: &Note) -> Result<()> {
+    async fn save_json(&self, note: &Note, condition: &WriteCondition) -> Result<Option<String>> {
         let url = self.note_json_url(note.folder_name.as_deref(), &note.id);
 
         #[cfg(debug_assertions)]
-        eprintln!("[WebDAV] PUT JSON: {}", url);
+        eprintln!("[WebDAV] PUT JSON: {} ({:?})", url, condition);
 
         let json_content =
             serde_json::to_string_pretty(note).map_err(|e| AppError::ParseError(e.to_string()))?;
 
-        let response = self
+        let mut request = self
             .client
             .put(&url)
-            .header("Authorization", &self.auth_header)
-            .header("Content-Type", "application/json")
-            .body(json_content)
-            .send()
+            .header("Content-Type", "application/json");
+        request = match condition {
+            WriteCondition::Unconditional => request,
+            WriteCondition::IfMatch(etag) => request.header("If-Match", etag),
+            WriteCondition::IfNoneMatch => request.header("If-None-Match", "*"),
+        };
+
+        let response = self
+            .send(request.body(json_content))
             .await
-            .map_err(|e| AppError::NetworkError(e.to_string()))?;
+            .map_err(network_error)?;
 
         let status = response.status();
+        if status == StatusCode::PRECONDITION_FAILED {
+            return Err(AppError::Conflict(format!(
+                "{} wurde auf dem Server zwischenzeitlich geändert",
+                note.id
+            )));
+        }
         if !status.is_success() {
             let error_body = response.text().await.unwrap_or_default();
-            return Err(AppError::WebDav(format!(
-                "PUT JSON failed: {} - {}",
-                status, error_body
-            )));
+            return Err(status_error(
+                status,
+                format!("PUT JSON failed: {} - {}", status, error_body),
+            ));
         }
 
-        Ok(())
+        Ok(etag_of(&response))
     }
 
     async fn save_markdown(&self, note: &Note) -> Result<()> {
@@ -427,12 +1692,7 @@ impl WebDavClient {
         if note.trashed_at.is_some() {
             let safe_title = sanitize_filename(&note.title, &note.id);
             let md_url = self.note_md_url(note.folder_name.as_deref(), &safe_title);
-            let _ = self
-                .client
-                .delete(&md_url)
-                .header("Authorization", &self.auth_header)
-                .send()
-                .await;
+            let _ = self.send(self.client.delete(&md_url)).await;
             return Ok(());
         }
 
@@ -441,21 +1701,24 @@ impl WebDavClient {
         let url = self.note_md_url(note.folder_name.as_deref(), &safe_title);
 
         let response = self
-            .client
-            .put(&url)
-            .header("Authorization", &self.auth_header)
-            .header("Content-Type", "text/markdown; charset=utf-8")
-            .body(markdown_content)
-            .send()
+            .send(
+                self.client
+                    .put(&url)
+                    .header("Content-Type", "text/markdown; charset=utf-8")
+                    .body(markdown_content),
+            )
             .await
-            .map_err(|e| AppError::NetworkError(e.to_string()))?;
+            .map_err(network_error)?;
 
         if !response.status().is_success() {
-            return Err(AppError::WebDav(format!(
-                "PUT Markdown failed: {} for note {}",
+            return Err(status_error(
                 response.status(),
-                note.id
-            )));
+                format!(
+                    "PUT Markdown failed: {} for note {}",
+                    response.status(),
+                    note.id
+                ),
+            ));
         }
 
         Ok(())
@@ -467,85 +1730,166 @@ impl WebDavClient {
         let folder = note.folder_name.as_deref();
 
         let json_url = self.note_json_url(folder, &note.id);
-        let _ = self
-            .client
-            .delete(&json_url)
-            .header("Authorization", &self.auth_header)
-            .send()
-            .await;
+        let _ = self.send(self.client.delete(&json_url)).await;
 
         let safe_title = sanitize_filename(&note.title, &note.id);
         let md_url = self.note_md_url(folder, &safe_title);
-        let _ = self
-            .client
-            .delete(&md_url)
-            .header("Authorization", &self.auth_header)
-            .send()
-            .await;
+        let _ = self.send(self.client.delete(&md_url)).await;
 
         Ok(())
     }
 
-    /// Verschiebt eine Notiz von `from_folder` nach `to_folder` (Copy-then-Delete).
-    /// Toleriert 404 beim Löschen des alten Pfades.
-    #[allow(dead_code)]
+    /// Verschiebt eine Notiz (JSON + Markdown-Zwilling) per `MOVE` von `from_folder` nach
+    /// `to_folder` — atomar je Datei, die Versionshistorie (Nextcloud) bleibt erhalten.
+    /// Server ohne MOVE (405/501) → Copy-then-Delete. Liegt die Notiz nicht mehr am alten
+    /// Pfad, ist nichts zu tun; existiert sie am Ziel bereits, wird nur der alte Pfad gelöscht.
     pub async fn move_note_file(
         &self,
         id: &str,
         from_folder: Option<&str>,
         to_folder: Option<&str>,
     ) -> Result<()> {
-        // Notiz am alten Pfad laden
-        let mut note = self.get_note(id, from_folder).await?;
+        // Server-Titel bestimmt den Namen des Markdown-Zwillings
+        let note = match self.get_note(id, from_folder).await {
+            Ok(note) => note,
+            Err(AppError::NoteNotFound(_)) => return Ok(()),
+            Err(e) => return Err(e),
+        };
+
+        if let Some(f) = to_folder {
+            self.ensure_folder_dirs(f).await;
+        }
+
+        let old_json = self.note_json_url(from_folder, id);
+        let safe_title = sanitize_filename(&note.title, id);
+        let old_md = self.note_md_url(from_folder, &safe_title);
+
+        match self
+            .move_resource(&old_json, &self.note_json_url(to_folder, id))
+            .await?
+        {
+            MoveOutcome::Moved | MoveOutcome::SourceMissing => {}
+            MoveOutcome::TargetExists => {
+                // Ziel wurde schon beschrieben (anderes Gerät / abgebrochener Move):
+                // alter Pfad ist überholt, der Upload aktualisiert das Ziel.
+                let _ = self.send(self.client.delete(&old_json)).await;
+                let _ = self.send(self.client.delete(&old_md)).await;
+                return Ok(());
+            }
+            MoveOutcome::Unsupported => {
+                return self.copy_note_file(note, from_folder, to_folder).await
+            }
+        }
+
+        // Markdown-Zwilling mitnehmen; fehlt er, legt der nächste Upload ihn neu an
+        let new_md = self.note_md_url(to_folder, &safe_title);
+        match self.move_resource(&old_md, &new_md).await {
+            Ok(MoveOutcome::TargetExists) => {
+                let _ = self.send(self.client.delete(&old_md)).await;
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("[WebDAV] MOVE {} fehlgeschlagen: {}", old_md, e),
+        }
+
+        Ok(())
+    }
 
-        // Ordner aktualisieren
+    /// Fallback für Server ohne MOVE: am neuen Pfad speichern, dann alte Dateien löschen.
+    /// Toleriert 404 beim Löschen des alten Pfades.
+    async fn copy_note_file(
+        &self,
+        mut note: Note,
+        from_folder: Option<&str>,
+        to_folder: Option<&str>,
+    ) -> Result<()> {
         note.folder_name = to_folder.map(str::to_owned);
         // Timestamp aktualisieren, damit Android die neuere Server-Version zieht
         // und nicht seine lokale Kopie (gleicher Timestamp) erneut hochlädt.
         note.updated_at = chrono::Utc::now().timestamp_millis();
 
-        // Ziel-Verzeichnisse anlegen (falls Ordner)
-        if let Some(f) = to_folder {
-            self.ensure_folder_dirs(f).await;
-        }
-
         // Am neuen Pfad speichern (JSON + MD)
-        self.save_json(&note).await?;
+        self.save_json(&note, &WriteCondition::Unconditional)
+            .await?;
         self.save_markdown(&note).await?;
 
         // Alten JSON-Pfad löschen (Fehler ignorieren)
-        let old_json = self.note_json_url(from_folder, id);
-        let _ = self
-            .client
-            .delete(&old_json)
-            .header("Authorization", &self.auth_header)
-            .send()
-            .await;
+        let old_json = self.note_json_url(from_folder, &note.id);
+        let _ = self.send(self.client.delete(&old_json)).await;
 
         // Alten MD-Pfad löschen (Fehler ignorieren)
-        let safe_title = sanitize_filename(&note.title, id);
+        let safe_title = sanitize_filename(&note.title, &note.id);
         let old_md = self.note_md_url(from_folder, &safe_title);
-        let _ = self
-            .client
-            .delete(&old_md)
-            .header("Authorization", &self.auth_header)
-            .send()
-            .await;
+        let _ = self.send(self.client.delete(&old_md)).await;
 
         Ok(())
     }
 
+    /// Benennt einen Ordner am Server um: JSON- und Markdown-Verzeichnis werden als ganze
+    /// Collection verschoben. `Ok(false)` wenn das nicht möglich war (kein MOVE, Ziel existiert
+    /// schon) — der Aufrufer verschiebt die Notizen dann einzeln.
+    pub async fn move_folder_dirs(&self, old_name: &str, new_name: &str) -> Result<bool> {
+        let json = self
+            .move_resource(
+                &self.folder_json_dir_url(old_name),
+                &self.folder_json_dir_url(new_name),
+            )
+            .await?;
+        if !matches!(json, MoveOutcome::Moved | MoveOutcome::SourceMissing) {
+            return Ok(false);
+        }
+        let md = self
+            .move_resource(
+                &self.folder_md_dir_url(old_name),
+                &self.folder_md_dir_url(new_name),
+            )
+            .await?;
+        if md == MoveOutcome::TargetExists {
+            // JSON ist umgezogen; die Markdown-Exporte schreibt der nächste Upload neu
+            let _ = self
+                .send(self.client.delete(self.folder_md_dir_url(old_name)))
+                .await;
+        }
+        Ok(true)
+    }
+
+    /// `MOVE` mit `Destination` und `Overwrite: F` — nie überschreiben, die Entscheidung trifft
+    /// der Aufrufer. Nicht idempotent, daher ohne Wiederholungen (siehe `send`).
+    async fn move_resource(&self, from: &str, to: &str) -> Result<MoveOutcome> {
+        if self.move_unsupported.load(Ordering::SeqCst) {
+            return Ok(MoveOutcome::Unsupported);
+        }
+        let response = self
+            .send(
+                self.client
+                    .request(MOVE.clone(), from)
+                    .header("Destination", to)
+                    .header("Overwrite", "F"),
+            )
+            .await
+            .map_err(network_error)?;
+        match response.status() {
+            s if s.is_success() => Ok(MoveOutcome::Moved),
+            StatusCode::NOT_FOUND => Ok(MoveOutcome::SourceMissing),
+            StatusCode::PRECONDITION_FAILED => Ok(MoveOutcome::TargetExists),
+            StatusCode::METHOD_NOT_ALLOWED | StatusCode::NOT_IMPLEMENTED => {
+                eprintln!("[WebDAV] MOVE nicht unterstützt → Copy-then-Delete");
+                self.move_unsupported.store(true, Ordering::SeqCst);
+                Ok(MoveOutcome::Unsupported)
+            }
+            StatusCode::UNAUTHORIZED => Err(AppError::InvalidCredentials),
+            s => Err(status_error(
+                s,
+                format!("MOVE {} → {} failed: {}", from, to, s),
+            )),
+        }
+    }
+
     // ── Ordner-Metadaten ────────────────────────────────────────────────────────
 
     /// Lädt `folders.json` vom Server (404 → leere Liste).
     pub async fn read_folders_meta(&self) -> Vec<FolderMeta> {
         let url = self.folders_file_url();
-        let resp = self
-            .client
-            .get(&url)
-            .header("Authorization", &self.auth_header)
-            .send()
-            .await;
+        let resp = self.send(self.client.get(&url)).await;
 
         let Ok(resp) = resp else {
             return Vec::new();
@@ -562,37 +1906,15 @@ impl WebDavClient {
         parse_folders_json(&text)
     }
 
-    /// Read-Modify-Write für `folders.json`:
-    /// GET remote → `mutation` anwenden → PUT zurück.
-    /// `mutation` erhält die aktuelle Liste und gibt die veränderte zurück.
+    /// Read-Modify-Write für `folders.json` (siehe `update_shared_file`).
+    /// `mutation` erhält die aktuelle Liste und gibt die veränderte zurück; sie wird bei
+    /// gleichzeitigen Änderungen anderer Geräte mit dem neuen Stand erneut aufgerufen.
     pub async fn write_folders_meta_merged(
         &self,
-        mutation: impl FnOnce(Vec<FolderMeta>) -> Vec<FolderMeta>,
+        mutation: impl FnMut(Vec<FolderMeta>) -> Vec<FolderMeta>,
     ) -> Result<Vec<FolderMeta>> {
-        let remote = self.read_folders_meta().await;
-        let updated = mutation(remote);
-        let json = serde_json::to_string_pretty(&updated)
-            .map_err(|e| AppError::ParseError(e.to_string()))?;
-
-        let url = self.folders_file_url();
-        let resp = self
-            .client
-            .put(&url)
-            .header("Authorization", &self.auth_header)
-            .header("Content-Type", "application/json")
-            .body(json)
-            .send()
+        self.update_shared_file(&self.folders_file_url(), parse_folders_json, mutation)
             .await
-            .map_err(|e| AppError::NetworkError(e.to_string()))?;
-
-        if !resp.status().is_success() {
-            return Err(AppError::WebDav(format!(
-                "PUT folders.json failed: {}",
-                resp.status()
-            )));
-        }
-
-        Ok(updated)
     }
 
     // ── Lösch-Ledger ────────────────────────────────────────────────────────────
@@ -600,12 +1922,7 @@ impl WebDavClient {
     /// Lädt `deletions.json` vom Server (404 oder Parse-Fehler → leeres Ledger).
     pub async fn read_deletions(&self) -> DeletionLedger {
         let url = self.deletions_file_url();
-        let resp = self
-            .client
-            .get(&url)
-            .header("Authorization", &self.auth_header)
-            .send()
-            .await;
+        let resp = self.send(self.client.get(&url)).await;
 
         let Ok(resp) = resp else {
             return DeletionLedger::default();
@@ -622,46 +1939,37 @@ impl WebDavClient {
         serde_json::from_str(&text).unwrap_or_default()
     }
 
-    /// Read-Modify-Write für `deletions.json`: GET → `mutation` → PUT.
+    /// Read-Modify-Write für `deletions.json` (siehe `update_shared_file`).
     async fn write_deletions_merged(
         &self,
-        mutation: impl FnOnce(DeletionLedger) -> DeletionLedger,
+        mutation: impl FnMut(DeletionLedger) -> DeletionLedger,
     ) -> Result<()> {
-        let remote = self.read_deletions().await;
-        let updated = mutation(remote);
-        let json = serde_json::to_string_pretty(&updated)
-            .map_err(|e| AppError::ParseError(e.to_string()))?;
-
-        let url = self.deletions_file_url();
-        let resp = self
-            .client
-            .put(&url)
-            .header("Authorization", &self.auth_header)
-            .header("Content-Type", "application/json")
-            .body(json)
-            .send()
-            .await
-            .map_err(|e| AppError::NetworkError(e.to_string()))?;
-
-        if !resp.status().is_success() {
-            return Err(AppError::WebDav(format!(
-                "PUT deletions.json failed: {}",
-                resp.status()
-            )));
-        }
-
-        Ok(())
+        self.update_shared_file(
+            &self.deletions_file_url(),
+            |text| serde_json::from_str(text).unwrap_or_default(),
+            mutation,
+        )
+        .await
+        .map(|_| ())
     }
 
     /// Fügt einen Lösch-Eintrag ins gemeinsame Ledger ein (read-modify-write).
     /// Best-effort: Fehler werden geloggt, aber nicht propagiert.
     #[allow(dead_code)]
-    pub async fn append_deletion(&self, id: &str, device_id: &str, now: i64, retention_ms: i64) {
+    pub async fn append_deletion(
+        &self,
+        id: &str,
+        device_id: &str,
+        now: i64,
+        stamp: Option<&Hlc>,
+        retention_ms: i64,
+    ) {
         let id = id.to_string();
         let device_id = device_id.to_string();
+        let stamp = stamp.cloned();
         let result = self
             .write_deletions_merged(move |ledger| {
-                merge_deletion(ledger, &id, &device_id, now, retention_ms)
+                merge_deletion(ledger, &id, &device_id, now, stamp.as_ref(), retention_ms)
             })
             .await;
         if let Err(e) = result {
@@ -677,6 +1985,7 @@ impl WebDavClient {
         ids: &[String],
         device_id: &str,
         now: i64,
+        stamp: Option<&Hlc>,
         retention_ms: i64,
     ) {
         if ids.is_empty() {
@@ -684,10 +1993,12 @@ impl WebDavClient {
         }
         let ids = ids.to_vec();
         let device_id = device_id.to_string();
+        let stamp = stamp.cloned();
         let result = self
             .write_deletions_merged(move |mut ledger| {
                 for id in &ids {
-                    ledger = merge_deletion(ledger, id, &device_id, now, retention_ms);
+                    ledger =
+                        merge_deletion(ledger, id, &device_id, now, stamp.as_ref(), retention_ms);
                 }
                 ledger
             })
@@ -723,71 +2034,324 @@ impl WebDavClient {
     pub async fn delete_note_by_id_folder(&self, id: &str, folder: Option<&str>) -> Result<()> {
         let json_url = self.note_json_url(folder, id);
         let resp = self
-            .client
-            .delete(&json_url)
-            .header("Authorization", &self.auth_header)
-            .send()
+            .send(self.client.delete(&json_url))
+            .await
+            .map_err(network_error)?;
+
+        match resp.status() {
+            s if s.is_success() || s == StatusCode::NOT_FOUND => Ok(()),
+            s => Err(status_error(
+                s,
+                format!("DELETE {} fehlgeschlagen: {}", id, s),
+            )),
+        }
+    }
+
+    // ── Gemeinsame Metadaten-Dateien (LOCK / bedingter Merge) ────────────────────
+
+    /// Read-Modify-Write einer von allen Geräten geteilten JSON-Datei.
+    ///
+    /// Bevorzugt unter exklusivem WebDAV-LOCK (mit Timeout; UNLOCK auch im Fehlerfall).
+    /// Server ohne LOCK-Support: GET → `mutation` → PUT mit `If-Match`, bei 412/423
+    /// erneut lesen und mergen. `mutation` kann daher mehrfach aufgerufen werden.
+    async fn update_shared_file<T: Default + Serialize>(
+        &self,
+        url: &str,
+        parse: impl Fn(&str) -> T,
+        mut mutation: impl FnMut(T) -> T,
+    ) -> Result<T> {
+        if let Some(token) = self.lock(url).await? {
+            let result = self
+                .update_shared_file_locked(url, &token, &parse, &mut mutation)
+                .await;
+            self.unlock(url, &token).await;
+            return result;
+        }
+
+        for attempt in 0..SHARED_FILE_ATTEMPTS {
+            let (current, version) = self.read_shared_file(url, &parse).await?;
+            let updated = mutation(current);
+            let condition = match version {
+                SharedFileVersion::Missing => WriteCondition::IfNoneMatch,
+                SharedFileVersion::ETag(etag) => WriteCondition::IfMatch(etag),
+                // Server ohne ETags: nur noch unbedingt möglich (Verhalten wie bisher)
+                SharedFileVersion::Unversioned => WriteCondition::Unconditional,
+            };
+            match self.put_shared_file(url, &updated, &condition, None).await {
+                Ok(()) => return Ok(updated),
+                Err(AppError::Conflict(reason)) => {
+                    eprintln!(
+                        "[WebDAV] {} gleichzeitig geändert ({}), Merge-Versuch {}/{}",
+                        url,
+                        reason,
+                        attempt + 2,
+                        SHARED_FILE_ATTEMPTS
+                    );
+                    tokio::time::sleep(backoff_delay(attempt, jitter())).await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Err(AppError::Conflict(format!(
+            "{}: zu viele gleichzeitige Änderungen",
+            url
+        )))
+    }
+
+    async fn update_shared_file_locked<T: Default + Serialize>(
+        &self,
+        url: &str,
+        token: &str,
+        parse: &impl Fn(&str) -> T,
+        mutation: &mut impl FnMut(T) -> T,
+    ) -> Result<T> {
+        let (current, _) = self.read_shared_file(url, parse).await?;
+        let updated = mutation(current);
+        self.put_shared_file(url, &updated, &WriteCondition::Unconditional, Some(token))
+            .await?;
+        Ok(updated)
+    }
+
+    /// Liest eine geteilte Datei samt Version. Anders als `read_folders_meta`/`read_deletions`
+    /// sind Lesefehler hier fatal — ein als leer angenommener Stand würde beim PUT die
+    /// Einträge anderer Geräte löschen.
+    async fn read_shared_file<T: Default>(
+        &self,
+        url: &str,
+        parse: &impl Fn(&str) -> T,
+    ) -> Result<(T, SharedFileVersion)> {
+        let response = self
+            .send(self.client.get(url))
             .await
-            .map_err(|e| AppError::NetworkError(e.to_string()))?;
+            .map_err(network_error)?;
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok((T::default(), SharedFileVersion::Missing)),
+            s if s.is_success() => {
+                let version = match etag_of(&response) {
+                    Some(etag) => SharedFileVersion::ETag(etag),
+                    None => SharedFileVersion::Unversioned,
+                };
+                let text = response.text().await.map_err(network_error)?;
+                Ok((parse(&text), version))
+            }
+            s => Err(status_error(s, format!("GET {} failed: {}", url, s))),
+        }
+    }
+
+    /// PUT einer geteilten Datei; 412 (ETag veraltet) und 423 (von anderem Gerät
+    /// gesperrt) werden zu `AppError::Conflict`.
+    async fn put_shared_file<T: Serialize>(
+        &self,
+        url: &str,
+        value: &T,
+        condition: &WriteCondition,
+        lock_token: Option<&str>,
+    ) -> Result<()> {
+        let json =
+            serde_json::to_string_pretty(value).map_err(|e| AppError::ParseError(e.to_string()))?;
+        let mut request = self
+            .client
+            .put(url)
+            .header("Content-Type", "application/json");
+        request = match condition {
+            WriteCondition::Unconditional => request,
+            WriteCondition::IfMatch(etag) => request.header("If-Match", etag),
+            WriteCondition::IfNoneMatch => request.header("If-None-Match", "*"),
+        };
+        if let Some(token) = lock_token {
+            request = request.header("If", format!("(<{}>)", token));
+        }
+
+        let response = self.send(request.body(json)).await.map_err(network_error)?;
+        match response.status() {
+            s if s.is_success() => Ok(()),
+            s @ (StatusCode::PRECONDITION_FAILED | StatusCode::LOCKED) => {
+                Err(AppError::Conflict(format!("PUT {}: {}", url, s)))
+            }
+            s => Err(status_error(s, format!("PUT {} failed: {}", url, s))),
+        }
+    }
+
+    /// Exklusiver Schreib-LOCK (RFC 4918 §9.10) mit Timeout, damit ein abgestürztes Gerät
+    /// die Datei nicht dauerhaft sperrt. `Ok(None)` wenn der Server kein LOCK kann
+    /// (einmal erkannt, danach ohne weiteren Versuch). Ist die Datei gesperrt, wird mit
+    /// Backoff erneut versucht; bleibt sie es, kommt `ServerBusy`.
+    async fn lock(&self, url: &str) -> Result<Option<String>> {
+        if self.lock_unsupported.load(Ordering::SeqCst) {
+            return Ok(None);
+        }
+        let body = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:lockinfo xmlns:d="DAV:">
+  <d:lockscope><d:exclusive/></d:lockscope>
+  <d:locktype><d:write/></d:locktype>
+  <d:owner>Simple Notes Desktop</d:owner>
+</d:lockinfo>"#;
+
+        for attempt in 0..LOCK_ATTEMPTS {
+            let response = self
+                .send(
+                    self.client
+                        .request(LOCK.clone(), url)
+                        .header("Depth", "0")
+                        .header("Timeout", format!("Second-{}", LOCK_TIMEOUT_SECS))
+                        .header("Content-Type", "application/xml")
+                        .body(body),
+                )
+                .await
+                .map_err(network_error)?;
+            match response.status() {
+                StatusCode::OK | StatusCode::CREATED => {
+                    let header = response
+                        .headers()
+                        .get("Lock-Token")
+                        .and_then(|v| v.to_str().ok())
+                        .map(str::to_owned);
+                    let text = response.text().await.unwrap_or_default();
+                    return match parse_lock_token(header.as_deref(), &text) {
+                        Some(token) => Ok(Some(token)),
+                        None => {
+                            eprintln!("[WebDAV] LOCK {} ohne Lock-Token → ohne Lock", url);
+                            self.lock_unsupported.store(true, Ordering::SeqCst);
+                            Ok(None)
+                        }
+                    };
+                }
+                StatusCode::LOCKED => {
+                    tokio::time::sleep(backoff_delay(attempt, jitter())).await;
+                }
+                StatusCode::UNAUTHORIZED => return Err(AppError::InvalidCredentials),
+                s => {
+                    eprintln!("[WebDAV] LOCK nicht unterstützt ({}) → bedingte PUTs", s);
+                    self.lock_unsupported.store(true, Ordering::SeqCst);
+                    return Ok(None);
+                }
+            }
+        }
+        Err(AppError::ServerBusy(format!("{} ist gesperrt", url)))
+    }
+
+    /// Gibt einen LOCK frei. Fehler werden nur geloggt — der Lock läuft spätestens nach
+    /// `LOCK_TIMEOUT_SECS` von selbst ab.
+    async fn unlock(&self, url: &str, token: &str) {
+        let result = self
+            .send(
+                self.client
+                    .request(UNLOCK.clone(), url)
+                    .header("Lock-Token", format!("<{}>", token)),
+            )
+            .await;
+        match result {
+            Ok(r) if r.status().is_success() => {}
+            Ok(r) => eprintln!("[WebDAV] UNLOCK {} fehlgeschlagen: {}", url, r.status()),
+            Err(e) => eprintln!("[WebDAV] UNLOCK {} fehlgeschlagen: {}", url, e),
+        }
+    }
 
-        match resp.status() {
-            s if s.is_success() || s == StatusCode::NOT_FOUND => Ok(()),
-            s => Err(AppError::WebDav(format!(
-                "DELETE {} fehlgeschlagen: {}",
-                id, s
-            ))),
+    // ── Diagnose ────────────────────────────────────────────────────────────────
+
+    /// Einzelner Request für die Server-Diagnose (`diagnostics.rs`): ohne Wiederholungen,
+    /// damit der Bericht die erste Antwort des Servers zeigt. Der Status wird nicht bewertet.
+    pub async fn probe(
+        &self,
+        method: Method,
+        url: &str,
+        headers: &[(&str, &str)],
+        body: Option<String>,
+    ) -> Result<ProbeResponse> {
+        let mut request = self.client.request(method, url);
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        if let Some(body) = body {
+            request = request.body(body);
         }
+        let request = request.build().map_err(network_error)?;
+        let response = self.execute(request).await.map_err(network_error)?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = response.text().await.map_err(network_error)?;
+        Ok(ProbeResponse {
+            status,
+            headers,
+            body,
+        })
     }
 
     // ── Interner PROPFIND-Helfer ─────────────────────────────────────────────────
 
-    async fn propfind_text(&self, url: &str, depth: &str) -> Result<String> {
+    /// PROPFIND mit den Props für Listing und Delta-Sync; liefert die geparste Multistatus-Antwort.
+    async fn propfind(&self, url: &str, depth: &str) -> Result<Multistatus> {
+        self.propfind_existing(url, depth)
+            .await?
+            .ok_or_else(|| AppError::WebDav(format!("PROPFIND failed: {}", StatusCode::NOT_FOUND)))
+    }
+
+    /// Wie `propfind`, aber `None` wenn die Ressource nicht existiert (404).
+    async fn propfind_existing(&self, url: &str, depth: &str) -> Result<Option<Multistatus>> {
         let body = r#"<?xml version="1.0" encoding="utf-8"?>
-<d:propfind xmlns:d="DAV:">
+<d:propfind xmlns:d="DAV:" xmlns:oc="http://owncloud.org/ns">
   <d:prop>
     <d:displayname/>
     <d:getcontenttype/>
     <d:resourcetype/>
+    <d:getetag/>
+    <d:getlastmodified/>
+    <d:getcontentlength/>
+    <d:quota-used-bytes/>
+    <d:quota-available-bytes/>
+    <oc:fileid/>
   </d:prop>
 </d:propfind>"#;
 
         let response = self
-            .client
-            .request(PROPFIND.clone(), url)
-            .header("Authorization", &self.auth_header)
-            .header("Depth", depth)
-            .header("Content-Type", "application/xml")
-            .body(body)
-            .send()
+            .send(
+                self.client
+                    .request(PROPFIND.clone(), url)
+                    .header("Depth", depth)
+                    .header("Content-Type", "application/xml")
+                    .body(body),
+            )
             .await
-            .map_err(|e| AppError::NetworkError(e.to_string()))?;
+            .map_err(network_error)?;
 
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
         if !response.status().is_success() && response.status() != StatusCode::MULTI_STATUS {
-            return Err(AppError::WebDav(format!(
-                "PROPFIND failed: {}",
-                response.status()
-            )));
+            return Err(status_error(
+                response.status(),
+                format!("PROPFIND failed: {}", response.status()),
+            ));
         }
 
-        response
-            .text()
-            .await
-            .map_err(|e| AppError::NetworkError(e.to_string()))
+        let text = response.text().await.map_err(network_error)?;
+        multistatus::parse(&text).map(Some)
     }
 }
 
-/// Fügt einen Lösch-Eintrag in ein Ledger ein, dedupliziert nach id (neuestes
-/// `deleted_at` gewinnt) und bereinigt Einträge älter als `retention_ms`.
-fn merge_deletion(
+/// Fügt einen Lösch-Eintrag in ein Ledger ein, dedupliziert nach id (neueste Löschung
+/// gewinnt — per HLC-Stempel, ohne Stempel nach `deleted_at`) und bereinigt Einträge älter
+/// als `retention_ms`.
+pub(crate) fn merge_deletion(
     mut ledger: DeletionLedger,
     id: &str,
     device_id: &str,
     now: i64,
+    stamp: Option<&Hlc>,
     retention_ms: i64,
 ) -> DeletionLedger {
     ledger.version = 1;
+    let mut record = DeletionRecord {
+        id: id.to_string(),
+        deleted_at: now,
+        device_id: device_id.to_string(),
+        ..Default::default()
+    };
+    if let Some(stamp) = stamp {
+        record.set_hlc(stamp);
+    }
     if let Some(pos) = ledger.deleted_notes.iter().position(|r| r.id == id) {
-        if ledger.deleted_notes[pos].deleted_at >= now {
+        if hlc::compare(&ledger.deleted_notes[pos], &record) != std::cmp::Ordering::Less {
             // Vorhandener Eintrag ist neuer oder gleich alt → nur bereinigen
             ledger
                 .deleted_notes
@@ -796,18 +2360,14 @@ fn merge_deletion(
         }
         ledger.deleted_notes.remove(pos);
     }
-    ledger.deleted_notes.push(DeletionRecord {
-        id: id.to_string(),
-        deleted_at: now,
-        device_id: device_id.to_string(),
-    });
+    ledger.deleted_notes.push(record);
     ledger
         .deleted_notes
         .retain(|r| now - r.deleted_at <= retention_ms);
     ledger
 }
 
-fn sanitize_filename(title: &str, id: &str) -> String {
+pub(crate) fn sanitize_filename(title: &str, id: &str) -> String {
     let sanitized: String = title
         .chars()
         .map(|c| match c {
@@ -882,12 +2442,13 @@ mod tests {
 
     fn make_client() -> WebDavClient {
         WebDavClient {
-            client: Client::builder()
-                .danger_accept_invalid_certs(true)
-                .build()
-                .unwrap(),
+            client: Client::new(),
             base_url: "http://server".to_string(),
-            auth_header: "Basic dGVzdA==".to_string(),
+            auth: Auth::Header(HeaderValue::from_static("Basic dGVzdA==")),
+            retry_budget: None,
+            concurrency: DEFAULT_CONCURRENCY,
+            lock_unsupported: Arc::new(AtomicBool::new(false)),
+            move_unsupported: Arc::new(AtomicBool::new(false)),
             sync_folder: "notes".to_string(),
         }
     }
@@ -975,7 +2536,7 @@ mod tests {
   </d:response>
 </d:multistatus>"#;
 
-        let subdirs = c.extract_subdirs_from_propfind(propfind_body);
+        let subdirs = c.extract_subdirs_from_propfind(&multistatus::parse(propfind_body).unwrap());
         assert_eq!(
             subdirs.len(),
             2,
@@ -989,14 +2550,173 @@ mod tests {
     #[test]
     fn test_extract_subdirs_deduplicates() {
         let c = make_client();
-        let body = r#"
+        let body = r#"<d:multistatus xmlns:d="DAV:">
 <d:response><d:href>/dav/notes/Work/</d:href></d:response>
 <d:response><d:href>/dav/notes/Work/</d:href></d:response>
-"#;
-        let subdirs = c.extract_subdirs_from_propfind(body);
+</d:multistatus>"#;
+        let subdirs = c.extract_subdirs_from_propfind(&multistatus::parse(body).unwrap());
         assert_eq!(subdirs.len(), 1);
     }
 
+    #[test]
+    fn test_parse_note_listing_extracts_etag_and_last_modified() {
+        let body = r#"<?xml version="1.0"?>
+<d:multistatus xmlns:d="DAV:">
+  <d:response>
+    <d:href>/dav/notes/</d:href>
+    <d:propstat><d:prop><d:resourcetype><d:collection/></d:resourcetype></d:prop></d:propstat>
+  </d:response>
+  <d:response>
+    <d:href>/dav/notes/11111111-1111-1111-1111-111111111111.json</d:href>
+    <d:propstat><d:prop>
+      <d:getetag>&quot;5f1b2c&quot;</d:getetag>
+      <d:getlastmodified>Tue, 01 Jul 2025 10:00:00 GMT</d:getlastmodified>
+    </d:prop></d:propstat>
+  </d:response>
+  <d:response>
+    <d:href>/dav/notes/22222222-2222-2222-2222-222222222222.json</d:href>
+    <d:propstat><d:prop><d:getetag>"abc"</d:getetag></d:prop></d:propstat>
+  </d:response>
+</d:multistatus>"#;
+        let entries = parse_note_listing(&multistatus::parse(body).unwrap(), None);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].id, "11111111-1111-1111-1111-111111111111");
+        assert_eq!(entries[0].etag.as_deref(), Some("\"5f1b2c\""));
+        assert_eq!(
+            entries[0].last_modified.as_deref(),
+            Some("Tue, 01 Jul 2025 10:00:00 GMT")
+        );
+        assert_eq!(entries[1].etag.as_deref(), Some("\"abc\""));
+        assert_eq!(entries[1].last_modified, None);
+    }
+
+    #[test]
+    fn test_parse_note_listing_other_prefix_and_folder() {
+        let body = r#"<multistatus xmlns="DAV:"><response>
+<href>/dav/notes/Work/AAAAAAAA-1111-1111-1111-111111111111.json</href>
+<propstat><prop><getetag>"x1"</getetag></prop></propstat>
+</response></multistatus>"#;
+        let entries = parse_note_listing(&multistatus::parse(body).unwrap(), Some("Work"));
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].id, "aaaaaaaa-1111-1111-1111-111111111111");
+        assert_eq!(entries[0].folder.as_deref(), Some("Work"));
+        assert_eq!(entries[0].etag.as_deref(), Some("\"x1\""));
+    }
+
+    #[test]
+    fn test_parse_note_listing_ignores_non_note_files_and_collections() {
+        let body = r#"<d:multistatus xmlns:d="DAV:">
+<d:response><d:href>/dav/notes/folders.json</d:href></d:response>
+<d:response><d:href>/dav/notes/11111111-1111-1111-1111-111111111111.json.bak</d:href></d:response>
+<d:response><d:href>/dav/notes/22222222-2222-2222-2222-222222222222.json/</d:href></d:response>
+</d:multistatus>"#;
+        assert!(parse_note_listing(&multistatus::parse(body).unwrap(), None).is_empty());
+    }
+
+    #[test]
+    fn test_extract_subdirs_absolute_hrefs_and_lp1_prefix() {
+        let c = make_client();
+        let body = r#"<D:multistatus xmlns:D="DAV:">
+<D:response xmlns:lp1="DAV:"><D:href>http://server/notes/</D:href>
+<D:propstat><D:prop><lp1:resourcetype><D:collection/></lp1:resourcetype></D:prop></D:propstat></D:response>
+<D:response xmlns:lp1="DAV:"><D:href>http://server/notes/Projekte</D:href>
+<D:propstat><D:prop><lp1:resourcetype><D:collection/></lp1:resourcetype></D:prop></D:propstat></D:response>
+</D:multistatus>"#;
+        let subdirs = c.extract_subdirs_from_propfind(&multistatus::parse(body).unwrap());
+        assert_eq!(subdirs, vec!["Projekte".to_string()]);
+    }
+
+    // ── sync-collection Tests ────────────────────────────────────────────────────
+
+    #[test]
+    fn test_parse_sync_collection_changes_and_removals() {
+        let body = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:multistatus xmlns:d="DAV:">
+  <d:response>
+    <d:href>/dav/notes/11111111-1111-1111-1111-111111111111.json</d:href>
+    <d:propstat>
+      <d:prop><d:getetag>&quot;e2&quot;</d:getetag></d:prop>
+      <d:status>HTTP/1.1 200 OK</d:status>
+    </d:propstat>
+    <d:propstat>
+      <d:prop><d:getlastmodified/></d:prop>
+      <d:status>HTTP/1.1 404 Not Found</d:status>
+    </d:propstat>
+  </d:response>
+  <d:response>
+    <d:href>/dav/notes/22222222-2222-2222-2222-222222222222.json</d:href>
+    <d:status>HTTP/1.1 404 Not Found</d:status>
+  </d:response>
+  <d:response>
+    <d:href>/dav/notes/Work%20Stuff/</d:href>
+    <d:propstat><d:prop><d:resourcetype><d:collection/></d:resourcetype></d:prop>
+    <d:status>HTTP/1.1 200 OK</d:status></d:propstat>
+  </d:response>
+  <d:response>
+    <d:href>/dav/notes/Old</d:href>
+    <d:status>HTTP/1.1 404 Not Found</d:status>
+  </d:response>
+  <d:sync-token>http://sabre.io/ns/sync/42</d:sync-token>
+</d:multistatus>"#;
+        let (delta, truncated) =
+            parse_sync_collection(&multistatus::parse(body).unwrap(), None, "notes");
+        assert!(!truncated);
+        assert_eq!(delta.token, "http://sabre.io/ns/sync/42");
+        assert_eq!(delta.changed.len(), 1);
+        assert_eq!(delta.changed[0].id, "11111111-1111-1111-1111-111111111111");
+        assert_eq!(delta.changed[0].etag.as_deref(), Some("\"e2\""));
+        assert_eq!(delta.removed, vec!["22222222-2222-2222-2222-222222222222"]);
+        assert_eq!(delta.changed_dirs, vec!["Work Stuff"]);
+        assert_eq!(delta.removed_dirs, vec!["Old"]);
+    }
+
+    #[test]
+    fn test_parse_sync_collection_truncated() {
+        let body = r#"<multistatus xmlns="DAV:">
+  <response><href>/dav/notes/Work/</href><status>HTTP/1.1 507 Insufficient Storage</status></response>
+  <response><href>/dav/notes/Work/11111111-1111-1111-1111-111111111111.json</href>
+    <propstat><prop><getetag>"a"</getetag></prop><status>HTTP/1.1 200 OK</status></propstat>
+  </response>
+  <sync-token>t-1</sync-token>
+</multistatus>"#;
+        let (delta, truncated) =
+            parse_sync_collection(&multistatus::parse(body).unwrap(), Some("Work"), "Work");
+        assert!(truncated);
+        assert_eq!(delta.token, "t-1");
+        assert_eq!(delta.changed[0].folder.as_deref(), Some("Work"));
+        assert!(delta.changed_dirs.is_empty());
+    }
+
+    #[test]
+    fn test_collection_delta_absorb_later_page_wins() {
+        let entry = |id: &str| NoteListing {
+            id: id.to_string(),
+            folder: None,
+            etag: Some("\"x\"".to_string()),
+            last_modified: None,
+        };
+        let mut delta = CollectionDelta {
+            token: "t1".to_string(),
+            changed: vec![entry("a")],
+            removed: vec!["b".to_string()],
+            ..Default::default()
+        };
+        delta.absorb(CollectionDelta {
+            token: "t2".to_string(),
+            changed: vec![entry("b")],
+            removed: vec!["a".to_string()],
+            ..Default::default()
+        });
+        assert_eq!(delta.token, "t2");
+        assert_eq!(delta.changed, vec![entry("b")]);
+        assert_eq!(delta.removed, vec!["a".to_string()]);
+    }
+
+    #[test]
+    fn test_xml_escape() {
+        assert_eq!(xml_escape("a<b>&c"), "a&lt;b&gt;&amp;c");
+    }
+
     // ── merge_deletion Tests ─────────────────────────────────────────────────────
 
     fn make_ledger(records: &[(&str, i64)]) -> crate::models::DeletionLedger {
@@ -1008,6 +2728,7 @@ mod tests {
                     id: id.to_string(),
                     deleted_at: *deleted_at,
                     device_id: "tauri-test".to_string(),
+                    ..Default::default()
                 })
                 .collect(),
         }
@@ -1016,7 +2737,7 @@ mod tests {
     #[test]
     fn test_merge_deletion_adds_new_entry() {
         let ledger = DeletionLedger::default();
-        let result = merge_deletion(ledger, "id-a", "tauri-x", 1000, 30_000);
+        let result = merge_deletion(ledger, "id-a", "tauri-x", 1000, None, 30_000);
         assert_eq!(result.deleted_notes.len(), 1);
         assert_eq!(result.deleted_notes[0].id, "id-a");
         assert_eq!(result.deleted_notes[0].deleted_at, 1000);
@@ -1027,7 +2748,7 @@ mod tests {
     fn test_merge_deletion_dedup_keeps_newest() {
         // Existing entry at t=500, new entry at t=1000 → replace with newer
         let ledger = make_ledger(&[("id-a", 500)]);
-        let result = merge_deletion(ledger, "id-a", "tauri-x", 1000, 100_000);
+        let result = merge_deletion(ledger, "id-a", "tauri-x", 1000, None, 100_000);
         assert_eq!(result.deleted_notes.len(), 1);
         assert_eq!(result.deleted_notes[0].deleted_at, 1000);
     }
@@ -1036,7 +2757,7 @@ mod tests {
     fn test_merge_deletion_dedup_keeps_existing_if_newer() {
         // Existing entry at t=2000, new entry at t=1000 → keep existing (newer)
         let ledger = make_ledger(&[("id-a", 2000)]);
-        let result = merge_deletion(ledger, "id-a", "tauri-x", 1000, 100_000);
+        let result = merge_deletion(ledger, "id-a", "tauri-x", 1000, None, 100_000);
         assert_eq!(result.deleted_notes.len(), 1);
         assert_eq!(result.deleted_notes[0].deleted_at, 2000);
     }
@@ -1045,7 +2766,7 @@ mod tests {
     fn test_merge_deletion_prunes_expired() {
         // now=100_000, retention=30_000 → entries with deleted_at < 70_000 must be dropped
         let ledger = make_ledger(&[("old", 60_000), ("recent", 80_000)]);
-        let result = merge_deletion(ledger, "new", "tauri-x", 100_000, 30_000);
+        let result = merge_deletion(ledger, "new", "tauri-x", 100_000, None, 30_000);
         let ids: Vec<&str> = result.deleted_notes.iter().map(|r| r.id.as_str()).collect();
         assert!(!ids.contains(&"old"), "expired entry must be pruned");
         assert!(ids.contains(&"recent"));
@@ -1062,7 +2783,7 @@ mod tests {
 
         let mut result = ledger;
         for id in &ids {
-            result = merge_deletion(result, id, "tauri-x", now, retention);
+            result = merge_deletion(result, id, "tauri-x", now, None, retention);
         }
 
         assert_eq!(result.deleted_notes.len(), 3);
@@ -1119,7 +2840,7 @@ mod tests {
 
         let mut result = ledger;
         for id in &ids {
-            result = merge_deletion(result, id, "tauri-x", now, retention);
+            result = merge_deletion(result, id, "tauri-x", now, None, retention);
         }
 
         // id-a: aktualisiert auf 3000 (neuer als 1000)
@@ -1153,7 +2874,7 @@ mod tests {
     fn test_merge_deletion_prune_only_on_same_id_newer_existing() {
         // id-a already has a newer entry; only pruning should happen, no duplicate added
         let ledger = make_ledger(&[("id-a", 5000), ("old", 0)]);
-        let result = merge_deletion(ledger, "id-a", "tauri-x", 1000, 100_000);
+        let result = merge_deletion(ledger, "id-a", "tauri-x", 1000, None, 100_000);
         // id-a kept with deleted_at=5000 (newer), "old" may be pruned if expired (0 < 100_000-100_000=0? no, 100_000-0=100_000 > 100_000 false, so "old" survives here)
         let a = result
             .deleted_notes
@@ -1171,4 +2892,691 @@ mod tests {
             "no duplicate"
         );
     }
+
+    // ── Authentifizierung ────────────────────────────────────────────────────────
+
+    fn creds(scheme: AuthScheme, username: &str, password: &str) -> Credentials {
+        Credentials {
+            url: "http://server".to_string(),
+            username: username.to_string(),
+            password: password.to_string(),
+            auth_scheme: scheme,
+        }
+    }
+
+    fn header_of(auth: &Auth) -> String {
+        match auth {
+            Auth::Header(v) => v.to_str().unwrap().to_string(),
+            Auth::Digest(_) => panic!("expected static header"),
+        }
+    }
+
+    #[test]
+    fn test_auth_static_headers() {
+        let basic = Auth::from_credentials(&creds(AuthScheme::Basic, "test", "")).unwrap();
+        assert_eq!(header_of(&basic), "Basic dGVzdDo=");
+        let flow = Auth::from_credentials(&creds(AuthScheme::NextcloudLoginFlow, "user", "app-pw"))
+            .unwrap();
+        assert_eq!(header_of(&flow), "Basic dXNlcjphcHAtcHc=");
+        let bearer = Auth::from_credentials(&creds(AuthScheme::Bearer, "", " tok.en \n")).unwrap();
+        assert_eq!(header_of(&bearer), "Bearer tok.en");
+        assert!(matches!(
+            Auth::from_credentials(&creds(AuthScheme::Digest, "u", "p")).unwrap(),
+            Auth::Digest(_)
+        ));
+    }
+
+    #[test]
+    fn test_parse_digest_challenge() {
+        let c = parse_digest_challenge(
+            r#"Digest realm="http-auth@example.org", qop="auth, auth-int", algorithm=SHA-256, nonce="7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v", opaque="FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS""#,
+        )
+        .unwrap();
+        assert_eq!(c.realm, "http-auth@example.org");
+        assert_eq!(c.nonce, "7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v");
+        assert_eq!(c.algorithm, DigestAlgorithm::Sha256);
+        assert!(c.qop_auth);
+        assert!(!c.stale);
+        assert_eq!(
+            c.opaque.as_deref(),
+            Some("FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS")
+        );
+
+        // Quoted commas, unquoted stale flag, no algorithm → MD5
+        let c = parse_digest_challenge(r#"digest realm="a, b", nonce="n1", stale=TRUE"#).unwrap();
+        assert_eq!(c.realm, "a, b");
+        assert_eq!(c.algorithm, DigestAlgorithm::Md5);
+        assert!(c.stale);
+        assert!(!c.qop_auth);
+
+        assert!(parse_digest_challenge(r#"Basic realm="x""#).is_none());
+        assert!(parse_digest_challenge(r#"Digest realm="x""#).is_none());
+        assert!(parse_digest_challenge(r#"Digest nonce="n", algorithm=SHA-512-256"#).is_none());
+    }
+
+    /// Testvektoren aus RFC 2617 §3.5 und RFC 7616 §3.9.1
+    #[test]
+    fn test_digest_response_rfc_vectors() {
+        let rfc2617 = DigestAuth::new("Mufasa", "Circle Of Life");
+        let challenge = DigestChallenge {
+            realm: "testrealm@host.com".to_string(),
+            nonce: "dcd98b7102dd2f0e8b11d0f600bfb0c093".to_string(),
+            opaque: Some("5ccc069c403ebaf9f0171e9517f40e41".to_string()),
+            algorithm: DigestAlgorithm::Md5,
+            qop_auth: true,
+            stale: false,
+        };
+        let header = rfc2617.response_header(&challenge, "GET", "/dir/index.html", 1, "0a4f113b");
+        assert!(header.contains(r#"response="6629fae49393a05397450978507c4ef1""#));
+        assert!(header.contains(r#"qop=auth, nc=00000001, cnonce="0a4f113b""#));
+        assert!(header.contains(r#"opaque="5ccc069c403ebaf9f0171e9517f40e41""#));
+
+        let rfc7616 = DigestAuth::new("Mufasa", "Circle of Life");
+        let mut challenge = DigestChallenge {
+            realm: "http-auth@example.org".to_string(),
+            nonce: "7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v".to_string(),
+            opaque: None,
+            algorithm: DigestAlgorithm::Md5,
+            qop_auth: true,
+            stale: false,
+        };
+        let cnonce = "f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ";
+        let header = rfc7616.response_header(&challenge, "GET", "/dir/index.html", 1, cnonce);
+        assert!(header.contains(r#"response="8ca523f5e9506fed4657c9700eebdbec""#));
+        challenge.algorithm = DigestAlgorithm::Sha256;
+        let header = rfc7616.response_header(&challenge, "GET", "/dir/index.html", 1, cnonce);
+        assert!(header.contains(
+            r#"response="753927fa0e85d155564e2e272a28d1802ca10daf4496794697cf8db5856cb6c1""#
+        ));
+        assert!(header.contains("algorithm=SHA-256"));
+    }
+
+    #[test]
+    fn test_digest_reuses_nonce_with_increasing_count() {
+        let digest = DigestAuth::new("u", "p");
+        assert!(digest.authorization("GET", "/notes/").is_none());
+
+        *digest.state.lock().unwrap() = Some((
+            parse_digest_challenge(r#"Digest realm="r", nonce="abc", qop="auth""#).unwrap(),
+            0,
+        ));
+        let first = digest.authorization("GET", "/notes/").unwrap();
+        let second = digest.authorization("PUT", "/notes/a.json").unwrap();
+        assert!(first.contains(r#"nonce="abc""#) && first.contains("nc=00000001"));
+        assert!(second.contains(r#"nonce="abc""#) && second.contains("nc=00000002"));
+        assert!(second.contains(r#"uri="/notes/a.json""#));
+    }
+
+    #[test]
+    fn test_nextcloud_login_flow_urls() {
+        assert_eq!(
+            nextcloud_base_url("https://cloud.example.com/remote.php/dav/files/anna/"),
+            "https://cloud.example.com"
+        );
+        assert_eq!(
+            nextcloud_base_url("https://example.com/nextcloud/"),
+            "https://example.com/nextcloud"
+        );
+        assert_eq!(
+            nextcloud_dav_url("https://example.com", "https://example.com/", "Anna Maria"),
+            "https://example.com/remote.php/dav/files/Anna%20Maria"
+        );
+        assert_eq!(
+            nextcloud_dav_url(
+                "https://example.com/remote.php/webdav/",
+                "https://example.com",
+                "anna"
+            ),
+            "https://example.com/remote.php/webdav"
+        );
+    }
+
+    // ── Wiederholungen ───────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_backoff_delay_grows_with_jitter_and_cap() {
+        assert_eq!(backoff_delay(0, 1.0), Duration::from_millis(500));
+        assert_eq!(backoff_delay(0, 0.0), Duration::from_millis(250));
+        assert_eq!(backoff_delay(2, 1.0), Duration::from_secs(2));
+        assert_eq!(backoff_delay(10, 1.0), RETRY_MAX_BACKOFF);
+        assert_eq!(backoff_delay(u32::MAX, 0.0), RETRY_MAX_BACKOFF / 2);
+        for _ in 0..100 {
+            let j = jitter();
+            assert!((0.0..1.0).contains(&j));
+        }
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        let now = chrono::DateTime::parse_from_rfc2822("Wed, 21 Oct 2015 07:28:00 GMT")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        assert_eq!(
+            parse_retry_after("120", now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:30 GMT", now),
+            Some(Duration::from_secs(30))
+        );
+        // Datum in der Vergangenheit → sofort
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:00:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn test_only_idempotent_methods_are_retried() {
+        for m in [Method::GET, Method::PUT, Method::DELETE, Method::OPTIONS] {
+            assert!(is_idempotent(&m), "{}", m);
+        }
+        assert!(is_idempotent(&PROPFIND));
+        assert!(is_idempotent(&MKCOL));
+        assert!(is_idempotent(&REPORT));
+        assert!(!is_idempotent(&Method::POST));
+        assert!(!is_idempotent(&Method::from_bytes(b"LOCK").unwrap()));
+        assert!(!is_idempotent(&Method::from_bytes(b"MOVE").unwrap()));
+    }
+
+    #[test]
+    fn test_status_error_classifies_transient_statuses() {
+        for status in [
+            StatusCode::TOO_MANY_REQUESTS,
+            StatusCode::SERVICE_UNAVAILABLE,
+            StatusCode::BAD_GATEWAY,
+            StatusCode::GATEWAY_TIMEOUT,
+        ] {
+            assert!(status_error(status, String::new()).is_retryable());
+        }
+        assert!(!status_error(StatusCode::NOT_FOUND, String::new()).is_retryable());
+        assert!(!status_error(StatusCode::INTERNAL_SERVER_ERROR, String::new()).is_retryable());
+        assert!(matches!(
+            status_error(StatusCode::INSUFFICIENT_STORAGE, String::new()),
+            AppError::InsufficientStorage(_)
+        ));
+    }
+
+    #[test]
+    fn test_retry_budget_is_shared_and_exhausts() {
+        let unlimited = make_client();
+        assert!(unlimited.take_retry());
+        assert!(!unlimited.retry_budget_exhausted());
+
+        let budgeted = unlimited.with_retry_budget(2);
+        let clone = budgeted.clone();
+        assert!(budgeted.take_retry());
+        assert!(clone.take_retry());
+        assert!(!budgeted.take_retry());
+        assert!(budgeted.retry_budget_exhausted());
+        // Das Budget gilt nur für die Kopie, nicht für den Ursprungs-Client
+        assert!(unlimited.take_retry());
+    }
+
+    #[test]
+    fn test_parse_lock_token_header_and_body() {
+        assert_eq!(
+            parse_lock_token(Some("<opaquelocktoken:abc>"), "").as_deref(),
+            Some("opaquelocktoken:abc")
+        );
+        let body = r#"<d:prop xmlns:d="DAV:"><d:lockdiscovery><d:activelock>
+            <d:locktoken><d:href>urn:uuid:42</d:href></d:locktoken>
+            </d:activelock></d:lockdiscovery></d:prop>"#;
+        assert_eq!(parse_lock_token(None, body).as_deref(), Some("urn:uuid:42"));
+        assert_eq!(parse_lock_token(Some("<>"), ""), None);
+    }
+
+    // ── Gleichzeitige Schreiber auf geteilte Dateien ────────────────────────────
+
+    use crate::test_server::TestServer;
+    use crate::tls::TlsPolicy;
+
+    fn server_client(server: &TestServer) -> WebDavClient {
+        let credentials = Credentials {
+            url: server.url.clone(),
+            username: "user".to_string(),
+            password: "secret".to_string(),
+            auth_scheme: AuthScheme::Basic,
+        };
+        WebDavClient::new(
+            &credentials,
+            "notes",
+            &TlsPolicy::System,
+            &ProxyPolicy::None,
+        )
+        .unwrap()
+    }
+
+    fn ledger_ids(server: &TestServer) -> Vec<String> {
+        let text = server.file("/notes/deletions.json").unwrap();
+        let ledger: DeletionLedger = serde_json::from_str(&text).unwrap();
+        let mut ids: Vec<String> = ledger.deleted_notes.into_iter().map(|r| r.id).collect();
+        ids.sort();
+        ids
+    }
+
+    async fn concurrent_deletions(server: &TestServer) {
+        server.put_file(
+            "/notes/deletions.json",
+            r#"{"deletedNotes":[{"id":"old","deletedAt":1,"deviceId":"x"}]}"#,
+        );
+        // Beide GETs sehen denselben Stand, bevor einer der PUTs ankommt
+        server.set_get_delay(Duration::from_millis(100));
+        let (a, b) = (server_client(server), server_client(server));
+        let day = 24 * 60 * 60 * 1000;
+        let (ids_a, ids_b) = (["from-a".to_string()], ["from-b".to_string()]);
+        tokio::join!(
+            a.append_deletions(&ids_a, "device-a", 10, None, 30 * day),
+            b.append_deletions(&ids_b, "device-b", 10, None, 30 * day),
+        );
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_deletion_appends_under_lock() {
+        let server = TestServer::start(true).await;
+        concurrent_deletions(&server).await;
+
+        assert_eq!(ledger_ids(&server), vec!["from-a", "from-b", "old"]);
+        // Der zweite Schreiber wartet auf das Lock (423) statt parallel zu lesen:
+        // keine verlorenen PUTs, beide Locks wieder freigegeben
+        assert_eq!(server.count("PUT"), 2);
+        assert_eq!(server.count("UNLOCK"), 2);
+        assert!(server.count("LOCK") > 2);
+        assert!(!server.is_locked("/notes/deletions.json"));
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_deletion_appends_without_lock_support() {
+        let server = TestServer::start(false).await;
+        concurrent_deletions(&server).await;
+
+        assert_eq!(ledger_ids(&server), vec!["from-a", "from-b", "old"]);
+        // Ein Schreiber verliert das If-Match-Rennen und wiederholt GET + PUT
+        assert_eq!(server.count("PUT"), 3);
+        assert_eq!(server.count("LOCK"), 2);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_folder_writes_on_missing_file() {
+        let server = TestServer::start(false).await;
+        server.set_get_delay(Duration::from_millis(100));
+        let (a, b) = (server_client(&server), server_client(&server));
+
+        let add = |name: &'static str| {
+            move |mut folders: Vec<FolderMeta>| {
+                folders.push(FolderMeta {
+                    name: name.to_string(),
+                    updated_at: 1,
+                    ..FolderMeta::default()
+                });
+                folders
+            }
+        };
+        let (ra, rb) = tokio::join!(
+            a.write_folders_meta_merged(add("Work")),
+            b.write_folders_meta_merged(add("Home")),
+        );
+        ra.unwrap();
+        rb.unwrap();
+
+        // If-None-Match: * verhindert, dass der zweite Erstschreiber den ersten überschreibt
+        let mut names: Vec<String> =
+            parse_folders_json(&server.file("/notes/folders.json").unwrap())
+                .into_iter()
+                .map(|f| f.name)
+                .collect();
+        names.sort();
+        assert_eq!(names, vec!["Home", "Work"]);
+    }
+
+    // ── Verschieben per MOVE ─────────────────────────────────────────────────────
+
+    /// Legt eine Notiz samt Markdown-Zwilling im Ordner `Work` an; liefert (id, md-Dateiname)
+    fn seed_note(server: &TestServer) -> (String, String) {
+        let note = Note::new("Einkauf".to_string(), "dev".to_string());
+        let md = format!("{}.md", sanitize_filename(&note.title, &note.id));
+        server.mkdir("/notes/Work/");
+        server.mkdir("/notes-md/Work/");
+        server.put_file(
+            &format!("/notes/Work/{}.json", note.id),
+            &serde_json::to_string(&note).unwrap(),
+        );
+        server.put_file(&format!("/notes-md/Work/{}", md), "# Einkauf");
+        (note.id, md)
+    }
+
+    #[tokio::test]
+    async fn test_move_note_file_uses_move() {
+        let server = TestServer::start(true).await;
+        let (id, md) = seed_note(&server);
+        let before = server.file(&format!("/notes/Work/{}.json", id));
+
+        server_client(&server)
+            .move_note_file(&id, Some("Work"), Some("Home"))
+            .await
+            .unwrap();
+
+        assert_eq!(server.file(&format!("/notes/Home/{}.json", id)), before);
+        assert!(server.file(&format!("/notes-md/Home/{}", md)).is_some());
+        assert!(server.file(&format!("/notes/Work/{}.json", id)).is_none());
+        assert!(server.file(&format!("/notes-md/Work/{}", md)).is_none());
+        assert_eq!(server.count("MOVE"), 2);
+        assert_eq!(server.count("PUT"), 0);
+    }
+
+    #[tokio::test]
+    async fn test_move_note_file_falls_back_to_copy_without_move() {
+        let server = TestServer::start(true).await;
+        server.disable_move();
+        let (id, md) = seed_note(&server);
+        let client = server_client(&server);
+
+        client
+            .move_note_file(&id, Some("Work"), None)
+            .await
+            .unwrap();
+
+        assert!(server.file(&format!("/notes/{}.json", id)).is_some());
+        assert!(server.file(&format!("/notes-md/{}", md)).is_some());
+        assert!(server.file(&format!("/notes/Work/{}.json", id)).is_none());
+        assert!(server.file(&format!("/notes-md/Work/{}", md)).is_none());
+        assert_eq!(server.count("MOVE"), 1);
+
+        // 405 wird gemerkt — weitere Moves versuchen kein MOVE mehr
+        let (id, _) = seed_note(&server);
+        client
+            .move_note_file(&id, Some("Work"), None)
+            .await
+            .unwrap();
+        assert_eq!(server.count("MOVE"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_move_note_file_existing_target_removes_old_path() {
+        let server = TestServer::start(true).await;
+        let (id, md) = seed_note(&server);
+        server.put_file(&format!("/notes/{}.json", id), "{}");
+
+        server_client(&server)
+            .move_note_file(&id, Some("Work"), None)
+            .await
+            .unwrap();
+
+        // Overwrite: F — das Ziel bleibt unangetastet, der alte Pfad verschwindet
+        assert_eq!(
+            server.file(&format!("/notes/{}.json", id)).as_deref(),
+            Some("{}")
+        );
+        assert!(server.file(&format!("/notes/Work/{}.json", id)).is_none());
+        assert!(server.file(&format!("/notes-md/Work/{}", md)).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_move_note_file_missing_source_is_noop() {
+        let server = TestServer::start(true).await;
+        server_client(&server)
+            .move_note_file("gone", Some("Work"), None)
+            .await
+            .unwrap();
+        assert_eq!(server.count("MOVE"), 0);
+    }
+
+    #[tokio::test]
+    async fn test_move_folder_dirs_moves_collections() {
+        let server = TestServer::start(true).await;
+        let (id, md) = seed_note(&server);
+
+        let moved = server_client(&server)
+            .move_folder_dirs("Work", "Job Notes")
+            .await
+            .unwrap();
+
+        assert!(moved);
+        assert!(server
+            .file(&format!("/notes/Job Notes/{}.json", id))
+            .is_some());
+        assert!(server
+            .file(&format!("/notes-md/Job Notes/{}", md))
+            .is_some());
+        assert!(!server.dir_names().contains(&"/notes/Work/".to_string()));
+        assert_eq!(server.count("MOVE"), 2);
+    }
+
+    #[test]
+    fn test_is_low_space() {
+        let mb = 1024 * 1024;
+        assert!(!is_low_space(Some(mb), None));
+        assert!(!is_low_space(Some(mb), Some(-3)));
+        assert!(is_low_space(Some(mb), Some(5 * mb)));
+        assert!(!is_low_space(Some(100 * mb), Some(900 * mb)));
+        // 30 MB frei von 1 GB: über der festen Grenze, aber unter 5 %
+        assert!(is_low_space(Some(994 * mb), Some(30 * mb)));
+    }
+
+    #[tokio::test]
+    async fn test_storage_usage_per_folder() {
+        let server = TestServer::start(true).await;
+        server.mkdir("/notes/");
+        server.mkdir("/notes-md/");
+        server.set_quota(4096, 1 << 30);
+        let (id, md) = seed_note(&server);
+        let json_len = server
+            .file(&format!("/notes/Work/{}.json", id))
+            .unwrap()
+            .len() as u64;
+        let root_id = "0b8e7f1c-3a2d-4e5f-8a9b-1c2d3e4f5a6b";
+        server.put_file(&format!("/notes/{}.json", root_id), "{\"id\":1}");
+        server.put_file("/notes/folders.json", "[]");
+        server.put_file("/notes-md/Root.md", "# R");
+
+        let usage = server_client(&server).storage_usage().await.unwrap();
+
+        assert_eq!(usage.quota_used_bytes, Some(4096));
+        assert_eq!(usage.quota_available_bytes, Some(1 << 30));
+        assert!(!usage.low_space);
+        assert_eq!(usage.notes, 2);
+        assert_eq!(
+            usage.folders,
+            vec![
+                FolderUsage {
+                    folder: None,
+                    notes: 1,
+                    json_bytes: 8,
+                    markdown_bytes: 3,
+                },
+                FolderUsage {
+                    folder: Some("Work".to_string()),
+                    notes: 1,
+                    json_bytes: json_len,
+                    markdown_bytes: server
+                        .file(&format!("/notes-md/Work/{}", md))
+                        .unwrap()
+                        .len() as u64,
+                },
+            ]
+        );
+        assert_eq!(usage.json_bytes, 8 + json_len);
+    }
+
+    #[tokio::test]
+    async fn test_move_folder_dirs_existing_target_falls_back() {
+        let server = TestServer::start(true).await;
+        let (id, _) = seed_note(&server);
+        server.mkdir("/notes/Home/");
+
+        let moved = server_client(&server)
+            .move_folder_dirs("Work", "Home")
+            .await
+            .unwrap();
+
+        assert!(!moved);
+        assert!(server.file(&format!("/notes/Work/{}.json", id)).is_some());
+    }
+
+    // ── Proxy ───────────────────────────────────────────────────────────────────
+
+    fn proxied_client(url: &str, proxy: ProxyPolicy) -> WebDavClient {
+        let credentials = Credentials {
+            url: url.to_string(),
+            username: "user".to_string(),
+            password: "secret".to_string(),
+            auth_scheme: AuthScheme::Basic,
+        };
+        WebDavClient::new(&credentials, "notes", &TlsPolicy::System, &proxy).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_http_proxy_forwards_requests_with_credentials() {
+        let server = TestServer::start(true).await;
+        server.mkdir("/notes/");
+        let proxy = ProxyPolicy::Http(crate::proxy::ProxyServer {
+            url: server.url.trim_start_matches("http://").to_string(),
+            username: "proxyuser".to_string(),
+            password: "proxypass".to_string(),
+            bypass: String::new(),
+        });
+
+        // Host ist nicht auflösbar — erreichbar nur über den Proxy
+        let client = proxied_client("http://notes.invalid", proxy);
+        assert!(client.test_connection().await.unwrap());
+
+        let proxied = server.proxied();
+        assert!(!proxied.is_empty());
+        let expected = format!("Basic {}", STANDARD.encode("proxyuser:proxypass"));
+        assert!(proxied
+            .iter()
+            .all(|auth| auth.as_deref() == Some(expected.as_str())));
+    }
+
+    #[tokio::test]
+    async fn test_proxy_bypass_connects_directly() {
+        let server = TestServer::start(true).await;
+        server.mkdir("/notes/");
+        let proxy = ProxyPolicy::Http(crate::proxy::ProxyServer {
+            // Nicht erreichbarer Proxy — darf für 127.0.0.1 nicht benutzt werden
+            url: "127.0.0.1:9".to_string(),
+            username: String::new(),
+            password: String::new(),
+            bypass: "localhost, 127.0.0.1".to_string(),
+        });
+
+        let client = proxied_client(&server.url, proxy);
+        assert!(client.test_connection().await.unwrap());
+        assert!(server.proxied().is_empty());
+    }
+
+    // ── Nextcloud-Versionen ──────────────────────────────────────────────────────
+
+    const VERSIONED_ID: &str = "11111111-2222-3333-4444-555555555555";
+
+    #[test]
+    fn test_nextcloud_versions_root() {
+        assert_eq!(
+            nextcloud_versions_root("https://cloud.example/remote.php/dav/files/alice").as_deref(),
+            Some("https://cloud.example/remote.php/dav/versions/alice/versions")
+        );
+        assert_eq!(
+            nextcloud_versions_root("https://cloud.example/nc/remote.php/dav/files/bob/Sub")
+                .as_deref(),
+            Some("https://cloud.example/nc/remote.php/dav/versions/bob/versions")
+        );
+        assert_eq!(
+            nextcloud_versions_root("https://cloud.example/remote.php/webdav"),
+            None
+        );
+        assert_eq!(nextcloud_versions_root("http://nas.local:8080"), None);
+    }
+
+    #[test]
+    fn test_parse_versions_newest_first() {
+        let listing = multistatus::parse(include_str!(
+            "../tests/fixtures/versions/nextcloud_versions.xml"
+        ))
+        .unwrap();
+        let versions = parse_versions(&listing);
+        assert_eq!(
+            versions,
+            vec![
+                ServerVersion {
+                    version_id: "1751364000".to_string(),
+                    timestamp: 1_751_364_000_000,
+                    size: Some(355),
+                },
+                ServerVersion {
+                    version_id: "1751277600".to_string(),
+                    timestamp: 1_751_277_600_000,
+                    size: Some(298),
+                },
+            ]
+        );
+    }
+
+    /// Test-Server mit aufgezeichneten Nextcloud-Antworten für Datei-ID und Versions-Listing.
+    async fn nextcloud_with_versions() -> (TestServer, WebDavClient) {
+        let server = TestServer::start(true).await;
+        server.record(
+            "PROPFIND",
+            &format!("/remote.php/dav/files/alice/notes/{}.json", VERSIONED_ID),
+            207,
+            include_str!("../tests/fixtures/versions/nextcloud_fileid.xml"),
+        );
+        server.record(
+            "PROPFIND",
+            "/remote.php/dav/versions/alice/versions/4711/",
+            207,
+            include_str!("../tests/fixtures/versions/nextcloud_versions.xml"),
+        );
+        let mut old = Note::new("Einkauf (alt)".to_string(), "dev".to_string());
+        old.id = VERSIONED_ID.to_string();
+        old.content = "Milch".to_string();
+        old.folder_name = Some("Work".to_string());
+        server.put_file(
+            "/remote.php/dav/versions/alice/versions/4711/1751277600",
+            &serde_json::to_string(&old).unwrap(),
+        );
+        let url = format!("{}/remote.php/dav/files/alice", server.url);
+        let client = proxied_client(&url, ProxyPolicy::None);
+        (server, client)
+    }
+
+    #[tokio::test]
+    async fn test_list_and_get_note_versions() {
+        let (_server, client) = nextcloud_with_versions().await;
+
+        let versions = client.list_note_versions(VERSIONED_ID, None).await.unwrap();
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions[0].version_id, "1751364000");
+
+        let note = client
+            .get_note_version(VERSIONED_ID, None, "1751277600")
+            .await
+            .unwrap();
+        assert_eq!(note.title, "Einkauf (alt)");
+        assert_eq!(note.content, "Milch");
+        // Ordner folgt dem aktuellen Pfad, nicht dem JSON der alten Fassung
+        assert_eq!(note.folder_name, None);
+
+        assert!(matches!(
+            client.get_note_version(VERSIONED_ID, None, "1").await,
+            Err(AppError::NoteNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_versions_unsupported_or_missing() {
+        let (server, client) = nextcloud_with_versions().await;
+        assert!(matches!(
+            client.list_note_versions("missing", None).await,
+            Err(AppError::NoteNotFound(_))
+        ));
+
+        // Kein Nextcloud-Pfad → gar nicht erst versuchen
+        assert!(matches!(
+            server_client(&server)
+                .list_note_versions(VERSIONED_ID, None)
+                .await,
+            Err(AppError::Unsupported(_))
+        ));
+    }
 }
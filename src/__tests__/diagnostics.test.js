@@ -0,0 +1,48 @@
+import { describe, expect, it } from 'vitest';
+import { formatBytes, formatDiagnosticReport } from '../utils/diagnostics.js';
+
+const report = {
+  generated_at: '2025-07-01T10:00:00Z',
+  app_version: '0.10.0',
+  server_url: 'https://cloud.example.com/remote.php/dav/files/anna',
+  sync_folder: 'notes',
+  tls: 'system trust store',
+  server_software: null,
+  dav_classes: ['1', '2', '3'],
+  allowed_methods: ['OPTIONS', 'PROPFIND'],
+  clock_offset_secs: 2,
+  quota_used_bytes: 1536,
+  quota_available_bytes: -3,
+  checks: [
+    { id: 'options', status: 'ok', detail: 'reachable (200 OK)', duration_ms: 42 },
+    { id: 'lock', status: 'warning', detail: 'not supported (405 Method Not Allowed)', duration_ms: 7 },
+    { id: 'get', status: 'skipped', detail: 'probe file could not be uploaded', duration_ms: 0 },
+  ],
+};
+
+describe('formatBytes', () => {
+  it('uses 1024-based units with one decimal', () => {
+    expect(formatBytes(512)).toBe('512 B');
+    expect(formatBytes(1536)).toBe('1.5 KB');
+    expect(formatBytes(5 * 1024 ** 3)).toBe('5.0 GB');
+  });
+});
+
+describe('formatDiagnosticReport', () => {
+  it('renders the header facts', () => {
+    const text = formatDiagnosticReport(report);
+    expect(text).toContain('Simple Notes Desktop 0.10.0');
+    expect(text).toContain('(sync folder /notes/)');
+    expect(text).toContain('Software: unknown');
+    expect(text).toContain('DAV: 1, 2, 3');
+    expect(text).toContain('Clock offset: +2 s');
+    expect(text).toContain('Quota: 1.5 KB used, no limit reported');
+  });
+
+  it('renders one line per check with status label and duration', () => {
+    const lines = formatDiagnosticReport(report).split('\n');
+    expect(lines).toContain('[OK]   options — reachable (200 OK) (42 ms)');
+    expect(lines).toContain('[WARN] lock — not supported (405 Method Not Allowed) (7 ms)');
+    expect(lines).toContain('[SKIP] get — probe file could not be uploaded');
+  });
+});
@@ -241,6 +241,31 @@ describe('DialogService', () => {
     });
   });
 
+  describe('chooseVersion()', () => {
+    const versions = [
+      { version_id: '1751364000', label: 'July 1' },
+      { version_id: '1751277600', label: 'June 30' },
+    ];
+
+    it('should list versions and return the selected id', async () => {
+      const promise = dialogService.chooseVersion({ versions });
+
+      const select = document.getElementById('dialog-version-select');
+      expect(select.options.length).toBe(2);
+      expect(select.options[0].textContent).toBe('July 1');
+      select.value = '1751277600';
+
+      document.getElementById('dialog-confirm-btn').click();
+      expect(await promise).toBe('1751277600');
+    });
+
+    it('should return undefined on cancel', async () => {
+      const promise = dialogService.chooseVersion({ versions });
+      document.getElementById('dialog-cancel-btn').click();
+      expect(await promise).toBeUndefined();
+    });
+  });
+
   describe('keyboard handling', () => {
     it('should close confirm dialog on Escape', async () => {
       const promise = dialogService.confirm({
@@ -265,6 +290,52 @@ describe('DialogService', () => {
     });
   });
 
+  describe('confirmConflictResolve()', () => {
+    it('should show both versions side by side and resolve keep_both', async () => {
+      const promise = dialogService.confirmConflictResolve({
+        isDeleted: false,
+        versions: {
+          local: { title: 'Einkauf', content: 'Milch <1l>' },
+          server: { title: 'Einkauf', content: 'Hafermilch' },
+        },
+      });
+      const message = document.getElementById('dialog-message');
+      expect(message.querySelectorAll('.conflict-side')).toHaveLength(2);
+      expect(message.textContent).toContain('Milch <1l>');
+      expect(message.textContent).toContain('Hafermilch');
+
+      document.getElementById('cr-both').click();
+      expect(await promise).toBe('keep_both');
+    });
+
+    it('should mention conflict markers only for merged text conflicts', async () => {
+      const local = { title: 'Einkauf', content: 'Milch' };
+      const server = { title: 'Einkauf', content: 'Hafermilch' };
+      const promise = dialogService.confirmConflictResolve({
+        isDeleted: false,
+        versions: { local, server },
+      });
+      const message = document.getElementById('dialog-message');
+      expect(message.textContent).toContain('Which version do you want to keep?');
+      expect(message.textContent).not.toContain('<<<<<<< Mine');
+      document.getElementById('cr-cancel').click();
+      expect(await promise).toBeNull();
+
+      dialogService.confirmConflictResolve({
+        isDeleted: false,
+        versions: { local, server, text_conflict: true },
+      });
+      expect(message.textContent).toContain('<<<<<<< Mine');
+    });
+
+    it('should not offer keep both for notes deleted on the server', async () => {
+      const promise = dialogService.confirmConflictResolve({ isDeleted: true });
+      expect(document.getElementById('cr-both')).toBeNull();
+      document.getElementById('cr-keep').click();
+      expect(await promise).toBe('keep_mine');
+    });
+  });
+
   describe('icon rendering', () => {
     it('should render info icon', () => {
       dialogService.alert({ title: 'Info', message: 'Test', type: 'info' });
@@ -279,6 +279,37 @@ describe('NoteService', () => {
     });
   });
 
+  describe('restoreServerVersion', () => {
+    it('restores via tauri and reloads the note list', async () => {
+      const restored = { id: '1', title: 'Old title', content: 'Old', syncStatus: 'PENDING' };
+      tauri.restoreServerVersion.mockResolvedValue(restored);
+      tauri.listNotes.mockResolvedValue([restored]);
+
+      const note = await noteService.restoreServerVersion('1', '1751277600');
+
+      expect(tauri.restoreServerVersion).toHaveBeenCalledWith('1', '1751277600');
+      expect(tauri.listNotes).toHaveBeenCalled();
+      expect(note).toEqual(restored);
+    });
+  });
+
+  describe('conflicts', () => {
+    it('resolves keep_both via tauri and reloads the note list', async () => {
+      tauri.resolveConflict.mockResolvedValue();
+      tauri.listNotes.mockResolvedValue([]);
+
+      await noteService.resolveConflict('1', 'keep_both');
+
+      expect(tauri.resolveConflict).toHaveBeenCalledWith('1', 'keep_both');
+      expect(tauri.listNotes).toHaveBeenCalled();
+    });
+
+    it('returns null when the conflict versions cannot be loaded', async () => {
+      tauri.getConflictVersions.mockRejectedValue(new Error('Not connected to server'));
+      expect(await noteService.getConflictVersions('1')).toBeNull();
+    });
+  });
+
   describe('subscribe', () => {
     it('should notify listeners on changes', async () => {
       const listener = vi.fn();
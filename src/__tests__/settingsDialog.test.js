@@ -12,6 +12,8 @@ vi.mock('../services/DialogService.js', () => ({
     info: vi.fn().mockResolvedValue(undefined),
     alert: vi.fn().mockResolvedValue(undefined),
     success: vi.fn().mockResolvedValue(undefined),
+    confirm: vi.fn().mockResolvedValue(true),
+    prompt: vi.fn().mockResolvedValue(null),
   },
 }));
 
@@ -37,13 +39,67 @@ function setupDOM() {
           <input type="checkbox" id="autosave-checkbox" />
         </div>
         <div class="settings-section hidden" data-section="connection">
+          <select id="profile-select"></select>
+          <input type="checkbox" id="profile-enabled-checkbox" />
+          <button id="new-profile-btn" type="button">New profile…</button>
+          <button id="rename-profile-btn" type="button">Rename…</button>
+          <button id="delete-profile-btn" type="button">Delete</button>
           <input type="checkbox" id="offline-mode-checkbox" />
+          <select id="sync-backend-select">
+            <option value="webdav">WebDAV server</option>
+            <option value="directory">Local folder</option>
+            <option value="s3">S3-compatible storage</option>
+            <option value="git">Git repository</option>
+          </select>
+          <div id="sync-directory-group" class="hidden"><input type="text" id="sync-directory-input" /></div>
+          <div id="git-remote-group" class="hidden"><input type="text" id="git-remote-input" /></div>
+          <div id="s3-region-group" class="hidden"><input type="text" id="s3-region-input" /></div>
           <input type="text" id="settings-server-url" />
+          <div id="auth-scheme-group">
+          <select id="auth-scheme-select">
+            <option value="basic">Basic</option>
+            <option value="digest">Digest</option>
+            <option value="bearer">Bearer token</option>
+            <option value="nextcloud_login_flow">Nextcloud login</option>
+          </select>
+          </div>
+          <div id="nextcloud-login-group" class="hidden"><button id="nextcloud-login-btn" type="button"></button></div>
+          <label id="settings-username-label">Username</label>
           <input type="text" id="settings-username" />
+          <label id="settings-password-label">Password</label>
           <input type="password" id="settings-password" />
           <button id="test-connection-btn" class="btn-secondary" type="button">Test connection</button>
           <span id="connection-status"></span>
+          <button id="diagnose-btn" type="button">Run diagnostics</button>
+          <button id="copy-diagnostics-btn" class="hidden" type="button">Copy report</button>
+          <pre id="diagnostics-output" class="hidden"></pre>
+          <button id="storage-usage-btn" type="button">Check storage</button>
+          <small id="storage-usage-warning" class="hidden"></small>
+          <pre id="storage-usage-output" class="hidden"></pre>
           <input type="text" id="sync-folder-input" placeholder="notes" maxlength="50" />
+          <input type="number" id="sync-concurrency-input" min="1" max="8" />
+          <select id="tls-mode-select">
+            <option value="system">System trust store</option>
+            <option value="custom_ca">Custom CA certificate</option>
+            <option value="pinned">Pin server certificate</option>
+            <option value="insecure">Disabled (insecure)</option>
+          </select>
+          <small id="tls-mode-hint"></small>
+          <div id="tls-ca-file-group" class="hidden"><input type="text" id="tls-ca-file-input" /></div>
+          <div id="tls-fingerprint-group" class="hidden"><input type="text" id="tls-fingerprint-input" /></div>
+          <select id="proxy-mode-select">
+            <option value="system">System proxy</option>
+            <option value="none">No proxy</option>
+            <option value="http">HTTP proxy</option>
+            <option value="socks5">SOCKS5 proxy</option>
+          </select>
+          <small id="proxy-mode-hint"></small>
+          <div id="proxy-server-group" class="hidden">
+            <input type="text" id="proxy-url-input" />
+            <input type="text" id="proxy-username-input" />
+            <input type="password" id="proxy-password-input" />
+            <input type="text" id="proxy-bypass-input" />
+          </div>
         </div>
         <div class="settings-section hidden" data-section="system">
           <input type="checkbox" id="tray-checkbox" />
@@ -93,6 +149,9 @@ describe('SettingsDialog', () => {
       default_open_mode: 'edit',
       font_size: 'system',
       offline_mode: true,
+      tls_mode: 'system',
+      tls_ca_file: '',
+      tls_pinned_fingerprint: '',
     });
     tauri.getDeviceId.mockResolvedValue('tauri-abc123');
     tauri.getCredentials.mockResolvedValue(null);
@@ -236,7 +295,15 @@ describe('SettingsDialog', () => {
       dialog.serverUsernameInput.value = 'admin';
       dialog.serverPasswordInput.value = 'pw';
       await dialog._testConnection();
-      expect(tauri.testConnection).toHaveBeenCalledWith('http://test.local', 'admin', 'pw', 'notes');
+      expect(tauri.testConnection).toHaveBeenCalledWith(
+        'http://test.local',
+        'admin',
+        'pw',
+        'notes',
+        'basic',
+        'webdav',
+        '',
+      );
       expect(dialogService.info).toHaveBeenCalled();
       expect(dialog.connectionStatus.textContent).toBe('Status: Reachable');
       // test_connection has no side effects: never stores a client, never disconnects
@@ -255,6 +322,93 @@ describe('SettingsDialog', () => {
     });
   });
 
+  describe('_runDiagnostics()', () => {
+    const report = {
+      generated_at: '2025-07-01T10:00:00Z',
+      app_version: '0.1.0',
+      server_url: 'http://test.local',
+      sync_folder: 'notes',
+      tls: 'system trust store',
+      server_software: null,
+      dav_classes: ['1', '2'],
+      allowed_methods: [],
+      clock_offset_secs: 0,
+      quota_used_bytes: null,
+      quota_available_bytes: null,
+      checks: [{ id: 'options', status: 'ok', detail: 'reachable (200 OK)', duration_ms: 3 }],
+    };
+
+    it('should show error when server fields are empty', async () => {
+      const dialog = new SettingsDialog();
+      await dialog._runDiagnostics();
+      expect(dialogService.error).toHaveBeenCalled();
+      expect(tauri.diagnoseServer).not.toHaveBeenCalled();
+    });
+
+    it('should render the report and offer copying it', async () => {
+      tauri.diagnoseServer.mockResolvedValue(report);
+      const dialog = new SettingsDialog();
+      dialog.serverUrlInput.value = 'http://test.local';
+      dialog.serverUsernameInput.value = 'admin';
+      dialog.serverPasswordInput.value = 'pw';
+      await dialog._runDiagnostics();
+      expect(tauri.diagnoseServer).toHaveBeenCalledWith('http://test.local', 'admin', 'pw', 'notes', 'basic');
+      expect(dialog.diagnosticsOutput.classList.contains('hidden')).toBe(false);
+      expect(dialog.diagnosticsOutput.textContent).toContain('[OK]   options — reachable (200 OK)');
+      expect(dialog.copyDiagnosticsBtn.classList.contains('hidden')).toBe(false);
+      expect(dialog.diagnoseBtn.disabled).toBe(false);
+    });
+
+    it('should show error dialog when the command fails', async () => {
+      tauri.diagnoseServer.mockRejectedValue('TLS configuration error: no CA file');
+      const dialog = new SettingsDialog();
+      dialog.serverUrlInput.value = 'http://test.local';
+      dialog.serverUsernameInput.value = 'admin';
+      dialog.serverPasswordInput.value = 'pw';
+      await dialog._runDiagnostics();
+      expect(dialogService.error).toHaveBeenCalled();
+      expect(dialog.diagnosticsOutput.classList.contains('hidden')).toBe(true);
+    });
+  });
+
+  describe('_loadStorageUsage()', () => {
+    const usage = {
+      quota_used_bytes: 1024,
+      quota_available_bytes: 2048,
+      low_space: true,
+      notes: 1,
+      json_bytes: 300,
+      markdown_bytes: 100,
+      folders: [{ folder: null, notes: 1, json_bytes: 300, markdown_bytes: 100 }],
+    };
+
+    it('should render usage and warn when space is low', async () => {
+      tauri.getStorageUsage.mockResolvedValue(usage);
+      const dialog = new SettingsDialog();
+      await dialog._loadStorageUsage();
+      expect(dialog.storageUsageOutput.classList.contains('hidden')).toBe(false);
+      expect(dialog.storageUsageOutput.textContent).toContain('(root): 1 notes');
+      expect(dialog.storageUsageWarning.classList.contains('hidden')).toBe(false);
+      expect(dialog.storageUsageWarning.textContent).toContain('2.0 KB left');
+    });
+
+    it('should hide the warning when there is enough space', async () => {
+      tauri.getStorageUsage.mockResolvedValue({ ...usage, low_space: false });
+      const dialog = new SettingsDialog();
+      await dialog._loadStorageUsage();
+      expect(dialog.storageUsageWarning.classList.contains('hidden')).toBe(true);
+    });
+
+    it('should only report errors when not silent', async () => {
+      tauri.getStorageUsage.mockRejectedValue('Not connected to server');
+      const dialog = new SettingsDialog();
+      await dialog._loadStorageUsage({ silent: true });
+      expect(dialogService.error).not.toHaveBeenCalled();
+      await dialog._loadStorageUsage();
+      expect(dialogService.error).toHaveBeenCalled();
+    });
+  });
+
   describe('handleSave()', () => {
     it('should save credentials whenever server fields are filled, even if offline', async () => {
       const dialog = new SettingsDialog();
@@ -267,9 +421,42 @@ describe('SettingsDialog', () => {
         url: 'http://test.local',
         username: 'admin',
         password: 'pw',
+        auth_scheme: 'basic',
       });
     });
 
+    it('should save bearer credentials without a username', async () => {
+      const dialog = new SettingsDialog();
+      await dialog.show();
+      dialog.authSchemeSelect.value = 'bearer';
+      dialog.authSchemeSelect.dispatchEvent(new Event('change'));
+      dialog.serverUrlInput.value = 'http://test.local';
+      dialog.serverPasswordInput.value = 'token123';
+      await dialog.handleSave();
+
+      expect(dialog.serverPasswordLabel.textContent).toBe('Token');
+      expect(tauri.saveCredentials).toHaveBeenCalledWith({
+        url: 'http://test.local',
+        username: '',
+        password: 'token123',
+        auth_scheme: 'bearer',
+      });
+    });
+
+    it('should restore the saved auth scheme and show the Nextcloud login button', async () => {
+      tauri.getCredentials.mockResolvedValue({
+        url: 'https://cloud.local',
+        username: 'anna',
+        password: 'app-pw',
+        auth_scheme: 'nextcloud_login_flow',
+      });
+      const dialog = new SettingsDialog();
+      await dialog.show();
+
+      expect(dialog.authSchemeSelect.value).toBe('nextcloud_login_flow');
+      expect(dialog.nextcloudLoginGroup.classList.contains('hidden')).toBe(false);
+    });
+
     it('should NOT save credentials when server fields are empty', async () => {
       const dialog = new SettingsDialog();
       await dialog.show();
@@ -358,6 +545,17 @@ describe('SettingsDialog', () => {
       expect(tauri.disconnect).toHaveBeenCalled();
     });
 
+    it('should clamp sync_concurrency to 1–8', async () => {
+      const dialog = new SettingsDialog();
+      await dialog.show();
+      expect(dialog.syncConcurrencyInput.value).toBe('4');
+
+      dialog.syncConcurrencyInput.value = '32';
+      await dialog.handleSave();
+
+      expect(tauri.saveSettings).toHaveBeenCalledWith(expect.objectContaining({ sync_concurrency: 8 }));
+    });
+
     it('should include offline_mode in saved settings', async () => {
       const dialog = new SettingsDialog();
       await dialog.show();
@@ -365,6 +563,388 @@ describe('SettingsDialog', () => {
 
       expect(tauri.saveSettings).toHaveBeenCalledWith(expect.objectContaining({ offline_mode: true }));
     });
+
+    it('should round-trip TLS settings unchanged', async () => {
+      tauri.getSettings.mockResolvedValue({
+        theme: 'system',
+        sync_folder: 'notes',
+        offline_mode: true,
+        tls_mode: 'pinned',
+        tls_ca_file: '',
+        tls_pinned_fingerprint: 'AB:CD',
+      });
+      const dialog = new SettingsDialog();
+      await dialog.show();
+
+      expect(dialog.tlsFingerprintGroup.classList.contains('hidden')).toBe(false);
+      expect(dialog.tlsCaFileGroup.classList.contains('hidden')).toBe(true);
+
+      await dialog.handleSave();
+
+      expect(tauri.saveSettings).toHaveBeenCalledWith(
+        expect.objectContaining({ tls_mode: 'pinned', tls_ca_file: '', tls_pinned_fingerprint: 'AB:CD' }),
+      );
+    });
+
+    it('should show proxy server fields only for HTTP and SOCKS5', async () => {
+      const dialog = new SettingsDialog();
+      await dialog.show();
+      expect(dialog.proxyModeSelect.value).toBe('system');
+      expect(dialog.proxyServerGroup.classList.contains('hidden')).toBe(true);
+
+      dialog.proxyModeSelect.value = 'socks5';
+      dialog.proxyModeSelect.dispatchEvent(new Event('change'));
+      expect(dialog.proxyServerGroup.classList.contains('hidden')).toBe(false);
+
+      dialog.proxyModeSelect.value = 'none';
+      dialog.proxyModeSelect.dispatchEvent(new Event('change'));
+      expect(dialog.proxyServerGroup.classList.contains('hidden')).toBe(true);
+    });
+
+    it('should refuse to save an HTTP proxy without address', async () => {
+      const dialog = new SettingsDialog();
+      await dialog.show();
+      dialog.proxyModeSelect.value = 'http';
+
+      await dialog.handleSave();
+
+      expect(dialogService.error).toHaveBeenCalled();
+      expect(tauri.saveSettings).not.toHaveBeenCalled();
+    });
+
+    it('should save proxy settings and reconnect when they change', async () => {
+      tauri.getSettings.mockResolvedValue({
+        theme: 'system',
+        sync_folder: 'notes',
+        offline_mode: false,
+        proxy_mode: 'system',
+      });
+      tauri.getCredentials.mockResolvedValue({
+        url: 'http://test.local',
+        username: 'admin',
+        password: 'pw',
+        auth_scheme: 'basic',
+      });
+      const dialog = new SettingsDialog();
+      await dialog.show();
+      dialog.proxyModeSelect.value = 'http';
+      dialog.proxyUrlInput.value = ' proxy.corp:3128 ';
+      dialog.proxyUsernameInput.value = 'alice';
+      dialog.proxyPasswordInput.value = 'secret';
+      dialog.proxyBypassInput.value = 'localhost';
+
+      await dialog.handleSave();
+
+      expect(tauri.saveSettings).toHaveBeenCalledWith(
+        expect.objectContaining({
+          proxy_mode: 'http',
+          proxy_url: 'proxy.corp:3128',
+          proxy_username: 'alice',
+          proxy_password: 'secret',
+          proxy_bypass: 'localhost',
+        }),
+      );
+      expect(tauri.connect).toHaveBeenCalledWith('http://test.local', 'admin', 'pw', 'notes', 'basic');
+    });
+
+    it('should show the sync directory field only for the local-folder backend', async () => {
+      const dialog = new SettingsDialog();
+      await dialog.show();
+      expect(dialog.syncBackendSelect.value).toBe('webdav');
+      expect(dialog.syncDirectoryGroup.classList.contains('hidden')).toBe(true);
+
+      dialog.syncBackendSelect.value = 'directory';
+      dialog.syncBackendSelect.dispatchEvent(new Event('change'));
+      expect(dialog.syncDirectoryGroup.classList.contains('hidden')).toBe(false);
+    });
+
+    it('should refuse to save the local-folder backend without a directory', async () => {
+      const dialog = new SettingsDialog();
+      await dialog.show();
+      dialog.syncBackendSelect.value = 'directory';
+
+      await dialog.handleSave();
+
+      expect(dialogService.error).toHaveBeenCalled();
+      expect(tauri.saveSettings).not.toHaveBeenCalled();
+    });
+
+    it('should switch to a sync directory without connecting to the server', async () => {
+      tauri.getSettings.mockResolvedValue({
+        theme: 'system',
+        sync_folder: 'notes',
+        offline_mode: false,
+      });
+      tauri.getCredentials.mockResolvedValue({
+        url: 'http://test.local',
+        username: 'admin',
+        password: 'pw',
+        auth_scheme: 'basic',
+      });
+      const dialog = new SettingsDialog();
+      const onReconnect = vi.fn();
+      dialog.onReconnect(onReconnect);
+      await dialog.show();
+      dialog.syncBackendSelect.value = 'directory';
+      dialog.syncDirectoryInput.value = ' /home/me/Sync ';
+
+      await dialog.handleSave();
+
+      expect(tauri.saveSettings).toHaveBeenCalledWith(
+        expect.objectContaining({ sync_backend: 'directory', sync_directory: '/home/me/Sync' }),
+      );
+      expect(tauri.connect).not.toHaveBeenCalled();
+      expect(tauri.disconnect).toHaveBeenCalled();
+      expect(onReconnect).toHaveBeenCalled();
+    });
+
+    it('should save the git backend with directory and remote without connecting', async () => {
+      tauri.getSettings.mockResolvedValue({
+        theme: 'system',
+        sync_folder: 'notes',
+        offline_mode: false,
+      });
+      const dialog = new SettingsDialog();
+      await dialog.show();
+      expect(dialog.gitRemoteGroup.classList.contains('hidden')).toBe(true);
+      dialog.syncBackendSelect.value = 'git';
+      dialog.syncBackendSelect.dispatchEvent(new Event('change'));
+      expect(dialog.syncDirectoryGroup.classList.contains('hidden')).toBe(false);
+      expect(dialog.gitRemoteGroup.classList.contains('hidden')).toBe(false);
+
+      await dialog.handleSave();
+      expect(dialogService.error).toHaveBeenCalled();
+      expect(tauri.saveSettings).not.toHaveBeenCalled();
+
+      dialog.syncDirectoryInput.value = '/home/me/notes-repo';
+      dialog.gitRemoteInput.value = ' git@example.com:me/notes.git ';
+      await dialog.handleSave();
+
+      expect(tauri.saveSettings).toHaveBeenCalledWith(
+        expect.objectContaining({
+          sync_backend: 'git',
+          sync_directory: '/home/me/notes-repo',
+          git_remote: 'git@example.com:me/notes.git',
+        }),
+      );
+      expect(tauri.connect).not.toHaveBeenCalled();
+      expect(tauri.disconnect).toHaveBeenCalled();
+    });
+
+    describe('migration', () => {
+      const ONLINE = { theme: 'system', sync_folder: 'notes', offline_mode: false };
+      const CREDS = { url: 'https://old.lan', username: 'anna', password: 'pw', auth_scheme: 'basic' };
+      const REPORT = { notes_copied: 12, notes_existing: 1, folders: 2, deletions: 3 };
+
+      beforeEach(() => {
+        tauri.getSettings.mockResolvedValue(ONLINE);
+        tauri.getCredentials.mockResolvedValue(CREDS);
+        tauri.connect.mockResolvedValue(true);
+      });
+
+      it('should copy notes to a new server before saving', async () => {
+        tauri.migrateSyncTarget.mockResolvedValue(REPORT);
+        const dialog = new SettingsDialog();
+        await dialog.show();
+        dialog.serverUrlInput.value = 'https://new.lan';
+
+        await dialog.handleSave();
+
+        expect(dialogService.confirm).toHaveBeenCalledWith(
+          expect.objectContaining({ title: 'Move notes to the new location?' }),
+        );
+        expect(tauri.migrateSyncTarget).toHaveBeenCalledWith({ ...CREDS, url: 'https://new.lan' }, 'notes', null);
+        expect(dialogService.info).toHaveBeenCalledWith(
+          expect.objectContaining({ message: expect.stringContaining('12 notes copied, 1 already there') }),
+        );
+        expect(tauri.saveSettings).toHaveBeenCalled();
+      });
+
+      it('should keep the old location when the copy fails', async () => {
+        tauri.migrateSyncTarget.mockRejectedValue('Migration failed: 2 von 5 Notizen fehlen am neuen Speicherort');
+        const dialog = new SettingsDialog();
+        await dialog.show();
+        dialog.syncFolderInput.value = 'archive';
+
+        await dialog.handleSave();
+
+        expect(tauri.migrateSyncTarget).toHaveBeenCalledWith(CREDS, 'archive', null);
+        expect(dialogService.error).toHaveBeenCalledWith(expect.objectContaining({ title: 'Move failed' }));
+        expect(tauri.saveSettings).not.toHaveBeenCalled();
+        expect(tauri.connect).not.toHaveBeenCalled();
+      });
+
+      it('should switch without copying when declined', async () => {
+        dialogService.confirm.mockResolvedValueOnce(false);
+        const dialog = new SettingsDialog();
+        await dialog.show();
+        dialog.syncFolderInput.value = 'archive';
+
+        await dialog.handleSave();
+
+        expect(tauri.migrateSyncTarget).not.toHaveBeenCalled();
+        expect(tauri.saveSettings).toHaveBeenCalledWith(expect.objectContaining({ sync_folder: 'archive' }));
+        expect(tauri.connect).toHaveBeenCalledWith('https://old.lan', 'anna', 'pw', 'archive', 'basic');
+      });
+
+      it('should offer the copy when the sync directory changes', async () => {
+        tauri.getSettings.mockResolvedValue({ ...ONLINE, sync_backend: 'directory', sync_directory: '/old' });
+        tauri.migrateSyncTarget.mockResolvedValue(REPORT);
+        const dialog = new SettingsDialog();
+        await dialog.show();
+        dialog.syncDirectoryInput.value = '/new';
+
+        await dialog.handleSave();
+
+        expect(tauri.migrateSyncTarget).toHaveBeenCalledWith(null, 'notes', '/new');
+        expect(tauri.saveSettings).toHaveBeenCalledWith(expect.objectContaining({ sync_directory: '/new' }));
+      });
+
+      it('should not offer the copy when going online for the first time', async () => {
+        tauri.getSettings.mockResolvedValue({ ...ONLINE, offline_mode: true });
+        const dialog = new SettingsDialog();
+        await dialog.show();
+        dialog.offlineCheckbox.checked = false;
+        dialog.syncFolderInput.value = 'archive';
+
+        await dialog.handleSave();
+
+        expect(tauri.migrateSyncTarget).not.toHaveBeenCalled();
+        expect(tauri.saveSettings).toHaveBeenCalled();
+      });
+    });
+
+    describe('profiles', () => {
+      const PROFILES = [
+        { id: 'default', name: 'Personal', enabled: true, active: true, server_url: 'https://home.lan' },
+        { id: 'p-work', name: 'Work', enabled: false, active: false, server_url: null },
+      ];
+
+      it('should list profiles with the active one selected', async () => {
+        tauri.listProfiles.mockResolvedValue(PROFILES);
+        const dialog = new SettingsDialog();
+        await dialog.show();
+
+        const options = [...dialog.profileSelect.options].map((o) => o.textContent);
+        expect(options).toEqual(['Personal', 'Work (background sync off)']);
+        expect(dialog.profileSelect.value).toBe('default');
+        expect(dialog.profileEnabledCheckbox.checked).toBe(true);
+        expect(dialog.deleteProfileBtn.disabled).toBe(true);
+      });
+
+      it('should switch profiles, reload the fields and notify the app', async () => {
+        tauri.listProfiles.mockResolvedValue(PROFILES);
+        tauri.switchProfile.mockResolvedValue([]);
+        const dialog = new SettingsDialog();
+        const onSwitch = vi.fn();
+        dialog.onProfileSwitch(onSwitch);
+        await dialog.show();
+        tauri.getCredentials.mockClear();
+
+        dialog.profileSelect.value = 'p-work';
+        dialog.profileSelect.dispatchEvent(new Event('change'));
+        await vi.waitFor(() => expect(onSwitch).toHaveBeenCalled());
+
+        expect(tauri.switchProfile).toHaveBeenCalledWith('p-work');
+        expect(tauri.getCredentials).toHaveBeenCalled();
+        expect(tauri.saveSettings).not.toHaveBeenCalled();
+      });
+
+      it('should create a profile and switch to it', async () => {
+        tauri.listProfiles.mockResolvedValue(PROFILES.slice(0, 1));
+        tauri.createProfile.mockResolvedValue([
+          PROFILES[0],
+          { id: 'p-new', name: 'Travel', enabled: true, active: false, server_url: null },
+        ]);
+        tauri.switchProfile.mockResolvedValue([]);
+        dialogService.prompt.mockResolvedValueOnce(' Travel ');
+        const dialog = new SettingsDialog();
+        await dialog.show();
+
+        await dialog._createProfile();
+
+        expect(tauri.createProfile).toHaveBeenCalledWith('Travel');
+        expect(tauri.switchProfile).toHaveBeenCalledWith('p-new');
+      });
+
+      it('should switch back to the default profile before deleting the active one', async () => {
+        tauri.listProfiles.mockResolvedValue([
+          { ...PROFILES[0], active: false },
+          { ...PROFILES[1], active: true },
+        ]);
+        tauri.switchProfile.mockResolvedValue([]);
+        tauri.deleteProfile.mockResolvedValue(PROFILES.slice(0, 1));
+        const dialog = new SettingsDialog();
+        await dialog.show();
+        expect(dialog.deleteProfileBtn.disabled).toBe(false);
+
+        await dialog._deleteProfile();
+
+        expect(dialogService.confirm).toHaveBeenCalled();
+        expect(tauri.switchProfile).toHaveBeenCalledWith('default');
+        expect(tauri.deleteProfile).toHaveBeenCalledWith('p-work');
+      });
+    });
+
+    it('should show region and access key labels for the S3 backend', async () => {
+      const dialog = new SettingsDialog();
+      await dialog.show();
+      expect(dialog.s3RegionGroup.classList.contains('hidden')).toBe(true);
+      expect(dialog.s3RegionInput.value).toBe('us-east-1');
+
+      dialog.syncBackendSelect.value = 's3';
+      dialog.syncBackendSelect.dispatchEvent(new Event('change'));
+      expect(dialog.s3RegionGroup.classList.contains('hidden')).toBe(false);
+      expect(dialog.authSchemeGroup.classList.contains('hidden')).toBe(true);
+      expect(dialog.serverUsernameLabel.textContent).toBe('Access key ID');
+      expect(dialog.serverPasswordLabel.textContent).toBe('Secret access key');
+
+      dialog.syncBackendSelect.value = 'webdav';
+      dialog.syncBackendSelect.dispatchEvent(new Event('change'));
+      expect(dialog.serverUsernameLabel.textContent).toBe('Username');
+      expect(dialog.serverPasswordLabel.textContent).toBe('Password');
+    });
+
+    it('should save the S3 backend and connect with the bucket endpoint', async () => {
+      tauri.getSettings.mockResolvedValue({
+        theme: 'system',
+        sync_folder: 'notes',
+        offline_mode: false,
+      });
+      const dialog = new SettingsDialog();
+      await dialog.show();
+      dialog.syncBackendSelect.value = 's3';
+      dialog.s3RegionInput.value = ' eu-central-1 ';
+      dialog.serverUrlInput.value = 'https://minio.lan:9000/notes-bucket';
+      dialog.serverUsernameInput.value = 'AKIAEXAMPLE';
+      dialog.serverPasswordInput.value = 'secret';
+
+      await dialog.handleSave();
+
+      expect(tauri.saveSettings).toHaveBeenCalledWith(
+        expect.objectContaining({ sync_backend: 's3', s3_region: 'eu-central-1' }),
+      );
+      expect(tauri.connect).toHaveBeenCalledWith(
+        'https://minio.lan:9000/notes-bucket',
+        'AKIAEXAMPLE',
+        'secret',
+        'notes',
+        'basic',
+      );
+      expect(tauri.disconnect).not.toHaveBeenCalled();
+    });
+
+    it('should ask for confirmation before disabling certificate checks', async () => {
+      dialogService.confirm.mockResolvedValueOnce(false);
+      const dialog = new SettingsDialog();
+      await dialog.show();
+      dialog.tlsModeSelect.value = 'insecure';
+
+      await dialog.handleSave();
+
+      expect(dialogService.confirm).toHaveBeenCalled();
+      expect(tauri.saveSettings).not.toHaveBeenCalled();
+    });
   });
 
   describe('handleCancel()', () => {
@@ -0,0 +1,38 @@
+import { describe, expect, it } from 'vitest';
+import { formatStorageUsage, storageWarning } from '../utils/storage.js';
+
+const usage = {
+  quota_used_bytes: 3 * 1024 * 1024,
+  quota_available_bytes: -3,
+  low_space: false,
+  notes: 3,
+  json_bytes: 3072,
+  markdown_bytes: 1024,
+  folders: [
+    { folder: null, notes: 1, json_bytes: 1024, markdown_bytes: 512 },
+    { folder: 'Work', notes: 2, json_bytes: 2048, markdown_bytes: 512 },
+  ],
+};
+
+describe('formatStorageUsage', () => {
+  it('renders quota, totals and one line per folder', () => {
+    expect(formatStorageUsage(usage).split('\n')).toEqual([
+      'Quota: 3.0 MB used, no limit reported',
+      'Notes: 3 — 3.0 KB JSON, 1.0 KB Markdown',
+      '',
+      '(root): 1 notes, 1.0 KB JSON, 512 B Markdown',
+      '/Work: 2 notes, 2.0 KB JSON, 512 B Markdown',
+    ]);
+  });
+});
+
+describe('storageWarning', () => {
+  it('returns null while there is enough space', () => {
+    expect(storageWarning(usage)).toBeNull();
+  });
+
+  it('names the remaining space when it runs low', () => {
+    const low = { ...usage, low_space: true, quota_available_bytes: 5 * 1024 * 1024 };
+    expect(storageWarning(low)).toContain('5.0 MB left');
+  });
+});
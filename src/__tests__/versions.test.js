@@ -0,0 +1,32 @@
+import { describe, expect, it } from 'vitest';
+import { formatVersionLabel, versionsErrorMessage } from '../utils/versions.js';
+
+describe('formatVersionLabel', () => {
+  it('shows local time and size', () => {
+    const timestamp = 1751364000000;
+    const when = new Date(timestamp).toLocaleString();
+    expect(formatVersionLabel({ version_id: '1751364000', timestamp, size: 2048 })).toBe(`${when} — 2.0 KB`);
+  });
+
+  it('omits a missing size', () => {
+    const timestamp = 1751277600000;
+    expect(formatVersionLabel({ version_id: '1751277600', timestamp, size: null })).toBe(
+      new Date(timestamp).toLocaleString()
+    );
+  });
+});
+
+describe('versionsErrorMessage', () => {
+  it('explains servers without versioning', () => {
+    expect(versionsErrorMessage('Not supported by server: Versionen (keine oc:fileid)')).toMatch(/Nextcloud/);
+  });
+
+  it('explains offline and not-yet-uploaded notes', () => {
+    expect(versionsErrorMessage('Not connected to server')).toMatch(/Connect/);
+    expect(versionsErrorMessage('Note not found: abc')).toMatch(/not been uploaded/);
+  });
+
+  it('passes other errors through', () => {
+    expect(versionsErrorMessage('Network error: timeout')).toBe('Could not load versions: Network error: timeout');
+  });
+});
@@ -22,6 +22,7 @@ import {
 } from '../utils/markdownToolbar.js';
 import { getColorPair } from '../utils/noteColors.js';
 import { UndoStack } from '../utils/UndoStack.js';
+import { formatVersionLabel, versionsErrorMessage } from '../utils/versions.js';
 
 /** Autosave debounce delay in milliseconds (matches Android app: 3 seconds) */
 const AUTOSAVE_DEBOUNCE_MS = 3000;
@@ -45,6 +46,7 @@ export class NoteEditor {
     this.undoBtn = document.getElementById('undo-btn');
     this.addItemHeaderBtn = document.getElementById('add-checklist-item-btn');
     this.colorBtn = document.getElementById('note-color-btn');
+    this.versionsBtn = document.getElementById('note-versions-btn');
 
     this.mdToolbar = document.getElementById('markdown-toolbar');
     this.mdBtnBold = document.getElementById('md-btn-bold');
@@ -128,6 +130,9 @@ export class NoteEditor {
       });
     });
 
+    // Server versions (Nextcloud)
+    this.versionsBtn?.addEventListener('click', () => this.handleVersions());
+
     // Markdown toolbar button listeners
     this.mdBtnBold?.addEventListener('click', () => this._mdFormat('bold'));
     this.mdBtnItalic?.addEventListener('click', () => this._mdFormat('italic'));
@@ -1092,6 +1097,41 @@ export class NoteEditor {
     if (resolved) this.loadNote(resolved);
   }
 
+  /** Browse the server's older versions of the current note and restore one as a new edit. */
+  async handleVersions() {
+    if (!this.currentNote) return;
+    const id = this.currentNote.id;
+    let versions;
+    try {
+      versions = await noteService.listServerVersions(id);
+    } catch (error) {
+      await dialogService.info({ title: 'Version History', message: versionsErrorMessage(error) });
+      return;
+    }
+    if (versions.length === 0) {
+      await dialogService.info({ title: 'Version History', message: 'The server has no older versions of this note.' });
+      return;
+    }
+    const versionId = await dialogService.chooseVersion({
+      versions: versions.map((v) => ({ version_id: v.version_id, label: formatVersionLabel(v) })),
+    });
+    if (!versionId || this.currentNote?.id !== id) return;
+
+    // Offene Eingaben zuerst speichern, sonst überschreibt der Autosave die Wiederherstellung
+    if (this.saveTimeout) {
+      clearTimeout(this.saveTimeout);
+      this.saveTimeout = null;
+    }
+    if (this._isDirty) await this.save();
+    try {
+      const restored = await noteService.restoreServerVersion(id, versionId);
+      if (this.currentNote?.id === id) this.loadNote(restored);
+    } catch (error) {
+      console.error('Failed to restore version:', error);
+      await dialogService.error({ title: 'Restore Failed', message: versionsErrorMessage(error) });
+    }
+  }
+
   /** Fire-and-forget save for a specific note snapshot (used to flush pending saves). */
   saveNoteImmediate(noteToSave) {
     noteService.saveNote(this._sanitizeForSave(noteToSave)).catch((err) => {
@@ -712,7 +712,8 @@ export class NotesList {
         e.stopPropagation();
         const id = item.dataset.id;
         const isDeleted = e.currentTarget.classList.contains('sync-deleted-badge');
-        const resolution = await dialogService.confirmConflictResolve({ isDeleted });
+        const versions = isDeleted ? null : await noteService.getConflictVersions(id);
+        const resolution = await dialogService.confirmConflictResolve({ isDeleted, versions });
         if (resolution === null) return;
         try {
           await noteService.resolveConflict(id, resolution);
@@ -1,6 +1,8 @@
 import { openUrl } from '@tauri-apps/plugin-opener';
 import { dialogService } from '../services/DialogService.js';
 import * as tauri from '../services/tauri.js';
+import { formatDiagnosticReport } from '../utils/diagnostics.js';
+import { formatStorageUsage, storageWarning } from '../utils/storage.js';
 import { MODE_BY_ID, THEME_IDS, THEMES } from '../utils/themes.js';
 
 /**
@@ -8,6 +10,26 @@ import { MODE_BY_ID, THEME_IDS, THEMES } from '../utils/themes.js';
  */
 const FONT_SCALE = { system: 1, small: 0.85, normal: 1, large: 1.15, xlarge: 1.3 };
 
+const TLS_HINTS = {
+  system: 'Server certificate must be trusted by the operating system.',
+  custom_ca: 'Also trust certificates issued by your own CA (self-hosted servers).',
+  pinned: 'Only accept the exact certificate seen on first connect.',
+  insecure: 'Warning: any certificate is accepted. Your password and notes can be intercepted.',
+};
+
+const PROXY_HINTS = {
+  system: 'Use the proxy configured in the operating system or HTTPS_PROXY/ALL_PROXY.',
+  none: 'Always connect directly, ignoring system proxy settings.',
+  http: 'HTTP(S) proxy; https:// servers are reached through a CONNECT tunnel.',
+  socks5: 'SOCKS5 proxy; host names are resolved by the proxy.',
+};
+
+const PROXY_KEYS = ['proxy_mode', 'proxy_url', 'proxy_username', 'proxy_password', 'proxy_bypass'];
+
+// Nextcloud Login Flow v2: poll interval and how long to wait for the browser approval
+const LOGIN_POLL_INTERVAL_MS = 2000;
+const LOGIN_TIMEOUT_MS = 10 * 60 * 1000;
+
 export class SettingsDialog {
   constructor() {
     this.dialog = document.getElementById('settings-dialog');
@@ -17,12 +39,37 @@ export class SettingsDialog {
     this.autostartCheckbox = document.getElementById('autostart-checkbox');
     this.deviceIdInput = document.getElementById('device-id');
     this.syncFolderInput = document.getElementById('sync-folder-input');
+    this.syncConcurrencyInput = document.getElementById('sync-concurrency-input');
     this.offlineCheckbox = document.getElementById('offline-mode-checkbox');
+    this.profileSelect = document.getElementById('profile-select');
+    this.profileEnabledCheckbox = document.getElementById('profile-enabled-checkbox');
+    this.newProfileBtn = document.getElementById('new-profile-btn');
+    this.renameProfileBtn = document.getElementById('rename-profile-btn');
+    this.deleteProfileBtn = document.getElementById('delete-profile-btn');
+    this.syncBackendSelect = document.getElementById('sync-backend-select');
+    this.syncDirectoryGroup = document.getElementById('sync-directory-group');
+    this.syncDirectoryInput = document.getElementById('sync-directory-input');
+    this.gitRemoteGroup = document.getElementById('git-remote-group');
+    this.gitRemoteInput = document.getElementById('git-remote-input');
+    this.s3RegionGroup = document.getElementById('s3-region-group');
+    this.s3RegionInput = document.getElementById('s3-region-input');
     this.serverUrlInput = document.getElementById('settings-server-url');
     this.serverUsernameInput = document.getElementById('settings-username');
     this.serverPasswordInput = document.getElementById('settings-password');
+    this.serverUsernameLabel = document.getElementById('settings-username-label');
+    this.serverPasswordLabel = document.getElementById('settings-password-label');
+    this.authSchemeGroup = document.getElementById('auth-scheme-group');
+    this.authSchemeSelect = document.getElementById('auth-scheme-select');
+    this.nextcloudLoginGroup = document.getElementById('nextcloud-login-group');
+    this.nextcloudLoginBtn = document.getElementById('nextcloud-login-btn');
     this.connectionStatus = document.getElementById('connection-status');
     this.testConnBtn = document.getElementById('test-connection-btn');
+    this.diagnoseBtn = document.getElementById('diagnose-btn');
+    this.copyDiagnosticsBtn = document.getElementById('copy-diagnostics-btn');
+    this.diagnosticsOutput = document.getElementById('diagnostics-output');
+    this.storageUsageBtn = document.getElementById('storage-usage-btn');
+    this.storageUsageWarning = document.getElementById('storage-usage-warning');
+    this.storageUsageOutput = document.getElementById('storage-usage-output');
     this.homeView = document.getElementById('settings-home');
     this.backBtn = document.getElementById('settings-back-btn');
     this.updatesCard = document.getElementById('updates-card');
@@ -39,8 +86,23 @@ export class SettingsDialog {
     this.installUpdateBtn = document.getElementById('install-update-btn');
     this.defaultOpenModeSelect = document.getElementById('default-open-mode-select');
     this.fontSizeChips = document.getElementById('font-size-chips');
+    this.tlsModeSelect = document.getElementById('tls-mode-select');
+    this.tlsModeHint = document.getElementById('tls-mode-hint');
+    this.tlsCaFileGroup = document.getElementById('tls-ca-file-group');
+    this.tlsCaFileInput = document.getElementById('tls-ca-file-input');
+    this.tlsFingerprintGroup = document.getElementById('tls-fingerprint-group');
+    this.tlsFingerprintInput = document.getElementById('tls-fingerprint-input');
+    this.proxyModeSelect = document.getElementById('proxy-mode-select');
+    this.proxyModeHint = document.getElementById('proxy-mode-hint');
+    this.proxyServerGroup = document.getElementById('proxy-server-group');
+    this.proxyUrlInput = document.getElementById('proxy-url-input');
+    this.proxyUsernameInput = document.getElementById('proxy-username-input');
+    this.proxyPasswordInput = document.getElementById('proxy-password-input');
+    this.proxyBypassInput = document.getElementById('proxy-bypass-input');
     this.onSaveCallback = null;
     this.onReconnectCallback = null;
+    this.onProfileSwitchCallback = null;
+    this._profiles = [];
     this.onViewChangelogCallback = null;
     this.originalTheme = null;
     this._originalFontSize = null;
@@ -82,6 +144,21 @@ export class SettingsDialog {
     });
 
     this.testConnBtn.addEventListener('click', () => this._testConnection());
+    this.diagnoseBtn.addEventListener('click', () => this._runDiagnostics());
+    this.copyDiagnosticsBtn.addEventListener('click', () => this._copyDiagnostics());
+    this.storageUsageBtn.addEventListener('click', () => this._loadStorageUsage());
+
+    this.tlsModeSelect.addEventListener('change', () => this._applyTlsMode());
+    this.proxyModeSelect.addEventListener('change', () => this._applyProxyMode());
+    this.syncBackendSelect.addEventListener('change', () => this._applySyncBackend());
+    this.authSchemeSelect.addEventListener('change', () => this._applyAuthScheme());
+    this.nextcloudLoginBtn.addEventListener('click', () => this._nextcloudLogin());
+
+    this.profileSelect.addEventListener('change', () => this._switchProfile(this.profileSelect.value));
+    this.profileEnabledCheckbox.addEventListener('change', () => this._setProfileEnabled());
+    this.newProfileBtn.addEventListener('click', () => this._createProfile());
+    this.renameProfileBtn.addEventListener('click', () => this._renameProfile());
+    this.deleteProfileBtn.addEventListener('click', () => this._deleteProfile());
 
     // Offline toggle: update status label live
     this.offlineCheckbox.addEventListener('change', () => this._applyOfflineState());
@@ -213,11 +290,27 @@ export class SettingsDialog {
   _applyOfflineState() {
     const offline = this.offlineCheckbox.checked;
     for (const el of [
+      this.syncBackendSelect,
+      this.syncDirectoryInput,
+      this.gitRemoteInput,
+      this.s3RegionInput,
       this.serverUrlInput,
       this.serverUsernameInput,
       this.serverPasswordInput,
+      this.authSchemeSelect,
+      this.nextcloudLoginBtn,
       this.syncFolderInput,
+      this.syncConcurrencyInput,
       this.testConnBtn,
+      this.storageUsageBtn,
+      this.tlsModeSelect,
+      this.tlsCaFileInput,
+      this.tlsFingerprintInput,
+      this.proxyModeSelect,
+      this.proxyUrlInput,
+      this.proxyUsernameInput,
+      this.proxyPasswordInput,
+      this.proxyBypassInput,
     ]) {
       if (el) el.disabled = offline;
     }
@@ -232,11 +325,99 @@ export class SettingsDialog {
     this._refreshConnectionStatus();
   }
 
+  _applyTlsMode() {
+    const mode = this.tlsModeSelect.value;
+    this.tlsCaFileGroup.classList.toggle('hidden', mode !== 'custom_ca');
+    this.tlsFingerprintGroup.classList.toggle('hidden', mode !== 'pinned');
+    this.tlsModeHint.textContent = TLS_HINTS[mode] || '';
+    this.tlsModeHint.classList.toggle('form-hint-warning', mode === 'insecure');
+  }
+
+  _applySyncBackend() {
+    const backend = this.syncBackendSelect.value;
+    // Git arbeitet ebenfalls in einem lokalen Verzeichnis (Arbeitsverzeichnis des Repos)
+    this.syncDirectoryGroup.classList.toggle('hidden', backend !== 'directory' && backend !== 'git');
+    this.gitRemoteGroup?.classList.toggle('hidden', backend !== 'git');
+    this.s3RegionGroup?.classList.toggle('hidden', backend !== 's3');
+    // S3 meldet sich per Access Key + Secret an (SigV4), nicht per HTTP-Auth
+    this.authSchemeGroup?.classList.toggle('hidden', backend === 's3');
+    this._applyAuthScheme();
+  }
+
+  _applyProxyMode() {
+    const mode = this.proxyModeSelect.value;
+    this.proxyServerGroup.classList.toggle('hidden', mode !== 'http' && mode !== 'socks5');
+    this.proxyModeHint.textContent = PROXY_HINTS[mode] || '';
+  }
+
+  _applyAuthScheme() {
+    const scheme = this.authSchemeSelect.value;
+    const s3 = this.syncBackendSelect.value === 's3';
+    this.nextcloudLoginGroup.classList.toggle('hidden', s3 || scheme !== 'nextcloud_login_flow');
+    if (this.serverUsernameLabel) {
+      this.serverUsernameLabel.textContent = s3 ? 'Access key ID' : 'Username';
+    }
+    if (this.serverPasswordLabel) {
+      let label = scheme === 'bearer' ? 'Token' : 'Password';
+      if (s3) label = 'Secret access key';
+      this.serverPasswordLabel.textContent = label;
+    }
+  }
+
+  // Bearer tokens need no username; every other scheme needs all three fields.
+  _hasServerDetails(url, username, password) {
+    const bearer = this.authSchemeSelect.value === 'bearer' && this.syncBackendSelect.value !== 's3';
+    return !!(url && password && (username || bearer));
+  }
+
+  async _nextcloudLogin() {
+    const url = this.serverUrlInput.value.trim();
+    if (!url) {
+      await dialogService.error({ title: 'Missing details', message: 'Enter the Nextcloud server URL first.' });
+      return;
+    }
+    const prevStatus = this.connectionStatus.textContent;
+    this.nextcloudLoginBtn.disabled = true;
+    try {
+      const flow = await tauri.startNextcloudLogin(url);
+      await openUrl(flow.login_url);
+      this.connectionStatus.textContent = 'Status: Waiting for browser login…';
+      const deadline = Date.now() + LOGIN_TIMEOUT_MS;
+      while (Date.now() < deadline && !this.dialog.classList.contains('hidden')) {
+        await new Promise((resolve) => setTimeout(resolve, LOGIN_POLL_INTERVAL_MS));
+        const creds = await tauri.pollNextcloudLogin(url, flow);
+        if (creds) {
+          this.serverUrlInput.value = creds.url;
+          this.serverUsernameInput.value = creds.username;
+          this.serverPasswordInput.value = creds.password;
+          this.connectionStatus.textContent = `Status: Logged in as ${creds.username} (not saved yet)`;
+          return;
+        }
+      }
+      this.connectionStatus.textContent = prevStatus;
+    } catch (e) {
+      this.connectionStatus.textContent = prevStatus;
+      await dialogService.error({ title: 'Nextcloud Login Failed', message: `${e.message || e}` });
+    } finally {
+      this.nextcloudLoginBtn.disabled = this.offlineCheckbox.checked;
+    }
+  }
+
   async _refreshConnectionStatus() {
+    if (this.syncBackendSelect.value === 'directory') {
+      this.connectionStatus.textContent = 'Status: Online (local folder)';
+      return;
+    }
+    if (this.syncBackendSelect.value === 'git') {
+      this.connectionStatus.textContent = 'Status: Online (git repository)';
+      return;
+    }
     try {
       const connected = await tauri.isConnected();
       if (this.offlineCheckbox.checked) return; // zwischenzeitlich auf Offline umgeschaltet
       this.connectionStatus.textContent = connected ? 'Status: Online' : 'Status: Online (not connected)';
+      // Quota früh prüfen, damit der Nutzer gewarnt wird bevor Uploads mit 507 scheitern
+      if (connected) this._loadStorageUsage({ silent: true });
     } catch (_e) {
       /* Status unverändert lassen */
     }
@@ -246,7 +427,7 @@ export class SettingsDialog {
     const url = this.serverUrlInput.value.trim();
     const username = this.serverUsernameInput.value.trim();
     const password = this.serverPasswordInput.value;
-    if (!url || !username || !password) {
+    if (!this._hasServerDetails(url, username, password)) {
       await dialogService.error({ title: 'Missing details', message: 'Enter server details first.' });
       return;
     }
@@ -257,7 +438,15 @@ export class SettingsDialog {
     try {
       // test_connection has no side effects (unlike connect, which stores the client
       // and uploads local notes) — so testing never silently changes the offline state.
-      const ok = await tauri.testConnection(url, username, password, syncFolder);
+      const ok = await tauri.testConnection(
+        url,
+        username,
+        password,
+        syncFolder,
+        this.authSchemeSelect.value,
+        this.syncBackendSelect.value,
+        this.s3RegionInput.value.trim(),
+      );
       if (ok) {
         this.connectionStatus.textContent = 'Status: Reachable';
         await dialogService.info({ title: 'Connection OK', message: 'Server reachable.' });
@@ -273,6 +462,173 @@ export class SettingsDialog {
     }
   }
 
+  async _runDiagnostics() {
+    const url = this.serverUrlInput.value.trim();
+    const username = this.serverUsernameInput.value.trim();
+    const password = this.serverPasswordInput.value;
+    if (!this._hasServerDetails(url, username, password)) {
+      await dialogService.error({ title: 'Missing details', message: 'Enter server details first.' });
+      return;
+    }
+    if (this.syncBackendSelect.value === 's3') {
+      await dialogService.info({
+        title: 'Diagnostics',
+        message: 'Diagnostics cover WebDAV servers only. Use "Test connection" to check S3 access.',
+      });
+      return;
+    }
+    const syncFolder = this.syncFolderInput.value.trim() || 'notes';
+    this.diagnoseBtn.disabled = true;
+    this.diagnoseBtn.textContent = 'Running diagnostics…';
+    try {
+      const report = await tauri.diagnoseServer(url, username, password, syncFolder, this.authSchemeSelect.value);
+      this.diagnosticsOutput.textContent = formatDiagnosticReport(report);
+      this.diagnosticsOutput.classList.remove('hidden');
+      this.copyDiagnosticsBtn.textContent = 'Copy report';
+      this.copyDiagnosticsBtn.classList.remove('hidden');
+    } catch (e) {
+      await dialogService.error({ title: 'Diagnostics Failed', message: `${e.message || e}` });
+    } finally {
+      this.diagnoseBtn.disabled = false;
+      this.diagnoseBtn.textContent = 'Run diagnostics';
+    }
+  }
+
+  async _loadStorageUsage({ silent = false } = {}) {
+    this.storageUsageBtn.disabled = true;
+    try {
+      const usage = await tauri.getStorageUsage();
+      this.storageUsageOutput.textContent = formatStorageUsage(usage);
+      this.storageUsageOutput.classList.remove('hidden');
+      const warning = storageWarning(usage);
+      this.storageUsageWarning.textContent = warning || '';
+      this.storageUsageWarning.classList.toggle('hidden', !warning);
+    } catch (e) {
+      if (!silent) {
+        await dialogService.error({ title: 'Storage Check Failed', message: `${e.message || e}` });
+      }
+    } finally {
+      this.storageUsageBtn.disabled = this.offlineCheckbox.checked;
+    }
+  }
+
+  async _copyDiagnostics() {
+    try {
+      await navigator.clipboard.writeText(this.diagnosticsOutput.textContent);
+      this.copyDiagnosticsBtn.textContent = 'Copied';
+    } catch (e) {
+      await dialogService.error({ title: 'Copy Failed', message: `${e.message || e}` });
+    }
+  }
+
+  _activeProfile() {
+    return this._profiles.find((p) => p.active) || null;
+  }
+
+  _renderProfiles(profiles) {
+    this._profiles = profiles || [];
+    this.profileSelect.innerHTML = '';
+    for (const p of this._profiles) {
+      const option = document.createElement('option');
+      option.value = p.id;
+      option.textContent = p.enabled ? p.name : `${p.name} (background sync off)`;
+      this.profileSelect.appendChild(option);
+    }
+    const active = this._activeProfile();
+    if (active) this.profileSelect.value = active.id;
+    this.profileEnabledCheckbox.checked = active?.enabled !== false;
+    // Das Standardprofil bleibt immer erhalten
+    this.deleteProfileBtn.disabled = !active || active.id === 'default';
+  }
+
+  async _loadProfiles() {
+    try {
+      this._renderProfiles(await tauri.listProfiles());
+    } catch (e) {
+      console.error('Failed to load profiles:', e);
+    }
+  }
+
+  async _switchProfile(id) {
+    if (!id || id === this._activeProfile()?.id) return;
+    try {
+      await tauri.switchProfile(id);
+      // Felder aus dem neuen Profil laden (Credentials, Sync-Ordner)
+      await this.show();
+      this._showSection('connection');
+      if (this.onProfileSwitchCallback) await this.onProfileSwitchCallback();
+    } catch (e) {
+      this._renderProfiles(this._profiles);
+      await dialogService.error({ title: 'Switch Failed', message: `${e.message || e}` });
+    }
+  }
+
+  async _createProfile() {
+    const name = await dialogService.prompt({
+      title: 'New profile',
+      message: 'Name of the new profile, e.g. the server or account it syncs with.',
+      placeholder: 'Work Nextcloud',
+      confirmText: 'Create',
+    });
+    if (!name?.trim()) return;
+    try {
+      const profiles = await tauri.createProfile(name.trim());
+      const created = profiles.find((p) => p.name === name.trim());
+      this._renderProfiles(profiles);
+      // Neues Profil gleich aktivieren, damit der Nutzer die Server-Daten eintragen kann
+      if (created) await this._switchProfile(created.id);
+    } catch (e) {
+      await dialogService.error({ title: 'Profile Error', message: `${e.message || e}` });
+    }
+  }
+
+  async _renameProfile() {
+    const active = this._activeProfile();
+    if (!active) return;
+    const name = await dialogService.prompt({
+      title: 'Rename profile',
+      message: 'New name of the profile',
+      defaultValue: active.name,
+      confirmText: 'Rename',
+    });
+    if (!name?.trim() || name.trim() === active.name) return;
+    try {
+      this._renderProfiles(await tauri.renameProfile(active.id, name.trim()));
+    } catch (e) {
+      await dialogService.error({ title: 'Profile Error', message: `${e.message || e}` });
+    }
+  }
+
+  async _deleteProfile() {
+    const active = this._activeProfile();
+    if (!active || active.id === 'default') return;
+    const confirmed = await dialogService.confirm({
+      title: 'Delete profile?',
+      message: `The notes of "${active.name}" are removed from this device. Notes on the server are kept.`,
+      confirmText: 'Delete',
+      type: 'danger',
+    });
+    if (!confirmed) return;
+    try {
+      // Nur inaktive Profile sind löschbar → vorher zum Standardprofil wechseln
+      await this._switchProfile('default');
+      this._renderProfiles(await tauri.deleteProfile(active.id));
+    } catch (e) {
+      await dialogService.error({ title: 'Profile Error', message: `${e.message || e}` });
+    }
+  }
+
+  async _setProfileEnabled() {
+    const active = this._activeProfile();
+    if (!active) return;
+    try {
+      this._renderProfiles(await tauri.setProfileEnabled(active.id, this.profileEnabledCheckbox.checked));
+    } catch (e) {
+      this.profileEnabledCheckbox.checked = active.enabled;
+      await dialogService.error({ title: 'Profile Error', message: `${e.message || e}` });
+    }
+  }
+
   async show() {
     // Load current settings
     try {
@@ -286,17 +642,53 @@ export class SettingsDialog {
       // Store original values for change detection
       this._previousSyncFolder = settings.sync_folder || 'notes';
       this._previousOffline = settings.offline_mode !== false;
+      this._previousSyncBackend = settings.sync_backend || 'webdav';
+      this._previousSyncDirectory = settings.sync_directory || '';
+      this._previousS3Region = settings.s3_region || 'us-east-1';
+      this._previousGitRemote = settings.git_remote || '';
+      this._previousTls = {
+        tls_mode: settings.tls_mode || 'system',
+        tls_ca_file: settings.tls_ca_file || '',
+        tls_pinned_fingerprint: settings.tls_pinned_fingerprint || '',
+      };
+      this._previousProxy = {
+        proxy_mode: settings.proxy_mode || 'system',
+        proxy_url: settings.proxy_url || '',
+        proxy_username: settings.proxy_username || '',
+        proxy_password: settings.proxy_password || '',
+        proxy_bypass: settings.proxy_bypass || '',
+      };
 
       this.selectTheme(settings.theme);
       this.autosaveCheckbox.checked = settings.autosave;
       this.trayCheckbox.checked = settings.minimize_to_tray || false;
       this.autostartCheckbox.checked = settings.autostart || false;
       this.syncFolderInput.value = settings.sync_folder || '';
+      this.syncConcurrencyInput.value = settings.sync_concurrency || 4;
       this.updateNotificationsCheckbox.checked = settings.update_notifications !== false;
       this.defaultOpenModeSelect.value = settings.default_open_mode || 'edit';
       this.deviceIdInput.value = deviceId;
       this._setActiveChip(this._originalFontSize);
       this.offlineCheckbox.checked = this._previousOffline;
+      this.syncBackendSelect.value = this._previousSyncBackend;
+      this.syncDirectoryInput.value = this._previousSyncDirectory;
+      this.s3RegionInput.value = this._previousS3Region;
+      this.gitRemoteInput.value = this._previousGitRemote;
+      this._applySyncBackend();
+      this.tlsModeSelect.value = this._previousTls.tls_mode;
+      this.tlsCaFileInput.value = this._previousTls.tls_ca_file;
+      this.tlsFingerprintInput.value = this._previousTls.tls_pinned_fingerprint;
+      this._applyTlsMode();
+      this.proxyModeSelect.value = this._previousProxy.proxy_mode;
+      this.proxyUrlInput.value = this._previousProxy.proxy_url;
+      this.proxyUsernameInput.value = this._previousProxy.proxy_username;
+      this.proxyPasswordInput.value = this._previousProxy.proxy_password;
+      this.proxyBypassInput.value = this._previousProxy.proxy_bypass;
+      this._applyProxyMode();
+      this.diagnosticsOutput.classList.add('hidden');
+      this.copyDiagnosticsBtn.classList.add('hidden');
+      this.storageUsageOutput.classList.add('hidden');
+      this.storageUsageWarning.classList.add('hidden');
 
       let creds = null;
       try {
@@ -307,14 +699,18 @@ export class SettingsDialog {
       this.serverUrlInput.value = creds?.url || '';
       this.serverUsernameInput.value = creds?.username || '';
       this.serverPasswordInput.value = creds?.password || '';
+      this.authSchemeSelect.value = creds?.auth_scheme || 'basic';
+      this._applyAuthScheme();
       // Snapshot for change detection (reconnect when online creds are edited)
       this._loadedCreds = {
         url: creds?.url || '',
         username: creds?.username || '',
         password: creds?.password || '',
+        auth_scheme: this.authSchemeSelect.value,
       };
 
       this._applyOfflineState();
+      await this._loadProfiles();
 
       // Update-Status korrekt anzeigen (verhindert stale Zustand aus vorheriger Session)
       this._restoreUpdateState();
@@ -375,7 +771,52 @@ export class SettingsDialog {
         default_open_mode: this.defaultOpenModeSelect.value,
         font_size: this._currentFontSize,
         offline_mode: offline,
+        sync_concurrency: Math.min(8, Math.max(1, Number.parseInt(this.syncConcurrencyInput.value, 10) || 4)),
+        tls_mode: this.tlsModeSelect.value,
+        tls_ca_file: this.tlsCaFileInput.value.trim(),
+        tls_pinned_fingerprint: this.tlsFingerprintInput.value.trim(),
+        proxy_mode: this.proxyModeSelect.value,
+        proxy_url: this.proxyUrlInput.value.trim(),
+        proxy_username: this.proxyUsernameInput.value.trim(),
+        proxy_password: this.proxyPasswordInput.value,
+        proxy_bypass: this.proxyBypassInput.value.trim(),
+        sync_backend: this.syncBackendSelect.value,
+        sync_directory: this.syncDirectoryInput.value.trim(),
+        s3_region: this.s3RegionInput.value.trim() || 'us-east-1',
+        git_remote: this.gitRemoteInput.value.trim(),
       };
+      const gitMode = settings.sync_backend === 'git';
+      const directoryMode = settings.sync_backend === 'directory' || gitMode;
+
+      if (directoryMode && !settings.sync_directory) {
+        await dialogService.error({
+          title: 'Sync directory missing',
+          message: gitMode
+            ? 'Enter the folder for the git working tree (it is created if it does not exist).'
+            : 'Enter the folder that Syncthing, Dropbox or a similar tool keeps in sync.',
+        });
+        return;
+      }
+
+      if ((settings.proxy_mode === 'http' || settings.proxy_mode === 'socks5') && !settings.proxy_url) {
+        await dialogService.error({
+          title: 'Proxy address missing',
+          message: 'Enter the proxy address (host:port) or choose another proxy mode.',
+        });
+        return;
+      }
+
+      if (settings.tls_mode === 'insecure' && this._previousTls?.tls_mode !== 'insecure') {
+        const confirmed = await dialogService.confirm({
+          title: 'Disable certificate check?',
+          message: TLS_HINTS.insecure,
+          confirmText: 'Disable',
+          type: 'warning',
+        });
+        if (!confirmed) return;
+      }
+
+      if (!(await this._offerMigration(settings, directoryMode))) return;
 
       await tauri.saveSettings(settings);
       // Update tray runtime state immediately (no restart needed)
@@ -385,8 +826,10 @@ export class SettingsDialog {
       const url = this.serverUrlInput.value.trim();
       const username = this.serverUsernameInput.value.trim();
       const password = this.serverPasswordInput.value;
-      if (url && username && password) {
-        await tauri.saveCredentials({ url, username, password });
+      const authScheme = this.authSchemeSelect.value;
+      const hasServerDetails = this._hasServerDetails(url, username, password);
+      if (hasServerDetails) {
+        await tauri.saveCredentials({ url, username, password, auth_scheme: authScheme });
       }
 
       // Reconcile connection only when something connection-relevant changed.
@@ -394,17 +837,27 @@ export class SettingsDialog {
         !!this._loadedCreds &&
         (url !== this._loadedCreds.url ||
           username !== this._loadedCreds.username ||
-          password !== this._loadedCreds.password);
+          password !== this._loadedCreds.password ||
+          authScheme !== this._loadedCreds.auth_scheme);
+      const tlsChanged =
+        !!this._previousTls &&
+        ['tls_mode', 'tls_ca_file', 'tls_pinned_fingerprint'].some((k) => settings[k] !== this._previousTls[k]);
+      const proxyChanged = !!this._previousProxy && PROXY_KEYS.some((k) => settings[k] !== this._previousProxy[k]);
       const connChanged =
         offline !== this._previousOffline ||
         settings.sync_folder !== this._previousSyncFolder ||
-        (!offline && credsChanged);
+        settings.sync_backend !== this._previousSyncBackend ||
+        settings.sync_directory !== this._previousSyncDirectory ||
+        settings.s3_region !== this._previousS3Region ||
+        settings.git_remote !== this._previousGitRemote ||
+        (!offline && (credsChanged || tlsChanged || proxyChanged));
       if (connChanged) {
         try {
-          if (offline) {
+          if (offline || directoryMode) {
+            // Im Verzeichnis- und Git-Modus synchronisiert der Server nicht mit
             await tauri.disconnect();
-          } else if (url && username && password) {
-            const ok = await tauri.connect(url, username, password, settings.sync_folder);
+          } else if (hasServerDetails) {
+            const ok = await tauri.connect(url, username, password, settings.sync_folder, authScheme);
             if (!ok) {
               await dialogService.error({
                 title: 'Connection Failed',
@@ -432,6 +885,59 @@ export class SettingsDialog {
     }
   }
 
+  /**
+   * Beim Wechsel von Sync-Ordner, Server oder Sync-Verzeichnis (gleiches Backend, online)
+   * anbieten, die Notizen ans neue Ziel zu kopieren. Gibt false zurück, wenn der Umzug
+   * fehlschlug — dann wird nicht gespeichert und das alte Ziel bleibt aktiv.
+   */
+  async _offerMigration(settings, directoryMode) {
+    if (settings.offline_mode || this._previousOffline) return true;
+    if (settings.sync_backend !== this._previousSyncBackend) return true;
+    const url = this.serverUrlInput.value.trim();
+    const username = this.serverUsernameInput.value.trim();
+    const password = this.serverPasswordInput.value;
+    const moved =
+      settings.sync_folder !== this._previousSyncFolder ||
+      (directoryMode
+        ? settings.sync_directory !== this._previousSyncDirectory
+        : !!this._loadedCreds?.url && url !== this._loadedCreds.url);
+    if (!moved) return true;
+    if (!directoryMode && !this._hasServerDetails(url, username, password)) return true;
+
+    const copy = await dialogService.confirm({
+      title: 'Move notes to the new location?',
+      message:
+        'Copy all notes, folders and deletions from the current location to the new one. ' +
+        'Without copying, the app starts over with whatever the new location contains.',
+      confirmText: 'Copy notes',
+      cancelText: 'Switch without copying',
+    });
+    if (!copy) return true;
+
+    const credentials = directoryMode ? null : { url, username, password, auth_scheme: this.authSchemeSelect.value };
+    try {
+      const report = await tauri.migrateSyncTarget(
+        credentials,
+        settings.sync_folder,
+        directoryMode ? settings.sync_directory : null,
+      );
+      const skipped = report.notes_existing ? `, ${report.notes_existing} already there` : '';
+      await dialogService.info({
+        title: 'Notes moved',
+        message:
+          `${report.notes_copied} notes copied${skipped}, ` +
+          `${report.folders} folders, ${report.deletions} deletions.`,
+      });
+      return true;
+    } catch (error) {
+      await dialogService.error({
+        title: 'Move failed',
+        message: `${error.message || error}. Nothing was switched; the current location stays active.`,
+      });
+      return false;
+    }
+  }
+
   renderThemeGrid() {
     this.themeGrid.innerHTML = '';
     for (const t of THEMES) {
@@ -508,6 +1014,10 @@ export class SettingsDialog {
     this.onReconnectCallback = callback;
   }
 
+  onProfileSwitch(callback) {
+    this.onProfileSwitchCallback = callback;
+  }
+
   onViewChangelog(callback) {
     this.onViewChangelogCallback = callback;
   }
@@ -239,6 +239,9 @@ class App {
       this.noteEditor.setDefaultOpenMode(settings.default_open_mode);
     });
 
+    // Profile switched: notes of the new profile, reconnect with its credentials
+    this.settingsDialog.onProfileSwitch(() => this.checkAutoConnect());
+
     // Settings "View changelog" callback
     this.settingsDialog.onViewChangelog(() => this.changelogDialog.showFull());
 
@@ -389,10 +392,18 @@ class App {
   // Sync-Button bleibt sichtbar und ein manueller/periodischer Sync kann es später erneut
   // versuchen.
   async _backgroundConnect(settings) {
+    // Lokales Sync-Verzeichnis und Git-Repository brauchen keine Server-Verbindung
+    if (settings?.sync_backend === 'directory' || settings?.sync_backend === 'git') return;
     try {
       const credentials = await tauri.getCredentials();
       if (credentials) {
-        await tauri.connect(credentials.url, credentials.username, credentials.password, settings.sync_folder || null);
+        await tauri.connect(
+          credentials.url,
+          credentials.username,
+          credentials.password,
+          settings.sync_folder || null,
+          credentials.auth_scheme || null,
+        );
       }
     } catch (error) {
       console.log('Auto-connect failed:', error);
@@ -525,18 +536,20 @@ class App {
 
   // Ensure a live WebDAV client before syncing (online mode). Throws on failure so
   // handleSync surfaces it via the error badge instead of a silent local-only no-op.
+  // Not needed when syncing into a local directory.
   async _ensureConnected() {
-    if (await tauri.isConnected()) return;
-    const creds = await tauri.getCredentials();
-    if (!creds) throw new Error('No server credentials saved');
-    let syncFolder = null;
+    let settings = null;
     try {
-      const s = await tauri.getSettings();
-      syncFolder = s.sync_folder || null;
+      settings = await tauri.getSettings();
     } catch (_e) {
       /* use default */
     }
-    const ok = await tauri.connect(creds.url, creds.username, creds.password, syncFolder);
+    if (settings?.sync_backend === 'directory' || settings?.sync_backend === 'git') return;
+    if (await tauri.isConnected()) return;
+    const creds = await tauri.getCredentials();
+    if (!creds) throw new Error('No server credentials saved');
+    const syncFolder = settings?.sync_folder || null;
+    const ok = await tauri.connect(creds.url, creds.username, creds.password, syncFolder, creds.auth_scheme || null);
     if (!ok) throw new Error('Could not reach server');
   }
 
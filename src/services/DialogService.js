@@ -578,24 +578,42 @@ class DialogService {
 
   /**
    * Resolve a sync conflict or server-deletion for a note.
-   * @param {{ isDeleted: boolean }} opts - isDeleted true = DELETED_ON_SERVER, false = CONFLICT
-   * @returns {Promise<'keep_mine'|'use_server'|null>} null = dismissed
+   * @param {{ isDeleted: boolean, versions?: {local: Object, server: Object|null, text_conflict?: boolean} }} opts -
+   *   isDeleted true = DELETED_ON_SERVER, false = CONFLICT; versions shows both sides next to each other,
+   *   text_conflict mentions the conflict markers the sync merged into the text
+   * @returns {Promise<'keep_mine'|'use_server'|'keep_both'|null>} null = dismissed
    */
-  confirmConflictResolve({ isDeleted }) {
+  confirmConflictResolve({ isDeleted, versions = null }) {
     return new Promise((resolve) => {
       this.titleEl.textContent = isDeleted ? 'Note Deleted on Server' : 'Sync Conflict';
       this.messageEl.innerHTML = isDeleted
         ? `<div>This note was deleted on the server by another device. What do you want to do?</div>`
-        : `<div>This note was modified on two devices at the same time. Which version do you want to keep?</div>`;
+        : versions?.text_conflict
+          ? `<div>This note was modified on two devices at the same time and the changes overlap. Overlapping lines ` +
+            `are marked with <code>&lt;&lt;&lt;&lt;&lt;&lt;&lt; Mine</code> / <code>&gt;&gt;&gt;&gt;&gt;&gt;&gt; Server</code> ` +
+            `in the text — edit them and keep yours, use the server version, or keep both as separate notes.</div>`
+          : `<div>This note was modified on two devices at the same time. Which version do you want to keep?</div>`;
+      if (!isDeleted && versions?.server) {
+        const side = (label, note) => `
+          <div class="conflict-side">
+            <div class="conflict-side-label">${label}</div>
+            <div class="conflict-side-title">${this._escapeHtml(note.title || 'Untitled')}</div>
+            <pre class="conflict-side-content">${this._escapeHtml(note.content || '')}</pre>
+          </div>`;
+        const compare = side('Mine', versions.local) + side('Server', versions.server);
+        this.messageEl.innerHTML += `<div class="conflict-compare">${compare}</div>`;
+      }
       this.iconContainer.innerHTML = this._getIcon('warning');
       this.iconContainer.className = 'dialog-icon dialog-icon-warning';
 
       const origActions = this.actionsContainer.innerHTML;
       const keepLabel = isDeleted ? 'Keep local' : 'Keep mine';
       const serverLabel = isDeleted ? 'Discard' : 'Use server';
+      const bothButton = isDeleted ? '' : '<button id="cr-both" class="btn-secondary">Keep both</button>';
       this.actionsContainer.innerHTML = `
         <button id="cr-cancel" class="btn-secondary">Cancel</button>
         <button id="cr-server" class="btn-danger">${serverLabel}</button>
+        ${bothButton}
         <button id="cr-keep" class="btn-primary">${keepLabel}</button>
       `;
 
@@ -611,6 +629,8 @@ class DialogService {
 
       document.getElementById('cr-keep').onclick = () => done('keep_mine');
       document.getElementById('cr-server').onclick = () => done('use_server');
+      const bothBtn = document.getElementById('cr-both');
+      if (bothBtn) bothBtn.onclick = () => done('keep_both');
       document.getElementById('cr-cancel').onclick = () => done(null);
 
       const handleKeydown = (e) => {
@@ -709,6 +729,61 @@ class DialogService {
     });
   }
 
+  /**
+   * Choose a server version of a note to restore.
+   * @param {{versions: Array<{version_id: string, label: string}>}} opts - newest first
+   * @returns {Promise<string|undefined>} version_id, undefined = cancelled
+   */
+  chooseVersion({ versions }) {
+    return new Promise((resolve) => {
+      this.resolvePromise = resolve;
+
+      this.titleEl.textContent = 'Restore Version';
+      this.messageEl.innerHTML = `
+        <div style="margin-bottom:0.5rem">Older versions kept by the server. The current text is replaced and synced as a new change.</div>
+        <select id="dialog-version-select" class="dialog-input" style="width:100%">
+          ${versions.map((v) => `<option value="${this._escapeAttr(v.version_id)}">${this._escapeHtml(v.label)}</option>`).join('')}
+        </select>
+      `;
+      this.confirmBtn.textContent = 'Restore';
+      this.confirmBtn.className = this._getButtonClass('warning');
+      this.cancelBtn.textContent = 'Cancel';
+      this.cancelBtn.style.display = '';
+      this.iconContainer.innerHTML = this._getIcon('info');
+      this.iconContainer.className = 'dialog-icon dialog-icon-info';
+      this.dialog.classList.remove('hidden');
+
+      setTimeout(() => document.getElementById('dialog-version-select')?.focus(), 100);
+
+      const handleConfirm = () => {
+        const val = document.getElementById('dialog-version-select')?.value;
+        this._cleanup();
+        resolve(val || undefined);
+      };
+
+      const handleCancel = () => {
+        this._cleanup();
+        resolve(undefined);
+      };
+
+      const handleKeydown = (e) => {
+        if (e.key === 'Escape') {
+          e.preventDefault();
+          handleCancel();
+        } else if (e.key === 'Enter' && !e.shiftKey) {
+          e.preventDefault();
+          handleConfirm();
+        }
+      };
+
+      this.confirmBtn.onclick = handleConfirm;
+      this.cancelBtn.onclick = handleCancel;
+      this._attachBackdropHandler(handleCancel);
+      document.addEventListener('keydown', handleKeydown);
+      this.keydownHandler = handleKeydown;
+    });
+  }
+
   _escapeAttr(str) {
     return String(str).replace(/"/g, '&quot;');
   }
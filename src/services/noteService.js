@@ -297,7 +297,7 @@ class NoteService {
   /**
    * Resolve a sync conflict.
    * @param {string} id - Note ID
-   * @param {'keep_mine'|'use_server'} resolution
+   * @param {'keep_mine'|'use_server'|'keep_both'} resolution
    */
   async resolveConflict(id, resolution) {
     await tauri.resolveConflict(id, resolution);
@@ -305,6 +305,41 @@ class NoteService {
     this.notify();
   }
 
+  /**
+   * Both sides of a conflict for a side-by-side comparison; null if they cannot be loaded
+   * (e.g. offline) — the conflict can still be resolved without it.
+   * @param {string} id - Note ID
+   */
+  async getConflictVersions(id) {
+    try {
+      return await tauri.getConflictVersions(id);
+    } catch (error) {
+      console.error('Failed to load conflict versions:', error);
+      return null;
+    }
+  }
+
+  /**
+   * Older server versions of a note (Nextcloud only), newest first.
+   * @param {string} id - Note ID
+   */
+  async listServerVersions(id) {
+    return await tauri.listServerVersions(id);
+  }
+
+  /**
+   * Restore a server version; the note becomes a pending local edit.
+   * @param {string} id - Note ID
+   * @param {string} versionId - version_id from listServerVersions
+   * @returns {Promise<Object>} Restored note
+   */
+  async restoreServerVersion(id, versionId) {
+    const note = await tauri.restoreServerVersion(id, versionId);
+    await this.loadNotes();
+    this.notify();
+    return note;
+  }
+
   /**
    * Rename a folder
    * @param {string} oldName
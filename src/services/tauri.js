@@ -4,11 +4,12 @@ import { invoke } from '@tauri-apps/api/core';
  * WebDAV Connection Service
  * @param {string} url - WebDAV server URL
  * @param {string} username - Username
- * @param {string} password - Password
+ * @param {string} password - Password (bearer scheme: the token)
  * @param {string|null} syncFolder - Sync folder name (default: "notes")
+ * @param {string|null} authScheme - "basic" (default), "digest", "bearer" or "nextcloud_login_flow"
  */
-export async function connect(url, username, password, syncFolder = null) {
-  return await invoke('connect', { url, username, password, syncFolder });
+export async function connect(url, username, password, syncFolder = null, authScheme = null) {
+  return await invoke('connect', { url, username, password, syncFolder, authScheme });
 }
 
 /**
@@ -65,7 +66,7 @@ export async function getCredentials() {
 
 /**
  * Save credentials
- * @param {Object} credentials - Credentials object {url, username, password}
+ * @param {Object} credentials - Credentials object {url, username, password, auth_scheme}
  */
 export async function saveCredentials(credentials) {
   return await invoke('save_credentials', { credentials });
@@ -78,6 +79,63 @@ export async function clearCredentials() {
   return await invoke('clear_credentials');
 }
 
+/**
+ * All profiles (server accounts), the active one flagged
+ * @returns {Promise<Array>} Profiles [{id, name, enabled, active, server_url}]
+ */
+export async function listProfiles() {
+  return await invoke('list_profiles');
+}
+
+/**
+ * Create an empty profile (not activated)
+ * @param {string} name - Display name
+ * @returns {Promise<Array>} Updated profile list
+ */
+export async function createProfile(name) {
+  return await invoke('create_profile', { name });
+}
+
+export async function renameProfile(id, name) {
+  return await invoke('rename_profile', { id, name });
+}
+
+/**
+ * Include or exclude a profile from background sync
+ * @returns {Promise<Array>} Updated profile list
+ */
+export async function setProfileEnabled(id, enabled) {
+  return await invoke('set_profile_enabled', { id, enabled });
+}
+
+/**
+ * Delete an inactive profile and its local notes (server data is kept)
+ * @returns {Promise<Array>} Updated profile list
+ */
+export async function deleteProfile(id) {
+  return await invoke('delete_profile', { id });
+}
+
+/**
+ * Make a profile active; its credentials and notes replace the current ones
+ * @returns {Promise<Array>} Updated profile list
+ */
+export async function switchProfile(id) {
+  return await invoke('switch_profile', { id });
+}
+
+/**
+ * Move to a new sync folder, server or sync directory, copying all notes, folders and the
+ * deletion ledger from the current location first. Settings switch only after a verified copy.
+ * @param {Object|null} credentials - {url, username, password, auth_scheme} for server backends
+ * @param {string} syncFolder - New sync folder name
+ * @param {string|null} syncDirectory - New sync directory (directory and git backends)
+ * @returns {Promise<Object>} {notes_copied, notes_existing, folders, deletions}
+ */
+export async function migrateSyncTarget(credentials, syncFolder, syncDirectory = null) {
+  return await invoke('migrate_sync_target', { credentials, syncFolder, syncDirectory });
+}
+
 /**
  * Get device ID
  * @returns {Promise<string>} Device ID
@@ -275,19 +333,115 @@ export async function sync() {
 /**
  * Resolve a sync conflict for a note.
  * @param {string} id - Note ID
- * @param {'keep_mine'|'use_server'} resolution - Which version to keep
+ * @param {'keep_mine'|'use_server'|'keep_both'} resolution - Which version to keep; keep_both saves
+ *   the local version as a new note and takes the server version under the original id
  * @returns {Promise<void>}
  */
 export async function resolveConflict(id, resolution) {
   return await invoke('resolve_conflict', { id, resolution });
 }
 
+/**
+ * Local, server and last-synced base version of a note in conflict.
+ * text_conflict: the sync merged both sides and marked overlapping lines in the content
+ * @param {string} id - Note ID
+ * @returns {Promise<{local: Object, server: Object|null, base: Object|null, text_conflict: boolean}>}
+ */
+export async function getConflictVersions(id) {
+  return await invoke('get_conflict_versions', { id });
+}
+
+/**
+ * Structured diff between two note versions. A version is either passed directly
+ * (`{source: 'note', note}`) or loaded by id (`{source: 'local'|'server'|'base'|'conflict_local', id}`).
+ * @param {Object} oldVersion - Older/left version
+ * @param {Object} newVersion - Newer/right version
+ * @returns {Promise<{fields: Array, content: Array, checklist: Array}>} Field changes {field, old, new},
+ *   content line hunks {old_start, old_count, new_start, new_count, old_text, new_text, words}
+ *   and checklist item changes tagged by `change` (added|removed|edited|moved)
+ */
+export async function diffNotes(oldVersion, newVersion) {
+  return await invoke('diff_notes', { old: oldVersion, new: newVersion });
+}
+
+/**
+ * Older server versions of a note (Nextcloud file versions), newest first
+ * @param {string} id - Note ID
+ * @returns {Promise<Array>} Versions [{version_id, timestamp, size}]
+ */
+export async function listServerVersions(id) {
+  return await invoke('list_server_versions', { id });
+}
+
+/**
+ * Fetch one server version of a note (preview, changes nothing locally)
+ * @param {string} id - Note ID
+ * @param {string} versionId - version_id from listServerVersions
+ * @returns {Promise<Object>} Note
+ */
+export async function getServerVersion(id, versionId) {
+  return await invoke('get_server_version', { id, versionId });
+}
+
+/**
+ * Restore a server version as a new local edit (uploaded on the next sync)
+ * @param {string} id - Note ID
+ * @param {string} versionId - version_id from listServerVersions
+ * @returns {Promise<Object>} Restored note
+ */
+export async function restoreServerVersion(id, versionId) {
+  return await invoke('restore_server_version', { id, versionId });
+}
+
 export async function disconnect() {
   return await invoke('disconnect');
 }
 
-export async function testConnection(url, username, password, syncFolder = null) {
-  return await invoke('test_connection', { url, username, password, syncFolder });
+export async function testConnection(
+  url,
+  username,
+  password,
+  syncFolder = null,
+  authScheme = null,
+  syncBackend = null,
+  s3Region = null,
+) {
+  return await invoke('test_connection', { url, username, password, syncFolder, authScheme, syncBackend, s3Region });
+}
+
+/**
+ * Run the server diagnostics (no side effects besides a probe file that is removed again)
+ * @returns {Promise<Object>} Report {server_url, dav_classes, clock_offset_secs, checks: [{id, status, detail, duration_ms}], …}
+ */
+export async function diagnoseServer(url, username, password, syncFolder = null, authScheme = null) {
+  return await invoke('diagnose_server', { url, username, password, syncFolder, authScheme });
+}
+
+/**
+ * Quota and note storage usage of the connected server
+ * @returns {Promise<Object>} Usage {quota_used_bytes, quota_available_bytes, low_space, notes, json_bytes, markdown_bytes, folders: [{folder, notes, json_bytes, markdown_bytes}]}
+ */
+export async function getStorageUsage() {
+  return await invoke('get_storage_usage');
+}
+
+/**
+ * Start Nextcloud Login Flow v2
+ * @param {string} url - Server URL (Nextcloud base or WebDAV URL)
+ * @returns {Promise<Object>} Flow {login_url, poll_endpoint, poll_token}
+ */
+export async function startNextcloudLogin(url) {
+  return await invoke('start_nextcloud_login', { url });
+}
+
+/**
+ * Poll a running Nextcloud Login Flow v2 once
+ * @param {string} url - Server URL the flow was started with
+ * @param {Object} flow - Flow returned by startNextcloudLogin
+ * @returns {Promise<Object|null>} Credentials once approved, null while pending
+ */
+export async function pollNextcloudLogin(url, flow) {
+  return await invoke('poll_nextcloud_login', { url, flow });
 }
 
 export async function isConnected() {
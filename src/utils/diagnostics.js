@@ -0,0 +1,65 @@
+/**
+ * Plain-text rendering of the server diagnostics report (diagnose_server command),
+ * shown in the connection settings and copied to the clipboard for bug reports.
+ */
+
+const STATUS_LABELS = { ok: 'OK', warning: 'WARN', failed: 'FAIL', skipped: 'SKIP' };
+const UNITS = ['B', 'KB', 'MB', 'GB', 'TB'];
+
+/**
+ * Format a byte count with 1024-based units (same output as the Rust side).
+ * @param {number} bytes
+ * @returns {string}
+ */
+export function formatBytes(bytes) {
+  let value = bytes;
+  let unit = 0;
+  while (Math.abs(value) >= 1024 && unit < UNITS.length - 1) {
+    value /= 1024;
+    unit += 1;
+  }
+  return unit === 0 ? `${bytes} B` : `${value.toFixed(1)} ${UNITS[unit]}`;
+}
+
+/**
+ * Describe quota-used/quota-available bytes (negative available = no limit reported).
+ * @param {number|null} used
+ * @param {number|null} available
+ * @returns {string}
+ */
+export function formatQuota(used, available) {
+  if (used == null && available == null) return 'not reported';
+  const usedText = used == null ? '?' : formatBytes(used);
+  if (available == null || available < 0) return `${usedText} used, no limit reported`;
+  return `${usedText} used, ${formatBytes(available)} available`;
+}
+
+function formatOffset(seconds) {
+  if (seconds == null) return 'unknown';
+  return `${seconds > 0 ? '+' : ''}${seconds} s`;
+}
+
+/**
+ * Render a diagnostics report as copyable plain text.
+ * @param {Object} report - DiagnosticReport from diagnoseServer()
+ * @returns {string}
+ */
+export function formatDiagnosticReport(report) {
+  const lines = [
+    `Simple Notes Desktop ${report.app_version} — server diagnostics (${report.generated_at})`,
+    `Server: ${report.server_url} (sync folder /${report.sync_folder}/)`,
+    `Software: ${report.server_software || 'unknown'}`,
+    `TLS: ${report.tls}`,
+    `DAV: ${report.dav_classes.join(', ') || 'none'}`,
+    `Allow: ${report.allowed_methods.join(', ') || 'not reported'}`,
+    `Clock offset: ${formatOffset(report.clock_offset_secs)}`,
+    `Quota: ${formatQuota(report.quota_used_bytes, report.quota_available_bytes)}`,
+    '',
+  ];
+  for (const check of report.checks) {
+    const label = `[${STATUS_LABELS[check.status] || check.status}]`.padEnd(7);
+    const duration = check.duration_ms > 0 ? ` (${check.duration_ms} ms)` : '';
+    lines.push(`${label}${check.id} — ${check.detail}${duration}`);
+  }
+  return lines.join('\n');
+}
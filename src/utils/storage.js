@@ -0,0 +1,36 @@
+/**
+ * Rendering of the server storage usage (get_storage_usage command) for the
+ * connection settings: note sizes per folder plus the quota of the sync folder.
+ */
+
+import { formatBytes, formatQuota } from './diagnostics.js';
+
+/**
+ * Render storage usage as plain text, one line per folder.
+ * @param {Object} usage - StorageUsage from getStorageUsage()
+ * @returns {string}
+ */
+export function formatStorageUsage(usage) {
+  const lines = [
+    `Quota: ${formatQuota(usage.quota_used_bytes, usage.quota_available_bytes)}`,
+    `Notes: ${usage.notes} — ${formatBytes(usage.json_bytes)} JSON, ${formatBytes(usage.markdown_bytes)} Markdown`,
+    '',
+  ];
+  for (const folder of usage.folders) {
+    const name = folder.folder == null ? '(root)' : `/${folder.folder}`;
+    lines.push(
+      `${name}: ${folder.notes} notes, ${formatBytes(folder.json_bytes)} JSON, ${formatBytes(folder.markdown_bytes)} Markdown`
+    );
+  }
+  return lines.join('\n');
+}
+
+/**
+ * Warning shown before uploads start failing with 507 Insufficient Storage.
+ * @param {Object} usage - StorageUsage from getStorageUsage()
+ * @returns {string|null} Warning text, or null when there is enough space
+ */
+export function storageWarning(usage) {
+  if (!usage.low_space) return null;
+  return `Server storage is almost full (${formatBytes(usage.quota_available_bytes)} left). Uploads will fail once it runs out.`;
+}
@@ -0,0 +1,34 @@
+/**
+ * Helpers for the Nextcloud file-versions browser (list_server_versions command).
+ */
+
+import { formatBytes } from './diagnostics.js';
+
+/**
+ * One-line label for a server version: local date/time plus size.
+ * @param {Object} version - ServerVersion {version_id, timestamp, size}
+ * @returns {string}
+ */
+export function formatVersionLabel(version) {
+  const when = new Date(version.timestamp).toLocaleString();
+  return version.size == null ? when : `${when} — ${formatBytes(version.size)}`;
+}
+
+/**
+ * User-facing message for a failed versions request.
+ * @param {unknown} error - Error string from the backend
+ * @returns {string}
+ */
+export function versionsErrorMessage(error) {
+  const text = String(error);
+  if (text.startsWith('Not supported by server')) {
+    return 'Version history is only available on Nextcloud servers with the Versions app enabled.';
+  }
+  if (text.startsWith('Not connected')) {
+    return 'Connect to your server to browse older versions.';
+  }
+  if (text.startsWith('Note not found')) {
+    return 'This note has not been uploaded to the server yet.';
+  }
+  return `Could not load versions: ${text}`;
+}